@@ -38,9 +38,9 @@ async fn main() {
 
     // All of the Microsoft AMQP clients represent the event body as an uninterpreted bag of bytes.
     let batch = vec![
-        Data(Binary::from("hello AMQP from rust")),
-        Data(Binary::from("another hello")),
-        Data(Binary::from("greeting again and again")),
+        Data::from(Binary::from("hello AMQP from rust")),
+        Data::from(Binary::from("another hello")),
+        Data::from(Binary::from("greeting again and again")),
     ];
     let message = Message::builder().data_batch(batch).build();
 
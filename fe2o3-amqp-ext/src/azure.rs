@@ -0,0 +1,137 @@
+//! Message annotations set by Azure Event Hubs and Service Bus
+//!
+//! # Example
+//!
+//! ```rust
+//! use fe2o3_amqp_ext::azure::AzureMessageExt;
+//! use fe2o3_amqp_types::messaging::Message;
+//!
+//! let mut message = Message::builder().value("event").build();
+//! message.set_partition_key("device-1");
+//!
+//! assert_eq!(message.partition_key(), Some("device-1"));
+//! assert_eq!(message.sequence_number(), None);
+//! ```
+
+use fe2o3_amqp_types::{
+    messaging::{annotations::AnnotationKey, Message, MessageAnnotations},
+    primitives::{Symbol, Timestamp, Value},
+};
+
+/// Message annotation carrying the sequence number of the message in the partition or entity
+pub const SEQUENCE_NUMBER: &str = "x-opt-sequence-number";
+
+/// Message annotation carrying the time at which the message was enqueued
+pub const ENQUEUED_TIME: &str = "x-opt-enqueued-time";
+
+/// Message annotation carrying the partition key of the message
+pub const PARTITION_KEY: &str = "x-opt-partition-key";
+
+/// Message annotation carrying the offset of the message in the Event Hubs partition
+pub const OFFSET: &str = "x-opt-offset";
+
+/// Typed getters of the `x-opt-*` message annotations used by Azure messaging services
+pub trait AzureMessageExt {
+    /// The value of the [`SEQUENCE_NUMBER`] annotation
+    fn sequence_number(&self) -> Option<i64>;
+
+    /// The value of the [`ENQUEUED_TIME`] annotation
+    fn enqueued_time(&self) -> Option<Timestamp>;
+
+    /// The value of the [`PARTITION_KEY`] annotation
+    fn partition_key(&self) -> Option<&str>;
+
+    /// Sets the [`PARTITION_KEY`] annotation, which is used by Event Hubs to choose the
+    /// partition of the message
+    fn set_partition_key(&mut self, partition_key: impl Into<String>);
+
+    /// The value of the [`OFFSET`] annotation
+    ///
+    /// The offset is usually sent as a string, and an offset sent as a long is converted into
+    /// its decimal representation.
+    fn offset(&self) -> Option<String>;
+}
+
+impl<B> AzureMessageExt for Message<B> {
+    fn sequence_number(&self) -> Option<i64> {
+        match annotation(self, SEQUENCE_NUMBER)? {
+            Value::Long(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn enqueued_time(&self) -> Option<Timestamp> {
+        match annotation(self, ENQUEUED_TIME)? {
+            Value::Timestamp(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn partition_key(&self) -> Option<&str> {
+        match annotation(self, PARTITION_KEY)? {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn set_partition_key(&mut self, partition_key: impl Into<String>) {
+        self.message_annotations
+            .get_or_insert_with(MessageAnnotations::default)
+            .0
+            .insert(
+                Symbol::from(PARTITION_KEY).into(),
+                Value::String(partition_key.into()),
+            );
+    }
+
+    fn offset(&self) -> Option<String> {
+        match annotation(self, OFFSET)? {
+            Value::String(value) => Some(value.clone()),
+            Value::Long(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+}
+
+fn annotation<'a, B>(message: &'a Message<B>, key: &str) -> Option<&'a Value> {
+    message
+        .message_annotations
+        .as_ref()?
+        .0
+        .get(&key as &dyn AnnotationKey)
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{
+        messaging::{Message, MessageAnnotations},
+        primitives::{Timestamp, Value},
+    };
+
+    use super::AzureMessageExt;
+
+    #[test]
+    fn read_event_hubs_annotations() {
+        let annotations = MessageAnnotations::builder()
+            .insert("x-opt-sequence-number", Value::Long(42))
+            .insert(
+                "x-opt-enqueued-time",
+                Value::Timestamp(Timestamp::from_milliseconds(1_000)),
+            )
+            .insert("x-opt-offset", Value::String(String::from("4096")))
+            .insert("x-opt-partition-key", Value::String(String::from("key")))
+            .build();
+        let message = Message::builder()
+            .message_annotations(annotations)
+            .value("event")
+            .build();
+
+        assert_eq!(message.sequence_number(), Some(42));
+        assert_eq!(
+            message.enqueued_time(),
+            Some(Timestamp::from_milliseconds(1_000))
+        );
+        assert_eq!(message.offset().as_deref(), Some("4096"));
+        assert_eq!(message.partition_key(), Some("key"));
+    }
+}
@@ -0,0 +1,683 @@
+//! CloudEvents v1.0 AMQP protocol binding
+//!
+//! A [`CloudEvent`] is mapped to and from an AMQP message in one of the two content modes of the
+//! [AMQP protocol binding](https://github.com/cloudevents/spec/blob/v1.0.2/cloudevents/bindings/amqp-protocol-binding.md).
+//!
+//! - Binary mode: the `datacontenttype` attribute is carried by the `content-type` property, the
+//!   other attributes and the extensions are carried by the application properties prefixed with
+//!   `cloudEvents:`, and the event data is the body of the message, either a `data` section or
+//!   an `amqp-value` section. The `cloudEvents_` prefix used by JMS clients is also accepted when
+//!   reading a message.
+//! - Structured mode (requires feature `"json"`): the whole event is encoded in the JSON event
+//!   format in a `data` section with the content type `application/cloudevents+json`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use fe2o3_amqp_ext::cloud_events::CloudEvent;
+//!
+//! let event = CloudEvent::new("1", "/sensors/1", "com.example.temperature")
+//!     .with_subject("room-1")
+//!     .with_data("application/json", br#"{"celsius":21.5}"#.to_vec());
+//!
+//! let message = event.clone().into_binary_message();
+//! let decoded = CloudEvent::from_message(message).unwrap();
+//! assert_eq!(decoded, event);
+//! ```
+
+use fe2o3_amqp_types::{
+    messaging::{AmqpValue, ApplicationProperties, Batch, Body, Data, Message, Properties},
+    primitives::{OrderedMap, SimpleValue, Symbol, Timestamp, Value},
+};
+
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::codec::essence;
+
+/// The prefix of the application properties carrying the attributes in binary mode
+pub const ATTRIBUTE_PREFIX: &str = "cloudEvents:";
+
+/// The prefix of the application properties carrying the attributes that is used by JMS clients
+pub const JMS_ATTRIBUTE_PREFIX: &str = "cloudEvents_";
+
+/// The content type of an event in structured mode with the JSON event format
+pub const STRUCTURED_JSON_CONTENT_TYPE: &str = "application/cloudevents+json";
+
+/// The version of the CloudEvents specification that is implemented
+pub const SPEC_VERSION: &str = "1.0";
+
+/// Error with mapping a message to a [`CloudEvent`]
+#[derive(Debug, thiserror::Error)]
+pub enum CloudEventError {
+    /// A required attribute is missing
+    #[error("Missing attribute {0:?}")]
+    MissingAttribute(&'static str),
+
+    /// An attribute has a type that is not allowed
+    #[error("Invalid value for attribute {0:?}")]
+    InvalidAttribute(String),
+
+    /// The spec version of the event is not supported
+    #[error("Unsupported spec version {0:?}")]
+    UnsupportedSpecVersion(String),
+
+    /// The message is in structured mode with an event format that is not supported
+    #[error("Unsupported event format {0:?}")]
+    UnsupportedFormat(String),
+
+    /// The body of the message cannot carry event data
+    #[error("The body of the message is not a data or an amqp-value section")]
+    UnsupportedBody,
+
+    /// Error with the JSON event format
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// Error with the base64 encoded data in the JSON event format
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+}
+
+/// The data of an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventData {
+    /// Bytes whose format is described by the `datacontenttype` attribute, carried by a `data`
+    /// section in binary mode
+    Binary(Vec<u8>),
+
+    /// An AMQP value, carried by an `amqp-value` section in binary mode
+    Value(Value),
+}
+
+/// A CloudEvents v1.0 event
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloudEvent {
+    /// `id`
+    pub id: String,
+
+    /// `source`, a URI-reference
+    pub source: String,
+
+    /// `specversion`
+    pub spec_version: String,
+
+    /// `type`
+    pub ty: String,
+
+    /// `datacontenttype`
+    pub data_content_type: Option<String>,
+
+    /// `dataschema`, a URI
+    pub data_schema: Option<String>,
+
+    /// `subject`
+    pub subject: Option<String>,
+
+    /// `time`
+    pub time: Option<Timestamp>,
+
+    /// The extension attributes
+    pub extensions: OrderedMap<String, SimpleValue>,
+
+    /// The event data
+    pub data: Option<EventData>,
+}
+
+impl CloudEvent {
+    /// Creates an event without data with the required attributes
+    pub fn new(id: impl Into<String>, source: impl Into<String>, ty: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            source: source.into(),
+            spec_version: String::from(SPEC_VERSION),
+            ty: ty.into(),
+            data_content_type: None,
+            data_schema: None,
+            subject: None,
+            time: None,
+            extensions: OrderedMap::new(),
+            data: None,
+        }
+    }
+
+    /// Sets the `subject` attribute
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Sets the `time` attribute
+    pub fn with_time(mut self, time: Timestamp) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Sets the `dataschema` attribute
+    pub fn with_data_schema(mut self, data_schema: impl Into<String>) -> Self {
+        self.data_schema = Some(data_schema.into());
+        self
+    }
+
+    /// Sets an extension attribute
+    pub fn with_extension(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<SimpleValue>,
+    ) -> Self {
+        self.extensions.insert(name.into(), value.into());
+        self
+    }
+
+    /// Sets the data and the `datacontenttype` attribute
+    pub fn with_data(mut self, content_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        self.data_content_type = Some(content_type.into());
+        self.data = Some(EventData::Binary(data.into()));
+        self
+    }
+
+    /// Sets the data to an AMQP value
+    pub fn with_value(mut self, value: impl Into<Value>) -> Self {
+        self.data = Some(EventData::Value(value.into()));
+        self
+    }
+
+    /// Maps the event to a message in binary mode
+    pub fn into_binary_message(self) -> Message<Body<Value>> {
+        let mut properties = ApplicationProperties::default();
+        let mut insert = |name: &str, value: SimpleValue| {
+            properties.insert(format!("{}{}", ATTRIBUTE_PREFIX, name), value);
+        };
+        insert("specversion", SimpleValue::String(self.spec_version));
+        insert("id", SimpleValue::String(self.id));
+        insert("source", SimpleValue::String(self.source));
+        insert("type", SimpleValue::String(self.ty));
+        if let Some(data_schema) = self.data_schema {
+            insert("dataschema", SimpleValue::String(data_schema));
+        }
+        if let Some(subject) = self.subject {
+            insert("subject", SimpleValue::String(subject));
+        }
+        if let Some(time) = self.time {
+            insert("time", SimpleValue::Timestamp(time));
+        }
+        for (name, value) in self.extensions {
+            insert(&name, value);
+        }
+
+        let body = match self.data {
+            Some(EventData::Binary(data)) => Body::Data(Batch::from(vec![Data::from(data)])),
+            Some(EventData::Value(value)) => Body::Value(AmqpValue(value)),
+            None => Body::Empty,
+        };
+        let content_type = self.data_content_type.map(|content_type| Properties {
+            content_type: Some(Symbol::from(content_type)),
+            ..Default::default()
+        });
+        Message::builder()
+            .properties(content_type)
+            .application_properties(properties)
+            .body(body)
+            .build()
+    }
+
+    /// Maps a message in either binary or structured mode to an event
+    ///
+    /// A message whose content type starts with `application/cloudevents` is in structured
+    /// mode, which requires feature `"json"`.
+    pub fn from_message(message: Message<Body<Value>>) -> Result<Self, CloudEventError> {
+        let content_type = message
+            .properties
+            .as_ref()
+            .and_then(|p| p.content_type.as_ref())
+            .map(|content_type| essence(content_type.as_str()));
+        match content_type {
+            Some(content_type) if content_type.starts_with("application/cloudevents") => {
+                from_structured_body(&content_type, message.body)
+            }
+            _ => Self::from_binary_message(message),
+        }
+    }
+
+    /// Maps a message in binary mode to an event
+    pub fn from_binary_message(message: Message<Body<Value>>) -> Result<Self, CloudEventError> {
+        let mut attributes = OrderedMap::new();
+        for (key, value) in message.application_properties.into_iter().flat_map(|p| p.0) {
+            let name = key
+                .strip_prefix(ATTRIBUTE_PREFIX)
+                .or_else(|| key.strip_prefix(JMS_ATTRIBUTE_PREFIX));
+            if let Some(name) = name {
+                attributes.insert(name.to_string(), value);
+            }
+        }
+
+        let mut take_string = |name: &'static str| match attributes.shift_remove(name) {
+            Some(SimpleValue::String(value)) => Ok(Some(value)),
+            Some(_) => Err(CloudEventError::InvalidAttribute(name.to_string())),
+            None => Ok(None),
+        };
+        let spec_version =
+            take_string("specversion")?.ok_or(CloudEventError::MissingAttribute("specversion"))?;
+        check_spec_version(&spec_version)?;
+        let id = take_string("id")?.ok_or(CloudEventError::MissingAttribute("id"))?;
+        let source = take_string("source")?.ok_or(CloudEventError::MissingAttribute("source"))?;
+        let ty = take_string("type")?.ok_or(CloudEventError::MissingAttribute("type"))?;
+        let data_schema = take_string("dataschema")?;
+        let subject = take_string("subject")?;
+        let time = match attributes.shift_remove("time") {
+            Some(SimpleValue::Timestamp(time)) => Some(time),
+            Some(SimpleValue::String(time)) => Some(
+                parse_rfc3339(&time)
+                    .ok_or_else(|| CloudEventError::InvalidAttribute(String::from("time")))?,
+            ),
+            Some(_) => return Err(CloudEventError::InvalidAttribute(String::from("time"))),
+            None => None,
+        };
+
+        let data = match message.body {
+            Body::Data(batch) => Some(EventData::Binary(
+                batch.into_iter().flat_map(|data| data.0.to_vec()).collect(),
+            )),
+            Body::Value(AmqpValue(value)) => Some(EventData::Value(value)),
+            Body::Empty => None,
+            Body::Sequence(_) => return Err(CloudEventError::UnsupportedBody),
+        };
+        let data_content_type = message
+            .properties
+            .and_then(|p| p.content_type)
+            .map(|content_type| content_type.0);
+
+        Ok(Self {
+            id,
+            source,
+            spec_version,
+            ty,
+            data_content_type,
+            data_schema,
+            subject,
+            time,
+            extensions: attributes,
+            data,
+        })
+    }
+}
+
+fn check_spec_version(spec_version: &str) -> Result<(), CloudEventError> {
+    match spec_version.split('.').next() {
+        Some("1") => Ok(()),
+        _ => Err(CloudEventError::UnsupportedSpecVersion(
+            spec_version.to_string(),
+        )),
+    }
+}
+
+#[cfg(feature = "json")]
+fn from_structured_body(
+    content_type: &str,
+    body: Body<Value>,
+) -> Result<CloudEvent, CloudEventError> {
+    if content_type != STRUCTURED_JSON_CONTENT_TYPE {
+        return Err(CloudEventError::UnsupportedFormat(content_type.to_string()));
+    }
+    let bytes: Vec<u8> = match body {
+        Body::Data(batch) => batch.into_iter().flat_map(|data| data.0.to_vec()).collect(),
+        _ => return Err(CloudEventError::UnsupportedBody),
+    };
+    CloudEvent::from_json(&bytes)
+}
+
+#[cfg(not(feature = "json"))]
+fn from_structured_body(_: &str, _: Body<Value>) -> Result<CloudEvent, CloudEventError> {
+    Err(CloudEventError::UnsupportedFormat(String::from(
+        STRUCTURED_JSON_CONTENT_TYPE,
+    )))
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use fe2o3_amqp_types::{
+        messaging::{Data, Message, Properties},
+        primitives::{OrderedMap, SimpleValue, Symbol},
+    };
+    use serde_json::{Map, Value as JsonValue};
+
+    use super::{
+        check_spec_version, essence, format_rfc3339, parse_rfc3339, CloudEvent, CloudEventError,
+        EventData, STRUCTURED_JSON_CONTENT_TYPE,
+    };
+
+    /// Whether the data with the content type is a JSON value. Data without a content type is
+    /// JSON in the JSON event format
+    fn is_json(content_type: Option<&str>) -> bool {
+        match content_type.map(essence) {
+            Some(content_type) => {
+                content_type == "application/json"
+                    || content_type == "text/json"
+                    || content_type.ends_with("+json")
+            }
+            None => true,
+        }
+    }
+
+    fn is_text(content_type: Option<&str>) -> bool {
+        content_type
+            .map(essence)
+            .is_some_and(|content_type| content_type.starts_with("text/"))
+    }
+
+    impl CloudEvent {
+        /// Encodes the event in the JSON event format
+        pub fn to_json(&self) -> Result<Vec<u8>, CloudEventError> {
+            let mut map = Map::new();
+            map.insert("specversion".into(), self.spec_version.clone().into());
+            map.insert("id".into(), self.id.clone().into());
+            map.insert("source".into(), self.source.clone().into());
+            map.insert("type".into(), self.ty.clone().into());
+            if let Some(data_content_type) = &self.data_content_type {
+                map.insert("datacontenttype".into(), data_content_type.clone().into());
+            }
+            if let Some(data_schema) = &self.data_schema {
+                map.insert("dataschema".into(), data_schema.clone().into());
+            }
+            if let Some(subject) = &self.subject {
+                map.insert("subject".into(), subject.clone().into());
+            }
+            if let Some(time) = &self.time {
+                let time = format_rfc3339(time)
+                    .ok_or_else(|| CloudEventError::InvalidAttribute(String::from("time")))?;
+                map.insert("time".into(), time.into());
+            }
+            for (name, value) in self.extensions.iter() {
+                map.insert(name.clone(), extension_to_json(name, value)?);
+            }
+
+            let content_type = self.data_content_type.as_deref();
+            match &self.data {
+                Some(EventData::Binary(bytes)) if is_json(content_type) => {
+                    map.insert("data".into(), serde_json::from_slice(bytes)?);
+                }
+                Some(EventData::Binary(bytes)) => match std::str::from_utf8(bytes) {
+                    Ok(text) if is_text(content_type) => {
+                        map.insert("data".into(), text.into());
+                    }
+                    _ => {
+                        map.insert("data_base64".into(), STANDARD.encode(bytes).into());
+                    }
+                },
+                Some(EventData::Value(value)) => {
+                    map.insert("data".into(), serde_json::to_value(value)?);
+                }
+                None => {}
+            }
+            serde_json::to_vec(&map).map_err(Into::into)
+        }
+
+        /// Decodes an event in the JSON event format
+        ///
+        /// The `data` member is kept as the bytes of its JSON encoding if the `datacontenttype`
+        /// is JSON, and as the bytes of the string otherwise.
+        pub fn from_json(bytes: &[u8]) -> Result<Self, CloudEventError> {
+            let mut map: Map<String, JsonValue> = serde_json::from_slice(bytes)?;
+            let mut take_string = |name: &'static str| match map.remove(name) {
+                Some(JsonValue::String(value)) => Ok(Some(value)),
+                Some(JsonValue::Null) | None => Ok(None),
+                Some(_) => Err(CloudEventError::InvalidAttribute(name.to_string())),
+            };
+            let spec_version = take_string("specversion")?
+                .ok_or(CloudEventError::MissingAttribute("specversion"))?;
+            check_spec_version(&spec_version)?;
+            let id = take_string("id")?.ok_or(CloudEventError::MissingAttribute("id"))?;
+            let source =
+                take_string("source")?.ok_or(CloudEventError::MissingAttribute("source"))?;
+            let ty = take_string("type")?.ok_or(CloudEventError::MissingAttribute("type"))?;
+            let data_content_type = take_string("datacontenttype")?;
+            let data_schema = take_string("dataschema")?;
+            let subject = take_string("subject")?;
+            let time = take_string("time")?
+                .map(|time| {
+                    parse_rfc3339(&time)
+                        .ok_or_else(|| CloudEventError::InvalidAttribute(String::from("time")))
+                })
+                .transpose()?;
+            let data_base64 = take_string("data_base64")?;
+
+            let data = match (map.remove("data"), data_base64) {
+                (Some(_), Some(_)) => {
+                    return Err(CloudEventError::InvalidAttribute(String::from("data")))
+                }
+                (_, Some(data_base64)) => Some(EventData::Binary(STANDARD.decode(data_base64)?)),
+                (Some(JsonValue::Null), None) | (None, None) => None,
+                (Some(JsonValue::String(text)), None) if !is_json(data_content_type.as_deref()) => {
+                    Some(EventData::Binary(text.into_bytes()))
+                }
+                (Some(data), None) => Some(EventData::Binary(serde_json::to_vec(&data)?)),
+            };
+
+            let mut extensions = OrderedMap::new();
+            for (name, value) in map {
+                let value = extension_from_json(&name, value)?;
+                extensions.insert(name, value);
+            }
+
+            Ok(Self {
+                id,
+                source,
+                spec_version,
+                ty,
+                data_content_type,
+                data_schema,
+                subject,
+                time,
+                extensions,
+                data,
+            })
+        }
+
+        /// Maps the event to a message in structured mode with the JSON event format
+        pub fn to_structured_message(&self) -> Result<Message<Data>, CloudEventError> {
+            let properties = Properties {
+                content_type: Some(Symbol::from(STRUCTURED_JSON_CONTENT_TYPE)),
+                ..Default::default()
+            };
+            Ok(Message::builder()
+                .properties(properties)
+                .data(self.to_json()?)
+                .build())
+        }
+    }
+
+    fn extension_to_json(name: &str, value: &SimpleValue) -> Result<JsonValue, CloudEventError> {
+        let value = match value {
+            SimpleValue::Bool(value) => JsonValue::from(*value),
+            SimpleValue::Byte(value) => JsonValue::from(*value),
+            SimpleValue::Short(value) => JsonValue::from(*value),
+            SimpleValue::Int(value) => JsonValue::from(*value),
+            SimpleValue::Long(value) => JsonValue::from(*value),
+            SimpleValue::Ubyte(value) => JsonValue::from(*value),
+            SimpleValue::Ushort(value) => JsonValue::from(*value),
+            SimpleValue::Uint(value) => JsonValue::from(*value),
+            SimpleValue::Ulong(value) => JsonValue::from(*value),
+            SimpleValue::String(value) => JsonValue::from(value.clone()),
+            SimpleValue::Symbol(value) => JsonValue::from(value.0.clone()),
+            SimpleValue::Binary(value) => JsonValue::from(STANDARD.encode(value.as_slice())),
+            SimpleValue::Timestamp(value) => match format_rfc3339(value) {
+                Some(value) => JsonValue::from(value),
+                None => return Err(CloudEventError::InvalidAttribute(name.to_string())),
+            },
+            _ => return Err(CloudEventError::InvalidAttribute(name.to_string())),
+        };
+        Ok(value)
+    }
+
+    fn extension_from_json(name: &str, value: JsonValue) -> Result<SimpleValue, CloudEventError> {
+        let value = match value {
+            JsonValue::Bool(value) => SimpleValue::Bool(value),
+            JsonValue::Number(number) => match number.as_i64().map(i32::try_from) {
+                Some(Ok(value)) => SimpleValue::Int(value),
+                Some(Err(_)) => SimpleValue::Long(number.as_i64().unwrap_or_default()),
+                None => return Err(CloudEventError::InvalidAttribute(name.to_string())),
+            },
+            JsonValue::String(value) => SimpleValue::String(value),
+            _ => return Err(CloudEventError::InvalidAttribute(name.to_string())),
+        };
+        Ok(value)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                  RFC 3339                                  */
+/* -------------------------------------------------------------------------- */
+
+/// Formats the timestamp in UTC, eg. `2018-04-05T17:31:00.123Z`. Returns `None` if the year is
+/// not within 0000 and 9999
+#[cfg_attr(not(feature = "json"), allow(dead_code))]
+fn format_rfc3339(timestamp: &Timestamp) -> Option<String> {
+    let nanos = i128::from(timestamp.milliseconds()) * 1_000_000;
+    OffsetDateTime::from_unix_timestamp_nanos(nanos)
+        .ok()?
+        .format(&Rfc3339)
+        .ok()
+}
+
+/// Parses a RFC 3339 date-time. The fraction of a second is truncated to milliseconds
+fn parse_rfc3339(s: &str) -> Option<Timestamp> {
+    let date_time = OffsetDateTime::parse(s, &Rfc3339).ok()?;
+    let millis = date_time.unix_timestamp_nanos().div_euclid(1_000_000);
+    i64::try_from(millis).ok().map(Timestamp::from_milliseconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{
+        messaging::{
+            message::__private::{Deserializable, Serializable},
+            Body, Message,
+        },
+        primitives::{SimpleValue, Timestamp, Value},
+    };
+    use serde_amqp::{from_slice, to_vec};
+
+    use super::{format_rfc3339, parse_rfc3339, CloudEvent, CloudEventError, EventData};
+
+    fn over_the_wire(message: Message<Body<Value>>) -> Message<Body<Value>> {
+        let buf = to_vec(&Serializable(message)).unwrap();
+        let message: Deserializable<Message<Body<Value>>> = from_slice(&buf).unwrap();
+        message.0
+    }
+
+    #[test]
+    fn rfc3339() {
+        let timestamp = parse_rfc3339("2018-04-05T17:31:00Z").unwrap();
+        assert_eq!(timestamp.milliseconds(), 1_522_949_460_000);
+        assert_eq!(
+            format_rfc3339(&timestamp),
+            Some(String::from("2018-04-05T17:31:00Z"))
+        );
+
+        let timestamp = parse_rfc3339("2018-04-05T19:31:00.1234+02:00").unwrap();
+        assert_eq!(timestamp.milliseconds(), 1_522_949_460_123);
+        assert_eq!(
+            format_rfc3339(&timestamp),
+            Some(String::from("2018-04-05T17:31:00.123Z"))
+        );
+
+        let timestamp = Timestamp::from_milliseconds(-1);
+        assert_eq!(
+            format_rfc3339(&timestamp),
+            Some(String::from("1969-12-31T23:59:59.999Z"))
+        );
+        assert_eq!(parse_rfc3339("1969-12-31T23:59:59.999Z"), Some(timestamp));
+
+        assert_eq!(parse_rfc3339("2018-04-05"), None);
+        assert_eq!(parse_rfc3339("2018-13-05T17:31:00Z"), None);
+        assert_eq!(parse_rfc3339("2024-02-31T00:00:00Z"), None);
+
+        let timestamp = Timestamp::from_milliseconds(i64::MAX);
+        assert_eq!(format_rfc3339(&timestamp), None);
+    }
+
+    #[test]
+    fn binary_mode_round_trip() {
+        let event = CloudEvent::new("A234-1234-1234", "/mycontext", "com.example.someevent")
+            .with_time(Timestamp::from_milliseconds(1_522_949_460_000))
+            .with_subject("subject")
+            .with_extension("traceparent", "00-abc-def-01")
+            .with_extension("priority", 3)
+            .with_data("text/plain", b"hello".to_vec());
+
+        let message = over_the_wire(event.clone().into_binary_message());
+        let properties = message.application_properties.as_ref().unwrap();
+        assert_eq!(
+            properties.get("cloudEvents:type"),
+            Some(&SimpleValue::String(String::from("com.example.someevent")))
+        );
+        assert_eq!(
+            properties.get("cloudEvents:time"),
+            Some(&SimpleValue::Timestamp(Timestamp::from_milliseconds(
+                1_522_949_460_000
+            )))
+        );
+        assert!(message.body.is_data());
+
+        let decoded = CloudEvent::from_message(message).unwrap();
+        assert_eq!(decoded, event);
+
+        let event = CloudEvent::new("1", "/source", "type").with_value(Value::Int(1));
+        let message = over_the_wire(event.clone().into_binary_message());
+        assert!(message.body.is_value());
+        assert_eq!(CloudEvent::from_message(message).unwrap(), event);
+    }
+
+    #[test]
+    fn binary_mode_requires_attributes() {
+        let mut message = CloudEvent::new("1", "/source", "type").into_binary_message();
+        message
+            .application_properties
+            .as_mut()
+            .unwrap()
+            .swap_remove("cloudEvents:source");
+        let err = CloudEvent::from_binary_message(message).unwrap_err();
+        assert!(matches!(err, CloudEventError::MissingAttribute("source")));
+
+        // The prefix used by JMS clients is accepted
+        let mut message = CloudEvent::new("1", "/source", "type").into_binary_message();
+        let properties = message.application_properties.as_mut().unwrap();
+        let spec_version = properties.swap_remove("cloudEvents:specversion").unwrap();
+        properties.insert(String::from("cloudEvents_specversion"), spec_version);
+        let event = CloudEvent::from_binary_message(message).unwrap();
+        assert_eq!(event.data, None::<EventData>);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn structured_mode_round_trip() {
+        use fe2o3_amqp_types::messaging::Data;
+
+        let event = CloudEvent::new("1", "/source", "com.example.json")
+            .with_time(Timestamp::from_milliseconds(1_522_949_460_000))
+            .with_extension("priority", 3)
+            .with_data("application/json", br#"{"celsius":21.5}"#.to_vec());
+        let message = event.to_structured_message().unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&message.body.0).unwrap();
+        assert_eq!(json["data"]["celsius"], 21.5);
+        assert_eq!(json["time"], "2018-04-05T17:31:00Z");
+        assert_eq!(json["priority"], 3);
+
+        let message = message.map_body(|Data(data)| Body::Data(vec![Data(data)].into()));
+        let decoded = CloudEvent::from_message(over_the_wire(message)).unwrap();
+        assert_eq!(decoded, event);
+
+        let event = CloudEvent::new("2", "/source", "com.example.binary")
+            .with_data("application/octet-stream", vec![0u8, 159, 146, 150]);
+        let json: serde_json::Value = serde_json::from_slice(&event.to_json().unwrap()).unwrap();
+        assert_eq!(json["data_base64"], "AJ+Slg==");
+        assert_eq!(
+            CloudEvent::from_json(&event.to_json().unwrap()).unwrap(),
+            event
+        );
+    }
+}
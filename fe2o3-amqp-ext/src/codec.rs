@@ -0,0 +1,868 @@
+//! Content-type aware encoding of typed payloads into `Data` message bodies
+//!
+//! An [`Encoder`] turns a value into bytes and a [`Decoder`] turns bytes back into a value. Both
+//! declare the MIME type that is carried by the `content-type` property of the message, and
+//! optionally the `content-encoding` when the bytes are compressed by a [`ContentEncoding`]
+//! (see [`Compressed`]).
+//!
+//! Because the content type and content encoding are part of the properties rather than the
+//! body, a payload is wrapped in [`Encoded`], which converts into a whole `Message<Data>` and can
+//! therefore be passed directly to `Sender::send`. [`Encoded`] also implements `FromBody` so that
+//! a message can be received with `Receiver::recv::<Encoded>()`, and [`decode_message`] checks
+//! the content type and content encoding of the received message before decoding.
+//!
+//! | Codec | Content type | Payload |
+//! |-------|--------------|---------|
+//! | [`Json`] | `application/json` | `T: Serialize + DeserializeOwned`, requires feature `"json"` |
+//! | [`Protobuf`] | `application/x-protobuf` | `T: prost::Message + Default`, requires feature `"prost"` |
+//! | [`Text`] | `text/plain; charset=utf-8` | `String`/`str` |
+//! | [`OctetStream`] | `application/octet-stream` | `Vec<u8>`/`[u8]` |
+//!
+//! | Content encoding | Name | Feature |
+//! |------------------|------|---------|
+//! | [`Gzip`] | `gzip` | `"gzip"` |
+//! | [`Deflate`] | `deflate` | `"deflate"` |
+//!
+//! Other formats and compression schemes are added by implementing the traits.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp_ext::codec::{decode_message, Compressed, Encoded, Gzip, Json};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Order {
+//!     id: u64,
+//! }
+//!
+//! let codec = Compressed::new(Json, Gzip::default());
+//! sender.send(Encoded::new(&codec, &Order { id: 1 })?).await?;
+//!
+//! let delivery = receiver.recv::<Encoded>().await?;
+//! let order: Order = decode_message(&codec, delivery.message())?;
+//! receiver.accept(&delivery).await?;
+//! ```
+//!
+//! # Implementing a codec and a content encoding
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp_ext::codec::{BodyCodec, CodecError, ContentEncoding, Decoder, Encoder};
+//!
+//! struct Cbor;
+//!
+//! impl BodyCodec for Cbor {
+//!     fn content_type(&self) -> &str {
+//!         "application/cbor"
+//!     }
+//! }
+//!
+//! impl<T: Serialize> Encoder<T> for Cbor {
+//!     fn encode(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+//!         let mut buf = Vec::new();
+//!         ciborium::into_writer(value, &mut buf).map_err(CodecError::custom)?;
+//!         Ok(buf)
+//!     }
+//! }
+//!
+//! impl<T: DeserializeOwned> Decoder<T> for Cbor {
+//!     fn decode(&self, bytes: &[u8]) -> Result<T, CodecError> {
+//!         ciborium::from_reader(bytes).map_err(CodecError::custom)
+//!     }
+//! }
+//!
+//! struct Brotli;
+//!
+//! impl ContentEncoding for Brotli {
+//!     fn name(&self) -> &str {
+//!         "br"
+//!     }
+//!
+//!     fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+//!         // ...
+//!     }
+//!
+//!     fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+//!         // ...
+//!     }
+//! }
+//! ```
+
+#[cfg(any(feature = "gzip", feature = "deflate"))]
+use std::io::{Read, Write};
+
+use fe2o3_amqp_types::{
+    messaging::{Data, FromBody, FromEmptyBody, Message, Properties},
+    primitives::Symbol,
+};
+
+/// Default maximum length of a payload decompressed by [`Gzip`] or [`Deflate`]
+#[cfg(any(feature = "gzip", feature = "deflate"))]
+pub const DEFAULT_MAX_DECOMPRESSED_LEN: usize = 64 * 1024 * 1024;
+
+/// Error with encoding or decoding a payload
+#[derive(Debug, thiserror::Error)]
+pub enum CodecError {
+    /// The content type of the message is not the one of the decoder
+    #[error("Expecting content type {expected:?}, found {found:?}")]
+    ContentTypeMismatch {
+        /// The content type of the decoder
+        expected: String,
+
+        /// The content type of the message
+        found: Option<String>,
+    },
+
+    /// The content encoding of the message is not the one of the decoder
+    #[error("Expecting content encoding {expected:?}, found {found:?}")]
+    ContentEncodingMismatch {
+        /// The content encoding of the decoder
+        expected: Option<String>,
+
+        /// The content encoding of the message
+        found: Option<String>,
+    },
+
+    /// Error with the UTF-8 encoding of a text payload
+    #[error(transparent)]
+    Utf8(#[from] std::string::FromUtf8Error),
+
+    /// IO error, eg. from a compression library
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// Error with the JSON payload
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// Error with the protobuf payload
+    #[cfg(feature = "prost")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
+    #[error(transparent)]
+    Protobuf(#[from] prost::DecodeError),
+
+    /// The decompressed payload is longer than the maximum length allowed by the content encoding
+    #[error("Decompressed payload exceeds the maximum length of {max_len} bytes")]
+    PayloadTooLarge {
+        /// The maximum length of the decompressed payload
+        max_len: usize,
+    },
+
+    /// Error from a codec or content encoding implemented outside of this crate
+    #[error(transparent)]
+    Custom(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl CodecError {
+    /// Wraps an error from a codec or content encoding implemented outside of this crate
+    pub fn custom(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self::Custom(error.into())
+    }
+}
+
+/// The content type and content encoding of an encoded payload
+pub trait BodyCodec {
+    /// The MIME type of the payload, which is set as the `content-type` property
+    fn content_type(&self) -> &str;
+
+    /// The content encoding applied to the payload, which is set as the `content-encoding`
+    /// property
+    fn content_encoding(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl<C> BodyCodec for &C
+where
+    C: BodyCodec + ?Sized,
+{
+    fn content_type(&self) -> &str {
+        (**self).content_type()
+    }
+
+    fn content_encoding(&self) -> Option<&str> {
+        (**self).content_encoding()
+    }
+}
+
+/// Encodes a value of type `T` into bytes
+pub trait Encoder<T: ?Sized>: BodyCodec {
+    /// Encodes the value
+    fn encode(&self, value: &T) -> Result<Vec<u8>, CodecError>;
+}
+
+/// Decodes a value of type `T` from bytes
+pub trait Decoder<T>: BodyCodec {
+    /// Decodes the value
+    fn decode(&self, bytes: &[u8]) -> Result<T, CodecError>;
+}
+
+/// A compression scheme applied on top of an encoded payload
+pub trait ContentEncoding {
+    /// The name of the encoding (eg. `"gzip"`), which is set as the `content-encoding` property
+    fn name(&self) -> &str;
+
+    /// Compresses the bytes
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError>;
+
+    /// Decompresses the bytes
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError>;
+}
+
+/// A body section that carries an encoded payload
+pub trait EncodablePayload {
+    /// The encoded bytes
+    fn payload(&self) -> &[u8];
+}
+
+impl EncodablePayload for Data {
+    fn payload(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+/// A payload encoded by a codec, which is sent and received as a `Data` body section
+///
+/// `Encoded` converts into a `Message<Data>` with the `content-type` and `content-encoding`
+/// properties of the codec, so it can be passed directly to `Sender::send`. It also implements
+/// `FromBody` so that a message can be received with `Receiver::recv::<Encoded>()`. The content
+/// type and content encoding of a received payload are carried by the properties of the message
+/// rather than the body, and are checked by [`decode_message`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Encoded {
+    data: Data,
+    content_type: Option<Symbol>,
+    content_encoding: Option<Symbol>,
+}
+
+impl Encoded {
+    /// Encodes `value` with `encoder`
+    pub fn new<T, E>(encoder: &E, value: &T) -> Result<Self, CodecError>
+    where
+        T: ?Sized,
+        E: Encoder<T> + ?Sized,
+    {
+        let bytes = encoder.encode(value)?;
+        Ok(Self {
+            data: Data::from(bytes),
+            content_type: Some(Symbol::from(encoder.content_type())),
+            content_encoding: encoder.content_encoding().map(Symbol::from),
+        })
+    }
+
+    /// The content type of the encoder
+    ///
+    /// This is `None` for a received payload, whose content type is found in the properties of
+    /// the message.
+    pub fn content_type(&self) -> Option<&Symbol> {
+        self.content_type.as_ref()
+    }
+
+    /// The content encoding of the encoder
+    ///
+    /// This is `None` for a received payload, whose content encoding is found in the properties
+    /// of the message.
+    pub fn content_encoding(&self) -> Option<&Symbol> {
+        self.content_encoding.as_ref()
+    }
+
+    /// Consumes the wrapper and returns the `Data` body section
+    pub fn into_data(self) -> Data {
+        self.data
+    }
+}
+
+impl EncodablePayload for Encoded {
+    fn payload(&self) -> &[u8] {
+        self.data.payload()
+    }
+}
+
+impl From<Encoded> for Message<Data> {
+    fn from(value: Encoded) -> Self {
+        let builder = Message::builder();
+        let builder = match (value.content_type, value.content_encoding) {
+            (None, None) => builder,
+            (content_type, content_encoding) => builder.properties(Properties {
+                content_type,
+                content_encoding,
+                ..Default::default()
+            }),
+        };
+        builder.data(value.data).build()
+    }
+}
+
+impl FromBody<'_> for Encoded {
+    type Body = Data;
+
+    fn from_body(deserializable: Self::Body) -> Self {
+        Self {
+            data: deserializable,
+            content_type: None,
+            content_encoding: None,
+        }
+    }
+}
+
+impl FromEmptyBody for Encoded {}
+
+/// Encodes `value` into a message with a `Data` body and the `content-type` and
+/// `content-encoding` properties of `encoder`
+pub fn encode_message<T, E>(encoder: &E, value: &T) -> Result<Message<Data>, CodecError>
+where
+    T: ?Sized,
+    E: Encoder<T> + ?Sized,
+{
+    Encoded::new(encoder, value).map(Into::into)
+}
+
+/// Decodes the body of `message` (either [`Data`] or [`Encoded`]) after checking its
+/// `content-type` and `content-encoding` properties against the ones of `decoder`
+///
+/// The content types are compared by their essence, ie. ignoring the parameters (eg.
+/// `charset`) and case. A message without a content type is accepted by any decoder.
+pub fn decode_message<T, D>(
+    decoder: &D,
+    message: &Message<impl EncodablePayload>,
+) -> Result<T, CodecError>
+where
+    D: Decoder<T> + ?Sized,
+{
+    let properties = message.properties.as_ref();
+    let content_type = properties.and_then(|p| p.content_type.as_ref());
+    if let Some(content_type) = content_type {
+        if essence(content_type.as_str()) != essence(decoder.content_type()) {
+            return Err(CodecError::ContentTypeMismatch {
+                expected: decoder.content_type().to_string(),
+                found: Some(content_type.0.clone()),
+            });
+        }
+    }
+
+    let content_encoding = properties.and_then(|p| p.content_encoding.as_ref());
+    let is_same_encoding = match (content_encoding, decoder.content_encoding()) {
+        (Some(found), Some(expected)) => found.as_str().eq_ignore_ascii_case(expected),
+        (None, None) => true,
+        _ => false,
+    };
+    if !is_same_encoding {
+        return Err(CodecError::ContentEncodingMismatch {
+            expected: decoder.content_encoding().map(ToString::to_string),
+            found: content_encoding.map(|s| s.0.clone()),
+        });
+    }
+
+    decoder.decode(message.body.payload())
+}
+
+/// The content type without its parameters in lower case
+pub(crate) fn essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// A codec whose payload is compressed with a [`ContentEncoding`]
+#[derive(Debug, Clone)]
+pub struct Compressed<C, E> {
+    codec: C,
+    encoding: E,
+}
+
+impl<C, E> Compressed<C, E> {
+    /// Compresses the payload of `codec` with `encoding`
+    pub fn new(codec: C, encoding: E) -> Self {
+        Self { codec, encoding }
+    }
+}
+
+impl<C, E> BodyCodec for Compressed<C, E>
+where
+    C: BodyCodec,
+    E: ContentEncoding,
+{
+    fn content_type(&self) -> &str {
+        self.codec.content_type()
+    }
+
+    fn content_encoding(&self) -> Option<&str> {
+        Some(self.encoding.name())
+    }
+}
+
+impl<T, C, E> Encoder<T> for Compressed<C, E>
+where
+    T: ?Sized,
+    C: Encoder<T>,
+    E: ContentEncoding,
+{
+    fn encode(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        let bytes = self.codec.encode(value)?;
+        self.encoding.compress(&bytes)
+    }
+}
+
+impl<T, C, E> Decoder<T> for Compressed<C, E>
+where
+    C: Decoder<T>,
+    E: ContentEncoding,
+{
+    fn decode(&self, bytes: &[u8]) -> Result<T, CodecError> {
+        let bytes = self.encoding.decompress(bytes)?;
+        self.codec.decode(&bytes)
+    }
+}
+
+/// Gzip (RFC 1952) content encoding
+#[cfg(feature = "gzip")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+#[derive(Debug, Clone, Copy)]
+pub struct Gzip {
+    level: flate2::Compression,
+    max_decompressed_len: usize,
+}
+
+#[cfg(feature = "gzip")]
+impl Default for Gzip {
+    fn default() -> Self {
+        Self {
+            level: flate2::Compression::default(),
+            max_decompressed_len: DEFAULT_MAX_DECOMPRESSED_LEN,
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl Gzip {
+    /// Creates a gzip content encoding with a compression level between 0 and 9
+    pub fn new(level: u32) -> Self {
+        Self {
+            level: flate2::Compression::new(level),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the maximum length of a decompressed payload, which defaults to
+    /// [`DEFAULT_MAX_DECOMPRESSED_LEN`]
+    pub fn max_decompressed_len(mut self, max_len: usize) -> Self {
+        self.max_decompressed_len = max_len;
+        self
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl ContentEncoding for Gzip {
+    fn name(&self) -> &str {
+        "gzip"
+    }
+
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), self.level);
+        encoder.write_all(bytes)?;
+        encoder.finish().map_err(Into::into)
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+        read_to_end_bounded(
+            flate2::read::GzDecoder::new(bytes),
+            self.max_decompressed_len,
+        )
+    }
+}
+
+/// Deflate content encoding
+///
+/// As in HTTP, the `"deflate"` content encoding is the zlib format (RFC 1950) wrapping the
+/// deflate compressed data.
+#[cfg(feature = "deflate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+#[derive(Debug, Clone, Copy)]
+pub struct Deflate {
+    level: flate2::Compression,
+    max_decompressed_len: usize,
+}
+
+#[cfg(feature = "deflate")]
+impl Default for Deflate {
+    fn default() -> Self {
+        Self {
+            level: flate2::Compression::default(),
+            max_decompressed_len: DEFAULT_MAX_DECOMPRESSED_LEN,
+        }
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl Deflate {
+    /// Creates a deflate content encoding with a compression level between 0 and 9
+    pub fn new(level: u32) -> Self {
+        Self {
+            level: flate2::Compression::new(level),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the maximum length of a decompressed payload, which defaults to
+    /// [`DEFAULT_MAX_DECOMPRESSED_LEN`]
+    pub fn max_decompressed_len(mut self, max_len: usize) -> Self {
+        self.max_decompressed_len = max_len;
+        self
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl ContentEncoding for Deflate {
+    fn name(&self) -> &str {
+        "deflate"
+    }
+
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), self.level);
+        encoder.write_all(bytes)?;
+        encoder.finish().map_err(Into::into)
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+        read_to_end_bounded(
+            flate2::read::ZlibDecoder::new(bytes),
+            self.max_decompressed_len,
+        )
+    }
+}
+
+/// Reads at most `max_len` bytes so that a small compressed payload cannot expand without bound
+#[cfg(any(feature = "gzip", feature = "deflate"))]
+fn read_to_end_bounded(reader: impl Read, max_len: usize) -> Result<Vec<u8>, CodecError> {
+    let limit = u64::try_from(max_len).unwrap_or(u64::MAX).saturating_add(1);
+    let mut buf = Vec::new();
+    reader.take(limit).read_to_end(&mut buf)?;
+    if buf.len() > max_len {
+        return Err(CodecError::PayloadTooLarge { max_len });
+    }
+    Ok(buf)
+}
+
+/// JSON codec
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json;
+
+#[cfg(feature = "json")]
+impl BodyCodec for Json {
+    fn content_type(&self) -> &str {
+        "application/json"
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T> Encoder<T> for Json
+where
+    T: serde_amqp::serde::Serialize + ?Sized,
+{
+    fn encode(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(value).map_err(Into::into)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T> Decoder<T> for Json
+where
+    T: serde_amqp::serde::de::DeserializeOwned,
+{
+    fn decode(&self, bytes: &[u8]) -> Result<T, CodecError> {
+        serde_json::from_slice(bytes).map_err(Into::into)
+    }
+}
+
+/// Protobuf codec
+#[cfg(feature = "prost")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Protobuf;
+
+#[cfg(feature = "prost")]
+impl BodyCodec for Protobuf {
+    fn content_type(&self) -> &str {
+        "application/x-protobuf"
+    }
+}
+
+#[cfg(feature = "prost")]
+impl<T> Encoder<T> for Protobuf
+where
+    T: prost::Message,
+{
+    fn encode(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        Ok(value.encode_to_vec())
+    }
+}
+
+#[cfg(feature = "prost")]
+impl<T> Decoder<T> for Protobuf
+where
+    T: prost::Message + Default,
+{
+    fn decode(&self, bytes: &[u8]) -> Result<T, CodecError> {
+        T::decode(bytes).map_err(Into::into)
+    }
+}
+
+/// UTF-8 text codec
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Text;
+
+impl BodyCodec for Text {
+    fn content_type(&self) -> &str {
+        "text/plain; charset=utf-8"
+    }
+}
+
+impl Encoder<str> for Text {
+    fn encode(&self, value: &str) -> Result<Vec<u8>, CodecError> {
+        Ok(value.as_bytes().to_vec())
+    }
+}
+
+impl Encoder<String> for Text {
+    fn encode(&self, value: &String) -> Result<Vec<u8>, CodecError> {
+        Ok(value.as_bytes().to_vec())
+    }
+}
+
+impl Decoder<String> for Text {
+    fn decode(&self, bytes: &[u8]) -> Result<String, CodecError> {
+        String::from_utf8(bytes.to_vec()).map_err(Into::into)
+    }
+}
+
+/// Codec of opaque binary payloads
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OctetStream;
+
+impl BodyCodec for OctetStream {
+    fn content_type(&self) -> &str {
+        "application/octet-stream"
+    }
+}
+
+impl Encoder<[u8]> for OctetStream {
+    fn encode(&self, value: &[u8]) -> Result<Vec<u8>, CodecError> {
+        Ok(value.to_vec())
+    }
+}
+
+impl Encoder<Vec<u8>> for OctetStream {
+    fn encode(&self, value: &Vec<u8>) -> Result<Vec<u8>, CodecError> {
+        Ok(value.clone())
+    }
+}
+
+impl Decoder<Vec<u8>> for OctetStream {
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+        Ok(bytes.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{
+        messaging::{message::__private::Deserializable, message::__private::Serializable, Data},
+        primitives::Symbol,
+    };
+    use serde_amqp::{from_slice, to_vec};
+
+    use super::{
+        decode_message, encode_message, CodecError, Compressed, ContentEncoding, Decoder, Encoded,
+        OctetStream, Text,
+    };
+
+    /// Reverses the bytes, standing in for a real compression scheme
+    struct Reverse;
+
+    impl ContentEncoding for Reverse {
+        fn name(&self) -> &str {
+            "x-reverse"
+        }
+
+        fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+            Ok(bytes.iter().rev().copied().collect())
+        }
+
+        fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+            self.compress(bytes)
+        }
+    }
+
+    #[test]
+    fn text_round_trip_over_the_wire() {
+        let message = encode_message(&Text, "hello").unwrap();
+        let content_type = message
+            .properties
+            .as_ref()
+            .and_then(|p| p.content_type.clone());
+        assert_eq!(
+            content_type,
+            Some(Symbol::from("text/plain; charset=utf-8"))
+        );
+
+        let buf = to_vec(&Serializable(message)).unwrap();
+        let message: Deserializable<fe2o3_amqp_types::messaging::Message<Data>> =
+            from_slice(&buf).unwrap();
+        let text: String = decode_message(&Text, &message.0).unwrap();
+        assert_eq!(text, "hello");
+
+        let err = decode_message::<Vec<u8>, _>(&OctetStream, &message.0).unwrap_err();
+        assert!(matches!(err, CodecError::ContentTypeMismatch { .. }));
+    }
+
+    #[test]
+    fn compressed_payload() {
+        let codec = Compressed::new(Text, Reverse);
+        let message = encode_message(&codec, "abc").unwrap();
+        assert_eq!(&message.body.0[..], b"cba");
+        let content_encoding = message
+            .properties
+            .as_ref()
+            .and_then(|p| p.content_encoding.clone());
+        assert_eq!(content_encoding, Some(Symbol::from("x-reverse")));
+
+        let text: String = decode_message(&codec, &message).unwrap();
+        assert_eq!(text, "abc");
+
+        // The decoder without the content encoding doesn't accept the compressed payload
+        let err = decode_message::<String, _>(&Text, &message).unwrap_err();
+        assert!(matches!(err, CodecError::ContentEncodingMismatch { .. }));
+        assert_eq!(
+            Decoder::<String>::decode(&codec, b"olleh").unwrap(),
+            "hello"
+        );
+    }
+
+    #[test]
+    fn encoded_round_trip_over_the_wire() {
+        let encoded = Encoded::new(&Compressed::new(Text, Reverse), "hello").unwrap();
+        let message: fe2o3_amqp_types::messaging::Message<Data> = encoded.into();
+        let buf = to_vec(&Serializable(message)).unwrap();
+
+        let message: Deserializable<fe2o3_amqp_types::messaging::Message<Encoded>> =
+            from_slice(&buf).unwrap();
+        let properties = message.0.properties.as_ref().unwrap();
+        assert_eq!(
+            properties.content_type,
+            Some(Symbol::from("text/plain; charset=utf-8"))
+        );
+        assert_eq!(properties.content_encoding, Some(Symbol::from("x-reverse")));
+        assert_eq!(message.0.body.content_type(), None);
+
+        let text: String = decode_message(&Compressed::new(Text, Reverse), &message.0).unwrap();
+        assert_eq!(text, "hello");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_round_trip() {
+        use super::Gzip;
+
+        let codec = Compressed::new(Text, Gzip::default());
+        let message = encode_message(&codec, "hello hello hello").unwrap();
+        assert_eq!(&message.body.0[..2], &[0x1f, 0x8b]);
+        let text: String = decode_message(&codec, &message).unwrap();
+        assert_eq!(text, "hello hello hello");
+
+        let gzip = Gzip::default().max_decompressed_len(16);
+        let err = gzip.decompress(&message.body.0).unwrap_err();
+        assert!(matches!(err, CodecError::PayloadTooLarge { max_len: 16 }));
+        assert_eq!(
+            Gzip::default()
+                .max_decompressed_len(17)
+                .decompress(&message.body.0)
+                .unwrap(),
+            b"hello hello hello"
+        );
+    }
+
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn deflate_round_trip() {
+        use super::Deflate;
+
+        let codec = Compressed::new(OctetStream, Deflate::new(9));
+        let payload = vec![7u8; 1024];
+        let message = encode_message(&codec, &payload).unwrap();
+        assert!(message.body.0.len() < payload.len());
+        let decoded: Vec<u8> = decode_message(&codec, &message).unwrap();
+        assert_eq!(decoded, payload);
+
+        let err = Deflate::default()
+            .max_decompressed_len(1023)
+            .decompress(&message.body.0)
+            .unwrap_err();
+        assert!(matches!(err, CodecError::PayloadTooLarge { max_len: 1023 }));
+    }
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn protobuf_round_trip() {
+        use super::Protobuf;
+
+        #[derive(Clone, PartialEq, prost::Message)]
+        struct Order {
+            #[prost(uint64, tag = "1")]
+            id: u64,
+            #[prost(string, repeated, tag = "2")]
+            items: Vec<String>,
+        }
+
+        let order = Order {
+            id: 1,
+            items: vec![String::from("widget")],
+        };
+        let message = encode_message(&Protobuf, &order).unwrap();
+        let content_type = message
+            .properties
+            .as_ref()
+            .and_then(|p| p.content_type.clone());
+        assert_eq!(content_type, Some(Symbol::from("application/x-protobuf")));
+
+        let decoded: Order = decode_message(&Protobuf, &message).unwrap();
+        assert_eq!(decoded, order);
+
+        let err = Decoder::<Order>::decode(&Protobuf, &[0x0a]).unwrap_err();
+        assert!(matches!(err, CodecError::Protobuf(_)));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_round_trip() {
+        use serde_amqp::serde::{Deserialize, Serialize};
+
+        use super::Json;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(crate = "serde_amqp::serde")]
+        struct Order {
+            id: u64,
+            items: Vec<String>,
+        }
+
+        let order = Order {
+            id: 1,
+            items: vec![String::from("widget")],
+        };
+        let message = encode_message(&Json, &order).unwrap();
+        assert_eq!(&message.body.0[..], br#"{"id":1,"items":["widget"]}"#);
+
+        let decoded: Order = decode_message(&Json, &message).unwrap();
+        assert_eq!(decoded, order);
+
+        let message = encode_message(&Json, &1).unwrap();
+        let err = decode_message::<Order, _>(&Json, &message).unwrap_err();
+        assert!(matches!(err, CodecError::Json(_)));
+    }
+}
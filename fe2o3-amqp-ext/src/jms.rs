@@ -0,0 +1,604 @@
+//! Mapping of JMS messages to AMQP messages
+//!
+//! This follows the conventions of the AMQP JMS Mapping used by Qpid JMS so that messages can be
+//! exchanged with JMS clients.
+//!
+//! - The JMS message type is carried by the `x-opt-jms-msg-type` message annotation
+//! - The type of the destination in the `to` and `reply-to` properties is carried by the
+//!   `x-opt-jms-dest` and `x-opt-jms-reply-to` message annotations
+//! - The body of each JMS message type is encoded as
+//!
+//! | JMS message type | Wrapper | Body section | Content type |
+//! |------------------|---------|--------------|--------------|
+//! | `TextMessage` | [`TextMessage`] | `amqp-value` holding a string or null | |
+//! | `BytesMessage` | [`BytesMessage`] | `data` | `application/octet-stream` |
+//! | `MapMessage` | [`MapMessage`] | `amqp-value` holding a map with string keys | |
+//! | `StreamMessage` | [`StreamMessage`] | `amqp-sequence` | |
+//! | `ObjectMessage` | [`ObjectMessage`] | `data` holding a serialized Java object | `application/x-java-serialized-object` |
+//!
+//! # Example
+//!
+//! ```rust
+//! use fe2o3_amqp_ext::jms::{JmsBody, JmsDestination, JmsMessageExt, JmsMessageType, TextMessage};
+//!
+//! let mut message = TextMessage::new("hello").into_jms_message();
+//! message.set_jms_destination(JmsDestination::Topic(String::from("prices")));
+//!
+//! assert_eq!(message.jms_message_type(), Some(JmsMessageType::TextMessage));
+//! assert_eq!(
+//!     message.jms_destination(),
+//!     Some(JmsDestination::Topic(String::from("prices")))
+//! );
+//! ```
+
+use fe2o3_amqp_types::{
+    messaging::{
+        annotations::AnnotationKey, AmqpSequence, AmqpValue, Data, FromBody, FromEmptyBody,
+        IntoBody, Message, MessageAnnotations, Properties,
+    },
+    primitives::{BinaryBytes, OrderedMap, Symbol, Value},
+};
+
+/// Message annotation carrying the [`JmsMessageType`]
+pub const JMS_MSG_TYPE: &str = "x-opt-jms-msg-type";
+
+/// Message annotation carrying the [`JmsDestinationType`] of the `to` property
+pub const JMS_DEST: &str = "x-opt-jms-dest";
+
+/// Message annotation carrying the [`JmsDestinationType`] of the `reply-to` property
+pub const JMS_REPLY_TO: &str = "x-opt-jms-reply-to";
+
+/// Content type of the body of a [`BytesMessage`]
+pub const OCTET_STREAM_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Content type of the body of an [`ObjectMessage`]
+pub const SERIALIZED_JAVA_OBJECT_CONTENT_TYPE: &str = "application/x-java-serialized-object";
+
+/// The type of a JMS message, carried by the [`JMS_MSG_TYPE`] message annotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JmsMessageType {
+    /// `Message`, which has no body
+    Message,
+
+    /// `ObjectMessage`
+    ObjectMessage,
+
+    /// `MapMessage`
+    MapMessage,
+
+    /// `BytesMessage`
+    BytesMessage,
+
+    /// `StreamMessage`
+    StreamMessage,
+
+    /// `TextMessage`
+    TextMessage,
+}
+
+impl JmsMessageType {
+    /// The value of the message annotation
+    pub fn code(&self) -> i8 {
+        match self {
+            JmsMessageType::Message => 0,
+            JmsMessageType::ObjectMessage => 1,
+            JmsMessageType::MapMessage => 2,
+            JmsMessageType::BytesMessage => 3,
+            JmsMessageType::StreamMessage => 4,
+            JmsMessageType::TextMessage => 5,
+        }
+    }
+
+    /// Returns the message type with the value of the message annotation
+    pub fn from_code(code: i8) -> Option<Self> {
+        match code {
+            0 => Some(JmsMessageType::Message),
+            1 => Some(JmsMessageType::ObjectMessage),
+            2 => Some(JmsMessageType::MapMessage),
+            3 => Some(JmsMessageType::BytesMessage),
+            4 => Some(JmsMessageType::StreamMessage),
+            5 => Some(JmsMessageType::TextMessage),
+            _ => None,
+        }
+    }
+}
+
+/// The type of a JMS destination, carried by the [`JMS_DEST`] and [`JMS_REPLY_TO`] message
+/// annotations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JmsDestinationType {
+    /// `Queue`
+    Queue,
+
+    /// `Topic`
+    Topic,
+
+    /// `TemporaryQueue`
+    TemporaryQueue,
+
+    /// `TemporaryTopic`
+    TemporaryTopic,
+}
+
+impl JmsDestinationType {
+    /// The value of the message annotation
+    pub fn code(&self) -> i8 {
+        match self {
+            JmsDestinationType::Queue => 0,
+            JmsDestinationType::Topic => 1,
+            JmsDestinationType::TemporaryQueue => 2,
+            JmsDestinationType::TemporaryTopic => 3,
+        }
+    }
+
+    /// Returns the destination type with the value of the message annotation
+    pub fn from_code(code: i8) -> Option<Self> {
+        match code {
+            0 => Some(JmsDestinationType::Queue),
+            1 => Some(JmsDestinationType::Topic),
+            2 => Some(JmsDestinationType::TemporaryQueue),
+            3 => Some(JmsDestinationType::TemporaryTopic),
+            _ => None,
+        }
+    }
+}
+
+/// A JMS destination, which is the address in the `to` or `reply-to` property together with the
+/// type of the destination
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JmsDestination {
+    /// `Queue`
+    Queue(String),
+
+    /// `Topic`
+    Topic(String),
+
+    /// `TemporaryQueue`
+    TemporaryQueue(String),
+
+    /// `TemporaryTopic`
+    TemporaryTopic(String),
+}
+
+impl JmsDestination {
+    /// Creates a destination of the type with the address
+    pub fn new(kind: JmsDestinationType, address: impl Into<String>) -> Self {
+        let address = address.into();
+        match kind {
+            JmsDestinationType::Queue => JmsDestination::Queue(address),
+            JmsDestinationType::Topic => JmsDestination::Topic(address),
+            JmsDestinationType::TemporaryQueue => JmsDestination::TemporaryQueue(address),
+            JmsDestinationType::TemporaryTopic => JmsDestination::TemporaryTopic(address),
+        }
+    }
+
+    /// The type of the destination
+    pub fn kind(&self) -> JmsDestinationType {
+        match self {
+            JmsDestination::Queue(_) => JmsDestinationType::Queue,
+            JmsDestination::Topic(_) => JmsDestinationType::Topic,
+            JmsDestination::TemporaryQueue(_) => JmsDestinationType::TemporaryQueue,
+            JmsDestination::TemporaryTopic(_) => JmsDestinationType::TemporaryTopic,
+        }
+    }
+
+    /// The address of the destination
+    pub fn address(&self) -> &str {
+        match self {
+            JmsDestination::Queue(address)
+            | JmsDestination::Topic(address)
+            | JmsDestination::TemporaryQueue(address)
+            | JmsDestination::TemporaryTopic(address) => address,
+        }
+    }
+
+    /// Consumes the destination and returns the address
+    pub fn into_address(self) -> String {
+        match self {
+            JmsDestination::Queue(address)
+            | JmsDestination::Topic(address)
+            | JmsDestination::TemporaryQueue(address)
+            | JmsDestination::TemporaryTopic(address) => address,
+        }
+    }
+}
+
+/// Reads and writes the JMS message annotations and properties of a [`Message`]
+pub trait JmsMessageExt {
+    /// The JMS message type, or `None` if the annotation is absent or unknown
+    fn jms_message_type(&self) -> Option<JmsMessageType>;
+
+    /// Sets the JMS message type annotation
+    fn set_jms_message_type(&mut self, message_type: JmsMessageType);
+
+    /// The destination in the `to` property, or `None` if either the property or the
+    /// [`JMS_DEST`] annotation is absent
+    fn jms_destination(&self) -> Option<JmsDestination>;
+
+    /// Sets the `to` property and the [`JMS_DEST`] annotation
+    fn set_jms_destination(&mut self, destination: JmsDestination);
+
+    /// The destination in the `reply-to` property, or `None` if either the property or the
+    /// [`JMS_REPLY_TO`] annotation is absent
+    fn jms_reply_to(&self) -> Option<JmsDestination>;
+
+    /// Sets the `reply-to` property and the [`JMS_REPLY_TO`] annotation
+    fn set_jms_reply_to(&mut self, destination: JmsDestination);
+}
+
+impl<B> JmsMessageExt for Message<B> {
+    fn jms_message_type(&self) -> Option<JmsMessageType> {
+        annotation_code(self, JMS_MSG_TYPE).and_then(JmsMessageType::from_code)
+    }
+
+    fn set_jms_message_type(&mut self, message_type: JmsMessageType) {
+        set_annotation_code(self, JMS_MSG_TYPE, message_type.code())
+    }
+
+    fn jms_destination(&self) -> Option<JmsDestination> {
+        let kind = annotation_code(self, JMS_DEST).and_then(JmsDestinationType::from_code)?;
+        let address = self.properties.as_ref()?.to.clone()?;
+        Some(JmsDestination::new(kind, address))
+    }
+
+    fn set_jms_destination(&mut self, destination: JmsDestination) {
+        set_annotation_code(self, JMS_DEST, destination.kind().code());
+        self.properties.get_or_insert_with(Properties::default).to =
+            Some(destination.into_address());
+    }
+
+    fn jms_reply_to(&self) -> Option<JmsDestination> {
+        let kind = annotation_code(self, JMS_REPLY_TO).and_then(JmsDestinationType::from_code)?;
+        let address = self.properties.as_ref()?.reply_to.clone()?;
+        Some(JmsDestination::new(kind, address))
+    }
+
+    fn set_jms_reply_to(&mut self, destination: JmsDestination) {
+        set_annotation_code(self, JMS_REPLY_TO, destination.kind().code());
+        self.properties
+            .get_or_insert_with(Properties::default)
+            .reply_to = Some(destination.into_address());
+    }
+}
+
+fn annotation_code<B>(message: &Message<B>, key: &str) -> Option<i8> {
+    let value = message
+        .message_annotations
+        .as_ref()?
+        .0
+        .get(&key as &dyn AnnotationKey)?;
+    match value {
+        Value::Byte(code) => Some(*code),
+        // Be lenient with peers that encode the annotation with a wider type
+        Value::Ubyte(code) => i8::try_from(*code).ok(),
+        Value::Short(code) => i8::try_from(*code).ok(),
+        Value::Int(code) => i8::try_from(*code).ok(),
+        Value::Long(code) => i8::try_from(*code).ok(),
+        _ => None,
+    }
+}
+
+fn set_annotation_code<B>(message: &mut Message<B>, key: &str, code: i8) {
+    message
+        .message_annotations
+        .get_or_insert_with(MessageAnnotations::default)
+        .0
+        .insert(Symbol::from(key).into(), Value::Byte(code));
+}
+
+/// A JMS message body that can be sent as an AMQP message
+pub trait JmsBody: IntoBody + Sized {
+    /// The JMS message type
+    const MESSAGE_TYPE: JmsMessageType;
+
+    /// The content type of the message, if any
+    const CONTENT_TYPE: Option<&'static str> = None;
+
+    /// Creates a message with the body and the [`JMS_MSG_TYPE`] annotation and content type of
+    /// the JMS message type
+    fn into_jms_message(self) -> Message<Self::Body> {
+        let properties = Self::CONTENT_TYPE.map(|content_type| Properties {
+            content_type: Some(Symbol::from(content_type)),
+            ..Default::default()
+        });
+        let mut message = Message::builder()
+            .properties(properties)
+            .body(self.into_body())
+            .build();
+        message.set_jms_message_type(Self::MESSAGE_TYPE);
+        message
+    }
+}
+
+/// A JMS `TextMessage`, whose text may be null
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextMessage(pub Option<String>);
+
+impl TextMessage {
+    /// Creates a text message
+    pub fn new(text: impl Into<String>) -> Self {
+        Self(Some(text.into()))
+    }
+
+    /// The text of the message
+    pub fn text(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+impl IntoBody for TextMessage {
+    type Body = AmqpValue<Option<String>>;
+
+    fn into_body(self) -> Self::Body {
+        AmqpValue(self.0)
+    }
+}
+
+impl FromBody<'_> for TextMessage {
+    type Body = AmqpValue<Option<String>>;
+
+    fn from_body(deserializable: Self::Body) -> Self {
+        Self(deserializable.0)
+    }
+}
+
+impl FromEmptyBody for TextMessage {
+    fn from_empty_body() -> Result<Self, serde_amqp::Error> {
+        Ok(Self(None))
+    }
+}
+
+impl JmsBody for TextMessage {
+    const MESSAGE_TYPE: JmsMessageType = JmsMessageType::TextMessage;
+}
+
+/// A JMS `BytesMessage`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BytesMessage(pub BinaryBytes);
+
+impl BytesMessage {
+    /// Creates a bytes message
+    pub fn new(bytes: impl Into<BinaryBytes>) -> Self {
+        Self(bytes.into())
+    }
+}
+
+impl IntoBody for BytesMessage {
+    type Body = Data;
+
+    fn into_body(self) -> Self::Body {
+        Data(self.0)
+    }
+}
+
+impl FromBody<'_> for BytesMessage {
+    type Body = Data;
+
+    fn from_body(deserializable: Self::Body) -> Self {
+        Self(deserializable.0)
+    }
+}
+
+impl FromEmptyBody for BytesMessage {
+    fn from_empty_body() -> Result<Self, serde_amqp::Error> {
+        Ok(Self::default())
+    }
+}
+
+impl JmsBody for BytesMessage {
+    const MESSAGE_TYPE: JmsMessageType = JmsMessageType::BytesMessage;
+    const CONTENT_TYPE: Option<&'static str> = Some(OCTET_STREAM_CONTENT_TYPE);
+}
+
+/// A JMS `MapMessage`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MapMessage(pub OrderedMap<String, Value>);
+
+impl MapMessage {
+    /// Creates an empty map message
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts an entry into the map
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+        self.0.insert(name.into(), value.into())
+    }
+
+    /// Returns the value of the entry with the name
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+}
+
+impl IntoBody for MapMessage {
+    type Body = AmqpValue<OrderedMap<String, Value>>;
+
+    fn into_body(self) -> Self::Body {
+        AmqpValue(self.0)
+    }
+}
+
+impl FromBody<'_> for MapMessage {
+    type Body = AmqpValue<OrderedMap<String, Value>>;
+
+    fn from_body(deserializable: Self::Body) -> Self {
+        Self(deserializable.0)
+    }
+}
+
+impl FromEmptyBody for MapMessage {
+    fn from_empty_body() -> Result<Self, serde_amqp::Error> {
+        Ok(Self::default())
+    }
+}
+
+impl JmsBody for MapMessage {
+    const MESSAGE_TYPE: JmsMessageType = JmsMessageType::MapMessage;
+}
+
+/// A JMS `StreamMessage`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamMessage(pub Vec<Value>);
+
+impl StreamMessage {
+    /// Creates an empty stream message
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a value to the stream
+    pub fn push(&mut self, value: impl Into<Value>) {
+        self.0.push(value.into())
+    }
+}
+
+impl IntoBody for StreamMessage {
+    type Body = AmqpSequence<Value>;
+
+    fn into_body(self) -> Self::Body {
+        AmqpSequence(self.0)
+    }
+}
+
+impl FromBody<'_> for StreamMessage {
+    type Body = AmqpSequence<Value>;
+
+    fn from_body(deserializable: Self::Body) -> Self {
+        Self(deserializable.0)
+    }
+}
+
+impl FromEmptyBody for StreamMessage {
+    fn from_empty_body() -> Result<Self, serde_amqp::Error> {
+        Ok(Self::default())
+    }
+}
+
+impl JmsBody for StreamMessage {
+    const MESSAGE_TYPE: JmsMessageType = JmsMessageType::StreamMessage;
+}
+
+/// A JMS `ObjectMessage` holding a Java object in the Java serialization format
+///
+/// The bytes are not interpreted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectMessage(pub BinaryBytes);
+
+impl ObjectMessage {
+    /// Creates an object message from a serialized Java object
+    pub fn new(serialized: impl Into<BinaryBytes>) -> Self {
+        Self(serialized.into())
+    }
+}
+
+impl IntoBody for ObjectMessage {
+    type Body = Data;
+
+    fn into_body(self) -> Self::Body {
+        Data(self.0)
+    }
+}
+
+impl FromBody<'_> for ObjectMessage {
+    type Body = Data;
+
+    fn from_body(deserializable: Self::Body) -> Self {
+        Self(deserializable.0)
+    }
+}
+
+impl FromEmptyBody for ObjectMessage {}
+
+impl JmsBody for ObjectMessage {
+    const MESSAGE_TYPE: JmsMessageType = JmsMessageType::ObjectMessage;
+    const CONTENT_TYPE: Option<&'static str> = Some(SERIALIZED_JAVA_OBJECT_CONTENT_TYPE);
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{
+        messaging::{
+            message::__private::{Deserializable, Serializable},
+            FromBody, IntoBody, Message,
+        },
+        primitives::{Symbol, Value},
+    };
+    use serde_amqp::{from_slice, to_vec};
+
+    use super::{
+        BytesMessage, JmsBody, JmsDestination, JmsMessageExt, JmsMessageType, MapMessage,
+        StreamMessage, TextMessage,
+    };
+
+    fn round_trip<T>(message: Message<<T as IntoBody>::Body>) -> Message<T>
+    where
+        T: JmsBody + for<'de> FromBody<'de>,
+    {
+        let buf = to_vec(&Serializable(message)).unwrap();
+        let message: Deserializable<Message<T>> = from_slice(&buf).unwrap();
+        message.0
+    }
+
+    #[test]
+    fn text_message_round_trip() {
+        let mut message = TextMessage::new("hello").into_jms_message();
+        message.set_jms_destination(JmsDestination::Queue(String::from("orders")));
+        message.set_jms_reply_to(JmsDestination::TemporaryQueue(String::from("tmp")));
+
+        let message = round_trip::<TextMessage>(message);
+        assert_eq!(message.body.text(), Some("hello"));
+        assert_eq!(
+            message.jms_message_type(),
+            Some(JmsMessageType::TextMessage)
+        );
+        assert_eq!(
+            message.jms_destination(),
+            Some(JmsDestination::Queue(String::from("orders")))
+        );
+        assert_eq!(
+            message.jms_reply_to(),
+            Some(JmsDestination::TemporaryQueue(String::from("tmp")))
+        );
+
+        let message = round_trip::<TextMessage>(TextMessage(None).into_jms_message());
+        assert_eq!(message.body, TextMessage(None));
+    }
+
+    #[test]
+    fn bytes_message_sets_content_type() {
+        let message = BytesMessage::new(vec![1u8, 2, 3]).into_jms_message();
+        let content_type = message
+            .properties
+            .as_ref()
+            .and_then(|p| p.content_type.clone());
+        assert_eq!(content_type, Some(Symbol::from("application/octet-stream")));
+
+        let message = round_trip::<BytesMessage>(message);
+        assert_eq!(&message.body.0[..], &[1, 2, 3]);
+        assert_eq!(
+            message.jms_message_type(),
+            Some(JmsMessageType::BytesMessage)
+        );
+    }
+
+    #[test]
+    fn map_and_stream_message_round_trip() {
+        let mut map = MapMessage::new();
+        map.insert("count", 3i32);
+        map.insert("name", "widget");
+        let message = round_trip::<MapMessage>(map.clone().into_jms_message());
+        assert_eq!(message.body, map);
+        assert_eq!(message.body.get("count"), Some(&Value::Int(3)));
+
+        let mut stream = StreamMessage::new();
+        stream.push(true);
+        stream.push(1.5f64);
+        let message = round_trip::<StreamMessage>(stream.clone().into_jms_message());
+        assert_eq!(message.body, stream);
+        assert_eq!(
+            message.jms_message_type(),
+            Some(JmsMessageType::StreamMessage)
+        );
+    }
+}
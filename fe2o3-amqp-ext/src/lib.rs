@@ -1,5 +1,19 @@
+#![cfg_attr(docsrs, feature(doc_cfg))]
 #![deny(missing_docs, missing_debug_implementations)]
 
 //! Extensions to `fe2o3-amqp`
+//!
+//! # Feature flags
+//!
+//! - `"json"`: enables the JSON codec [`codec::Json`] and the structured mode of
+//!   [`cloud_events`]
+//! - `"prost"`: enables the protobuf codec `codec::Protobuf`
+//! - `"gzip"`: enables the gzip content encoding `codec::Gzip`
+//! - `"deflate"`: enables the deflate content encoding `codec::Deflate`
 
+pub mod azure;
+pub mod cloud_events;
+pub mod codec;
 pub mod filters;
+pub mod jms;
+pub mod selector;
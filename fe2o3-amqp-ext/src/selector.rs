@@ -0,0 +1,1305 @@
+//! JMS message selectors
+//!
+//! A [`Selector`] parses the SQL-92 based message selector grammar of JMS, which is the string
+//! carried by a [`SelectorFilter`], and evaluates it against a [`Message`].
+//!
+//! The following identifiers are resolved from the header, properties and message annotations of
+//! the message and all other identifiers are looked up in the `application_properties`
+//!
+//! | Identifier | Value |
+//! |------------|-------|
+//! | `JMSDeliveryMode` | `'PERSISTENT'` if `header.durable` is set, `'NON_PERSISTENT'` otherwise |
+//! | `JMSPriority` | `header.priority` |
+//! | `JMSMessageID` | `properties.message_id` |
+//! | `JMSCorrelationID` | `properties.correlation_id` |
+//! | `JMSTimestamp` | `properties.creation_time` |
+//! | `JMSExpiration` | `properties.absolute_expiry_time` |
+//! | `JMSType` | the `x-opt-jms-type` message annotation, or `properties.subject` |
+//! | `JMSXUserID` | `properties.user_id` |
+//! | `JMSXGroupID` | `properties.group_id` |
+//! | `JMSXGroupSeq` | `properties.group_sequence` |
+//! | `JMSXDeliveryCount` | `header.delivery_count` + 1 |
+//!
+//! Expressions are evaluated with the three-valued logic of SQL: an identifier that is not set
+//! is `NULL`, and any comparison or arithmetic involving `NULL` is unknown. A message is selected
+//! only if the selector evaluates to `TRUE`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use fe2o3_amqp_ext::selector::Selector;
+//! use fe2o3_amqp_types::messaging::{ApplicationProperties, Message};
+//!
+//! let selector = Selector::parse("color IN ('red', 'blue') AND weight BETWEEN 1 AND 10").unwrap();
+//!
+//! let message = Message::builder()
+//!     .application_properties(
+//!         ApplicationProperties::builder()
+//!             .insert("color", "red")
+//!             .insert("weight", 5i32)
+//!             .build(),
+//!     )
+//!     .value("hello")
+//!     .build();
+//! assert!(selector.matches(&message));
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use fe2o3_amqp_types::{
+    messaging::{annotations::AnnotationKey, Message, MessageId},
+    primitives::{SimpleValue, Value},
+};
+
+use crate::filters::SelectorFilter;
+
+/// Message annotation that carries the JMS type
+const JMS_TYPE_ANNOTATION: &str = "x-opt-jms-type";
+
+/// Default priority of a message without a header
+const DEFAULT_PRIORITY: i64 = 4;
+
+/// Maximum nesting depth of a selector
+///
+/// Both the number of nested operators and the number of nested parentheses or unary operators
+/// are limited, which bounds the recursion of parsing, evaluating and dropping a selector that
+/// may be received from a peer.
+pub const MAX_NESTING_DEPTH: usize = 128;
+
+/// Error parsing a selector
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SelectorError {
+    /// A character that doesn't start any token
+    #[error("Unexpected character {ch:?} at position {position}")]
+    UnexpectedChar {
+        /// The character
+        ch: char,
+        /// Byte offset of the character
+        position: usize,
+    },
+
+    /// A string literal without the closing quote
+    #[error("Unterminated string literal starting at position {position}")]
+    UnterminatedString {
+        /// Byte offset of the opening quote
+        position: usize,
+    },
+
+    /// A numeric literal that is malformed or out of range
+    #[error("Invalid numeric literal {literal:?} at position {position}")]
+    InvalidNumber {
+        /// The literal
+        literal: String,
+        /// Byte offset of the literal
+        position: usize,
+    },
+
+    /// A token that is not allowed by the grammar
+    #[error("Unexpected {found} at position {position}, expecting {expected}")]
+    UnexpectedToken {
+        /// The token found
+        found: String,
+        /// What the grammar expects
+        expected: &'static str,
+        /// Byte offset of the token
+        position: usize,
+    },
+
+    /// The escape of a `LIKE` pattern is not a single character
+    #[error("The escape of a LIKE pattern must be a single character, found {0:?}")]
+    InvalidEscape(String),
+
+    /// The selector is nested deeper than [`MAX_NESTING_DEPTH`]
+    #[error("The selector exceeds the maximum nesting depth of {MAX_NESTING_DEPTH} at position {position}")]
+    NestingTooDeep {
+        /// Byte offset of the token at which the limit is exceeded
+        position: usize,
+    },
+}
+
+/// A parsed JMS message selector
+#[derive(Debug, Clone)]
+pub struct Selector {
+    source: String,
+    expr: Expr,
+}
+
+impl Selector {
+    /// Parses a selector
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        let tokens = tokenize(selector)?;
+        let mut parser = Parser {
+            tokens,
+            index: 0,
+            end: selector.len(),
+            depth: 0,
+        };
+        let node = parser.expression()?;
+        parser.expect_end()?;
+        Ok(Self {
+            source: selector.to_string(),
+            expr: node.expr,
+        })
+    }
+
+    /// The selector string
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluates the selector against the message
+    ///
+    /// `None` is returned if the result is unknown
+    pub fn evaluate<B>(&self, message: &Message<B>) -> Option<bool> {
+        self.expr.eval(message).to_bool()
+    }
+
+    /// Whether the message is selected, ie. the selector evaluates to `TRUE`
+    pub fn matches<B>(&self, message: &Message<B>) -> bool {
+        self.evaluate(message) == Some(true)
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<&SelectorFilter> for Selector {
+    type Error = SelectorError;
+
+    fn try_from(filter: &SelectorFilter) -> Result<Self, Self::Error> {
+        Self::parse(&filter.0)
+    }
+}
+
+impl From<&Selector> for SelectorFilter {
+    fn from(selector: &Selector) -> Self {
+        SelectorFilter(selector.source.clone())
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                    Lexer                                   */
+/* -------------------------------------------------------------------------- */
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Long(i64),
+    Double(f64),
+    And,
+    Or,
+    Not,
+    Between,
+    In,
+    Like,
+    Escape,
+    Is,
+    Null,
+    True,
+    False,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "identifier {}", s),
+            Token::Str(s) => write!(f, "string '{}'", s),
+            Token::Long(n) => write!(f, "number {}", n),
+            Token::Double(n) => write!(f, "number {}", n),
+            Token::And => f.write_str("AND"),
+            Token::Or => f.write_str("OR"),
+            Token::Not => f.write_str("NOT"),
+            Token::Between => f.write_str("BETWEEN"),
+            Token::In => f.write_str("IN"),
+            Token::Like => f.write_str("LIKE"),
+            Token::Escape => f.write_str("ESCAPE"),
+            Token::Is => f.write_str("IS"),
+            Token::Null => f.write_str("NULL"),
+            Token::True => f.write_str("TRUE"),
+            Token::False => f.write_str("FALSE"),
+            Token::Eq => f.write_str("'='"),
+            Token::Ne => f.write_str("'<>'"),
+            Token::Lt => f.write_str("'<'"),
+            Token::Le => f.write_str("'<='"),
+            Token::Gt => f.write_str("'>'"),
+            Token::Ge => f.write_str("'>='"),
+            Token::Plus => f.write_str("'+'"),
+            Token::Minus => f.write_str("'-'"),
+            Token::Star => f.write_str("'*'"),
+            Token::Slash => f.write_str("'/'"),
+            Token::LParen => f.write_str("'('"),
+            Token::RParen => f.write_str("')'"),
+            Token::Comma => f.write_str("','"),
+        }
+    }
+}
+
+fn is_ident_start(ch: char) -> bool {
+    ch.is_alphabetic() || ch == '_' || ch == '$'
+}
+
+fn is_ident_part(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '$'
+}
+
+fn keyword(ident: &str) -> Option<Token> {
+    let token = match ident.to_ascii_uppercase().as_str() {
+        "AND" => Token::And,
+        "OR" => Token::Or,
+        "NOT" => Token::Not,
+        "BETWEEN" => Token::Between,
+        "IN" => Token::In,
+        "LIKE" => Token::Like,
+        "ESCAPE" => Token::Escape,
+        "IS" => Token::Is,
+        "NULL" => Token::Null,
+        "TRUE" => Token::True,
+        "FALSE" => Token::False,
+        _ => return None,
+    };
+    Some(token)
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, SelectorError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(position, ch)) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let token = match ch {
+            '\'' => {
+                chars.next();
+                let mut literal = String::new();
+                loop {
+                    match chars.next() {
+                        // A quote is escaped by doubling it
+                        Some((_, '\'')) => match chars.peek() {
+                            Some((_, '\'')) => {
+                                chars.next();
+                                literal.push('\'');
+                            }
+                            _ => break,
+                        },
+                        Some((_, ch)) => literal.push(ch),
+                        None => return Err(SelectorError::UnterminatedString { position }),
+                    }
+                }
+                Token::Str(literal)
+            }
+            ch if is_ident_start(ch) => {
+                let mut ident = String::new();
+                while let Some(&(_, ch)) = chars.peek() {
+                    if !is_ident_part(ch) {
+                        break;
+                    }
+                    ident.push(ch);
+                    chars.next();
+                }
+                keyword(&ident).unwrap_or(Token::Ident(ident))
+            }
+            ch if ch.is_ascii_digit() || ch == '.' => {
+                let mut literal = String::new();
+                while let Some(&(_, ch)) = chars.peek() {
+                    let is_exponent_sign = (ch == '+' || ch == '-')
+                        && literal.ends_with(['e', 'E'])
+                        && !literal.starts_with("0x")
+                        && !literal.starts_with("0X");
+                    if !(ch.is_ascii_alphanumeric() || ch == '.' || is_exponent_sign) {
+                        break;
+                    }
+                    literal.push(ch);
+                    chars.next();
+                }
+                parse_number(&literal).ok_or(SelectorError::InvalidNumber { literal, position })?
+            }
+            '=' => {
+                chars.next();
+                Token::Eq
+            }
+            '<' => {
+                chars.next();
+                match chars.peek() {
+                    Some((_, '>')) => {
+                        chars.next();
+                        Token::Ne
+                    }
+                    Some((_, '=')) => {
+                        chars.next();
+                        Token::Le
+                    }
+                    _ => Token::Lt,
+                }
+            }
+            '>' => {
+                chars.next();
+                match chars.peek() {
+                    Some((_, '=')) => {
+                        chars.next();
+                        Token::Ge
+                    }
+                    _ => Token::Gt,
+                }
+            }
+            '+' | '-' | '*' | '/' | '(' | ')' | ',' => {
+                chars.next();
+                match ch {
+                    '+' => Token::Plus,
+                    '-' => Token::Minus,
+                    '*' => Token::Star,
+                    '/' => Token::Slash,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => Token::Comma,
+                }
+            }
+            ch => return Err(SelectorError::UnexpectedChar { ch, position }),
+        };
+        tokens.push((token, position));
+    }
+
+    Ok(tokens)
+}
+
+fn parse_number(literal: &str) -> Option<Token> {
+    if let Some(hex) = literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        let hex = hex.trim_end_matches(['l', 'L']);
+        return i64::from_str_radix(hex, 16).ok().map(Token::Long);
+    }
+
+    let is_integer = literal
+        .trim_end_matches(['l', 'L'])
+        .chars()
+        .all(|ch| ch.is_ascii_digit());
+    if is_integer {
+        let digits = literal.trim_end_matches(['l', 'L']);
+        // An octal literal starts with a zero
+        let radix = if digits.len() > 1 && digits.starts_with('0') {
+            8
+        } else {
+            10
+        };
+        return i64::from_str_radix(digits, radix).ok().map(Token::Long);
+    }
+
+    let digits = literal.trim_end_matches(['f', 'F', 'd', 'D']);
+    if digits
+        .chars()
+        .any(|ch| ch.is_ascii_alphabetic() && ch != 'e' && ch != 'E')
+    {
+        return None;
+    }
+    digits.parse::<f64>().ok().map(Token::Double)
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                   Parser                                   */
+/* -------------------------------------------------------------------------- */
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Val),
+    Ident(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Arith(ArithOp, Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    Between {
+        expr: Box<Expr>,
+        low: Box<Expr>,
+        high: Box<Expr>,
+        negated: bool,
+    },
+    In {
+        expr: Box<Expr>,
+        list: Vec<String>,
+        negated: bool,
+    },
+    Like {
+        expr: Box<Expr>,
+        pattern: Vec<LikeToken>,
+        negated: bool,
+    },
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LikeToken {
+    Char(char),
+    /// `_`
+    One,
+    /// `%`
+    Any,
+}
+
+/// An expression with the depth of its tree
+struct Node {
+    expr: Expr,
+    depth: usize,
+}
+
+impl Node {
+    fn leaf(expr: Expr) -> Self {
+        Self { expr, depth: 1 }
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    /// Position reported for errors at the end of the input
+    end: usize,
+    /// Number of nested recursive calls, which bounds the stack usage of parsing
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(token, _)| token)
+    }
+
+    fn next_if(&mut self, expected: &Token) -> bool {
+        if self.peek() == Some(expected) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> SelectorError {
+        match self.tokens.get(self.index) {
+            Some((token, position)) => SelectorError::UnexpectedToken {
+                found: token.to_string(),
+                expected,
+                position: *position,
+            },
+            None => SelectorError::UnexpectedToken {
+                found: String::from("end of selector"),
+                expected,
+                position: self.end,
+            },
+        }
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.index)
+            .map(|(_, position)| *position)
+            .unwrap_or(self.end)
+    }
+
+    fn too_deep(&self) -> SelectorError {
+        SelectorError::NestingTooDeep {
+            position: self.position(),
+        }
+    }
+
+    /// Enters a recursive call
+    fn enter(&mut self) -> Result<(), SelectorError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(self.too_deep());
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Creates a node whose deepest child has a depth of `depth`
+    fn nest(&self, depth: usize, expr: Expr) -> Result<Node, SelectorError> {
+        if depth >= MAX_NESTING_DEPTH {
+            return Err(self.too_deep());
+        }
+        Ok(Node {
+            expr,
+            depth: depth + 1,
+        })
+    }
+
+    fn expect(&mut self, expected: Token, name: &'static str) -> Result<(), SelectorError> {
+        if self.next_if(&expected) {
+            Ok(())
+        } else {
+            Err(self.unexpected(name))
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), SelectorError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected("end of selector")),
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<String, SelectorError> {
+        match self.peek() {
+            Some(Token::Str(s)) => {
+                let s = s.clone();
+                self.index += 1;
+                Ok(s)
+            }
+            _ => Err(self.unexpected("string literal")),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Node, SelectorError> {
+        let mut node = self.and_expression()?;
+        while self.next_if(&Token::Or) {
+            let rhs = self.and_expression()?;
+            node = self.nest(
+                node.depth.max(rhs.depth),
+                Expr::Or(Box::new(node.expr), Box::new(rhs.expr)),
+            )?;
+        }
+        Ok(node)
+    }
+
+    fn and_expression(&mut self) -> Result<Node, SelectorError> {
+        let mut node = self.not_expression()?;
+        while self.next_if(&Token::And) {
+            let rhs = self.not_expression()?;
+            node = self.nest(
+                node.depth.max(rhs.depth),
+                Expr::And(Box::new(node.expr), Box::new(rhs.expr)),
+            )?;
+        }
+        Ok(node)
+    }
+
+    fn not_expression(&mut self) -> Result<Node, SelectorError> {
+        if self.next_if(&Token::Not) {
+            self.enter()?;
+            let node = self.not_expression()?;
+            self.leave();
+            return self.nest(node.depth, Expr::Not(Box::new(node.expr)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Node, SelectorError> {
+        let lhs = self.additive()?;
+
+        let op = match self.peek() {
+            Some(Token::Eq) => Some(CompareOp::Eq),
+            Some(Token::Ne) => Some(CompareOp::Ne),
+            Some(Token::Lt) => Some(CompareOp::Lt),
+            Some(Token::Le) => Some(CompareOp::Le),
+            Some(Token::Gt) => Some(CompareOp::Gt),
+            Some(Token::Ge) => Some(CompareOp::Ge),
+            _ => None,
+        };
+        if let Some(op) = op {
+            self.index += 1;
+            let rhs = self.additive()?;
+            return self.nest(
+                lhs.depth.max(rhs.depth),
+                Expr::Compare(op, Box::new(lhs.expr), Box::new(rhs.expr)),
+            );
+        }
+
+        if self.next_if(&Token::Is) {
+            let negated = self.next_if(&Token::Not);
+            self.expect(Token::Null, "NULL")?;
+            return self.nest(
+                lhs.depth,
+                Expr::IsNull {
+                    expr: Box::new(lhs.expr),
+                    negated,
+                },
+            );
+        }
+
+        let negated = self.next_if(&Token::Not);
+        let depth = lhs.depth;
+        let expr = Box::new(lhs.expr);
+        if self.next_if(&Token::Between) {
+            let low = self.additive()?;
+            self.expect(Token::And, "AND")?;
+            let high = self.additive()?;
+            self.nest(
+                depth.max(low.depth).max(high.depth),
+                Expr::Between {
+                    expr,
+                    low: Box::new(low.expr),
+                    high: Box::new(high.expr),
+                    negated,
+                },
+            )
+        } else if self.next_if(&Token::In) {
+            self.expect(Token::LParen, "'('")?;
+            let mut list = vec![self.expect_string()?];
+            while self.next_if(&Token::Comma) {
+                list.push(self.expect_string()?);
+            }
+            self.expect(Token::RParen, "')'")?;
+            self.nest(
+                depth,
+                Expr::In {
+                    expr,
+                    list,
+                    negated,
+                },
+            )
+        } else if self.next_if(&Token::Like) {
+            let pattern = self.expect_string()?;
+            let escape = if self.next_if(&Token::Escape) {
+                let escape = self.expect_string()?;
+                let mut chars = escape.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) => Some(ch),
+                    _ => return Err(SelectorError::InvalidEscape(escape)),
+                }
+            } else {
+                None
+            };
+            self.nest(
+                depth,
+                Expr::Like {
+                    expr,
+                    pattern: compile_like(&pattern, escape),
+                    negated,
+                },
+            )
+        } else if negated {
+            Err(self.unexpected("BETWEEN, IN or LIKE"))
+        } else {
+            Ok(Node { expr: *expr, depth })
+        }
+    }
+
+    fn additive(&mut self) -> Result<Node, SelectorError> {
+        let mut node = self.multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => ArithOp::Add,
+                Some(Token::Minus) => ArithOp::Sub,
+                _ => return Ok(node),
+            };
+            self.index += 1;
+            let rhs = self.multiplicative()?;
+            node = self.nest(
+                node.depth.max(rhs.depth),
+                Expr::Arith(op, Box::new(node.expr), Box::new(rhs.expr)),
+            )?;
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Node, SelectorError> {
+        let mut node = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => ArithOp::Mul,
+                Some(Token::Slash) => ArithOp::Div,
+                _ => return Ok(node),
+            };
+            self.index += 1;
+            let rhs = self.unary()?;
+            node = self.nest(
+                node.depth.max(rhs.depth),
+                Expr::Arith(op, Box::new(node.expr), Box::new(rhs.expr)),
+            )?;
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, SelectorError> {
+        if self.next_if(&Token::Minus) {
+            self.enter()?;
+            let node = self.unary()?;
+            self.leave();
+            return self.nest(node.depth, Expr::Neg(Box::new(node.expr)));
+        }
+        if self.next_if(&Token::Plus) {
+            self.enter()?;
+            let node = self.unary()?;
+            self.leave();
+            return Ok(node);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Node, SelectorError> {
+        let expr = match self.peek() {
+            Some(Token::LParen) => {
+                self.index += 1;
+                self.enter()?;
+                let node = self.expression()?;
+                self.leave();
+                self.expect(Token::RParen, "')'")?;
+                return Ok(node);
+            }
+            Some(Token::Ident(name)) => Expr::Ident(name.clone()),
+            Some(Token::Str(s)) => Expr::Literal(Val::Str(s.clone())),
+            Some(Token::Long(n)) => Expr::Literal(Val::Long(*n)),
+            Some(Token::Double(n)) => Expr::Literal(Val::Double(*n)),
+            Some(Token::True) => Expr::Literal(Val::Bool(true)),
+            Some(Token::False) => Expr::Literal(Val::Bool(false)),
+            _ => return Err(self.unexpected("an identifier, literal or '('")),
+        };
+        self.index += 1;
+        Ok(Node::leaf(expr))
+    }
+}
+
+fn compile_like(pattern: &str, escape: Option<char>) -> Vec<LikeToken> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        let token = match ch {
+            ch if Some(ch) == escape => match chars.next() {
+                Some(escaped) => LikeToken::Char(escaped),
+                None => LikeToken::Char(ch),
+            },
+            '%' => LikeToken::Any,
+            '_' => LikeToken::One,
+            ch => LikeToken::Char(ch),
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn like_matches(pattern: &[LikeToken], value: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    // matched[j] is whether the pattern so far matches the first j characters
+    let mut matched = vec![false; value.len() + 1];
+    matched[0] = true;
+    for token in pattern {
+        let mut next = vec![false; value.len() + 1];
+        match token {
+            LikeToken::Any => {
+                let mut any = false;
+                for j in 0..=value.len() {
+                    any |= matched[j];
+                    next[j] = any;
+                }
+            }
+            LikeToken::One => next[1..].copy_from_slice(&matched[..value.len()]),
+            LikeToken::Char(ch) => {
+                for j in 1..=value.len() {
+                    next[j] = matched[j - 1] && value[j - 1] == *ch;
+                }
+            }
+        }
+        matched = next;
+    }
+    matched[value.len()]
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                 Evaluation                                 */
+/* -------------------------------------------------------------------------- */
+
+/// Value of an expression. `Null` stands for an unknown value
+#[derive(Debug, Clone, PartialEq)]
+enum Val {
+    Null,
+    Bool(bool),
+    Long(i64),
+    Double(f64),
+    Str(String),
+}
+
+impl Val {
+    fn to_bool(&self) -> Option<bool> {
+        match self {
+            Val::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Val::Long(n) => Some(*n as f64),
+            Val::Double(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl From<Option<bool>> for Val {
+    fn from(value: Option<bool>) -> Self {
+        value.map(Val::Bool).unwrap_or(Val::Null)
+    }
+}
+
+impl From<&SimpleValue> for Val {
+    fn from(value: &SimpleValue) -> Self {
+        match value {
+            SimpleValue::Bool(b) => Val::Bool(*b),
+            SimpleValue::Ubyte(n) => Val::Long(*n as i64),
+            SimpleValue::Ushort(n) => Val::Long(*n as i64),
+            SimpleValue::Uint(n) => Val::Long(*n as i64),
+            SimpleValue::Ulong(n) => i64::try_from(*n).map(Val::Long).unwrap_or(Val::Null),
+            SimpleValue::Byte(n) => Val::Long(*n as i64),
+            SimpleValue::Short(n) => Val::Long(*n as i64),
+            SimpleValue::Int(n) => Val::Long(*n as i64),
+            SimpleValue::Long(n) => Val::Long(*n),
+            SimpleValue::Float(n) => Val::Double(n.into_inner() as f64),
+            SimpleValue::Double(n) => Val::Double(n.into_inner()),
+            SimpleValue::Char(ch) => Val::Str(ch.to_string()),
+            SimpleValue::Timestamp(t) => Val::Long(t.milliseconds()),
+            SimpleValue::String(s) => Val::Str(s.clone()),
+            SimpleValue::Symbol(s) => Val::Str(s.as_str().to_string()),
+            _ => Val::Null,
+        }
+    }
+}
+
+fn uuid_string(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn message_id(id: &MessageId) -> Val {
+    match id {
+        MessageId::String(s) => Val::Str(s.clone()),
+        MessageId::Ulong(n) => Val::Str(n.to_string()),
+        MessageId::Uuid(uuid) => Val::Str(uuid_string(&uuid.clone().into_inner())),
+        MessageId::Binary(_) => Val::Null,
+    }
+}
+
+/// Resolves an identifier against the message
+fn resolve<B>(message: &Message<B>, name: &str) -> Val {
+    let properties = message.properties.as_ref();
+    match name {
+        "JMSDeliveryMode" => {
+            let durable = message.header.as_ref().map(|h| h.durable).unwrap_or(false);
+            let mode = if durable {
+                "PERSISTENT"
+            } else {
+                "NON_PERSISTENT"
+            };
+            Val::Str(mode.to_string())
+        }
+        "JMSPriority" => message
+            .header
+            .as_ref()
+            .map(|h| Val::Long(h.priority.0 as i64))
+            .unwrap_or(Val::Long(DEFAULT_PRIORITY)),
+        "JMSMessageID" => properties
+            .and_then(|p| p.message_id.as_ref())
+            .map(message_id)
+            .unwrap_or(Val::Null),
+        "JMSCorrelationID" => properties
+            .and_then(|p| p.correlation_id.as_ref())
+            .map(message_id)
+            .unwrap_or(Val::Null),
+        "JMSTimestamp" => properties
+            .and_then(|p| p.creation_time.as_ref())
+            .map(|t| Val::Long(t.milliseconds()))
+            .unwrap_or(Val::Null),
+        "JMSExpiration" => properties
+            .and_then(|p| p.absolute_expiry_time.as_ref())
+            .map(|t| Val::Long(t.milliseconds()))
+            .unwrap_or(Val::Null),
+        "JMSType" => {
+            let annotation = message.message_annotations.as_ref().and_then(|a| {
+                match a.get(&JMS_TYPE_ANNOTATION as &dyn AnnotationKey) {
+                    Some(Value::String(s)) => Some(s.clone()),
+                    Some(Value::Symbol(s)) => Some(s.as_str().to_string()),
+                    _ => None,
+                }
+            });
+            annotation
+                .or_else(|| properties.and_then(|p| p.subject.clone()))
+                .map(Val::Str)
+                .unwrap_or(Val::Null)
+        }
+        "JMSXUserID" => properties
+            .and_then(|p| p.user_id.as_ref())
+            .and_then(|id| std::str::from_utf8(id).ok())
+            .map(|id| Val::Str(id.to_string()))
+            .unwrap_or(Val::Null),
+        "JMSXGroupID" => properties
+            .and_then(|p| p.group_id.clone())
+            .map(Val::Str)
+            .unwrap_or(Val::Null),
+        "JMSXGroupSeq" => properties
+            .and_then(|p| p.group_sequence)
+            .map(|seq| Val::Long(seq as i64))
+            .unwrap_or(Val::Null),
+        "JMSXDeliveryCount" => {
+            let count = message
+                .header
+                .as_ref()
+                .map(|h| h.delivery_count)
+                .unwrap_or(0);
+            Val::Long(count as i64 + 1)
+        }
+        _ => message
+            .application_properties
+            .as_ref()
+            .and_then(|p| p.0.get(name))
+            .map(Val::from)
+            .unwrap_or(Val::Null),
+    }
+}
+
+fn compare(op: CompareOp, lhs: &Val, rhs: &Val) -> Val {
+    let ordering = match (lhs, rhs) {
+        (Val::Long(l), Val::Long(r)) => l.partial_cmp(r),
+        (Val::Long(_) | Val::Double(_), Val::Long(_) | Val::Double(_)) => {
+            lhs.as_f64().partial_cmp(&rhs.as_f64())
+        }
+        // Strings and booleans only support equality
+        (Val::Str(_), Val::Str(_)) | (Val::Bool(_), Val::Bool(_)) => {
+            return match op {
+                CompareOp::Eq => Val::Bool(lhs == rhs),
+                CompareOp::Ne => Val::Bool(lhs != rhs),
+                _ => Val::Null,
+            };
+        }
+        _ => return Val::Null,
+    };
+
+    let ordering = match ordering {
+        Some(ordering) => ordering,
+        // NaN
+        None => return Val::Bool(op == CompareOp::Ne),
+    };
+    let result = match op {
+        CompareOp::Eq => ordering.is_eq(),
+        CompareOp::Ne => ordering.is_ne(),
+        CompareOp::Lt => ordering.is_lt(),
+        CompareOp::Le => ordering.is_le(),
+        CompareOp::Gt => ordering.is_gt(),
+        CompareOp::Ge => ordering.is_ge(),
+    };
+    Val::Bool(result)
+}
+
+fn arith(op: ArithOp, lhs: &Val, rhs: &Val) -> Val {
+    match (lhs, rhs) {
+        (Val::Long(l), Val::Long(r)) => {
+            let result = match op {
+                ArithOp::Add => l.checked_add(*r),
+                ArithOp::Sub => l.checked_sub(*r),
+                ArithOp::Mul => l.checked_mul(*r),
+                ArithOp::Div => l.checked_div(*r),
+            };
+            result.map(Val::Long).unwrap_or(Val::Null)
+        }
+        _ => match (lhs.as_f64(), rhs.as_f64()) {
+            (Some(l), Some(r)) => {
+                let result = match op {
+                    ArithOp::Add => l + r,
+                    ArithOp::Sub => l - r,
+                    ArithOp::Mul => l * r,
+                    ArithOp::Div => l / r,
+                };
+                Val::Double(result)
+            }
+            _ => Val::Null,
+        },
+    }
+}
+
+fn and(lhs: Option<bool>, rhs: Option<bool>) -> Option<bool> {
+    match (lhs, rhs) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+fn or(lhs: Option<bool>, rhs: Option<bool>) -> Option<bool> {
+    match (lhs, rhs) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    }
+}
+
+fn negate_if(negated: bool, value: Option<bool>) -> Val {
+    if negated {
+        value.map(|b| !b).into()
+    } else {
+        value.into()
+    }
+}
+
+impl Expr {
+    fn eval<B>(&self, message: &Message<B>) -> Val {
+        match self {
+            Expr::Literal(val) => val.clone(),
+            Expr::Ident(name) => resolve(message, name),
+            Expr::Not(expr) => expr.eval(message).to_bool().map(|b| !b).into(),
+            Expr::And(lhs, rhs) => {
+                let lhs = lhs.eval(message).to_bool();
+                if lhs == Some(false) {
+                    return Val::Bool(false);
+                }
+                and(lhs, rhs.eval(message).to_bool()).into()
+            }
+            Expr::Or(lhs, rhs) => {
+                let lhs = lhs.eval(message).to_bool();
+                if lhs == Some(true) {
+                    return Val::Bool(true);
+                }
+                or(lhs, rhs.eval(message).to_bool()).into()
+            }
+            Expr::Compare(op, lhs, rhs) => compare(*op, &lhs.eval(message), &rhs.eval(message)),
+            Expr::Arith(op, lhs, rhs) => arith(*op, &lhs.eval(message), &rhs.eval(message)),
+            Expr::Neg(expr) => match expr.eval(message) {
+                Val::Long(n) => n.checked_neg().map(Val::Long).unwrap_or(Val::Null),
+                Val::Double(n) => Val::Double(-n),
+                _ => Val::Null,
+            },
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let value = expr.eval(message);
+                let ge_low = compare(CompareOp::Ge, &value, &low.eval(message)).to_bool();
+                let le_high = compare(CompareOp::Le, &value, &high.eval(message)).to_bool();
+                negate_if(*negated, and(ge_low, le_high))
+            }
+            Expr::In {
+                expr,
+                list,
+                negated,
+            } => {
+                let result = match expr.eval(message) {
+                    Val::Str(s) => Some(list.contains(&s)),
+                    _ => None,
+                };
+                negate_if(*negated, result)
+            }
+            Expr::Like {
+                expr,
+                pattern,
+                negated,
+            } => {
+                let result = match expr.eval(message) {
+                    Val::Str(s) => Some(like_matches(pattern, &s)),
+                    _ => None,
+                };
+                negate_if(*negated, result)
+            }
+            Expr::IsNull { expr, negated } => {
+                let is_null = expr.eval(message) == Val::Null;
+                Val::Bool(is_null != *negated)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::messaging::{
+        AmqpValue, ApplicationProperties, Header, Message, Properties,
+    };
+
+    use super::{Selector, SelectorError};
+
+    fn message() -> Message<AmqpValue<&'static str>> {
+        Message::builder()
+            .header(Header::builder().durable(true).priority(7).build())
+            .properties(
+                Properties::builder()
+                    .message_id(String::from("id-1"))
+                    .subject("order")
+                    .build(),
+            )
+            .application_properties(
+                ApplicationProperties::builder()
+                    .insert("color", "red")
+                    .insert("weight", 25i32)
+                    .insert("price", 9.5f64)
+                    .insert("path", "a_b%c")
+                    .insert("urgent", true)
+                    .build(),
+            )
+            .value("body")
+            .build()
+    }
+
+    fn eval(selector: &str) -> Option<bool> {
+        Selector::parse(selector).unwrap().evaluate(&message())
+    }
+
+    #[test]
+    fn comparisons_and_arithmetic() {
+        assert_eq!(eval("color = 'red'"), Some(true));
+        assert_eq!(eval("color <> 'red'"), Some(false));
+        assert_eq!(eval("weight > 20 AND weight <= 25"), Some(true));
+        assert_eq!(eval("weight * 2 - 10 = 40"), Some(true));
+        assert_eq!(eval("price > weight / 3"), Some(true));
+        assert_eq!(eval("-weight < 0"), Some(true));
+        assert_eq!(eval("urgent = TRUE"), Some(true));
+        assert_eq!(eval("urgent"), Some(true));
+        // Strings only support equality
+        assert_eq!(eval("color > 'blue'"), None);
+    }
+
+    #[test]
+    fn header_and_properties() {
+        assert_eq!(eval("JMSDeliveryMode = 'PERSISTENT'"), Some(true));
+        assert_eq!(eval("JMSPriority BETWEEN 5 AND 9"), Some(true));
+        assert_eq!(eval("JMSMessageID = 'id-1'"), Some(true));
+        assert_eq!(eval("JMSType = 'order'"), Some(true));
+        assert_eq!(eval("JMSXDeliveryCount = 1"), Some(true));
+    }
+
+    #[test]
+    fn between_in_like() {
+        assert_eq!(eval("weight NOT BETWEEN 1 AND 10"), Some(true));
+        assert_eq!(eval("color IN ('blue', 'red')"), Some(true));
+        assert_eq!(eval("color NOT IN ('blue', 'green')"), Some(true));
+        assert_eq!(eval("color LIKE 'r_d'"), Some(true));
+        assert_eq!(eval("color LIKE '%e%'"), Some(true));
+        assert_eq!(eval("color NOT LIKE 'b%'"), Some(true));
+        assert_eq!(eval("path LIKE 'a\\_b\\%%' ESCAPE '\\'"), Some(true));
+        assert_eq!(eval("path LIKE 'a\\_c%' ESCAPE '\\'"), Some(false));
+    }
+
+    #[test]
+    fn three_valued_logic() {
+        assert_eq!(eval("missing IS NULL"), Some(true));
+        assert_eq!(eval("color IS NOT NULL"), Some(true));
+        assert_eq!(eval("missing = 1"), None);
+        assert_eq!(eval("NOT (missing = 1)"), None);
+        assert_eq!(eval("missing = 1 AND color = 'blue'"), Some(false));
+        assert_eq!(eval("missing = 1 OR color = 'red'"), Some(true));
+        assert_eq!(eval("missing = 1 OR color = 'blue'"), None);
+        assert_eq!(eval("missing IN ('a')"), None);
+        assert_eq!(eval("missing + 1 > 0"), None);
+
+        let selector = Selector::parse("missing = 1").unwrap();
+        assert!(!selector.matches(&message()));
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(eval("weight = 0x19"), Some(true));
+        assert_eq!(eval("weight = 031"), Some(true));
+        assert_eq!(eval("weight = 25L"), Some(true));
+        assert_eq!(eval("price = 95e-1"), Some(true));
+        assert_eq!(eval("price = .95E1"), Some(true));
+        assert_eq!(eval("'it''s' = 'it''s'"), Some(true));
+        assert_eq!(eval("color = 'RED' or true"), Some(true));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            Selector::parse("color = 'red"),
+            Err(SelectorError::UnterminatedString { position: 8 })
+        ));
+        assert!(matches!(
+            Selector::parse("color = # 1"),
+            Err(SelectorError::UnexpectedChar { ch: '#', .. })
+        ));
+        assert!(matches!(
+            Selector::parse("color IN (1)"),
+            Err(SelectorError::UnexpectedToken { position: 10, .. })
+        ));
+        assert!(matches!(
+            Selector::parse("color NOT 1"),
+            Err(SelectorError::UnexpectedToken { .. })
+        ));
+        assert!(matches!(
+            Selector::parse("weight = 12abc"),
+            Err(SelectorError::InvalidNumber { .. })
+        ));
+        assert!(matches!(
+            Selector::parse("color LIKE 'a' ESCAPE 'ab'"),
+            Err(SelectorError::InvalidEscape(_))
+        ));
+        assert!(matches!(
+            Selector::parse("(color = 'red'"),
+            Err(SelectorError::UnexpectedToken { position: 14, .. })
+        ));
+    }
+
+    #[test]
+    fn nesting_depth() {
+        let nested = |open: &str, close: &str, n: usize| {
+            format!("{}urgent{}", open.repeat(n), close.repeat(n))
+        };
+        assert_eq!(eval(&nested("(", ")", 100)), Some(true));
+        assert_eq!(eval(&format!("{}0 = 0", "0 + ".repeat(100))), Some(true));
+
+        let too_deep = [
+            nested("(", ")", 100_000),
+            nested("NOT ", "", 100_000),
+            nested("-", "", 100_000),
+            format!("urgent{}", " OR urgent".repeat(100_000)),
+            format!("{}1", "1 + ".repeat(100_000)),
+            format!("{}urgent{}", "(urgent AND ".repeat(200), ")".repeat(200)),
+        ];
+        for selector in too_deep {
+            assert!(matches!(
+                Selector::parse(&selector),
+                Err(SelectorError::NestingTooDeep { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Errors associated with proxy tunneling
+
+use std::io;
+
+/// Error establishing a tunnel through a proxy
+#[derive(Debug, thiserror::Error)]
+pub enum ProxyError {
+    /// IO error
+    #[error("IO Error {0:?}")]
+    Io(#[from] io::Error),
+
+    /// Error parsing the proxy url
+    #[error(transparent)]
+    UrlError(#[from] url::ParseError),
+
+    /// Proxy url scheme is not supported
+    #[error(r#"Invalid proxy scheme. Only "http", "socks5" and "socks5h" are supported."#)]
+    InvalidScheme,
+
+    /// The proxy url has the `"https"` scheme. TLS between the client and the proxy is not
+    /// supported, so the CONNECT request would be sent in plain text to a TLS port.
+    #[error("TLS connections to the proxy are not supported")]
+    HttpsProxyNotSupported,
+
+    /// Proxy or target host is invalid
+    #[error("Invalid host")]
+    InvalidHost,
+
+    /// Username or password is too long for SOCKS5 authentication
+    #[error("Invalid proxy credentials")]
+    InvalidCredentials,
+
+    /// The proxy responded to the CONNECT request with a non-2xx status code
+    #[error("HTTP proxy rejected CONNECT with status {} {}", .status, .reason)]
+    HttpConnectRejected {
+        /// HTTP status code
+        status: u16,
+        /// Reason phrase
+        reason: String,
+    },
+
+    /// The response to the CONNECT request is malformed
+    #[error("Invalid HTTP proxy response")]
+    InvalidHttpResponse,
+
+    /// The SOCKS5 proxy does not accept any of the offered authentication methods
+    #[error("SOCKS5 proxy does not accept any offered authentication method")]
+    Socks5NoAcceptableAuthMethod,
+
+    /// The SOCKS5 proxy rejected the supplied credentials
+    #[error("Proxy authentication failed")]
+    AuthenticationFailed,
+
+    /// The SOCKS5 proxy responded to the CONNECT command with a non-zero reply code
+    #[error("SOCKS5 proxy rejected CONNECT with reply code {0:#04x}")]
+    Socks5ConnectRejected(u8),
+
+    /// The response from the SOCKS5 proxy is malformed
+    #[error("Invalid SOCKS5 proxy response")]
+    InvalidSocks5Response,
+}
@@ -1,3 +1,4 @@
+use alloc::string::String;
 use serde_amqp::macros::{DeserializeComposite, SerializeComposite};
 
 use super::{ErrorCondition, Fields};
@@ -23,8 +24,8 @@ pub struct Error {
     pub info: Option<Fields>,
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Error")
             .field("condition", &self.condition)
             .field("description", &self.description)
@@ -33,7 +34,7 @@ impl std::fmt::Display for Error {
     }
 }
 
-impl std::error::Error for Error {}
+impl crate::StdError for Error {}
 
 impl Error {
     /// Creates a new Error
@@ -1,4 +1,4 @@
-use std::{
+use core::{
     convert::{TryFrom, TryInto},
     fmt::{Debug, Display},
 };
@@ -25,12 +25,12 @@ pub enum SessionError {
 }
 
 impl Display for SessionError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         Debug::fmt(&self, f)
     }
 }
 
-impl std::error::Error for SessionError {}
+impl crate::StdError for SessionError {}
 
 impl From<SessionError> for ErrorCondition {
     fn from(err: SessionError) -> Self {
@@ -1,4 +1,5 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs, missing_debug_implementations)]
 
 //! Implements AMQP1.0 data types as defined in the core [specification](http://docs.oasis-open.org/amqp/core/v1.0/os/amqp-core-overview-v1.0-os.html).
@@ -11,6 +12,8 @@
 //! Please note that `Performative` will require both `"transport"` and `"messaging"` feature flags
 //! enabled.
 //!
+//! - `"std"`: enables the use of the standard library. Without it, the crate only depends on
+//!   `core` and `alloc`.
 //! - `"primitive"`: enables the primitive types defined in part 1.6 in the core specification.
 //! - `"transport"`: enables most of the types defined in part 2.4, 2.5, and 2.8 of the core specifiction.
 //! - `"messaging"`: enables the types defined in part 2.7 and part 3 defined in the core specification
@@ -19,6 +22,7 @@
 //!
 //! ```toml
 //! default = [
+//!     "std",
 //!     "primitive",
 //!     "transport",
 //!     "messaging",
@@ -87,6 +91,14 @@
 // 41. "sasl-code"
 //
 
+extern crate alloc;
+
+#[cfg(all(feature = "std", feature = "transport"))]
+pub(crate) use std::error::Error as StdError;
+
+#[cfg(all(not(feature = "std"), feature = "transport"))]
+pub(crate) use core::error::Error as StdError;
+
 #[cfg_attr(docsrs, doc(cfg(feature = "primitive")))]
 #[cfg(feature = "primitive")]
 pub mod primitives;
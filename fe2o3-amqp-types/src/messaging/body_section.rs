@@ -1,10 +1,10 @@
-use std::{
-    borrow::Cow,
-    collections::{BTreeMap, HashMap},
-    rc::Rc,
-    sync::Arc,
+use alloc::{
+    borrow::Cow, boxed::Box, collections::BTreeMap, rc::Rc, string::String, sync::Arc, vec::Vec,
 };
+#[cfg(feature = "std")]
+use std::collections::HashMap;
 
+use bytes::Bytes;
 use serde::{de, ser, Deserialize, Serialize};
 use serde_amqp::{
     lazy::LazyValue,
@@ -22,7 +22,7 @@ use super::AmqpValue;
 use super::{AmqpSequence, Batch, Body, Data};
 
 pub(crate) mod __private {
-    use std::{rc::Rc, sync::Arc};
+    use alloc::{boxed::Box, rc::Rc, sync::Arc};
 
     /// Marker trait for message body.
     ///
@@ -146,7 +146,18 @@ pub trait TransposeOption<'de, To: FromBody<'de>>: BodySection + Sized {
 /// 5. [`Batch<AmqpSequence>`]
 /// 6. [`Batch<Data>`]
 /// 7. `Option<B> where B: DeserializableBody`
-pub trait DeserializableBody<'de>: Deserialize<'de> + BodySection {}
+pub trait DeserializableBody<'de>: Deserialize<'de> + BodySection {
+    /// Deserializes the body section sharing the buffer of `source`, which holds the encoded
+    /// message being deserialized, instead of copying where the body section supports it
+    #[doc(hidden)]
+    fn deserialize_shared<D>(deserializer: D, source: &Bytes) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let _ = source;
+        Self::deserialize(deserializer)
+    }
+}
 
 /// Convert the type to a `SerializableBody` which includes:
 ///
@@ -416,7 +427,7 @@ impl IntoBody for SymbolRef<'_> {
 
 impl<K, V> IntoBody for OrderedMap<K, V>
 where
-    K: ser::Serialize + std::hash::Hash + Eq,
+    K: ser::Serialize + core::hash::Hash + Eq,
     V: ser::Serialize,
 {
     type Body = AmqpValue<Self>;
@@ -426,9 +437,10 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 impl<K, V> IntoBody for HashMap<K, V>
 where
-    K: ser::Serialize + std::hash::Hash + Eq,
+    K: ser::Serialize + core::hash::Hash + Eq,
     V: ser::Serialize,
 {
     type Body = AmqpValue<Self>;
@@ -515,7 +527,7 @@ impl_from_deserializable_or_empty_body!(AmqpValue, T:Deserialize<'de>; Array);
 blanket_impl_from_empty_body!(K, V; OrderedMap);
 impl<'de, K, V> FromBody<'de> for OrderedMap<K, V>
 where
-    K: de::Deserialize<'de> + std::hash::Hash + Eq,
+    K: de::Deserialize<'de> + core::hash::Hash + Eq,
     V: de::Deserialize<'de>,
 {
     type Body = AmqpValue<Self>;
@@ -525,10 +537,12 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 blanket_impl_from_empty_body!(K, V; HashMap);
+#[cfg(feature = "std")]
 impl<'de, K, V> FromBody<'de> for HashMap<K, V>
 where
-    K: de::Deserialize<'de> + std::hash::Hash + Eq,
+    K: de::Deserialize<'de> + core::hash::Hash + Eq,
     V: de::Deserialize<'de>,
 {
     type Body = AmqpValue<Self>;
@@ -1,3 +1,4 @@
+use alloc::format;
 use serde::{
     de::{self, VariantAccess},
     ser,
@@ -45,7 +46,7 @@ struct FieldVisitor {}
 impl de::Visitor<'_> for FieldVisitor {
     type Value = Field;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("variant identifier")
     }
 
@@ -114,7 +115,7 @@ struct Visitor {}
 impl<'de> de::Visitor<'de> for Visitor {
     type Value = DeliveryState;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("enum DeliveryState")
     }
 
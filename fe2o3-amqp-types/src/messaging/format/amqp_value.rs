@@ -1,11 +1,11 @@
-use std::fmt::Display;
+use core::fmt::Display;
 
 use serde::{de, ser, Serialize};
 use serde_amqp::{DeserializeComposite, SerializeComposite};
 
 use crate::messaging::{
-    AsBodyRef, DeserializableBody, FromBody, FromEmptyBody, IntoBody, SerializableBody,
-    TransposeOption, __private::BodySection,
+    __private::BodySection, AsBodyRef, DeserializableBody, FromBody, FromEmptyBody, IntoBody,
+    SerializableBody, TransposeOption,
 };
 
 /// 3.2.8 AMQP Value
@@ -28,7 +28,7 @@ impl<T> Display for AmqpValue<T>
 where
     T: Display,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "AmqpValue({})", self.0)
     }
 }
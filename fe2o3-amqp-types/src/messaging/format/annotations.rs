@@ -1,6 +1,7 @@
 //! Implements 3.2.10 Annotations
 
-use std::{
+use alloc::string::String;
+use core::{
     borrow::Borrow,
     hash::{Hash, Hasher},
 };
@@ -10,10 +11,10 @@ use serde::{
     Serialize,
 };
 use serde_amqp::{
+    __constants::VALUE,
     format_code::EncodingCodes,
     primitives::{OrderedMap, Symbol, SymbolRef, Ulong},
     Value,
-    __constants::VALUE,
 };
 
 /// 3.2.10 Annotations
@@ -114,7 +115,7 @@ struct FieldVisitor {}
 impl de::Visitor<'_> for FieldVisitor {
     type Value = Field;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("OwnedKey variant")
     }
 
@@ -149,7 +150,7 @@ struct Visitor {}
 impl<'de> de::Visitor<'de> for Visitor {
     type Value = OwnedKey;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("OwnedKey")
     }
 
@@ -256,16 +257,16 @@ impl<'a> Borrow<dyn AnnotationKey + 'a> for OwnedKey {
     }
 }
 
-impl PartialEq for (dyn AnnotationKey + '_) {
+impl PartialEq for dyn AnnotationKey + '_ {
     fn eq(&self, other: &Self) -> bool {
         self.key().eq(&other.key())
     }
 }
 
-impl Eq for (dyn AnnotationKey + '_) {}
+impl Eq for dyn AnnotationKey + '_ {}
 
-impl PartialOrd for (dyn AnnotationKey + '_) {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+impl PartialOrd for dyn AnnotationKey + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         // self.key().partial_cmp(&other.key())
 
         // clippy::non_canonical_partial_ord_impl
@@ -277,13 +278,13 @@ impl PartialOrd for (dyn AnnotationKey + '_) {
     }
 }
 
-impl Ord for (dyn AnnotationKey + '_) {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+impl Ord for dyn AnnotationKey + '_ {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.key().cmp(&other.key())
     }
 }
 
-impl Hash for (dyn AnnotationKey + '_) {
+impl Hash for dyn AnnotationKey + '_ {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.key().hash(state)
     }
@@ -1,6 +1,15 @@
-use std::{borrow::Cow, fmt::Display};
-
-use serde_amqp::{primitives::Binary, DeserializeComposite, SerializeComposite, Value};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use bytes::Bytes;
+use serde::de;
+use serde_amqp::{
+    __constants::DESCRIBED_BASIC,
+    descriptor::Descriptor,
+    primitives::{Binary, BinaryBytes, Shared},
+    DeserializeComposite, SerializeComposite, Value,
+};
 
 use crate::messaging::{
     Batch, DeserializableBody, FromBody, FromEmptyBody, IntoBody, SerializableBody,
@@ -11,6 +20,11 @@ use crate::messaging::{
 /// <type name="data" class="restricted" source="binary" provides="section">
 ///     <descriptor name="amqp:data:binary" code="0x00000000:0x00000075"/>
 /// </type>
+///
+/// The content is backed by [`Bytes`]. When a message with a single `Data` section is decoded
+/// with [`decode_message_from_bytes`](crate::messaging::message::DecodeIntoMessage::decode_message_from_bytes)
+/// (eg. when received by a link), it shares the buffer of the incoming transfer frames instead
+/// of being copied.
 #[derive(
     Debug, Clone, PartialEq, Eq, PartialOrd, Ord, SerializeComposite, DeserializeComposite, Hash,
 )]
@@ -19,35 +33,47 @@ use crate::messaging::{
     code = "0x0000_0000:0x0000_0075",
     encoding = "basic"
 )]
-pub struct Data(pub Binary);
+pub struct Data(pub BinaryBytes);
+
+impl From<BinaryBytes> for Data {
+    fn from(value: BinaryBytes) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Bytes> for Data {
+    fn from(value: Bytes) -> Self {
+        Self(BinaryBytes(value))
+    }
+}
 
 impl From<Binary> for Data {
     fn from(value: Binary) -> Self {
-        Self(value)
+        Self(BinaryBytes::from(value))
     }
 }
 
 impl From<Vec<u8>> for Data {
     fn from(value: Vec<u8>) -> Self {
-        Self(Binary::from(value))
+        Self(BinaryBytes::from(value))
     }
 }
 
 impl<const N: usize> From<[u8; N]> for Data {
     fn from(value: [u8; N]) -> Self {
-        Self(Binary::from(value))
+        Self(BinaryBytes::from(value.to_vec()))
     }
 }
 
 impl From<&[u8]> for Data {
     fn from(value: &[u8]) -> Self {
-        Self(Binary::from(value))
+        Self(BinaryBytes(Bytes::copy_from_slice(value)))
     }
 }
 
 impl<'a> From<Cow<'a, [u8]>> for Data {
     fn from(value: Cow<'a, [u8]>) -> Self {
-        Self(Binary::from(value.to_vec()))
+        Self(BinaryBytes::from(value.into_owned()))
     }
 }
 
@@ -56,7 +82,7 @@ impl TryFrom<Value> for Data {
 
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         if let Value::Binary(buf) = value {
-            Ok(Data(buf))
+            Ok(Data(BinaryBytes::from(buf)))
         } else {
             Err(value)
         }
@@ -64,7 +90,7 @@ impl TryFrom<Value> for Data {
 }
 
 impl Display for Data {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "Data of length: {}", self.0.len())
     }
 }
@@ -100,7 +126,43 @@ impl BodySection for Data {}
 
 impl SerializableBody for Data {}
 
-impl DeserializableBody<'_> for Data {}
+impl<'de> DeserializableBody<'de> for Data {
+    fn deserialize_shared<D>(deserializer: D, source: &Bytes) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple_struct(DESCRIBED_BASIC, 2, SharedDataVisitor { source })
+    }
+}
+
+/// Visits [`Data`] like the derived `Deserialize` but shares the buffer of `source`
+struct SharedDataVisitor<'s> {
+    source: &'s Bytes,
+}
+
+impl<'de> de::Visitor<'de> for SharedDataVisitor<'_> {
+    type Value = Data;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("struct amqp:data:binary")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        match seq.next_element()? {
+            Some(Descriptor::Name(name)) if name.as_str() == "amqp:data:binary" => {}
+            Some(Descriptor::Code(0x0000_0000_0000_0075)) => {}
+            Some(_) => return Err(de::Error::custom("Descriptor mismatch")),
+            None => return Err(de::Error::custom("Expecting descriptor")),
+        }
+        match seq.next_element_seed(Shared::<BinaryBytes>::new(self.source))? {
+            Some(binary) => Ok(Data(binary)),
+            None => Err(de::Error::custom("Insufficient number of items")),
+        }
+    }
+}
 
 impl IntoBody for Data {
     type Body = Self;
@@ -174,10 +236,14 @@ where
 
 #[cfg(test)]
 mod tests {
+    use bytes::Bytes;
     use serde_amqp::{from_slice, to_vec};
 
     use crate::messaging::{
-        message::__private::{Deserializable, Serializable},
+        message::{
+            DecodeIntoMessage,
+            __private::{Deserializable, Serializable},
+        },
         Batch, Message,
     };
 
@@ -207,4 +273,17 @@ mod tests {
 
         assert_eq!(decoded.0.body.into_inner(), expected);
     }
+
+    #[test]
+    fn test_data_shares_buffer_with_bytes() {
+        let msg = Message::builder().data(TEST_STR.as_bytes()).build();
+        let buf = Bytes::from(to_vec(&Serializable(msg)).unwrap());
+        let decoded = Data::decode_message_from_bytes(&buf).unwrap();
+
+        let body = &decoded.body.0;
+        assert_eq!(body, TEST_STR.as_bytes());
+        let start = buf.as_ptr() as usize;
+        let ptr = body.as_ptr() as usize;
+        assert!(ptr >= start && ptr + body.len() <= start + buf.len());
+    }
 }
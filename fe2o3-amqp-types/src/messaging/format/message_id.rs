@@ -1,5 +1,6 @@
 //! Message ID
 
+use alloc::string::String;
 use serde::{
     de::{self, VariantAccess},
     Serialize,
@@ -80,7 +81,7 @@ struct FieldVisitor {}
 impl de::Visitor<'_> for FieldVisitor {
     type Value = Field;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("MessageId variant")
     }
 
@@ -117,7 +118,7 @@ struct Visitor {}
 impl<'de> de::Visitor<'de> for Visitor {
     type Value = MessageId;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("enum MessageId")
     }
 
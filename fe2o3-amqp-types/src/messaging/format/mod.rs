@@ -1,10 +1,11 @@
+use alloc::string::String;
+use core::ops::{Deref, DerefMut};
 use serde::{Deserialize, Serialize};
 use serde_amqp::{
     macros::{DeserializeComposite, SerializeComposite},
     primitives::{OrderedMap, Ubyte},
     value::Value,
 };
-use std::ops::{Deref, DerefMut};
 
 use crate::primitives::SimpleValue;
 
@@ -1,5 +1,6 @@
 //! Implementation of message properties
 
+use alloc::string::String;
 use serde_amqp::{
     primitives::{Binary, Symbol, Timestamp},
     DeserializeComposite, SerializeComposite,
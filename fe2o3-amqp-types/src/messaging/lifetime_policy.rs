@@ -1,3 +1,4 @@
+use alloc::{boxed::Box, format, vec};
 use serde::{
     de::{self, VariantAccess},
     ser,
@@ -224,7 +225,7 @@ struct FieldVisitor {}
 impl de::Visitor<'_> for FieldVisitor {
     type Value = Field;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("enum LifetimePolicy")
     }
 
@@ -278,7 +279,7 @@ struct Visitor {}
 impl<'de> de::Visitor<'de> for Visitor {
     type Value = LifetimePolicy;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("enum LifetimePolicy")
     }
 
@@ -359,9 +360,12 @@ impl From<LifetimePolicy> for Fields {
 
 #[cfg(test)]
 mod tests {
-    use serde_amqp::{from_slice, to_value, to_vec};
+    use serde_amqp::{
+        described::Described, descriptor::Descriptor, from_slice, from_value, primitives::Symbol,
+        to_value, to_vec, Value,
+    };
 
-    use super::{DeleteOnClose, LifetimePolicy};
+    use super::{DeleteOnClose, DeleteOnNoMessages, LifetimePolicy};
 
     #[test]
     fn test_serialize_enum_and_struct() {
@@ -391,4 +395,24 @@ mod tests {
         let value = to_value(&delete_on_close).unwrap();
         println!("{:?}", value);
     }
+
+    #[test]
+    fn test_lifetime_policy_from_value() {
+        let value = Value::from(DeleteOnNoMessages::new());
+        let policy: LifetimePolicy = from_value(value).unwrap();
+        assert!(matches!(policy, LifetimePolicy::DeleteOnNoMessages(_)));
+
+        let value = Value::Described(Box::new(Described {
+            descriptor: Descriptor::Name(Symbol::from("amqp:delete-on-no-links:list")),
+            value: Value::List(vec![]),
+        }));
+        let policy: LifetimePolicy = from_value(value).unwrap();
+        assert!(matches!(policy, LifetimePolicy::DeleteOnNoLinks(_)));
+
+        let value = Value::Described(Box::new(Described {
+            descriptor: Descriptor::Code(0x24),
+            value: Value::List(vec![]),
+        }));
+        assert!(from_value::<LifetimePolicy>(value).is_err());
+    }
 }
@@ -1,10 +1,11 @@
-use std::{fmt::Display, marker::PhantomData};
+use alloc::{vec, vec::Vec};
+use core::{fmt::Display, marker::PhantomData};
 
 use serde::{
     de::{self, VariantAccess},
     ser, Serialize,
 };
-use serde_amqp::{primitives::Binary, Value};
+use serde_amqp::{primitives::BinaryBytes, Value};
 
 use crate::messaging::{
     AmqpSequence, AmqpValue, Batch, Data, DeserializableBody, FromBody, FromEmptyBody, IntoBody,
@@ -69,7 +70,7 @@ impl<T> Body<T> {
 
     /// Consume the delivery into the body if the body is one or more [`Data`].
     /// An error will be returned if otherwise
-    pub fn try_into_data(self) -> Result<impl Iterator<Item = Binary>, Self> {
+    pub fn try_into_data(self) -> Result<impl Iterator<Item = BinaryBytes>, Self> {
         match self {
             Body::Data(batch) => Ok(batch.into_iter().map(|data| data.0)),
             _ => Err(self),
@@ -96,7 +97,7 @@ impl<T> Body<T> {
 
     /// Get a reference to the delivery body if the body is one or more [`Data`].
     /// An error will be returned otherwise
-    pub fn try_as_data(&self) -> Result<impl Iterator<Item = &Binary>, &Self> {
+    pub fn try_as_data(&self) -> Result<impl Iterator<Item = &BinaryBytes>, &Self> {
         match self {
             Body::Data(batch) => Ok(batch.iter().map(|data| &data.0)),
             _ => Err(self),
@@ -117,7 +118,7 @@ impl<T> Display for Body<T>
 where
     T: Display,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match &self {
             Body::Value(val) => write!(f, "{}", val),
             Body::Data(_) => write!(f, "Data"),
@@ -183,7 +184,7 @@ enum Field {
 impl de::Visitor<'_> for FieldVisitor {
     type Value = Field;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("Body variant. One of Vec<Data>, Vec<AmqpSequence>, AmqpValue")
     }
 
@@ -231,7 +232,7 @@ where
 {
     type Value = Body<T>;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("enum Body")
     }
 
@@ -0,0 +1,236 @@
+//! Lazy access to the sections of an encoded message
+
+use bytes::Bytes;
+use serde::Serialize;
+use serde_amqp::{
+    descriptor::Descriptor,
+    dump::sections::{
+        APPLICATION_PROPERTIES, DELIVERY_ANNOTATIONS, FOOTER, HEADER, MESSAGE_ANNOTATIONS,
+        PROPERTIES,
+    },
+    lazy::{LazyValue, LazyValues},
+};
+
+/// An encoded message whose sections are located without decoding the message
+///
+/// The sections are found by skipping over the encoded values by their size prefix, and the
+/// returned [`LazyValue`]s share the buffer of the message. This is useful to route or filter
+/// messages by a few fields, eg. reading `application-properties["tenant"]`.
+///
+/// The sections are returned with the descriptor, so they can be deserialized into the
+/// corresponding section type, eg. `serde_amqp::from_slice::<Properties>(section.as_slice())`.
+///
+/// Any encoded message can be wrapped, eg. the payload of an unsettled delivery restored from a
+/// link store. The receiver in `fe2o3-amqp` decodes the sections of a received message eagerly,
+/// only the body can be left encoded with `Body<LazyValue>`.
+///
+/// # Example
+///
+/// ```rust
+/// use fe2o3_amqp_types::messaging::{
+///     message::{LazyMessage, __private::Serializable},
+///     ApplicationProperties, Message,
+/// };
+///
+/// let message = Message::builder()
+///     .application_properties(
+///         ApplicationProperties::builder()
+///             .insert("tenant", "contoso")
+///             .build(),
+///     )
+///     .value("hello")
+///     .build();
+/// let buf = serde_amqp::to_vec(&Serializable(message)).unwrap();
+///
+/// let lazy = LazyMessage::new(buf);
+/// let tenant = lazy.application_property("tenant").unwrap().unwrap();
+/// let tenant: String = serde_amqp::from_slice(tenant.as_slice()).unwrap();
+/// assert_eq!(tenant, "contoso");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LazyMessage {
+    bytes: Bytes,
+}
+
+impl LazyMessage {
+    /// Creates a lazy message from the encoded message, eg. the payload of a transfer
+    pub fn new(bytes: impl Into<Bytes>) -> Self {
+        Self {
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Get the encoded message
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Convert into the encoded message
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
+
+    /// Returns an iterator over the encoded sections
+    pub fn sections(&self) -> LazyValues {
+        LazyValues::new(self.bytes.clone())
+    }
+
+    /// Returns the first section with a descriptor that matches either the `code` or the `name`
+    pub fn section(&self, code: u64, name: &str) -> Result<Option<LazyValue>, serde_amqp::Error> {
+        for section in self.sections() {
+            let section = section?;
+            let matches = match section.descriptor()? {
+                Some(Descriptor::Code(value)) => value == code,
+                Some(Descriptor::Name(value)) => value.as_str() == name,
+                None => false,
+            };
+            if matches {
+                return Ok(Some(section));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the header section
+    pub fn header(&self) -> Result<Option<LazyValue>, serde_amqp::Error> {
+        self.section(HEADER.0, HEADER.1)
+    }
+
+    /// Returns the delivery annotations section
+    pub fn delivery_annotations(&self) -> Result<Option<LazyValue>, serde_amqp::Error> {
+        self.section(DELIVERY_ANNOTATIONS.0, DELIVERY_ANNOTATIONS.1)
+    }
+
+    /// Returns the message annotations section
+    pub fn message_annotations(&self) -> Result<Option<LazyValue>, serde_amqp::Error> {
+        self.section(MESSAGE_ANNOTATIONS.0, MESSAGE_ANNOTATIONS.1)
+    }
+
+    /// Returns the properties section
+    pub fn properties(&self) -> Result<Option<LazyValue>, serde_amqp::Error> {
+        self.section(PROPERTIES.0, PROPERTIES.1)
+    }
+
+    /// Returns the application properties section
+    pub fn application_properties(&self) -> Result<Option<LazyValue>, serde_amqp::Error> {
+        self.section(APPLICATION_PROPERTIES.0, APPLICATION_PROPERTIES.1)
+    }
+
+    /// Returns the footer section
+    pub fn footer(&self) -> Result<Option<LazyValue>, serde_amqp::Error> {
+        self.section(FOOTER.0, FOOTER.1)
+    }
+
+    /// Returns the value of an application property
+    pub fn application_property(&self, key: &str) -> Result<Option<LazyValue>, serde_amqp::Error> {
+        map_entry(self.application_properties()?, key)
+    }
+
+    /// Returns the value of a message annotation. The key is usually a
+    /// [`Symbol`](crate::primitives::Symbol).
+    pub fn message_annotation<K>(&self, key: &K) -> Result<Option<LazyValue>, serde_amqp::Error>
+    where
+        K: Serialize + ?Sized,
+    {
+        map_entry(self.message_annotations()?, key)
+    }
+}
+
+impl From<Bytes> for LazyMessage {
+    fn from(bytes: Bytes) -> Self {
+        Self::new(bytes)
+    }
+}
+
+fn map_entry<K>(section: Option<LazyValue>, key: &K) -> Result<Option<LazyValue>, serde_amqp::Error>
+where
+    K: Serialize + ?Sized,
+{
+    match section {
+        Some(section) => match section.described_value()? {
+            Some(map) => map.get_key(key),
+            None => Ok(None),
+        },
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_amqp::{from_slice, primitives::Symbol, to_vec};
+
+    use crate::messaging::{
+        message::__private::Serializable, ApplicationProperties, Header, Message,
+        MessageAnnotations, Priority, Properties,
+    };
+
+    use super::LazyMessage;
+
+    fn encoded_message() -> Vec<u8> {
+        let message = Message::builder()
+            .header(Header {
+                priority: Priority(7),
+                ..Default::default()
+            })
+            .message_annotations(
+                MessageAnnotations::builder()
+                    .insert(Symbol::from("x-opt-partition"), 3i32)
+                    .build(),
+            )
+            .properties(Properties::builder().message_id(1u64).build())
+            .application_properties(
+                ApplicationProperties::builder()
+                    .insert("region", "west")
+                    .insert("tenant", "contoso")
+                    .build(),
+            )
+            .data(vec![0u8; 1024])
+            .build();
+        to_vec(&Serializable(message)).unwrap()
+    }
+
+    #[test]
+    fn test_find_sections() {
+        let lazy = LazyMessage::new(encoded_message());
+        assert_eq!(lazy.sections().count(), 5);
+
+        let header = lazy.header().unwrap().unwrap();
+        let header: Header = from_slice(header.as_slice()).unwrap();
+        assert_eq!(header.priority, Priority(7));
+
+        let properties = lazy.properties().unwrap().unwrap();
+        let properties: Properties = from_slice(properties.as_slice()).unwrap();
+        assert_eq!(properties.message_id, Some(1u64.into()));
+
+        assert!(lazy.delivery_annotations().unwrap().is_none());
+        assert!(lazy.footer().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_application_property() {
+        let lazy = LazyMessage::new(encoded_message());
+        let tenant = lazy.application_property("tenant").unwrap().unwrap();
+        let tenant: String = from_slice(tenant.as_slice()).unwrap();
+        assert_eq!(tenant, "contoso");
+        assert!(lazy.application_property("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_message_annotation() {
+        let lazy = LazyMessage::new(encoded_message());
+        let partition = lazy
+            .message_annotation(&Symbol::from("x-opt-partition"))
+            .unwrap()
+            .unwrap();
+        let partition: i32 = from_slice(partition.as_slice()).unwrap();
+        assert_eq!(partition, 3);
+    }
+
+    #[test]
+    fn test_message_without_sections() {
+        let message = Message::builder().value("hello").build();
+        let lazy = LazyMessage::new(to_vec(&Serializable(message)).unwrap());
+        assert!(lazy.application_property("tenant").unwrap().is_none());
+        assert!(lazy.header().unwrap().is_none());
+    }
+}
@@ -0,0 +1,205 @@
+//! Expiry and redelivery of a message
+
+use serde_amqp::primitives::Timestamp;
+
+use crate::messaging::{Header, MessageAnnotations, Outcome};
+
+use super::Message;
+
+impl<B> Message<B> {
+    /// The time at which the message expires, or `None` if the message never expires
+    ///
+    /// This is the earliest of the `absolute_expiry_time` of the properties and the
+    /// `creation_time` of the properties plus the `ttl` of the header. The `ttl` is ignored if
+    /// the `creation_time` is not set, use [`expiry_time_since`](Self::expiry_time_since) to
+    /// count the `ttl` from the arrival of the message instead.
+    pub fn expiry_time(&self) -> Option<Timestamp> {
+        let creation_time = self
+            .properties
+            .as_ref()
+            .and_then(|p| p.creation_time.clone());
+        self.expiry_time_inner(creation_time)
+    }
+
+    /// The time at which the message expires if the `ttl` of the header is counted from
+    /// `arrival`, or `None` if the message never expires
+    ///
+    /// The `absolute_expiry_time` of the properties still applies if it is earlier.
+    pub fn expiry_time_since(&self, arrival: Timestamp) -> Option<Timestamp> {
+        self.expiry_time_inner(Some(arrival))
+    }
+
+    fn expiry_time_inner(&self, ttl_start: Option<Timestamp>) -> Option<Timestamp> {
+        let absolute = self
+            .properties
+            .as_ref()
+            .and_then(|p| p.absolute_expiry_time.as_ref())
+            .map(Timestamp::milliseconds);
+        let relative = match (ttl_start, self.header.as_ref().and_then(|h| h.ttl)) {
+            (Some(start), Some(ttl)) => Some(start.milliseconds().saturating_add(i64::from(ttl))),
+            _ => None,
+        };
+        match (absolute, relative) {
+            (Some(a), Some(r)) => Some(a.min(r)),
+            (a, r) => a.or(r),
+        }
+        .map(Timestamp::from_milliseconds)
+    }
+
+    /// Whether the message has expired at `now`
+    pub fn is_expired_at(&self, now: &Timestamp) -> bool {
+        self.expiry_time()
+            .map(|expiry| expiry.milliseconds() <= now.milliseconds())
+            .unwrap_or(false)
+    }
+
+    /// The number of unsuccessful previous delivery attempts
+    pub fn delivery_count(&self) -> u32 {
+        self.header
+            .as_ref()
+            .map(|h| h.delivery_count)
+            .unwrap_or_default()
+    }
+
+    /// Whether the message has not been acquired by any other link
+    pub fn is_first_acquirer(&self) -> bool {
+        self.header
+            .as_ref()
+            .map(|h| h.first_acquirer)
+            .unwrap_or_default()
+    }
+
+    /// Updates the message for a redelivery after the receiver reported `outcome`. Returns
+    /// whether the message should be redelivered
+    ///
+    /// | Outcome | Redelivered | Update |
+    /// |---------|-------------|--------|
+    /// | [`Accepted`](crate::messaging::Accepted) | no | none |
+    /// | [`Rejected`](crate::messaging::Rejected) | no | none |
+    /// | [`Released`](crate::messaging::Released) | yes | `first_acquirer` is cleared |
+    /// | [`Modified`](crate::messaging::Modified) | yes | `first_acquirer` is cleared, `delivery_count` is incremented if `delivery_failed` is set, and the `message_annotations` of the outcome are merged into the message annotations |
+    ///
+    /// A message that is modified with `undeliverable_here` set is still redelivered, and the
+    /// caller is responsible for not redelivering it to the same link.
+    pub fn prepare_redelivery(&mut self, outcome: &Outcome) -> bool {
+        let modified = match outcome {
+            Outcome::Released(_) => None,
+            Outcome::Modified(modified) => Some(modified),
+            _ => return false,
+        };
+
+        // Only touch the header if it is present or needs to change
+        if self.header.is_some() || modified.is_some_and(|m| m.delivery_failed == Some(true)) {
+            let header = self.header.get_or_insert_with(Header::default);
+            header.first_acquirer = false;
+            if modified.is_some_and(|m| m.delivery_failed == Some(true)) {
+                header.delivery_count = header.delivery_count.saturating_add(1);
+            }
+        }
+
+        if let Some(annotations) = modified.and_then(|m| m.message_annotations.as_ref()) {
+            let message_annotations = self
+                .message_annotations
+                .get_or_insert_with(MessageAnnotations::default);
+            for (key, value) in annotations.iter() {
+                message_annotations
+                    .0
+                    .insert(key.clone().into(), value.clone());
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_amqp::primitives::Timestamp;
+
+    use crate::{
+        definitions::Fields,
+        messaging::{
+            annotations::AnnotationKey, Accepted, Header, Message, Modified, Properties, Released,
+        },
+    };
+
+    #[test]
+    fn expiry_time() {
+        let message = Message::builder()
+            .header(Header::builder().ttl(Some(1000)).build())
+            .properties(
+                Properties::builder()
+                    .creation_time(Timestamp::from_milliseconds(5000))
+                    .build(),
+            )
+            .value(1)
+            .build();
+        assert_eq!(
+            message.expiry_time(),
+            Some(Timestamp::from_milliseconds(6000))
+        );
+        assert_eq!(
+            message.expiry_time_since(Timestamp::from_milliseconds(5500)),
+            Some(Timestamp::from_milliseconds(6500))
+        );
+        assert!(!message.is_expired_at(&Timestamp::from_milliseconds(5999)));
+        assert!(message.is_expired_at(&Timestamp::from_milliseconds(6000)));
+
+        let message = Message::builder()
+            .header(Header::builder().ttl(Some(1000)).build())
+            .properties(
+                Properties::builder()
+                    .creation_time(Timestamp::from_milliseconds(5000))
+                    .absolute_expiry_time(Timestamp::from_milliseconds(5500))
+                    .build(),
+            )
+            .value(1)
+            .build();
+        assert_eq!(
+            message.expiry_time(),
+            Some(Timestamp::from_milliseconds(5500))
+        );
+
+        let message = Message::builder()
+            .header(Header::builder().ttl(Some(1000)).build())
+            .value(1)
+            .build();
+        assert_eq!(message.expiry_time(), None);
+        assert!(!message.is_expired_at(&Timestamp::from_milliseconds(i64::MAX)));
+    }
+
+    #[test]
+    fn prepare_redelivery() {
+        let mut message = Message::builder()
+            .header(Header::builder().first_acquirer(true).build())
+            .value(1)
+            .build();
+
+        assert!(!message.prepare_redelivery(&Accepted {}.into()));
+        assert!(message.is_first_acquirer());
+
+        assert!(message.prepare_redelivery(&Released {}.into()));
+        assert!(!message.is_first_acquirer());
+        assert_eq!(message.delivery_count(), 0);
+
+        let mut annotations = Fields::new();
+        annotations.insert("x-opt-reason".into(), "timeout".into());
+        let modified = Modified {
+            delivery_failed: Some(true),
+            undeliverable_here: None,
+            message_annotations: Some(annotations),
+        };
+        assert!(message.prepare_redelivery(&modified.into()));
+        assert_eq!(message.delivery_count(), 1);
+        let reason = message
+            .message_annotations
+            .as_ref()
+            .and_then(|a| a.get(&"x-opt-reason" as &dyn AnnotationKey));
+        assert_eq!(reason, Some(&"timeout".into()));
+
+        // A message without header gets one only when the delivery count changes
+        let mut message = Message::builder().value(1).build();
+        assert!(message.prepare_redelivery(&Released {}.into()));
+        assert!(message.header.is_none());
+    }
+}
@@ -1,7 +1,8 @@
 //! Implementation of Message as defined in AMQP 1.0 protocol Part 3.2
 
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
+use bytes::Bytes;
 use serde::{
     de::{self},
     ser::SerializeStruct,
@@ -10,13 +11,22 @@ use serde::{
 use serde_amqp::__constants::{DESCRIBED_BASIC, DESCRIPTOR};
 
 use super::{
-    AmqpSequence, AmqpValue, ApplicationProperties, Batch, Data, DeliveryAnnotations, Footer,
-    FromBody, Header, IntoBody, MessageAnnotations, Properties, SerializableBody,
+    AmqpSequence, AmqpValue, ApplicationProperties, Batch, Data, DeliveryAnnotations,
+    DeserializableBody, Footer, FromBody, Header, IntoBody, MessageAnnotations, Properties,
+    SerializableBody,
 };
 
 mod body;
 pub use body::*;
 
+mod lazy;
+pub use lazy::*;
+
+mod lifecycle;
+
+mod typed;
+pub use typed::MapSectionError;
+
 #[doc(hidden)]
 pub mod __private {
     #[derive(Debug)]
@@ -43,6 +53,13 @@ pub trait DecodeIntoMessage: Sized {
     fn decode_message_from_reader<'de>(
         reader: impl serde_amqp::read::Read<'de>,
     ) -> Result<Message<Self>, Self::DecodeError>;
+
+    /// Decode bytes into [`Message<T>`]
+    ///
+    /// A [`Data`] body shares the buffer of `bytes` instead of being copied.
+    fn decode_message_from_bytes(bytes: &Bytes) -> Result<Message<Self>, Self::DecodeError> {
+        Self::decode_message_from_reader(serde_amqp::read::BytesReader::new(bytes))
+    }
 }
 
 impl<T> DecodeIntoMessage for T
@@ -59,6 +76,12 @@ where
         let mut de = serde_amqp::de::Deserializer::new(reader);
         Deserializable::<Message<T>>::deserialize(&mut de).map(|deserializable| deserializable.0)
     }
+
+    fn decode_message_from_bytes(bytes: &Bytes) -> Result<Message<Self>, Self::DecodeError> {
+        let reader = serde_amqp::read::BytesReader::new(bytes);
+        let mut de = serde_amqp::de::Deserializer::new(reader);
+        Message::<T>::deserialize_with_source(&mut de, Some(bytes))
+    }
 }
 
 /// AMQP 1.0 Message
@@ -233,7 +256,7 @@ struct FieldVisitor {}
 impl de::Visitor<'_> for FieldVisitor {
     type Value = Field;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("Field")
     }
 
@@ -281,17 +304,38 @@ impl<'de> de::Deserialize<'de> for Field {
     }
 }
 
-struct Visitor<B> {
+/// Deserializes a body section sharing the buffer of `source`
+struct SharedBody<'s, T> {
+    source: &'s Bytes,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T> de::DeserializeSeed<'de> for SharedBody<'_, T>
+where
+    T: DeserializableBody<'de>,
+{
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize_shared(deserializer, self.source)
+    }
+}
+
+struct Visitor<'s, B> {
     marker: PhantomData<B>,
+    source: Option<&'s Bytes>,
 }
 
-impl<'de, B> de::Visitor<'de> for Visitor<B>
+impl<'de, B> de::Visitor<'de> for Visitor<'_, B>
 where
     B: FromBody<'de>,
 {
     type Value = Message<B>;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("struct Message")
     }
 
@@ -337,7 +381,13 @@ where
                     count += 1;
                 }
                 Field::Body => {
-                    let deserializable: Option<B::Body> = seq.next_element()?;
+                    let deserializable: Option<B::Body> = match self.source {
+                        Some(source) => seq.next_element_seed(SharedBody {
+                            source,
+                            marker: PhantomData,
+                        })?,
+                        None => seq.next_element()?,
+                    };
                     body = deserializable.map(<B as FromBody>::from_body);
                     count += 1;
                 }
@@ -371,6 +421,17 @@ where
     B: FromBody<'de>,
 {
     fn deserialize<D>(deserializer: D) -> Result<Self, <D as serde::Deserializer<'de>>::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Self::deserialize_with_source(deserializer, None)
+    }
+
+    /// The body section shares the buffer of `source` if available
+    fn deserialize_with_source<D>(
+        deserializer: D,
+        source: Option<&Bytes>,
+    ) -> Result<Self, <D as serde::Deserializer<'de>>::Error>
     where
         D: serde::Deserializer<'de>,
     {
@@ -394,6 +455,7 @@ where
             ],
             Visitor::<B> {
                 marker: PhantomData,
+                source,
             },
         )
     }
@@ -635,7 +697,7 @@ mod tests {
 
     #[test]
     fn test_convert_data_into_message() {
-        let data = Data(Binary::from("hello AMQP"));
+        let data = Data::from(Binary::from("hello AMQP"));
         let message = Message::from(data);
         let buf = to_vec(&Serializable(message)).unwrap();
         assert_eq!(buf[2], 0x75);
@@ -679,7 +741,7 @@ mod tests {
     #[test]
     fn test_serialize_deserialize_body() {
         let data = b"amqp".to_vec();
-        let data = Data(ByteBuf::from(data));
+        let data = Data::from(ByteBuf::from(data));
         let body = Body::<Value>::Data(vec![data].into());
         let serialized = to_vec(&body).unwrap();
         println!("{:x?}", serialized);
@@ -833,7 +895,7 @@ mod tests {
     fn test_encode_message_builder_with_data_batch() {
         use serde_amqp::extensions::TransparentVec;
 
-        let data = Data(Binary::from(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]));
+        let data = Data::from(Binary::from(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]));
         let data_batch = TransparentVec::new(vec![data.clone(), data.clone(), data.clone()]);
         let message = Message::builder()
             .header(Header::default())
@@ -854,7 +916,7 @@ mod tests {
     fn test_encode_message_with_data_batch() {
         use serde_amqp::extensions::TransparentVec;
 
-        let data = Data(Binary::from(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]));
+        let data = Data::from(Binary::from(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]));
         let data_batch = TransparentVec::new(vec![data.clone(), data.clone(), data.clone()]);
         let message = Message {
             header: Some(Header::default()),
@@ -885,7 +947,7 @@ mod tests {
         ];
         let message: Deserializable<Message<Batch<Data>>> = from_slice(&buf[..]).unwrap();
 
-        let data = Data(Binary::from(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]));
+        let data = Data::from(Binary::from(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]));
         let data_batch = vec![data.clone(), data.clone(), data.clone()];
         let expected = Message::builder()
             .header(Header::default())
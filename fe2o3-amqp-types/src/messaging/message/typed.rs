@@ -0,0 +1,636 @@
+//! Typed access to the application properties and message annotations of a message
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::{self, Vec},
+};
+use core::fmt::Display;
+
+use serde::{
+    de::{self, value::StringDeserializer, DeserializeOwned, IntoDeserializer},
+    forward_to_deserialize_any,
+    ser::{self, Impossible},
+    Serialize,
+};
+use serde_amqp::{primitives::Symbol, value::Deserializer as ValueDeserializer, Value};
+
+use crate::{
+    messaging::{annotations::OwnedKey, ApplicationProperties, MessageAnnotations},
+    primitives::SimpleValue,
+};
+
+use super::Message;
+
+/// Error with (de)serializing a type from/to the application properties or message annotations
+/// of a message
+#[derive(Debug)]
+pub enum MapSectionError {
+    /// A key required by the type is not found
+    MissingKey(&'static str),
+
+    /// The value of the key cannot be (de)serialized as the type of the field
+    InvalidValue {
+        /// The key of the entry
+        key: String,
+
+        /// The underlying error
+        source: serde_amqp::Error,
+    },
+
+    /// The type is not serialized as a struct or a map with string keys
+    NotAMap,
+
+    /// Custom error
+    Custom(String),
+}
+
+impl Display for MapSectionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MapSectionError::MissingKey(key) => write!(f, "Missing key {:?}", key),
+            MapSectionError::InvalidValue { key, source } => {
+                write!(f, "Invalid value for key {:?}: {}", key, source)
+            }
+            MapSectionError::NotAMap => f.write_str("Expecting a struct or a map with string keys"),
+            MapSectionError::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl de::StdError for MapSectionError {}
+
+impl de::Error for MapSectionError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::MissingKey(field)
+    }
+}
+
+impl ser::Error for MapSectionError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl<B> Message<B> {
+    /// Deserializes the application properties into `T`
+    ///
+    /// Each field of `T` is looked up by name. A missing application properties section is
+    /// treated as an empty one, so a field that is not an `Option` results in
+    /// [`MapSectionError::MissingKey`]. Entries that do not correspond to a field are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use fe2o3_amqp_types::messaging::{ApplicationProperties, Message};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Order {
+    ///     id: i64,
+    ///     region: Option<String>,
+    /// }
+    ///
+    /// let message = Message::builder()
+    ///     .application_properties(ApplicationProperties::builder().insert("id", 42i64).build())
+    ///     .value("order")
+    ///     .build();
+    /// let order: Order = message.deserialize_application_properties().unwrap();
+    /// assert_eq!(order.id, 42);
+    /// assert_eq!(order.region, None);
+    /// ```
+    pub fn deserialize_application_properties<T>(&self) -> Result<T, MapSectionError>
+    where
+        T: DeserializeOwned,
+    {
+        let entries = self
+            .application_properties
+            .iter()
+            .flat_map(|properties| properties.0.iter())
+            .map(|(key, value)| (key.clone(), Value::from(value.clone())))
+            .collect();
+        T::deserialize(MapSectionDeserializer::new(entries))
+    }
+
+    /// Serializes `value` into the application properties, which is created if absent
+    ///
+    /// The fields of `value` overwrite the entries with the same key and the other entries are
+    /// kept. A field that is serialized as null is not written. A field whose value is not a
+    /// simple type results in [`MapSectionError::InvalidValue`], in which case the application
+    /// properties are left unchanged.
+    pub fn serialize_application_properties<T>(&mut self, value: &T) -> Result<(), MapSectionError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let entries = value
+            .serialize(MapSectionSerializer)?
+            .into_iter()
+            .map(|(key, value)| match SimpleValue::try_from(value) {
+                Ok(value) => Ok((key, value)),
+                Err(source) => Err(MapSectionError::InvalidValue { key, source }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let properties = self
+            .application_properties
+            .get_or_insert_with(ApplicationProperties::default);
+        for (key, value) in entries {
+            properties.0.insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// Deserializes the message annotations with symbol keys into `T`
+    ///
+    /// This follows the same rules as
+    /// [`deserialize_application_properties`](Self::deserialize_application_properties).
+    /// Annotations with ulong keys are ignored.
+    pub fn deserialize_message_annotations<T>(&self) -> Result<T, MapSectionError>
+    where
+        T: DeserializeOwned,
+    {
+        let entries = self
+            .message_annotations
+            .iter()
+            .flat_map(|annotations| annotations.0.iter())
+            .filter_map(|(key, value)| match key {
+                OwnedKey::Symbol(key) => Some((key.0.clone(), value.clone())),
+                OwnedKey::Ulong(_) => None,
+            })
+            .collect();
+        T::deserialize(MapSectionDeserializer::new(entries))
+    }
+
+    /// Serializes `value` into the message annotations with symbol keys, which is created if
+    /// absent
+    ///
+    /// This follows the same rules as
+    /// [`serialize_application_properties`](Self::serialize_application_properties) except that
+    /// the values are not restricted to simple types.
+    pub fn serialize_message_annotations<T>(&mut self, value: &T) -> Result<(), MapSectionError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let entries = value.serialize(MapSectionSerializer)?;
+        let annotations = self
+            .message_annotations
+            .get_or_insert_with(MessageAnnotations::default);
+        for (key, value) in entries {
+            annotations.0.insert(OwnedKey::Symbol(Symbol(key)), value);
+        }
+        Ok(())
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                Deserializer                                */
+/* -------------------------------------------------------------------------- */
+
+/// Deserializes the entries of a map section as a map
+struct MapSectionDeserializer {
+    entries: vec::IntoIter<(String, Value)>,
+    value: Option<(String, Value)>,
+}
+
+impl MapSectionDeserializer {
+    fn new(entries: Vec<(String, Value)>) -> Self {
+        Self {
+            entries: entries.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for MapSectionDeserializer {
+    type Error = MapSectionError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_map(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapSectionDeserializer {
+    type Error = MapSectionError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.entries.next() {
+            Some((key, value)) => {
+                let deserializer: StringDeserializer<MapSectionError> =
+                    key.clone().into_deserializer();
+                self.value = Some((key, value));
+                seed.deserialize(deserializer).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| MapSectionError::Custom(String::from("Value is missing")))?;
+        seed.deserialize(ValueDeserializer::new(value))
+            .map_err(|source| MapSectionError::InvalidValue { key, source })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                 Serializer                                 */
+/* -------------------------------------------------------------------------- */
+
+/// Serializes a struct or a map with string keys into the entries of a map section
+struct MapSectionSerializer;
+
+type Entries = Vec<(String, Value)>;
+
+impl ser::Serializer for MapSectionSerializer {
+    type Ok = Entries;
+    type Error = MapSectionError;
+
+    type SerializeSeq = Impossible<Entries, MapSectionError>;
+    type SerializeTuple = Impossible<Entries, MapSectionError>;
+    type SerializeTupleStruct = Impossible<Entries, MapSectionError>;
+    type SerializeTupleVariant = Impossible<Entries, MapSectionError>;
+    type SerializeMap = EntriesSerializer;
+    type SerializeStruct = EntriesSerializer;
+    type SerializeStructVariant = Impossible<Entries, MapSectionError>;
+
+    fn serialize_bool(self, _: bool) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_char(self, _: char) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_str(self, _: &str) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(EntriesSerializer::with_capacity(len.unwrap_or_default()))
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(EntriesSerializer::with_capacity(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(MapSectionError::NotAMap)
+    }
+}
+
+struct EntriesSerializer {
+    entries: Entries,
+    key: Option<String>,
+}
+
+impl EntriesSerializer {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            key: None,
+        }
+    }
+
+    fn push<T>(&mut self, key: String, value: &T) -> Result<(), MapSectionError>
+    where
+        T: ?Sized + Serialize,
+    {
+        match serde_amqp::to_value(&value) {
+            Ok(Value::Null) => Ok(()),
+            Ok(value) => {
+                self.entries.push((key, value));
+                Ok(())
+            }
+            Err(source) => Err(MapSectionError::InvalidValue { key, source }),
+        }
+    }
+}
+
+impl ser::SerializeStruct for EntriesSerializer {
+    type Ok = Entries;
+    type Error = MapSectionError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(String::from(key), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.entries)
+    }
+}
+
+impl ser::SerializeMap for EntriesSerializer {
+    type Ok = Entries;
+    type Error = MapSectionError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = match serde_amqp::to_value(&key) {
+            Ok(Value::String(key)) | Ok(Value::Symbol(Symbol(key))) => key,
+            Ok(key) => {
+                return Err(MapSectionError::Custom(format!(
+                    "Expecting a string key, found {:?}",
+                    key
+                )))
+            }
+            Err(err) => return Err(MapSectionError::Custom(err.to_string())),
+        };
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| MapSectionError::Custom(String::from("Key is missing")))?;
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::BTreeMap, string::String};
+
+    use serde::{Deserialize, Serialize};
+    use serde_amqp::primitives::Timestamp;
+
+    use crate::{
+        messaging::{annotations::AnnotationKey, ApplicationProperties, Message},
+        primitives::{SimpleValue, Value},
+    };
+
+    use super::MapSectionError;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        id: i64,
+        region: Option<String>,
+        express: bool,
+    }
+
+    #[test]
+    fn application_properties_round_trip() {
+        let order = Order {
+            id: 7,
+            region: None,
+            express: true,
+        };
+        let mut message = Message::builder()
+            .application_properties(
+                ApplicationProperties::builder()
+                    .insert("other", "kept")
+                    .build(),
+            )
+            .value(1)
+            .build();
+        message.serialize_application_properties(&order).unwrap();
+
+        let properties = message.application_properties.as_ref().unwrap();
+        assert_eq!(properties.get("id"), Some(&SimpleValue::Long(7)));
+        assert_eq!(properties.get("region"), None);
+        assert_eq!(
+            properties.get("other"),
+            Some(&SimpleValue::String(String::from("kept")))
+        );
+
+        let decoded: Order = message.deserialize_application_properties().unwrap();
+        assert_eq!(decoded, order);
+
+        let mut map = BTreeMap::new();
+        map.insert("region", "eu");
+        message.serialize_application_properties(&map).unwrap();
+        let decoded: Order = message.deserialize_application_properties().unwrap();
+        assert_eq!(decoded.region.as_deref(), Some("eu"));
+    }
+
+    #[test]
+    fn missing_and_invalid_keys() {
+        let message = Message::builder()
+            .application_properties(ApplicationProperties::builder().insert("id", 1i64).build())
+            .value(1)
+            .build();
+        let err = message
+            .deserialize_application_properties::<Order>()
+            .unwrap_err();
+        assert!(matches!(err, MapSectionError::MissingKey("express")));
+
+        let message = Message::builder()
+            .application_properties(
+                ApplicationProperties::builder()
+                    .insert("id", "not a number")
+                    .insert("express", false)
+                    .build(),
+            )
+            .value(1)
+            .build();
+        let err = message
+            .deserialize_application_properties::<Order>()
+            .unwrap_err();
+        assert!(matches!(err, MapSectionError::InvalidValue { key, .. } if key == "id"));
+
+        let mut message = Message::builder().value(1).build();
+        let err = message.serialize_application_properties(&1).unwrap_err();
+        assert!(matches!(err, MapSectionError::NotAMap));
+        let err = message
+            .serialize_application_properties(&BTreeMap::from([("list", [1, 2])]))
+            .unwrap_err();
+        assert!(matches!(err, MapSectionError::InvalidValue { key, .. } if key == "list"));
+        assert!(message.application_properties.is_none());
+    }
+
+    #[test]
+    fn message_annotations_round_trip() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Annotations {
+            #[serde(rename = "x-opt-enqueued-time")]
+            enqueued_time: Timestamp,
+            #[serde(rename = "x-opt-sequence-number")]
+            sequence_number: i64,
+        }
+
+        let annotations = Annotations {
+            enqueued_time: Timestamp::from_milliseconds(1000),
+            sequence_number: 3,
+        };
+        let mut message = Message::builder().value(1).build();
+        message.serialize_message_annotations(&annotations).unwrap();
+
+        let value = message
+            .message_annotations
+            .as_ref()
+            .and_then(|a| a.get(&"x-opt-sequence-number" as &dyn AnnotationKey));
+        assert_eq!(value, Some(&Value::Long(3)));
+
+        let decoded: Annotations = message.deserialize_message_annotations().unwrap();
+        assert_eq!(decoded, annotations);
+    }
+}
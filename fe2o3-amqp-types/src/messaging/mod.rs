@@ -1,5 +1,6 @@
 //! Types defined in AMQP 1.0 specification Part 3: Messaging
 
+use alloc::vec;
 use serde::{Deserialize, Serialize};
 use serde_amqp::extensions::TransparentVec;
 use serde_amqp::primitives::{Array, OrderedMap};
@@ -59,7 +59,7 @@ mod target_archetype_serde_impl {
     impl de::Visitor<'_> for FieldVisitor {
         type Value = Field;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
             formatter.write_str("variant identifier for TargetArchetype")
         }
 
@@ -112,7 +112,7 @@ mod target_archetype_serde_impl {
     impl<'de> de::Visitor<'de> for Visitor {
         type Value = TargetArchetype;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
             formatter.write_str("variant identifier for TargetArchetype")
         }
 
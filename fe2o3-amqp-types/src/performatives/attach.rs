@@ -1,3 +1,4 @@
+use alloc::{boxed::Box, string::String};
 use serde_amqp::{
     macros::{DeserializeComposite, SerializeComposite},
     primitives::{Array, Boolean, OrderedMap, Symbol, Ulong},
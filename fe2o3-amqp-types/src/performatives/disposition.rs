@@ -112,7 +112,7 @@ mod tests {
                 struct FieldVisitor {}
                 impl<'de> serde_amqp::serde::de::Visitor<'de> for FieldVisitor {
                     type Value = Field;
-                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                         formatter.write_str("field identifier")
                     }
                     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -155,7 +155,7 @@ mod tests {
                 struct Visitor {}
                 impl<'de> serde_amqp::serde::de::Visitor<'de> for Visitor {
                     type Value = Disposition;
-                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                         formatter.write_str("struct amqp:disposition:list")
                     }
                     fn visit_seq<A>(self, mut __seq: A) -> Result<Self::Value, A::Error>
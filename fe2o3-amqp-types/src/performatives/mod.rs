@@ -52,6 +52,7 @@ pub enum Performative {
 }
 
 mod performative_impl {
+    use alloc::format;
     use serde::{
         de::{self, VariantAccess},
         ser,
@@ -95,7 +96,7 @@ mod performative_impl {
     impl de::Visitor<'_> for FieldVisitor {
         type Value = Field;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
             formatter.write_str("variant identifier")
         }
 
@@ -158,7 +159,7 @@ mod performative_impl {
     impl<'de> de::Visitor<'de> for Visitor {
         type Value = Performative;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
             formatter.write_str("enum DeliveryState")
         }
 
@@ -1,6 +1,7 @@
 //! Simple values. A subset of the primitive types.
 
 use super::*;
+use alloc::string::{String, ToString};
 
 /// A subset of `Value`
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
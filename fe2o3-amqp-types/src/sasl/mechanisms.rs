@@ -1,5 +1,6 @@
 //! Manually implement Serialize and Deserialize for SaslMechanisms
 
+use alloc::vec;
 use serde::{de, ser};
 use serde_amqp::primitives::{Array, Symbol};
 
@@ -61,7 +62,7 @@ impl<'de> serde_amqp::serde::de::Deserialize<'de> for SaslMechanisms {
         struct FieldVisitor {}
         impl de::Visitor<'_> for FieldVisitor {
             type Value = Field;
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("field identifier")
             }
             fn visit_str<_E>(self, v: &str) -> Result<Self::Value, _E>
@@ -101,7 +102,7 @@ impl<'de> serde_amqp::serde::de::Deserialize<'de> for SaslMechanisms {
         }
         impl<'de> serde_amqp::serde::de::Visitor<'de> for Visitor {
             type Value = SaslMechanisms;
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("struct amqp:sasl-mechanisms:list")
             }
             fn visit_seq<_A>(self, mut __seq: _A) -> Result<Self::Value, _A::Error>
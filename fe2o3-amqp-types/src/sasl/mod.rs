@@ -1,5 +1,6 @@
 //! Types defined in AMQP 1.0 specification Part 5.3: SASL
 
+use alloc::string::String;
 use serde_amqp::{
     primitives::{Array, Binary, Symbol},
     DeserializeComposite, SerializeComposite,
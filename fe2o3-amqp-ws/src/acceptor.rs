@@ -0,0 +1,450 @@
+//! Server side of the AMQP WebSocket binding
+//!
+//! [`WebSocketStream::accept`] performs the server side of the WebSocket handshake on an incoming
+//! stream and negotiates the `"amqp"` subprotocol. The resulting stream implements
+//! `tokio::io::AsyncRead` and `tokio::io::AsyncWrite` and can be passed to
+//! `fe2o3_amqp::acceptor::ConnectionAcceptor::accept` directly.
+//!
+//! [`accept_or_http`] additionally allows a listener to serve plain HTTP requests (e.g. health
+//! checks) on the same port. The HTTP request head is read first and the stream is only upgraded
+//! if the request asks for a WebSocket upgrade; otherwise the parsed request is handed back to the
+//! user together with the stream so that an HTTP response can be written.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use fe2o3_amqp::acceptor::ConnectionAcceptor;
+//! use fe2o3_amqp_ws::acceptor::{accept_or_http, Incoming};
+//! use tokio::{io::AsyncWriteExt, net::TcpListener};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let tcp_listener = TcpListener::bind("localhost:5673").await.unwrap();
+//!     let connection_acceptor = ConnectionAcceptor::new("example-listener");
+//!
+//!     while let Ok((stream, addr)) = tcp_listener.accept().await {
+//!         match accept_or_http(stream, None).await.unwrap() {
+//!             Incoming::WebSocket(ws_stream) => {
+//!                 let connection = connection_acceptor.accept(ws_stream).await.unwrap();
+//!                 // ...
+//!             }
+//!             Incoming::Http { request, mut stream } => {
+//!                 println!("HTTP {} {} from {:?}", request.method(), request.uri(), addr);
+//!                 stream
+//!                     .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+//!                     .await
+//!                     .unwrap();
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::{
+    io::{self, Cursor, Read},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use pin_project_lite::pin_project;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+use tokio_tungstenite::accept_hdr_async_with_config;
+use tungstenite::{
+    error::CapacityError,
+    handshake::server::{ErrorResponse, Request, Response},
+    http::{HeaderValue, StatusCode},
+    protocol::WebSocketConfig,
+};
+
+use crate::{
+    native::{TokioWebSocketStream, SEC_WEBSOCKET_PROTOCOL},
+    Error, WebSocketStream, SEC_WEBSOCKET_PROTOCOL_AMQP,
+};
+
+/// Maximum length of the HTTP request head read by [`accept_or_http`]
+pub const MAX_REQUEST_HEAD_LEN: usize = 8 * 1024;
+
+/// Maximum number of HTTP headers parsed by [`accept_or_http`]
+const MAX_REQUEST_HEADERS: usize = 64;
+
+impl<S> WebSocketStream<TokioWebSocketStream<S>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Calls [`tokio_tungstenite::accept_hdr_async`] internally and responds with the
+    /// `"Sec-WebSocket-Protocol"` HTTP header set to `"amqp"`
+    ///
+    /// The handshake is rejected with HTTP status code 400 if the client does not offer the
+    /// `"amqp"` subprotocol.
+    pub async fn accept(stream: S) -> Result<Self, Error> {
+        Self::accept_with_config(stream, None).await
+    }
+
+    /// Calls [`tokio_tungstenite::accept_hdr_async_with_config`] internally and responds with the
+    /// `"Sec-WebSocket-Protocol"` HTTP header set to `"amqp"`
+    ///
+    /// The handshake is rejected with HTTP status code 400 if the client does not offer the
+    /// `"amqp"` subprotocol.
+    #[allow(clippy::result_large_err)] // TODO: refactor
+    pub async fn accept_with_config(
+        stream: S,
+        config: Option<WebSocketConfig>,
+    ) -> Result<Self, Error> {
+        let mut handshake: Option<Result<(Request, Response), Error>> = None;
+        let callback = |request: &Request, response: Response| {
+            match negotiate_amqp_subprotocol(request, response) {
+                Ok(response) => {
+                    handshake = Some(Ok((clone_request(request), response.clone())));
+                    Ok(response)
+                }
+                Err(error) => {
+                    let error_response = reject(&error);
+                    handshake = Some(Err(error));
+                    Err(error_response)
+                }
+            }
+        };
+
+        let result = accept_hdr_async_with_config(stream, callback, config).await;
+        match (result, handshake) {
+            (Ok(ws_stream), Some(Ok((request, response)))) => {
+                let response = response.map(|_| None);
+                Ok(Self::from(TokioWebSocketStream::new_accepted(
+                    ws_stream, request, response,
+                )))
+            }
+            // The error response has been sent by tungstenite
+            (Err(_), Some(Err(error))) => Err(error),
+            (Err(error), _) => Err(error.into()),
+            (Ok(_), _) => unreachable!("Callback is always invoked on a successful handshake"),
+        }
+    }
+}
+
+pin_project! {
+    /// A stream that yields the bytes already read from the inner stream before reading from the
+    /// inner stream again
+    ///
+    /// This is returned by [`accept_or_http`] which needs to read the HTTP request head before
+    /// deciding whether the stream should be upgraded to a WebSocket.
+    #[derive(Debug)]
+    pub struct PrefixedStream<S> {
+        prefix: Option<Cursor<Vec<u8>>>,
+        #[pin]
+        inner: S,
+    }
+}
+
+impl<S> PrefixedStream<S> {
+    fn new(prefix: Vec<u8>, inner: S) -> Self {
+        let prefix = match prefix.is_empty() {
+            true => None,
+            false => Some(Cursor::new(prefix)),
+        };
+        Self { prefix, inner }
+    }
+
+    /// Gets a reference to the inner stream
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the inner stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Returns the inner stream and the prefix bytes that have not been read yet
+    pub fn into_parts(self) -> (Vec<u8>, S) {
+        let prefix = match self.prefix {
+            Some(cursor) => {
+                let pos = cursor.position() as usize;
+                let mut buf = cursor.into_inner();
+                buf.drain(..pos);
+                buf
+            }
+            None => Vec::new(),
+        };
+        (prefix, self.inner)
+    }
+}
+
+impl<S: AsyncRead> AsyncRead for PrefixedStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        if let Some(cursor) = this.prefix {
+            let remaining = cursor.get_ref().len() as u64 - cursor.position();
+            if remaining > 0 {
+                let len_to_read = buf.remaining().min(remaining as usize);
+                let unfilled_buf = buf.initialize_unfilled_to(len_to_read);
+                let len = Read::read(cursor, unfilled_buf)?;
+                buf.advance(len);
+                return Poll::Ready(Ok(()));
+            }
+            *this.prefix = None;
+        }
+        this.inner.poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for PrefixedStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+/// Result of [`accept_or_http`]
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum Incoming<S> {
+    /// The client requested a WebSocket upgrade and the handshake with the `"amqp"` subprotocol
+    /// has completed
+    WebSocket(WebSocketStream<TokioWebSocketStream<PrefixedStream<S>>>),
+
+    /// The client sent a plain HTTP request. The request head has been consumed from the stream
+    /// and any bytes of the request body that were read along with it will be yielded first by
+    /// `stream`. The user is responsible for writing an HTTP response.
+    Http {
+        /// The HTTP request head
+        request: Request,
+
+        /// The underlying stream
+        stream: PrefixedStream<S>,
+    },
+}
+
+/// Reads the HTTP request head from `stream` and performs the server side WebSocket handshake
+/// with the `"amqp"` subprotocol if the client requests a WebSocket upgrade. Otherwise, the
+/// request is returned as [`Incoming::Http`] so that a plain HTTP endpoint (e.g. a health check)
+/// can be served on the same port.
+///
+/// The request head is limited to [`MAX_REQUEST_HEAD_LEN`] bytes.
+pub async fn accept_or_http<S>(
+    mut stream: S,
+    config: Option<WebSocketConfig>,
+) -> Result<Incoming<S>, Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut buf = Vec::with_capacity(1024);
+    let (head_len, request) = loop {
+        if let Some(parsed) = parse_request_head(&buf)? {
+            break parsed;
+        }
+        if buf.len() >= MAX_REQUEST_HEAD_LEN {
+            return Err(Error::Capacity(CapacityError::MessageTooLong {
+                size: buf.len(),
+                max_size: MAX_REQUEST_HEAD_LEN,
+            }));
+        }
+
+        let mut chunk = [0u8; 1024];
+        let max_len = chunk.len().min(MAX_REQUEST_HEAD_LEN - buf.len());
+        let n = stream.read(&mut chunk[..max_len]).await?;
+        if n == 0 {
+            return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    if is_websocket_upgrade(&request) {
+        // tungstenite needs to parse the request head again
+        let stream = PrefixedStream::new(buf, stream);
+        WebSocketStream::accept_with_config(stream, config)
+            .await
+            .map(Incoming::WebSocket)
+    } else {
+        buf.drain(..head_len);
+        let stream = PrefixedStream::new(buf, stream);
+        Ok(Incoming::Http { request, stream })
+    }
+}
+
+#[allow(clippy::result_large_err)] // TODO: refactor
+fn parse_request_head(buf: &[u8]) -> Result<Option<(usize, Request)>, Error> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_REQUEST_HEADERS];
+    let mut req = httparse::Request::new(&mut headers);
+    let head_len = match req.parse(buf)? {
+        httparse::Status::Complete(len) => len,
+        httparse::Status::Partial => return Ok(None),
+    };
+
+    let mut builder = Request::builder()
+        .method(req.method.unwrap_or_default())
+        .uri(req.path.unwrap_or_default())
+        .version(match req.version {
+            Some(0) => http::Version::HTTP_10,
+            _ => http::Version::HTTP_11,
+        });
+    for header in req.headers.iter() {
+        builder = builder.header(header.name, header.value);
+    }
+    let request = builder.body(())?;
+    Ok(Some((head_len, request)))
+}
+
+fn is_websocket_upgrade(request: &Request) -> bool {
+    request
+        .headers()
+        .get_all(http::header::UPGRADE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|token| token.trim().eq_ignore_ascii_case("websocket"))
+}
+
+#[allow(clippy::result_large_err)] // TODO: refactor
+fn negotiate_amqp_subprotocol(request: &Request, mut response: Response) -> Result<Response, Error> {
+    let mut protocols = request
+        .headers()
+        .get_all(SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .peekable();
+    if protocols.peek().is_none() {
+        return Err(Error::MissingSecWebSocketProtocol);
+    }
+
+    // Sec-WebSocket-Protocol HTTP header
+    //
+    // Identifies the WebSocket subprotocol. For this AMQP WebSocket binding, the value MUST be
+    // set to the US-ASCII text string “amqp” which refers to the 1.0 version of the AMQP 1.0
+    // or greater, with version negotiation as defined by AMQP 1.0.
+    let offers_amqp = protocols
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|token| token.trim() == SEC_WEBSOCKET_PROTOCOL_AMQP);
+    if !offers_amqp {
+        return Err(Error::SecWebSocketProtocolIsNotAmqp);
+    }
+
+    response.headers_mut().insert(
+        SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static(SEC_WEBSOCKET_PROTOCOL_AMQP),
+    );
+    Ok(response)
+}
+
+fn reject(error: &Error) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(error.to_string()));
+    *response.status_mut() = StatusCode::BAD_REQUEST;
+    response
+}
+
+fn clone_request(request: &Request) -> Request {
+    let mut builder = Request::builder()
+        .method(request.method().clone())
+        .uri(request.uri().clone())
+        .version(request.version());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = request.headers().clone();
+    }
+    builder
+        .body(())
+        .expect("Cloning a valid request should not fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp::acceptor::ConnectionAcceptor;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::{accept_or_http, Incoming};
+    use crate::{Error, WebSocketStream};
+
+    #[tokio::test]
+    async fn test_accept_amqp_subprotocol() {
+        let tcp_listener = TcpListener::bind("localhost:0").await.unwrap();
+        let addr = tcp_listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = tcp_listener.accept().await.unwrap();
+            let ws_stream = WebSocketStream::accept(stream).await.unwrap();
+            assert!(ws_stream.request().is_some());
+            ConnectionAcceptor::new("test-acceptor")
+                .accept(ws_stream)
+                .await
+                .unwrap()
+        });
+
+        let ws_stream = WebSocketStream::connect(format!("ws://{}", addr))
+            .await
+            .unwrap();
+        assert_eq!(
+            ws_stream.response().headers()["Sec-WebSocket-Protocol"],
+            "amqp"
+        );
+        let mut connection = fe2o3_amqp::Connection::builder()
+            .container_id("test-client")
+            .open_with_stream(ws_stream)
+            .await
+            .unwrap();
+        let listener_connection = server.await.unwrap();
+
+        connection.close().await.unwrap();
+        drop(listener_connection);
+    }
+
+    #[tokio::test]
+    async fn test_reject_missing_subprotocol() {
+        let (mut client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move { WebSocketStream::accept(server).await });
+
+        client
+            .write_all(
+                b"GET / HTTP/1.1\r\n\
+                Host: localhost\r\n\
+                Connection: Upgrade\r\n\
+                Upgrade: websocket\r\n\
+                Sec-WebSocket-Version: 13\r\n\
+                Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+            )
+            .await
+            .unwrap();
+
+        let result = server.await.unwrap();
+        assert!(matches!(result, Err(Error::MissingSecWebSocketProtocol)));
+
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        assert!(response.starts_with(b"HTTP/1.1 400"));
+    }
+
+    #[tokio::test]
+    async fn test_accept_or_http_plain_request() {
+        let (mut client, server) = tokio::io::duplex(4096);
+
+        client
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\nbody")
+            .await
+            .unwrap();
+
+        match accept_or_http(server, None).await.unwrap() {
+            Incoming::Http { request, stream } => {
+                assert_eq!(request.uri(), "/health");
+                let (prefix, _) = stream.into_parts();
+                assert_eq!(prefix, b"body");
+            }
+            Incoming::WebSocket(_) => panic!("Expecting a plain HTTP request"),
+        }
+    }
+}
@@ -68,6 +68,12 @@ pub enum Error {
     /// `tungstenite::Error::AttackAttempt` error. Attack attempt detected.
     #[error("Attack attempt detected")]
     AttackAttempt,
+
+    /// Error establishing a tunnel through the proxy
+    #[cfg_attr(docsrs, doc(cfg(feature = "proxy")))]
+    #[cfg(all(feature = "proxy", not(target_arch = "wasm32")))]
+    #[error(transparent)]
+    Proxy(crate::proxy::ProxyError),
 }
 
 impl<T: Into<tungstenite::Error>> From<T> for Error {
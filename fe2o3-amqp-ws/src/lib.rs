@@ -22,6 +22,7 @@
 //! | `native-tls-vendored` | Enables "tokio-tungstenite/native-tls-vendored" |
 //! | `rustls-tls-native-roots` | Enables "tokio-tungstenite/rustls-tls-native-roots" |
 //! | `rustls-tls-webpki-roots` | Enables "tokio-tungstenite/rustls-tls-webpki-roots" |
+//! | `proxy` | Enables HTTP CONNECT and SOCKS5 proxy tunneling |
 //!
 //! # Example
 //!
@@ -66,6 +67,31 @@
 //! }
 //! ```
 //!
+//! ## Server side
+//!
+//! [`WebSocketStream::accept`] performs the server side of the handshake and negotiates the
+//! `"amqp"` subprotocol, and the resulting stream can be passed to
+//! `fe2o3_amqp::acceptor::ConnectionAcceptor::accept`. See the [`acceptor`] module for serving
+//! plain HTTP requests (e.g. health checks) on the same port.
+//!
+//! ```rust,no_run
+//! use fe2o3_amqp::acceptor::ConnectionAcceptor;
+//! use fe2o3_amqp_ws::WebSocketStream;
+//! use tokio::net::TcpListener;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let tcp_listener = TcpListener::bind("localhost:5673").await.unwrap();
+//!     let connection_acceptor = ConnectionAcceptor::new("example-listener");
+//!
+//!     while let Ok((stream, _)) = tcp_listener.accept().await {
+//!         let ws_stream = WebSocketStream::accept(stream).await.unwrap();
+//!         let connection = connection_acceptor.accept(ws_stream).await.unwrap();
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
 //! ## WebAssembly support
 //!
 //! Experimental support for `wasm32-unknown-unknown` target has been added since "0.3.0" and uses a
@@ -89,6 +115,11 @@ mod macros;
 
 cfg_not_wasm32! {
     pub mod native;
+    pub mod acceptor;
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "proxy")))]
+    #[cfg(feature = "proxy")]
+    pub mod proxy;
 }
 
 cfg_wasm32! {
@@ -22,7 +22,7 @@ use crate::WsMessage;
 
 use super::{Error, WebSocketStream};
 
-const SEC_WEBSOCKET_PROTOCOL: &str = "Sec-WebSocket-Protocol";
+pub(crate) const SEC_WEBSOCKET_PROTOCOL: &str = "Sec-WebSocket-Protocol";
 
 pin_project! {
     /// This a simple wrapper around [`tokio_tungstenite::WebSocketStream`]
@@ -30,6 +30,7 @@ pin_project! {
     pub struct TokioWebSocketStream<S>{
         #[pin]
         stream: tokio_tungstenite::WebSocketStream<S>,
+        request: Option<Request>,
         response: Response,
     }
 }
@@ -44,9 +45,22 @@ impl<S> From<TokioWebSocketStream<S>> for WebSocketStream<TokioWebSocketStream<S
 }
 
 impl<S> TokioWebSocketStream<S> {
-    fn new(stream: tokio_tungstenite::WebSocketStream<S>, response: Response) -> Self {
+    pub(crate) fn new(stream: tokio_tungstenite::WebSocketStream<S>, response: Response) -> Self {
         Self {
             stream,
+            request: None,
+            response,
+        }
+    }
+
+    pub(crate) fn new_accepted(
+        stream: tokio_tungstenite::WebSocketStream<S>,
+        request: Request,
+        response: Response,
+    ) -> Self {
+        Self {
+            stream,
+            request: Some(request),
             response,
         }
     }
@@ -99,9 +113,21 @@ where
 impl WebSocketStream<TokioWebSocketStream<MaybeTlsStream<TcpStream>>> {
     /// Calls [`tokio_tungstenite::connect_async`] internally with `"Sec-WebSocket-Protocol"` HTTP
     /// header of the `req` set to `"amqp"`
+    ///
+    /// With the `"proxy"` feature enabled, the connection is established through the proxy
+    /// configured in the environment (see [`proxy`](crate::proxy)).
     pub async fn connect(addr: impl AsRef<str>) -> Result<Self, Error> {
         let req = addr.as_ref();
         let request = map_amqp_websocket_request(req)?;
+
+        #[cfg(feature = "proxy")]
+        if let Some((proxy, host, port)) =
+            crate::proxy::from_env(request.uri()).map_err(Error::Proxy)?
+        {
+            return crate::proxy::connect_with_config(request, None, false, &proxy, &host, port)
+                .await;
+        }
+
         let (mut ws_stream, response) = connect_async(request).await?;
         match verify_response(response) {
             Ok(response) => Ok(Self::from(TokioWebSocketStream::new(ws_stream, response))),
@@ -118,6 +144,9 @@ impl WebSocketStream<TokioWebSocketStream<MaybeTlsStream<TcpStream>>> {
     /// `disable_nagle` specifies if the Nagle’s algorithm must be disabled, 
     /// i.e. `set_nodelay(true)`. If you don’t know what the Nagle’s algorithm is, 
     /// better leave it set to `false`.
+    ///
+    /// With the `"proxy"` feature enabled, the connection is established through the proxy
+    /// configured in the environment (see [`proxy`](crate::proxy)).
     pub async fn connect_with_config(
         addr: impl AsRef<str>,
         config: Option<WebSocketConfig>,
@@ -125,6 +154,22 @@ impl WebSocketStream<TokioWebSocketStream<MaybeTlsStream<TcpStream>>> {
     ) -> Result<Self, Error> {
         let req = addr.as_ref();
         let request = map_amqp_websocket_request(req)?;
+
+        #[cfg(feature = "proxy")]
+        if let Some((proxy, host, port)) =
+            crate::proxy::from_env(request.uri()).map_err(Error::Proxy)?
+        {
+            return crate::proxy::connect_with_config(
+                request,
+                config,
+                disable_nagle,
+                &proxy,
+                &host,
+                port,
+            )
+            .await;
+        }
+
         let (mut ws_stream, response) = connect_async_with_config(request, config, disable_nagle).await?;
         match verify_response(response) {
             Ok(response) => Ok(Self::from(TokioWebSocketStream::new(ws_stream, response))),
@@ -145,6 +190,15 @@ where
         &self.inner.response
     }
 
+    /// Returns the [`Request`] of the WebSocket handshake received from the client
+    ///
+    /// This is only available on streams established by
+    /// [`WebSocketStream::accept`](crate::WebSocketStream::accept) or
+    /// [`accept_or_http`](crate::acceptor::accept_or_http)
+    pub fn request(&self) -> Option<&Request> {
+        self.inner.request.as_ref()
+    }
+
     /// Calls [`tokio_tungstenite::client_async`] internally with `"Sec-WebSocket-Protocol"` HTTP
     /// header of the `req` set to `"amqp"`
     pub async fn connect_with_stream(
@@ -286,7 +340,8 @@ impl WebSocketStream<TokioWebSocketStream<MaybeTlsStream<TcpStream>>> {
     }
 }
 
-fn map_amqp_websocket_request(req: impl IntoClientRequest) -> Result<Request, tungstenite::Error> {
+#[allow(clippy::result_large_err)] // `tungstenite::Error` is returned as is by `into_client_request`
+pub(crate) fn map_amqp_websocket_request(req: impl IntoClientRequest) -> Result<Request, tungstenite::Error> {
     let mut request = req.into_client_request()?;
 
     // Sec-WebSocket-Protocol HTTP header
@@ -302,7 +357,7 @@ fn map_amqp_websocket_request(req: impl IntoClientRequest) -> Result<Request, tu
 }
 
 #[allow(clippy::result_large_err)] // TODO: refactor
-fn verify_response(response: Response) -> Result<Response, Error> {
+pub(crate) fn verify_response(response: Response) -> Result<Response, Error> {
     use http::StatusCode;
 
     // If the Client does not receive a response with HTTP status code 101 and an HTTP
@@ -0,0 +1,612 @@
+//! A small in-memory broker built on top of the acceptors
+//!
+//! The [`Broker`] stores messages in named queues that live only in memory. It is intended for
+//! hermetic tests and examples that would otherwise require a broker running in a container.
+//!
+//! - A link whose remote peer is a sender is routed to the queue named by the target address
+//! - A link whose remote peer is a receiver is routed to the queue named by the source address
+//! - Queues are created on the first attach that refers to them
+//! - Dynamic nodes are created through `on_dynamic_source` and `on_dynamic_target`
+//! - Credit and settle modes are honored by the underlying links
+//! - Released and modified messages are requeued, see [`Message::prepare_redelivery`]
+//! - Expired messages (see [`Message::expiry_time`]), rejected messages and messages exceeding
+//!   the [`max_delivery_count`](Broker::max_delivery_count) are moved to the
+//!   [`dead_letter_queue`](Broker::dead_letter_queue) if one is set, and dropped otherwise.
+//!   Messages consumed from the dead-letter queue itself never expire, and are dropped if
+//!   rejected
+//!
+//! # Start the broker on a loopback port
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp::{acceptor::broker::Broker, Connection};
+//!
+//! let broker = Broker::new();
+//! let handle = broker.start().await.unwrap();
+//! let mut connection = Connection::open("client", &handle.url()[..]).await.unwrap();
+//! // ...
+//! handle.shutdown().await;
+//! ```
+//!
+//! # Connect to the broker over an in-memory duplex stream
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp::{acceptor::broker::Broker, Connection};
+//!
+//! let broker = Broker::new();
+//! let stream = broker.connect_duplex(64 * 1024);
+//! let mut connection = Connection::builder()
+//!     .container_id("client")
+//!     .open_with_stream(stream)
+//!     .await
+//!     .unwrap();
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use fe2o3_amqp_types::{
+    definitions::{self, AmqpError},
+    messaging::{Body, Message, Outcome, Source, Target},
+    primitives::Value,
+};
+use parking_lot::Mutex;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream},
+    net::{TcpListener, ToSocketAddrs},
+    sync::{oneshot, Notify},
+    task::JoinHandle,
+};
+
+use crate::{
+    connection::OpenError,
+    link::{DetachError, LinkStateError, Receiver, RecvError, SendError, Sender},
+    util::now_timestamp,
+};
+
+use super::{
+    server::{LinkHandler, Server},
+    ConnectionAcceptor, LinkAcceptor, LinkEndpoint,
+};
+
+/// Default container id of the broker
+pub const DEFAULT_BROKER_CONTAINER_ID: &str = "fe2o3-amqp-broker";
+
+const DYNAMIC_NODE_PREFIX: &str = "dynamic-";
+
+/// The message type stored in the queues
+pub type QueuedMessage = Message<Body<Value>>;
+
+/// A named in-memory queue
+#[derive(Debug, Default)]
+pub struct Queue {
+    messages: Mutex<VecDeque<QueuedMessage>>,
+    notify: Notify,
+}
+
+impl Queue {
+    /// Appends a message to the back of the queue
+    pub fn push(&self, message: QueuedMessage) {
+        self.messages.lock().push_back(message);
+        self.notify.notify_one();
+    }
+
+    /// Returns a message to the front of the queue, eg. when it is released by the consumer
+    pub fn requeue(&self, message: QueuedMessage) {
+        self.messages.lock().push_front(message);
+        self.notify.notify_one();
+    }
+
+    /// Removes the message at the front of the queue
+    pub fn pop(&self) -> Option<QueuedMessage> {
+        self.messages.lock().pop_front()
+    }
+
+    /// Number of messages in the queue
+    pub fn len(&self) -> usize {
+        self.messages.lock().len()
+    }
+
+    /// Whether the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.messages.lock().is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+struct BrokerState {
+    queues: Mutex<HashMap<String, Arc<Queue>>>,
+    next_dynamic_id: AtomicU64,
+}
+
+impl BrokerState {
+    fn queue(&self, name: &str) -> Arc<Queue> {
+        self.queues
+            .lock()
+            .entry(name.to_string())
+            .or_default()
+            .clone()
+    }
+
+    fn create_dynamic_queue(&self) -> String {
+        let id = self.next_dynamic_id.fetch_add(1, Ordering::Relaxed);
+        let name = format!("{}{}", DYNAMIC_NODE_PREFIX, id);
+        self.queue(&name);
+        name
+    }
+}
+
+/// An in-memory broker
+///
+/// Cloning the broker is cheap and all clones share the same queues.
+#[derive(Debug, Clone)]
+pub struct Broker {
+    container_id: String,
+    state: Arc<BrokerState>,
+    dead_letter_queue: Option<String>,
+    max_delivery_count: Option<u32>,
+}
+
+impl Default for Broker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Broker {
+    /// Creates a broker with no queues
+    pub fn new() -> Self {
+        Self::with_container_id(DEFAULT_BROKER_CONTAINER_ID)
+    }
+
+    /// Creates a broker with the supplied container id
+    pub fn with_container_id(container_id: impl Into<String>) -> Self {
+        Self {
+            container_id: container_id.into(),
+            state: Default::default(),
+            dead_letter_queue: None,
+            max_delivery_count: None,
+        }
+    }
+
+    /// Moves the messages that cannot be delivered to the queue with the name instead of
+    /// dropping them
+    pub fn dead_letter_queue(mut self, name: impl Into<String>) -> Self {
+        self.dead_letter_queue = Some(name.into());
+        self
+    }
+
+    /// Dead-letters a message once its `delivery_count` exceeds `count`
+    pub fn max_delivery_count(mut self, count: u32) -> Self {
+        self.max_delivery_count = Some(count);
+        self
+    }
+
+    /// Returns the queue with the name, creating it if it doesn't exist
+    pub fn queue(&self, name: &str) -> Arc<Queue> {
+        self.state.queue(name)
+    }
+
+    /// Returns the names of all the queues
+    pub fn queue_names(&self) -> Vec<String> {
+        self.state.queues.lock().keys().cloned().collect()
+    }
+
+    /// Returns the number of messages in the queue, or `None` if the queue doesn't exist
+    pub fn queue_len(&self, name: &str) -> Option<usize> {
+        self.state.queues.lock().get(name).map(|queue| queue.len())
+    }
+
+    /// Creates a [`Server`] that routes links to the queues of the broker
+    #[allow(clippy::type_complexity)]
+    pub fn server(
+        &self,
+    ) -> Server<
+        ConnectionAcceptor<(), ()>,
+        impl Fn(Source) -> Option<Source> + Send + Sync + 'static,
+        impl Fn(Target) -> Option<Target> + Send + Sync + 'static,
+    > {
+        let source_state = self.state.clone();
+        let target_state = self.state.clone();
+        let link_acceptor = LinkAcceptor::builder()
+            .on_dynamic_source(move |mut source: Source| {
+                source.address = Some(source_state.create_dynamic_queue());
+                Some(source)
+            })
+            .on_dynamic_target(move |mut target: Target| {
+                target.address = Some(target_state.create_dynamic_queue());
+                Some(target)
+            })
+            .build();
+
+        Server::new(ConnectionAcceptor::new(self.container_id.clone()))
+            .link_acceptor(link_acceptor)
+            .fallback(self.link_handler())
+    }
+
+    /// Starts the broker on a random port on the loopback interface
+    pub async fn start(&self) -> io::Result<BrokerHandle> {
+        self.bind("127.0.0.1:0").await
+    }
+
+    /// Starts the broker on the address
+    pub async fn bind(&self, addr: impl ToSocketAddrs) -> io::Result<BrokerHandle> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let (shutdown, shutdown_rx) = oneshot::channel();
+        let server = self.server();
+        let task = tokio::spawn(server.serve_with_shutdown(listener, async {
+            let _ = shutdown_rx.await;
+        }));
+        Ok(BrokerHandle {
+            local_addr,
+            shutdown,
+            task,
+        })
+    }
+
+    /// Serves a single connection over the stream until it is closed
+    pub async fn serve_stream<Io>(&self, stream: Io) -> Result<(), OpenError>
+    where
+        Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static,
+    {
+        let server = self.server();
+        let connection = server.connection_acceptor.accept(stream).await?;
+        server.serve_connection(connection).await;
+        Ok(())
+    }
+
+    /// Creates an in-memory duplex stream whose other end is served by the broker
+    ///
+    /// The returned stream can be passed to `connection::Builder::open_with_stream`.
+    pub fn connect_duplex(&self, max_buf_size: usize) -> DuplexStream {
+        let (client, server) = tokio::io::duplex(max_buf_size);
+        let broker = self.clone();
+        tokio::spawn(async move {
+            let _ = broker.serve_stream(server).await;
+        });
+        client
+    }
+
+    fn link_handler(&self) -> impl LinkHandler {
+        let state = self.state.clone();
+        let dead_letter_queue = self.dead_letter_queue.clone();
+        let max_delivery_count = self.max_delivery_count;
+        move |link: LinkEndpoint| {
+            let state = state.clone();
+            let dead_letter_queue = dead_letter_queue.clone();
+            async move {
+                match link {
+                    LinkEndpoint::Sender(sender) => {
+                        match sender.source().as_ref().and_then(|s| s.address.as_deref()) {
+                            Some(address) => {
+                                let queue = state.queue(address);
+                                // The messages in the dead-letter queue are not dead-lettered
+                                // again
+                                let dead_letter = match dead_letter_queue {
+                                    Some(name) if name == address => None,
+                                    name => Some(DeadLetter {
+                                        queue: name.map(|name| state.queue(&name)),
+                                        max_delivery_count,
+                                    }),
+                                };
+                                send_from_queue(queue, dead_letter, sender).await
+                            }
+                            None => reject(LinkEndpoint::Sender(sender)).await,
+                        }
+                    }
+                    LinkEndpoint::Receiver(receiver) => {
+                        match receiver
+                            .target()
+                            .as_ref()
+                            .and_then(|t| t.address.as_deref())
+                        {
+                            Some(address) => {
+                                let queue = state.queue(address);
+                                receive_into_queue(queue, receiver).await
+                            }
+                            None => reject(LinkEndpoint::Receiver(receiver)).await,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A handle to a broker that is running on a TCP port
+#[derive(Debug)]
+pub struct BrokerHandle {
+    local_addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl BrokerHandle {
+    /// The address the broker is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// The url that can be used to open a connection to the broker
+    pub fn url(&self) -> String {
+        format!("amqp://{}", self.local_addr)
+    }
+
+    /// Stops the broker and closes all connections with `amqp:connection:forced`
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(());
+        let _ = self.task.await;
+    }
+}
+
+async fn reject(link: LinkEndpoint) {
+    let error = definitions::Error::new(
+        AmqpError::InvalidField,
+        Some(String::from("The broker requires an address")),
+        None,
+    );
+    let _ = match link {
+        LinkEndpoint::Sender(sender) => sender.close_with_error(error).await,
+        LinkEndpoint::Receiver(receiver) => receiver.close_with_error(error).await,
+    };
+}
+
+async fn receive_into_queue(queue: Arc<Queue>, mut receiver: Receiver) {
+    loop {
+        let delivery = match receiver.recv::<Body<Value>>().await {
+            Ok(delivery) => delivery,
+            // The receiver has already replied to the detach, and the link is not closed so
+            // that the remote peer can resume it
+            Err(RecvError::LinkStateError(LinkStateError::RemoteDetached)) => return,
+            Err(_) => break,
+        };
+        let (info, message) = delivery.into_parts();
+        queue.push(message);
+        // This is a no-op if the delivery is already settled by the remote sender
+        if receiver.accept(info).await.is_err() {
+            break;
+        }
+    }
+    let _ = receiver.close().await;
+}
+
+/// Where the messages that cannot be delivered go
+#[derive(Debug)]
+struct DeadLetter {
+    queue: Option<Arc<Queue>>,
+    max_delivery_count: Option<u32>,
+}
+
+impl DeadLetter {
+    fn push(&self, message: QueuedMessage) {
+        if let Some(queue) = &self.queue {
+            queue.push(message)
+        }
+    }
+
+    fn is_exceeded(&self, message: &QueuedMessage) -> bool {
+        self.max_delivery_count
+            .map(|max| message.delivery_count() > max)
+            .unwrap_or(false)
+    }
+}
+
+async fn send_from_queue(queue: Arc<Queue>, dead_letter: Option<DeadLetter>, mut sender: Sender) {
+    let detach_error = loop {
+        // Only take a message from the queue when it can be sent immediately so that it
+        // remains available to other consumers
+        tokio::select! {
+            _ = sender.credit_available() => {},
+            error = sender.on_detach() => break Some(error),
+        }
+
+        let notified = queue.notify.notified();
+        let message = match queue.pop() {
+            Some(message) => message,
+            None => {
+                tokio::select! {
+                    _ = notified => continue,
+                    error = sender.on_detach() => break Some(error),
+                }
+            }
+        };
+
+        if let Some(dead_letter) = &dead_letter {
+            if message.is_expired_at(&now_timestamp()) {
+                dead_letter.push(message);
+                continue;
+            }
+        }
+
+        match sender.send(message.clone()).await {
+            Ok(Outcome::Rejected(_)) => {
+                if let Some(dead_letter) = &dead_letter {
+                    dead_letter.push(message)
+                }
+            }
+            Ok(outcome) => {
+                let mut message = message;
+                if message.prepare_redelivery(&outcome) {
+                    match &dead_letter {
+                        Some(dead_letter) if dead_letter.is_exceeded(&message) => {
+                            dead_letter.push(message)
+                        }
+                        _ => queue.requeue(message),
+                    }
+                }
+            }
+            Err(SendError::LinkStateError(LinkStateError::RemoteDetached)) => {
+                // The sender has already replied to the detach
+                queue.requeue(message);
+                return;
+            }
+            Err(_) => {
+                queue.requeue(message);
+                break None;
+            }
+        }
+    };
+
+    // A link detached by the remote peer is not closed so that it can be resumed
+    match detach_error {
+        Some(DetachError::DetachedByRemote) => {
+            let _ = sender.detach().await;
+        }
+        _ => {
+            let _ = sender.close().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::messaging::{Message, Source, Target};
+
+    use crate::{connection::Connection, link::Receiver, link::Sender, session::Session};
+
+    use super::Broker;
+
+    #[tokio::test]
+    async fn broker_routes_messages_over_duplex() {
+        let broker = Broker::new();
+        let stream = broker.connect_duplex(64 * 1024);
+        let mut connection = Connection::builder()
+            .container_id("client")
+            .open_with_stream(stream)
+            .await
+            .unwrap();
+        let mut session = Session::begin(&mut connection).await.unwrap();
+
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+        for i in 0..3 {
+            let outcome = sender.send(Message::from(i.to_string())).await.unwrap();
+            outcome.accepted_or("Not accepted").unwrap();
+        }
+        assert_eq!(broker.queue_len("q1"), Some(3));
+
+        let mut receiver = Receiver::attach(&mut session, "receiver", "q1")
+            .await
+            .unwrap();
+        for i in 0..3 {
+            let delivery = receiver.recv::<String>().await.unwrap();
+            receiver.accept(&delivery).await.unwrap();
+            assert_eq!(delivery.body(), &i.to_string());
+        }
+        assert_eq!(broker.queue_len("q1"), Some(0));
+
+        sender.close().await.unwrap();
+        receiver.close().await.unwrap();
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn broker_creates_dynamic_nodes() {
+        let broker = Broker::new();
+        let handle = broker.start().await.unwrap();
+        let mut connection = Connection::open("client", &handle.url()[..]).await.unwrap();
+        let mut session = Session::begin(&mut connection).await.unwrap();
+
+        let mut receiver = Receiver::builder()
+            .name("dynamic-receiver")
+            .source(Source::builder().dynamic(true).build())
+            .attach(&mut session)
+            .await
+            .unwrap();
+        let address = receiver
+            .source()
+            .as_ref()
+            .and_then(|s| s.address.clone())
+            .unwrap();
+        assert!(broker.queue_names().contains(&address));
+
+        let mut sender = Sender::builder()
+            .name("sender")
+            .target(Target::builder().address(address).build())
+            .attach(&mut session)
+            .await
+            .unwrap();
+        sender.send("hello").await.unwrap();
+        let delivery = receiver.recv::<String>().await.unwrap();
+        receiver.accept(&delivery).await.unwrap();
+        assert_eq!(delivery.body(), "hello");
+
+        sender.close().await.unwrap();
+        receiver.close().await.unwrap();
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+        handle.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn broker_dead_letters_undeliverable_messages() {
+        use fe2o3_amqp_types::{
+            messaging::{Modified, Properties},
+            primitives::Timestamp,
+        };
+
+        let broker = Broker::new().dead_letter_queue("dlq").max_delivery_count(1);
+        let stream = broker.connect_duplex(64 * 1024);
+        let mut connection = Connection::builder()
+            .container_id("client")
+            .open_with_stream(stream)
+            .await
+            .unwrap();
+        let mut session = Session::begin(&mut connection).await.unwrap();
+
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+        let expired = Message::builder()
+            .properties(
+                Properties::builder()
+                    .absolute_expiry_time(Timestamp::from_milliseconds(1))
+                    .build(),
+            )
+            .value("expired")
+            .build();
+        sender.send(expired).await.unwrap();
+        sender.send("rejected").await.unwrap();
+        sender.send("failed").await.unwrap();
+
+        let mut receiver = Receiver::attach(&mut session, "receiver", "q1")
+            .await
+            .unwrap();
+        let delivery = receiver.recv::<String>().await.unwrap();
+        assert_eq!(delivery.body(), "rejected");
+        receiver.reject(&delivery, None).await.unwrap();
+
+        let modified = Modified {
+            delivery_failed: Some(true),
+            undeliverable_here: None,
+            message_annotations: None,
+        };
+        for delivery_count in 0..2 {
+            let delivery = receiver.recv::<String>().await.unwrap();
+            assert_eq!(delivery.body(), "failed");
+            assert_eq!(delivery.message().delivery_count(), delivery_count);
+            receiver.modify(&delivery, modified.clone()).await.unwrap();
+        }
+
+        let mut dead_letters = Receiver::attach(&mut session, "dead-letters", "dlq")
+            .await
+            .unwrap();
+        for body in ["expired", "rejected", "failed"] {
+            let delivery = dead_letters.recv::<String>().await.unwrap();
+            dead_letters.accept(&delivery).await.unwrap();
+            assert_eq!(delivery.body(), body);
+        }
+        assert_eq!(broker.queue_len("q1"), Some(0));
+
+        sender.close().await.unwrap();
+        receiver.close().await.unwrap();
+        dead_letters.close().await.unwrap();
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+    }
+}
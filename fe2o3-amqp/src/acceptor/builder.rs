@@ -14,13 +14,15 @@ use fe2o3_amqp_types::{
 
 use crate::{
     connection::{DEFAULT_CHANNEL_MAX, DEFAULT_MAX_FRAME_SIZE, DEFAULT_OUTGOING_BUFFER_SIZE},
+    link::receiver::ExpiredMessagePolicy,
     util::{Initialized, Uninitialized},
 };
 
 use super::{
-    link::LinkAcceptor, local_receiver_link::LocalReceiverLinkAcceptor,
-    local_sender_link::LocalSenderLinkAcceptor, session::SessionAcceptor, ConnectionAcceptor,
-    SaslAcceptor, SupportedReceiverSettleModes, SupportedSenderSettleModes,
+    dynamic_node::DynamicNodeRegistry, filter::FilterRegistry, link::LinkAcceptor,
+    local_receiver_link::LocalReceiverLinkAcceptor, local_sender_link::LocalSenderLinkAcceptor,
+    session::SessionAcceptor, terminus::TerminusRegistry, ConnectionAcceptor, SaslAcceptor,
+    SupportedReceiverSettleModes, SupportedSenderSettleModes,
 };
 
 cfg_transaction! {
@@ -491,6 +493,40 @@ where
         self
     }
 
+    /// Set how a local receiver handles messages that have expired when they arrive
+    pub fn expired_message_policy(mut self, policy: ExpiredMessagePolicy) -> Self {
+        self.inner.local_receiver_acceptor.expired_message_policy = policy;
+        self
+    }
+
+    /// Retains the terminus state of accepted links in the given registry so that a remote
+    /// peer reattaching a link with the same name resumes the link
+    ///
+    /// See [`TerminusRegistry`] for what is retained and when it expires
+    pub fn terminus_registry(mut self, registry: TerminusRegistry) -> Self {
+        self.inner.shared.terminus_registry = Some(registry);
+        self
+    }
+
+    /// Tracks the dynamic nodes created by `on_dynamic_source` and `on_dynamic_target` in the
+    /// given registry, which deletes them according to their lifetime policy
+    ///
+    /// See [`DynamicNodeRegistry`] for how the lifetime policies are enforced
+    pub fn dynamic_node_registry(mut self, registry: DynamicNodeRegistry) -> Self {
+        self.inner.shared.dynamic_node_registry = Some(registry);
+        self
+    }
+
+    /// Sets the filters supported by a local sender. The filters requested by the remote
+    /// receiver that are not in the registry are removed from the source sent back to the
+    /// remote peer
+    ///
+    /// See [`FilterRegistry`] for how a filter is matched
+    pub fn supported_filters(mut self, filters: FilterRegistry) -> Self {
+        self.inner.shared.supported_filters = Some(filters);
+        self
+    }
+
     /// Sets how to handle dynamic target
     ///
     /// If a valid target is created, a `Some(target)` should be returned. If dynamic
@@ -505,6 +541,7 @@ where
             credit_mode: self.inner.local_receiver_acceptor.credit_mode,
             target_capabilities: self.inner.local_receiver_acceptor.target_capabilities,
             auto_accept: self.inner.local_receiver_acceptor.auto_accept,
+            expired_message_policy: self.inner.local_receiver_acceptor.expired_message_policy,
             on_dynamic_target: op,
             target_marker: PhantomData,
             verify_incoming_source: self.inner.local_receiver_acceptor.verify_incoming_source,
@@ -484,6 +484,11 @@ impl endpoint::Connection for ListenerConnection {
         self.connection.local_open()
     }
 
+    #[inline]
+    fn remote_open(&self) -> Option<&Open> {
+        self.connection.remote_open()
+    }
+
     #[inline]
     fn allocate_session(
         &mut self,
@@ -0,0 +1,486 @@
+//! In-memory transport that connects a client connection directly to a connection acceptor
+//!
+//! [`connection_pair`] wires a client [`Connection`](crate::Connection) to a [`ConnectionAcceptor`]
+//! over a pair of in-memory streams and returns both ends once the protocol header and SASL
+//! negotiation are completed. Faults can be injected on either end with [`Faults`] to test
+//! reconnection and resumption deterministically.
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp::{
+//!     acceptor::{duplex::{connection_pair, DuplexConfig, Faults}, ConnectionAcceptor},
+//!     Connection,
+//! };
+//!
+//! let config = DuplexConfig::default().client_faults(Faults::new().drop_after(4096));
+//! let (mut connection, mut listener_connection) = connection_pair(
+//!     Connection::builder().container_id("client"),
+//!     &ConnectionAcceptor::new("server"),
+//!     config,
+//! )
+//! .await
+//! .unwrap();
+//! ```
+
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use pin_project_lite::pin_project;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf},
+    time::Sleep,
+};
+
+use crate::connection::{mode::ConnectorWithId, Builder, ConnectionHandle, OpenError};
+
+use super::{server::AcceptConnection, ListenerConnectionHandle};
+
+/// Default buffer size of each direction of the in-memory stream
+pub const DEFAULT_DUPLEX_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Faults that are injected into the writing half of a [`FaultyStream`]
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    delay: Option<Duration>,
+    drop_after: Option<usize>,
+    max_write_size: Option<usize>,
+}
+
+impl Faults {
+    /// Creates an empty set of faults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delays every write by the duration
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Drops the connection once `bytes` are written
+    ///
+    /// The inner stream is shut down by the write that reaches the limit so that the remote peer
+    /// observes an EOF right away. Writes beyond the limit fail with `io::ErrorKind::BrokenPipe`.
+    pub fn drop_after(mut self, bytes: usize) -> Self {
+        self.drop_after = Some(bytes);
+        self
+    }
+
+    /// Splits writes into chunks of at most `bytes`, a size of zero is treated as one
+    pub fn max_write_size(mut self, bytes: usize) -> Self {
+        self.max_write_size = Some(bytes.max(1));
+        self
+    }
+}
+
+pin_project! {
+    /// A stream that injects [`Faults`] into the inner stream
+    #[derive(Debug)]
+    pub struct FaultyStream<S> {
+        #[pin]
+        inner: S,
+        faults: Faults,
+        written: usize,
+        dropped: bool,
+        shut_down: bool,
+        sleep: Option<Pin<Box<Sleep>>>,
+    }
+}
+
+impl<S> FaultyStream<S> {
+    /// Wraps the stream
+    pub fn new(inner: S, faults: Faults) -> Self {
+        Self {
+            inner,
+            faults,
+            written: 0,
+            dropped: false,
+            shut_down: false,
+            sleep: None,
+        }
+    }
+
+    /// Total number of bytes written to the inner stream
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Whether the connection has been dropped by the `drop_after` fault
+    pub fn is_dropped(&self) -> bool {
+        self.dropped
+    }
+
+    /// Get a reference to the inner stream
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Consumes the wrapper and returns the inner stream
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: AsyncRead> AsyncRead for FaultyStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        if *this.dropped {
+            // EOF
+            return Poll::Ready(Ok(()));
+        }
+        this.inner.poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for FaultyStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut this = self.project();
+
+        if let Some(drop_after) = this.faults.drop_after {
+            if *this.written >= drop_after {
+                *this.dropped = true;
+            }
+        }
+
+        if *this.dropped {
+            futures_util::ready!(poll_shutdown_dropped(this.inner, this.shut_down, cx))?;
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+
+        if let Some(delay) = this.faults.delay {
+            let sleep = this
+                .sleep
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep(delay)));
+            futures_util::ready!(sleep.as_mut().poll(cx));
+        }
+
+        let mut len = buf.len();
+        if let Some(max_write_size) = this.faults.max_write_size {
+            len = len.min(max_write_size);
+        }
+        if let Some(drop_after) = this.faults.drop_after {
+            len = len.min(drop_after - *this.written);
+        }
+
+        let result = futures_util::ready!(this.inner.as_mut().poll_write(cx, &buf[..len]));
+        // The delay has been served once the write completes
+        *this.sleep = None;
+        let n = result?;
+        *this.written += n;
+
+        if let Some(drop_after) = this.faults.drop_after {
+            if *this.written >= drop_after {
+                *this.dropped = true;
+                // The bytes are already written, so a pending or failed shutdown is driven again
+                // (and its error surfaced) by the next write or flush
+                if let Poll::Ready(Ok(())) = this.inner.poll_shutdown(cx) {
+                    *this.shut_down = true;
+                }
+            }
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.project();
+        if *this.dropped {
+            futures_util::ready!(poll_shutdown_dropped(this.inner, this.shut_down, cx))?;
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        this.inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+/// Shuts down the inner stream of a dropped [`FaultyStream`] so that the remote peer observes
+/// an EOF
+fn poll_shutdown_dropped<S: AsyncWrite>(
+    inner: Pin<&mut S>,
+    shut_down: &mut bool,
+    cx: &mut Context<'_>,
+) -> Poll<io::Result<()>> {
+    if !*shut_down {
+        futures_util::ready!(inner.poll_shutdown(cx))?;
+        *shut_down = true;
+    }
+    Poll::Ready(Ok(()))
+}
+
+/// Configuration of the in-memory streams created by [`duplex`] and [`connection_pair`]
+#[derive(Debug, Clone)]
+pub struct DuplexConfig {
+    /// Buffer size of each direction of the stream
+    pub max_buf_size: usize,
+
+    /// Faults injected into the client end
+    pub client_faults: Faults,
+
+    /// Faults injected into the server end
+    pub server_faults: Faults,
+}
+
+impl Default for DuplexConfig {
+    fn default() -> Self {
+        Self {
+            max_buf_size: DEFAULT_DUPLEX_BUFFER_SIZE,
+            client_faults: Faults::default(),
+            server_faults: Faults::default(),
+        }
+    }
+}
+
+impl DuplexConfig {
+    /// Sets the buffer size of each direction of the stream
+    pub fn max_buf_size(mut self, max_buf_size: usize) -> Self {
+        self.max_buf_size = max_buf_size;
+        self
+    }
+
+    /// Sets the faults injected into the client end
+    pub fn client_faults(mut self, faults: Faults) -> Self {
+        self.client_faults = faults;
+        self
+    }
+
+    /// Sets the faults injected into the server end
+    pub fn server_faults(mut self, faults: Faults) -> Self {
+        self.server_faults = faults;
+        self
+    }
+}
+
+/// Creates a connected pair of in-memory streams. The first one is the client end and the
+/// second one is the server end.
+pub fn duplex(config: DuplexConfig) -> (FaultyStream<DuplexStream>, FaultyStream<DuplexStream>) {
+    let (client, server) = tokio::io::duplex(config.max_buf_size);
+    (
+        FaultyStream::new(client, config.client_faults),
+        FaultyStream::new(server, config.server_faults),
+    )
+}
+
+/// Opens a client connection to the acceptor over a pair of in-memory streams
+///
+/// Both ends are returned after the protocol header, SASL (if configured on both ends) and
+/// `Open` exchange are completed.
+pub async fn connection_pair<A>(
+    client: Builder<'_, ConnectorWithId, ()>,
+    acceptor: &A,
+    config: DuplexConfig,
+) -> Result<(ConnectionHandle<()>, ListenerConnectionHandle), OpenError>
+where
+    A: AcceptConnection,
+{
+    let (client_stream, server_stream) = duplex(config);
+    tokio::try_join!(
+        client.open_with_stream(client_stream),
+        acceptor.accept_connection(server_stream)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        task::Poll,
+        time::{Duration, Instant},
+    };
+
+    use fe2o3_amqp_types::definitions::SenderSettleMode;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::{
+        acceptor::{ConnectionAcceptor, LinkAcceptor, LinkEndpoint, SessionAcceptor},
+        connection::Connection,
+        link::Sender,
+        session::Session,
+    };
+
+    use super::{connection_pair, duplex, DuplexConfig, Faults};
+
+    #[tokio::test]
+    async fn split_writes_are_reassembled() {
+        let config = DuplexConfig::default()
+            .client_faults(Faults::new().max_write_size(3))
+            .server_faults(Faults::new().max_write_size(1));
+        let (mut connection, mut listener_connection) = connection_pair(
+            Connection::builder().container_id("client"),
+            &ConnectionAcceptor::new("server"),
+            config,
+        )
+        .await
+        .unwrap();
+
+        let server = tokio::spawn(async move {
+            let mut session = SessionAcceptor::new()
+                .accept(&mut listener_connection)
+                .await
+                .unwrap();
+            let link = LinkAcceptor::new().accept(&mut session).await.unwrap();
+            let mut receiver = match link {
+                LinkEndpoint::Receiver(receiver) => receiver,
+                LinkEndpoint::Sender(_) => panic!("Expecting a receiver"),
+            };
+            let delivery = receiver.recv::<String>().await.unwrap();
+            receiver.accept(&delivery).await.unwrap();
+            let _ = receiver.close().await;
+            let _ = session.on_end().await;
+            let _ = listener_connection.on_close().await;
+            delivery.into_body()
+        });
+
+        let mut session = Session::begin(&mut connection).await.unwrap();
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+        sender.send("hello").await.unwrap();
+        sender.close().await.unwrap();
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+
+        assert_eq!(server.await.unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn dropped_connection_is_observed_by_both_ends() {
+        let config = DuplexConfig::default().client_faults(Faults::new().drop_after(1024));
+        let (mut connection, mut listener_connection) = connection_pair(
+            Connection::builder().container_id("client"),
+            &ConnectionAcceptor::new("server"),
+            config,
+        )
+        .await
+        .unwrap();
+
+        let server = tokio::spawn(async move {
+            let mut session = SessionAcceptor::new()
+                .accept(&mut listener_connection)
+                .await
+                .unwrap();
+            if let Ok(LinkEndpoint::Receiver(mut receiver)) =
+                LinkAcceptor::new().accept(&mut session).await
+            {
+                while let Ok(delivery) = receiver.recv::<String>().await {
+                    let _ = receiver.accept(&delivery).await;
+                }
+            }
+            listener_connection.on_close().await
+        });
+
+        let mut session = Session::begin(&mut connection).await.unwrap();
+        let mut sender = Sender::builder()
+            .name("sender")
+            .target("q1")
+            .sender_settle_mode(SenderSettleMode::Settled)
+            .attach(&mut session)
+            .await
+            .unwrap();
+        let payload = "a".repeat(100);
+        // Pre-settled sends only fail once the session is stopped by the dropped connection
+        while sender.send(payload.as_str()).await.is_ok() {}
+        assert!(connection.on_close().await.is_err());
+        assert!(server.await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn writes_after_drop_fail() {
+        let config = DuplexConfig::default().client_faults(Faults::new().drop_after(8));
+        let (mut client, mut server) = duplex(config);
+        client.write_all(b"AMQP\x00\x01\x00\x00").await.unwrap();
+        assert!(client.write_all(b"more").await.is_err());
+        assert!(client.is_dropped());
+
+        let mut buf = Vec::new();
+        server.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"AMQP\x00\x01\x00\x00");
+    }
+
+    #[tokio::test]
+    async fn remote_observes_eof_once_limit_is_reached() {
+        let config = DuplexConfig::default().client_faults(Faults::new().drop_after(8));
+        let (mut client, mut server) = duplex(config);
+        client
+            .write_all(b"AMQP\x00\x01\x00\x00more")
+            .await
+            .unwrap_err();
+        assert!(client.is_dropped());
+        assert_eq!(client.written(), 8);
+
+        // No further write is needed for the server to see the EOF
+        let mut buf = Vec::new();
+        tokio::time::timeout(Duration::from_secs(1), server.read_to_end(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(buf, b"AMQP\x00\x01\x00\x00");
+
+        let config = DuplexConfig::default().client_faults(Faults::new().drop_after(4));
+        let (mut client, mut server) = duplex(config);
+        client.write_all(b"AMQP").await.unwrap();
+        assert!(client.is_dropped());
+
+        let mut buf = Vec::new();
+        tokio::time::timeout(Duration::from_secs(1), server.read_to_end(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(buf, b"AMQP");
+    }
+
+    #[tokio::test]
+    async fn zero_max_write_size_is_clamped() {
+        let config = DuplexConfig::default().client_faults(Faults::new().max_write_size(0));
+        let (mut client, mut server) = duplex(config);
+        assert_eq!(client.write(b"AMQP").await.unwrap(), 1);
+
+        let mut buf = [0u8; 1];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"A");
+    }
+
+    #[tokio::test]
+    async fn writes_are_delayed() {
+        let config =
+            DuplexConfig::default().client_faults(Faults::new().delay(Duration::from_millis(50)));
+        let (mut client, mut server) = duplex(config);
+
+        let start = Instant::now();
+        client.write_all(b"hello").await.unwrap();
+        let mut buf = [0u8; 5];
+        server.read_exact(&mut buf).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(&buf, b"hello");
+    }
+
+    #[tokio::test]
+    async fn pending_write_is_not_delayed_again() {
+        let config = DuplexConfig::default()
+            .max_buf_size(4)
+            .client_faults(Faults::new().delay(Duration::from_millis(10)));
+        let (mut client, mut server) = duplex(config);
+        client.write_all(b"AMQP").await.unwrap();
+
+        let mut write = tokio_test::task::spawn(client.write(b"more"));
+        assert!(write.poll().is_pending());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        // The delay has elapsed but the buffer is full
+        assert!(write.poll().is_pending());
+
+        let mut buf = [0u8; 4];
+        server.read_exact(&mut buf).await.unwrap();
+        assert!(matches!(write.poll(), Poll::Ready(Ok(4))));
+    }
+}
@@ -0,0 +1,317 @@
+//! Lifetime of dynamic nodes created by a [`LinkAcceptor`](super::LinkAcceptor)
+//!
+//! A [`DynamicNodeRegistry`] keeps track of the nodes created by `on_dynamic_source` and
+//! `on_dynamic_target`, counts the links attached to them and invokes a deletion callback once
+//! the lifetime policy of a node is satisfied.
+//!
+//! The lifetime policy is taken from the `"lifetime-policy"` entry of the
+//! `dynamic_node_properties` of the terminus returned by `on_dynamic_source` or
+//! `on_dynamic_target`, and defaults to [`DeleteOnClose`] if the entry is absent.
+//!
+//! | Lifetime policy | The node is deleted when |
+//! |-----------------|--------------------------|
+//! | [`DeleteOnClose`] | the link that caused its creation is gone |
+//! | [`DeleteOnNoLinks`] | no link is attached to it |
+//! | [`DeleteOnNoMessages`] | it holds no message |
+//! | [`DeleteOnNoLinksOrMessages`] | no link is attached to it and it holds no message |
+//!
+//! The acceptor doesn't know what is stored on a node, so the number of messages must be
+//! reported with [`DynamicNodeRegistry::set_message_count`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp::acceptor::{LinkAcceptor, dynamic_node::DynamicNodeRegistry};
+//!
+//! let registry = DynamicNodeRegistry::new(|address, _policy| {
+//!     println!("Deleting {}", address);
+//! });
+//! let link_acceptor = LinkAcceptor::builder()
+//!     .on_dynamic_source(|mut source| {
+//!         source.address = Some(String::from("dynamic-queue"));
+//!         Some(source)
+//!     })
+//!     .dynamic_node_registry(registry.clone())
+//!     .build();
+//! ```
+//!
+//! [`DeleteOnClose`]: fe2o3_amqp_types::messaging::DeleteOnClose
+//! [`DeleteOnNoLinks`]: fe2o3_amqp_types::messaging::DeleteOnNoLinks
+//! [`DeleteOnNoMessages`]: fe2o3_amqp_types::messaging::DeleteOnNoMessages
+//! [`DeleteOnNoLinksOrMessages`]: fe2o3_amqp_types::messaging::DeleteOnNoLinksOrMessages
+
+use std::{collections::HashMap, sync::Arc};
+
+use fe2o3_amqp_types::messaging::{DeleteOnClose, LifetimePolicy, NodeProperties};
+use parking_lot::Mutex;
+
+use super::terminus::LinkWatch;
+
+/// Key of the lifetime policy in the dynamic node properties
+const LIFETIME_POLICY: &str = "lifetime-policy";
+
+type OnDelete = dyn Fn(&str, &LifetimePolicy) + Send + Sync;
+
+#[derive(Debug)]
+struct Node {
+    /// Distinguishes a node from a later node with the same address
+    id: u64,
+    policy: LifetimePolicy,
+    links: usize,
+    messages: usize,
+}
+
+impl Node {
+    fn is_expired(&self) -> bool {
+        match self.policy {
+            LifetimePolicy::DeleteOnClose(_) => false,
+            LifetimePolicy::DeleteOnNoLinks(_) => self.links == 0,
+            LifetimePolicy::DeleteOnNoMessages(_) => self.messages == 0,
+            LifetimePolicy::DeleteOnNoLinksOrMessages(_) => self.links == 0 && self.messages == 0,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    next_id: u64,
+    nodes: HashMap<String, Node>,
+}
+
+/// Dynamic nodes created by a [`LinkAcceptor`](super::LinkAcceptor) and the links attached to
+/// them
+///
+/// The registry is cheap to clone and clones share the same state.
+#[derive(Clone)]
+pub struct DynamicNodeRegistry {
+    inner: Arc<Mutex<Inner>>,
+    on_delete: Arc<OnDelete>,
+}
+
+impl std::fmt::Debug for DynamicNodeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicNodeRegistry")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl DynamicNodeRegistry {
+    /// Creates an empty registry that invokes `on_delete` with the address and lifetime policy
+    /// of a node once the node should be deleted
+    pub fn new(on_delete: impl Fn(&str, &LifetimePolicy) + Send + Sync + 'static) -> Self {
+        Self {
+            inner: Default::default(),
+            on_delete: Arc::new(on_delete),
+        }
+    }
+
+    /// Number of tracked dynamic nodes
+    pub fn len(&self) -> usize {
+        self.inner.lock().nodes.len()
+    }
+
+    /// Whether no dynamic node is tracked
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().nodes.is_empty()
+    }
+
+    /// Whether a dynamic node with the address is tracked
+    pub fn contains(&self, address: &str) -> bool {
+        self.inner.lock().nodes.contains_key(address)
+    }
+
+    /// Number of links attached to the dynamic node, or `None` if the node is not tracked
+    pub fn link_count(&self, address: &str) -> Option<usize> {
+        self.inner.lock().nodes.get(address).map(|node| node.links)
+    }
+
+    /// Reports the number of messages held by the dynamic node
+    ///
+    /// The node is deleted if its lifetime policy is
+    /// [`DeleteOnNoMessages`](fe2o3_amqp_types::messaging::DeleteOnNoMessages) or
+    /// [`DeleteOnNoLinksOrMessages`](fe2o3_amqp_types::messaging::DeleteOnNoLinksOrMessages) and
+    /// is satisfied
+    pub fn set_message_count(&self, address: &str, count: usize) {
+        let mut inner = self.inner.lock();
+        let expired = match inner.nodes.get_mut(address) {
+            Some(node) => {
+                node.messages = count;
+                node.is_expired()
+            }
+            None => false,
+        };
+        if expired {
+            self.delete(inner, address);
+        }
+    }
+
+    /// Stops tracking the dynamic node without invoking the deletion callback. Returns whether
+    /// the node was tracked
+    pub fn remove(&self, address: &str) -> bool {
+        self.inner.lock().nodes.remove(address).is_some()
+    }
+
+    /// Tracks a node created for the link that is watched by `watch`
+    pub(crate) fn create(
+        &self,
+        address: String,
+        properties: Option<&NodeProperties>,
+        watch: LinkWatch,
+    ) {
+        let policy = lifetime_policy(properties);
+        let id = {
+            let mut inner = self.inner.lock();
+            inner.next_id += 1;
+            let id = inner.next_id;
+            let node = Node {
+                id,
+                policy,
+                links: 0,
+                messages: 0,
+            };
+            inner.nodes.insert(address.clone(), node);
+            id
+        };
+        self.watch_link(address, id, true, watch)
+    }
+
+    /// Counts a link attached to the node with the address if the node is tracked
+    pub(crate) fn attach(&self, address: String, watch: LinkWatch) {
+        let id = match self.inner.lock().nodes.get(&address) {
+            Some(node) => node.id,
+            None => return,
+        };
+        self.watch_link(address, id, false, watch)
+    }
+
+    fn watch_link(&self, address: String, id: u64, is_creator: bool, watch: LinkWatch) {
+        match self.inner.lock().nodes.get_mut(&address) {
+            Some(node) if node.id == id => node.links += 1,
+            _ => return,
+        }
+
+        let registry = self.clone();
+        tokio::spawn(async move {
+            watch.link_detached().await;
+            registry.detach(&address, id, is_creator);
+        });
+    }
+
+    fn detach(&self, address: &str, id: u64, is_creator: bool) {
+        let mut inner = self.inner.lock();
+        let expired = match inner.nodes.get_mut(address) {
+            Some(node) if node.id == id => {
+                node.links = node.links.saturating_sub(1);
+                match node.policy {
+                    LifetimePolicy::DeleteOnClose(_) => is_creator,
+                    LifetimePolicy::DeleteOnNoMessages(_) => false,
+                    _ => node.is_expired(),
+                }
+            }
+            _ => false,
+        };
+        if expired {
+            self.delete(inner, address);
+        }
+    }
+
+    fn delete(&self, mut inner: parking_lot::MutexGuard<'_, Inner>, address: &str) {
+        let node = inner.nodes.remove(address);
+        drop(inner);
+        if let Some(node) = node {
+            (self.on_delete)(address, &node.policy);
+        }
+    }
+}
+
+/// Finds the lifetime policy in the dynamic node properties
+///
+/// Defaults to [`DeleteOnClose`] if the policy is missing or is not a known lifetime policy
+fn lifetime_policy(properties: Option<&NodeProperties>) -> LifetimePolicy {
+    properties
+        .and_then(|p| p.get(LIFETIME_POLICY))
+        .and_then(|value| serde_amqp::from_value(value.clone()).ok())
+        .unwrap_or_else(|| DeleteOnClose {}.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{
+        messaging::{
+            DeleteOnNoLinks, DeleteOnNoLinksOrMessages, LifetimePolicy, NodeProperties, Source,
+        },
+        primitives::{Symbol, Value},
+    };
+    use serde_amqp::{described::Described, descriptor::Descriptor};
+
+    use super::{lifetime_policy, DynamicNodeRegistry, Node};
+
+    #[test]
+    fn lifetime_policy_from_node_properties() {
+        let source = Source::builder()
+            .dynamic(true)
+            .add_lifetime_policy(DeleteOnNoLinks {})
+            .build();
+        let policy = lifetime_policy(source.dynamic_node_properties.as_ref());
+        assert!(matches!(policy, LifetimePolicy::DeleteOnNoLinks(_)));
+
+        let policy = lifetime_policy(None);
+        assert!(matches!(policy, LifetimePolicy::DeleteOnClose(_)));
+    }
+
+    #[test]
+    fn lifetime_policy_with_symbol_or_unknown_descriptor() {
+        let mut properties = NodeProperties::default();
+        properties.insert(
+            Symbol::from("lifetime-policy"),
+            Value::Described(Box::new(Described {
+                descriptor: Descriptor::Name(Symbol::from(
+                    "amqp:delete-on-no-links-or-messages:list",
+                )),
+                value: Value::List(vec![]),
+            })),
+        );
+        let policy = lifetime_policy(Some(&properties));
+        assert!(matches!(
+            policy,
+            LifetimePolicy::DeleteOnNoLinksOrMessages(_)
+        ));
+
+        properties.insert(
+            Symbol::from("lifetime-policy"),
+            Value::Described(Box::new(Described {
+                descriptor: Descriptor::Code(0x0000_468c_0000_0000),
+                value: Value::List(vec![]),
+            })),
+        );
+        let policy = lifetime_policy(Some(&properties));
+        assert!(matches!(policy, LifetimePolicy::DeleteOnClose(_)));
+    }
+
+    #[test]
+    fn message_count_deletes_node_without_links() {
+        let deleted = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let deleted_clone = deleted.clone();
+        let registry = DynamicNodeRegistry::new(move |address, _| {
+            deleted_clone.lock().push(address.to_string())
+        });
+        registry.inner.lock().nodes.insert(
+            String::from("q1"),
+            Node {
+                id: 1,
+                policy: DeleteOnNoLinksOrMessages {}.into(),
+                links: 1,
+                messages: 0,
+            },
+        );
+
+        registry.set_message_count("q1", 3);
+        registry.set_message_count("q1", 0);
+        assert!(registry.contains("q1"));
+
+        registry.detach("q1", 1, true);
+        assert!(!registry.contains("q1"));
+        assert_eq!(*deleted.lock(), vec![String::from("q1")]);
+    }
+}
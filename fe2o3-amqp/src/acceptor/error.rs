@@ -1,6 +1,6 @@
 //! Implements errors for the acceptors
 
-use crate::link::{ReceiverAttachError, SenderAttachError};
+use crate::link::{ReceiverAttachError, SendError, SenderAttachError};
 
 /// Error accepting incoming attach
 #[derive(Debug, thiserror::Error)]
@@ -16,6 +16,10 @@ pub enum AcceptorAttachError {
     /// Local receiver is unable to accept incoming attach from remote sender
     #[error("Local receiver is unable to accept incoming attach from remote sender")]
     LocalReceiver(ReceiverAttachError),
+
+    /// Local sender is unable to resume the unsettled deliveries of a retained terminus
+    #[error("Local sender is unable to resume unsettled deliveries")]
+    LocalSenderResume(SendError),
 }
 
 impl From<SenderAttachError> for AcceptorAttachError {
@@ -0,0 +1,135 @@
+//! Negotiation of the source filters requested by a remote receiver
+//!
+//! A [`FilterRegistry`] declares the filter types supported by a
+//! [`LinkAcceptor`](super::LinkAcceptor). When a remote receiver attaches, the filters in its
+//! source that are not supported are removed from the source echoed back to the remote peer, so
+//! the filter set in the attach frame sent by the acceptor is the filter actually in place.
+//!
+//! A filter is identified by the descriptor of its value, which can be either the descriptor
+//! name or the descriptor code. The key in the filter set is chosen by the remote receiver and
+//! is not used, so a filter using the legacy format, where the value is not a described type,
+//! is never supported.
+//!
+//! The descriptors of the filter types in the AMQP Capabilities Registry are provided by
+//! `fe2o3_amqp_ext::filters`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp::acceptor::{LinkAcceptor, filter::FilterRegistry};
+//! use fe2o3_amqp_ext::filters::{NoLocalFilter, SelectorFilter};
+//!
+//! let filters = FilterRegistry::new()
+//!     .with_filter(SelectorFilter::descriptor_name(), SelectorFilter::descriptor_code())
+//!     .with_filter(NoLocalFilter::descriptor_name(), NoLocalFilter::descriptor_code());
+//! let link_acceptor = LinkAcceptor::builder()
+//!     .supported_filters(filters)
+//!     .build();
+//! ```
+
+use std::collections::HashSet;
+
+use fe2o3_amqp_types::{
+    messaging::FilterSet,
+    primitives::{Symbol, Value},
+};
+use serde_amqp::descriptor::Descriptor;
+
+/// The filter types supported by a [`LinkAcceptor`](super::LinkAcceptor)
+#[derive(Debug, Clone, Default)]
+pub struct FilterRegistry {
+    names: HashSet<Symbol>,
+    codes: HashSet<u64>,
+}
+
+impl FilterRegistry {
+    /// Creates a registry that doesn't support any filter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a filter type identified by its descriptor name and code
+    pub fn with_filter(mut self, name: impl Into<Symbol>, code: u64) -> Self {
+        self.insert(name, code);
+        self
+    }
+
+    /// Adds a filter type identified by its descriptor name and code
+    pub fn insert(&mut self, name: impl Into<Symbol>, code: u64) {
+        self.names.insert(name.into());
+        self.codes.insert(code);
+    }
+
+    /// Whether the filter entry with the value `value` is supported
+    pub fn supports(&self, value: &Value) -> bool {
+        match value {
+            Value::Described(described) => match &described.descriptor {
+                Descriptor::Name(name) => self.names.contains(name),
+                Descriptor::Code(code) => self.codes.contains(code),
+            },
+            // A filter without a descriptor cannot be identified
+            _ => false,
+        }
+    }
+
+    /// Removes the unsupported entries from the filter set. The filter set is set to `None` if
+    /// no entry is left
+    pub(crate) fn retain_supported(&self, filter: &mut Option<FilterSet>) {
+        if let Some(filter_set) = filter {
+            filter_set
+                .as_inner_mut()
+                .retain(|_, value| self.supports(value));
+            if filter_set.is_empty() {
+                *filter = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_ext::filters::{NoLocalFilter, SelectorFilter, Xquery};
+    use fe2o3_amqp_types::{messaging::Source, primitives::Symbol};
+    use serde_amqp::{described::Described, descriptor::Descriptor, Value};
+
+    use super::FilterRegistry;
+
+    #[test]
+    fn unsupported_filters_are_removed() {
+        let registry = FilterRegistry::new()
+            .with_filter(
+                SelectorFilter::descriptor_name(),
+                SelectorFilter::descriptor_code(),
+            )
+            .with_filter(
+                NoLocalFilter::descriptor_name(),
+                NoLocalFilter::descriptor_code(),
+            );
+
+        let by_name = Described {
+            descriptor: Descriptor::Name(NoLocalFilter::descriptor_name()),
+            value: Value::List(vec![]),
+        };
+        let source = Source::builder()
+            .add_to_filter("selector", SelectorFilter::new("color = 'red'"))
+            .add_to_filter("no-local", by_name)
+            .add_to_filter("xquery", Xquery::new("/a"))
+            .add_to_filter_using_legacy_format("legacy", "value")
+            .build();
+
+        let mut filter = source.filter;
+        registry.retain_supported(&mut filter);
+        let keys: Vec<&Symbol> = filter.as_ref().unwrap().keys().collect();
+        assert_eq!(
+            keys,
+            vec![&Symbol::from("selector"), &Symbol::from("no-local")]
+        );
+
+        let mut filter = Source::builder()
+            .add_to_filter("xquery", Xquery::new("/a"))
+            .build()
+            .filter;
+        registry.retain_supported(&mut filter);
+        assert!(filter.is_none());
+    }
+}
@@ -16,9 +16,10 @@ use fe2o3_amqp_types::{
 use crate::{connection::DEFAULT_OUTGOING_BUFFER_SIZE, session::SessionHandle, util::Initialized};
 
 use super::{
-    builder::Builder, error::AcceptorAttachError, local_receiver_link::LocalReceiverLinkAcceptor,
+    builder::Builder, dynamic_node::DynamicNodeRegistry, error::AcceptorAttachError,
+    filter::FilterRegistry, local_receiver_link::LocalReceiverLinkAcceptor,
     local_sender_link::LocalSenderLinkAcceptor, session::ListenerSessionHandle,
-    SupportedReceiverSettleModes, SupportedSenderSettleModes,
+    terminus::TerminusRegistry, SupportedReceiverSettleModes, SupportedSenderSettleModes,
 };
 
 /// Listener side link endpoint
@@ -67,6 +68,17 @@ pub(crate) struct SharedLinkAcceptorFields {
     /// If this field is None, an incoming attach whose desired receiver settle
     /// mode is not supported will then be rejected
     pub fallback_rcv_settle_mode: ReceiverSettleMode,
+
+    /// Retains the terminus state of accepted links so that they can be resumed
+    pub terminus_registry: Option<TerminusRegistry>,
+
+    /// Tracks the dynamic nodes created for accepted links and deletes them according to their
+    /// lifetime policy
+    pub dynamic_node_registry: Option<DynamicNodeRegistry>,
+
+    /// The filters supported by a local sender. All filters requested by the remote receiver
+    /// are echoed back if this field is `None`
+    pub supported_filters: Option<FilterRegistry>,
 }
 
 impl Default for SharedLinkAcceptorFields {
@@ -81,6 +93,9 @@ impl Default for SharedLinkAcceptorFields {
             fallback_snd_settle_mode: SenderSettleMode::default(),
             supported_rcv_settle_modes: SupportedReceiverSettleModes::default(),
             fallback_rcv_settle_mode: ReceiverSettleMode::default(),
+            terminus_registry: None,
+            dynamic_node_registry: None,
+            supported_filters: None,
         }
     }
 }
@@ -112,6 +127,10 @@ impl Default for SharedLinkAcceptorFields {
 /// |`properties`| `None` |
 /// |`buffer_size`| [`u16::MAX`] |
 /// |`credit_mode`| [`CreditMode::Auto(DEFAULT_CREDIT)`] |
+/// |`expired_message_policy`| `ExpiredMessagePolicy::Deliver` |
+/// |`terminus_registry`| `None` |
+/// |`dynamic_node_registry`| `None` |
+/// |`supported_filters`| `None` |
 ///
 /// # Customize acceptor
 ///
@@ -205,8 +224,7 @@ where
                 .local_sender_acceptor
                 .accept_incoming_attach(&self.shared, remote_attach, session)
                 .await
-                .map(LinkEndpoint::Sender)
-                .map_err(Into::into),
+                .map(LinkEndpoint::Sender),
         }
     }
 
@@ -6,18 +6,20 @@ use std::{
 };
 
 use fe2o3_amqp_types::{
+    definitions::Role,
     messaging::{Target, TargetArchetype},
     performatives::Attach,
     primitives::Symbol,
 };
 use parking_lot::RwLock;
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     control::SessionControl,
     endpoint::{InputHandle, LinkAttach, LinkExt},
     link::{
-        receiver::{CreditMode, ReceiverInner},
+        receiver::{CreditMode, ExpiredMessagePolicy, ReceiverInner},
         state::{LinkFlowState, LinkFlowStateInner, LinkState},
         target_archetype::TargetArchetypeExt,
         LinkFrame, LinkIncomingItem, LinkRelay, ReceiverAttachError, ReceiverLink,
@@ -26,7 +28,10 @@ use crate::{
     Receiver,
 };
 
-use super::link::SharedLinkAcceptorFields;
+use super::{
+    link::SharedLinkAcceptorFields,
+    terminus::{LinkWatch, RetainedTerminus, RetainedUnsettled, Retention},
+};
 
 /// An acceptor for a remote Sender link
 ///
@@ -44,12 +49,15 @@ where
     pub target_capabilities: Option<Vec<C>>,
 
     /// Whether the receiver will automatically accept all incoming deliveries
-    /// 
+    ///
     /// # Default
     ///
     /// `false`
     pub auto_accept: bool,
 
+    /// How the receiver handles messages that have expired when they arrive
+    pub expired_message_policy: ExpiredMessagePolicy,
+
     pub on_dynamic_target: F,
     pub target_marker: PhantomData<T>,
 
@@ -69,6 +77,7 @@ impl<C, T> Default for LocalReceiverLinkAcceptor<C, T, fn(T) -> Option<T>> {
             credit_mode: CreditMode::default(),
             target_capabilities: None,
             auto_accept: false,
+            expired_message_policy: ExpiredMessagePolicy::default(),
             on_dynamic_target: reject_dynamic_target,
             target_marker: PhantomData,
             verify_incoming_source: true,
@@ -87,11 +96,25 @@ where
         remote_attach: Attach,
         session: &mut SessionHandle<R>,
     ) -> Result<Receiver, ReceiverAttachError> {
+        let retention = match &shared.terminus_registry {
+            Some(registry) => {
+                Retention::lookup(
+                    registry,
+                    &session.control,
+                    &remote_attach.name,
+                    Role::Receiver,
+                )
+                .await
+            }
+            None => None,
+        };
+
         self.accept_incoming_attach_inner(
             shared,
             remote_attach,
             session.control.clone(),
             session.outgoing.clone(),
+            retention,
         )
         .await
         .map(|inner| Receiver { inner })
@@ -110,6 +133,7 @@ where
         remote_attach: Attach,
         control: mpsc::Sender<SessionControl>,
         outgoing: mpsc::Sender<LinkFrame>,
+        mut retention: Option<Retention>,
     ) -> Result<ReceiverInner<ReceiverLink<T>>, ReceiverAttachError>
     where
         T: Into<TargetArchetype>
@@ -138,6 +162,17 @@ where
             shared.fallback_rcv_settle_mode.clone()
         };
 
+        let (resumed_target, resumed_unsettled) =
+            match retention.as_mut().and_then(|r| r.resumed.take()) {
+                Some(terminus) => (terminus.target, terminus.unsettled),
+                None => (None, None),
+            };
+        let is_dynamic = resumed_target.is_none()
+            && matches!(
+                remote_attach.target.as_deref(),
+                Some(TargetArchetype::Target(target)) if target.dynamic
+            );
+
         // Create channels for Session-Link communication
         let (incoming_tx, mut incoming_rx) = mpsc::channel::<LinkIncomingItem>(shared.buffer_size);
 
@@ -155,14 +190,19 @@ where
         let flow_state_consumer = flow_state;
 
         // Comparing unsettled should be taken care of in `on_incoming_attach`
-        let unsettled = Arc::new(RwLock::new(None));
+        let unsettled = match resumed_unsettled {
+            Some(RetainedUnsettled::Receiver(unsettled)) => unsettled,
+            _ => Arc::new(RwLock::new(None)),
+        };
+        let detached = CancellationToken::new();
         let link_handle = LinkRelay::Receiver {
-            tx: incoming_tx,
+            tx: incoming_tx.clone(),
             output_handle: (),
             flow_state: flow_state_producer,
             unsettled: unsettled.clone(),
             receiver_settle_mode: rcv_settle_mode.clone(),
             more: false,
+            detached: Some(detached.clone().drop_guard()),
         };
 
         // Allocate link in session
@@ -177,9 +217,11 @@ where
 
         let mut err = None;
         // **the receiver is considered to hold the authoritative version of the target properties**,
-        let local_target = remote_attach
-            .target
-            .clone()
+        //
+        // The target of a resumed terminus takes precedence over the one in the incoming attach
+        let local_target = resumed_target
+            .map(|t| Box::new(TargetArchetype::Target(t)))
+            .or_else(|| remote_attach.target.clone())
             .map(|t| T::try_from(*t))
             .transpose()
             .map(|target| {
@@ -218,6 +260,7 @@ where
             unsettled,
             verify_incoming_source: self.verify_incoming_source,
             verify_incoming_target: self.verify_incoming_target,
+            allow_unsupported_filters: false,
         };
 
         // `on_incoming_attach` should always be evaluated
@@ -245,12 +288,47 @@ where
             _ => link.send_attach(&outgoing, &control, false).await?,
         }
 
+        let target = link.target.clone().map(Into::into).and_then(|t| match t {
+            TargetArchetype::Target(t) => Some(t),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        });
+
+        if let Some(registry) = &shared.dynamic_node_registry {
+            if let Some(address) = target.as_ref().and_then(|t| t.address.clone()) {
+                let watch = LinkWatch {
+                    link: incoming_tx.clone(),
+                    detached: None,
+                    session: control.clone(),
+                    connection: None,
+                };
+                if is_dynamic {
+                    let properties = target
+                        .as_ref()
+                        .and_then(|t| t.dynamic_node_properties.as_ref());
+                    registry.create(address, properties, watch);
+                } else {
+                    registry.attach(address, watch);
+                }
+            }
+        }
+
+        if let Some(retention) = retention {
+            let terminus = RetainedTerminus {
+                source: link.source.clone(),
+                target,
+                unsettled: Some(RetainedUnsettled::Receiver(link.unsettled.clone())),
+            };
+            retention.retain(terminus, incoming_tx, detached, control.clone());
+        }
+
         let mut inner = ReceiverInner {
             link,
             buffer_size: shared.buffer_size,
             credit_mode: self.credit_mode.clone(),
             processed: AtomicU32::new(0),
             auto_accept: self.auto_accept,
+            expired_message_policy: self.expired_message_policy,
             session: control.clone(),
             outgoing,
             incoming: incoming_rx,
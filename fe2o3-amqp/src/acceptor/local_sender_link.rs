@@ -3,27 +3,32 @@
 use std::{marker::PhantomData, sync::Arc};
 
 use fe2o3_amqp_types::{
-    definitions::SequenceNo,
+    definitions::{Role, SequenceNo},
     messaging::{Source, Target},
     performatives::Attach,
     primitives::Symbol,
 };
 use parking_lot::RwLock;
 use tokio::sync::{mpsc, Notify};
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     endpoint::{InputHandle, LinkAttach, LinkExt},
     link::{
         sender::SenderInner,
         state::{LinkFlowState, LinkFlowStateInner, LinkState},
-        LinkRelay, SenderAttachError, SenderLink,
+        LinkRelay, SenderAttachError, SenderAttachExchange, SenderLink,
     },
     session::SessionHandle,
     util::{Consumer, Producer},
     Sender,
 };
 
-use super::link::SharedLinkAcceptorFields;
+use super::{
+    error::AcceptorAttachError,
+    link::SharedLinkAcceptorFields,
+    terminus::{LinkWatch, RetainedTerminus, RetainedUnsettled, Retention},
+};
 
 /// An acceptor for a remote receiver link
 ///
@@ -78,7 +83,7 @@ where
         shared: &SharedLinkAcceptorFields,
         remote_attach: Attach,
         session: &mut SessionHandle<R>,
-    ) -> Result<Sender, SenderAttachError> {
+    ) -> Result<Sender, AcceptorAttachError> {
         let snd_settle_mode = if shared
             .supported_snd_settle_modes
             .supports(&remote_attach.snd_settle_mode)
@@ -96,6 +101,25 @@ where
             shared.fallback_rcv_settle_mode.clone()
         };
 
+        let mut retention = match &shared.terminus_registry {
+            Some(registry) => {
+                Retention::lookup(
+                    registry,
+                    &session.control,
+                    &remote_attach.name,
+                    Role::Sender,
+                )
+                .await
+            }
+            None => None,
+        };
+        let resumed = retention.as_mut().and_then(|r| r.resumed.take());
+        let is_resuming = resumed.is_some();
+        let (resumed_source, resumed_unsettled) = match resumed {
+            Some(terminus) => (terminus.source, terminus.unsettled),
+            None => (None, None),
+        };
+
         let (incoming_tx, mut incoming_rx) = mpsc::channel(shared.buffer_size);
 
         let flow_state_inner = LinkFlowStateInner {
@@ -111,13 +135,18 @@ where
         let flow_state_producer = Producer::new(notifier.clone(), flow_state.clone());
         let flow_state_consumer = Consumer::new(notifier, flow_state);
 
-        let unsettled = Arc::new(RwLock::new(None));
+        let unsettled = match resumed_unsettled {
+            Some(RetainedUnsettled::Sender(unsettled)) => unsettled,
+            _ => Arc::new(RwLock::new(None)),
+        };
+        let detached = CancellationToken::new();
         let link_handle = LinkRelay::Sender {
-            tx: incoming_tx,
+            tx: incoming_tx.clone(),
             output_handle: (),
             flow_state: flow_state_producer,
             unsettled: unsettled.clone(),
             receiver_settle_mode: remote_attach.rcv_settle_mode.clone(),
+            detached: Some(detached.clone().drop_guard()),
         };
 
         // Allocate link in session
@@ -128,23 +157,34 @@ where
             link_handle,
             input_handle,
         )
-        .await?;
+        .await
+        .map_err(SenderAttachError::from)?;
 
         // In this case, the sender is considered to hold the authoritative version of the
         // version of the source properties
-        let local_source = remote_attach.source.clone().and_then(|s| {
-            if s.dynamic {
-                (self.on_dynamic_source)(*s).map(|mut s| {
-                    s.capabilities = self.source_capabilities.clone().map(Into::into);
-                    s
-                })
-            } else {
-                let mut source = *s;
-                source.capabilities = self.source_capabilities.clone().map(Into::into);
-                Some(source)
-            }
+        //
+        // The source of a resumed terminus takes precedence over the one in the incoming attach
+        let mut local_source = resumed_source.or_else(|| {
+            remote_attach.source.clone().and_then(|s| {
+                if s.dynamic {
+                    (self.on_dynamic_source)(*s).map(|mut s| {
+                        s.capabilities = self.source_capabilities.clone().map(Into::into);
+                        s
+                    })
+                } else {
+                    let mut source = *s;
+                    source.capabilities = self.source_capabilities.clone().map(Into::into);
+                    Some(source)
+                }
+            })
         });
 
+        // The sending endpoint sets the filter actually in place, so only the supported filters
+        // are echoed back
+        if let (Some(filters), Some(source)) = (&shared.supported_filters, &mut local_source) {
+            filters.retain_supported(&mut source.filter);
+        }
+
         let mut link = SenderLink::<Target> {
             role: PhantomData,
             local_state: LinkState::Unattached, // will be set in `on_incoming_attach`
@@ -163,12 +203,18 @@ where
             unsettled,
             verify_incoming_source: self.verify_incoming_source,
             verify_incoming_target: self.verify_incoming_target,
+            allow_unsupported_filters: false,
         };
 
         let outgoing = session.outgoing.clone();
+        let is_dynamic =
+            !is_resuming && matches!(&remote_attach.source, Some(source) if source.dynamic);
 
-        match link.on_incoming_attach(remote_attach) {
-            Ok(_) => link.send_attach(&outgoing, &session.control, false).await?,
+        let exchange = match link.on_incoming_attach(remote_attach) {
+            Ok(exchange) => {
+                link.send_attach(&outgoing, &session.control, false).await?;
+                exchange
+            }
             Err(attach_error) => {
                 // Complete attach then detach should any error happen
                 link.send_attach(&outgoing, &session.control, false).await?;
@@ -185,19 +231,63 @@ where
                                 &mut incoming_rx,
                                 &session.control,
                             )
-                            .await);
+                            .await
+                            .into());
+                    }
+                }
+                SenderAttachExchange::Complete
+            }
+        };
+
+        if let Some(registry) = &shared.dynamic_node_registry {
+            if let Some(source) = &link.source {
+                if let Some(address) = source.address.clone() {
+                    let watch = LinkWatch {
+                        link: incoming_tx.clone(),
+                        detached: None,
+                        session: session.control.clone(),
+                        connection: None,
+                    };
+                    if is_dynamic {
+                        registry.create(address, source.dynamic_node_properties.as_ref(), watch);
+                    } else {
+                        registry.attach(address, watch);
                     }
                 }
             }
         }
 
-        let inner = SenderInner {
+        if let Some(retention) = retention {
+            let terminus = RetainedTerminus {
+                source: link.source.clone(),
+                target: link.target.clone(),
+                unsettled: Some(RetainedUnsettled::Sender(link.unsettled.clone())),
+            };
+            retention.retain(terminus, incoming_tx, detached, session.control.clone());
+        }
+
+        let mut inner = SenderInner {
             link,
             buffer_size: shared.buffer_size,
             session: session.control.clone(),
             outgoing,
             incoming: incoming_rx,
         };
+        // Only the deliveries of a resumed terminus are resent. This waits for link credit
+        // from the remote receiver
+        match exchange {
+            SenderAttachExchange::IncompleteUnsettled(resuming_deliveries)
+            | SenderAttachExchange::Resume(resuming_deliveries)
+                if is_resuming =>
+            {
+                inner
+                    .resume_deliveries(resuming_deliveries)
+                    .await
+                    .map_err(AcceptorAttachError::LocalSenderResume)?;
+            }
+            _ => {}
+        }
+
         Ok(Sender { inner })
     }
 }
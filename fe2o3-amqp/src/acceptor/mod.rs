@@ -1,13 +1,19 @@
 //! Acceptors for fine control over incoming connections, sessions, and links
 
+pub mod broker;
 pub mod builder;
 pub mod connection;
+pub mod duplex;
+pub mod dynamic_node;
 pub mod error;
+pub mod filter;
 pub mod link;
 pub mod local_receiver_link;
 pub mod local_sender_link;
 pub mod sasl_acceptor;
+pub mod server;
 pub mod session;
+pub mod terminus;
 
 cfg_scram! {
     pub mod scram;
@@ -19,9 +25,11 @@ use fe2o3_amqp_types::{
 };
 
 pub use self::connection::{ConnectionAcceptor, ListenerConnectionHandle};
+pub use self::filter::FilterRegistry;
 pub use self::link::{LinkAcceptor, LinkEndpoint};
 pub use self::sasl_acceptor::{SaslAcceptor, SaslAnonymousMechanism, SaslPlainMechanism};
 pub use self::session::{ListenerSessionHandle, SessionAcceptor};
+pub use self::terminus::TerminusRegistry;
 
 /// A half established session that is initiated by the remote peer
 #[derive(Debug)]
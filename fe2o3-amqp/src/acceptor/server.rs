@@ -0,0 +1,758 @@
+//! A generic serve loop that drives the connection, session and link acceptors
+//!
+//! The [`Server`] owns the loop that is otherwise hand-written by every listener, ie.
+//! `TcpListener::accept` → [`ConnectionAcceptor`] → [`SessionAcceptor`] → [`LinkAcceptor`], and
+//! dispatches every accepted [`LinkEndpoint`] to a [`LinkHandler`] that is selected by the address
+//! of the link.
+//!
+//! ```rust,ignore
+//! use tokio::net::TcpListener;
+//! use fe2o3_amqp::acceptor::{ConnectionAcceptor, LinkEndpoint, server::Server};
+//!
+//! let tcp_listener = TcpListener::bind("localhost:5672").await.unwrap();
+//! let server = Server::new(ConnectionAcceptor::new("example-listener"))
+//!     .max_connections(128)
+//!     .route("q1", |link: LinkEndpoint| async move {
+//!         if let LinkEndpoint::Receiver(mut receiver) = link {
+//!             while let Ok(delivery) = receiver.recv::<String>().await {
+//!                 let _ = receiver.accept(&delivery).await;
+//!             }
+//!         }
+//!     });
+//!
+//! // Closes all connections with `amqp:connection:forced` once ctrl-c is received
+//! server
+//!     .serve_with_shutdown(tcp_listener, async { tokio::signal::ctrl_c().await.unwrap(); })
+//!     .await;
+//! ```
+
+use std::{collections::HashMap, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use fe2o3_amqp_types::{
+    definitions::{self, AmqpError, ConnectionError},
+    messaging::{Source, Target},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, TcpStream},
+    sync::{watch, OwnedSemaphorePermit, Semaphore},
+    task::JoinSet,
+};
+
+use crate::connection::OpenError;
+
+use super::{
+    ConnectionAcceptor, LinkAcceptor, LinkEndpoint, ListenerConnectionHandle,
+    ListenerSessionHandle, SaslAcceptor, SessionAcceptor,
+};
+
+/// Default time to wait for connections to finish the closing handshake during a
+/// graceful shutdown
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SHUTDOWN_DESCRIPTION: &str = "Server is shutting down";
+
+/// A handler for accepted links
+///
+/// This is implemented for all `Fn(LinkEndpoint) -> impl Future<Output = ()>` closures
+pub trait LinkHandler: Send + Sync + 'static {
+    /// Handles an accepted link. The returned future will be spawned as a new task that is
+    /// awaited before the session of the link is considered finished.
+    fn call(&self, link: LinkEndpoint) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+}
+
+impl<F, Fut> LinkHandler for F
+where
+    F: Fn(LinkEndpoint) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    fn call(&self, link: LinkEndpoint) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin((self)(link))
+    }
+}
+
+/// Dispatches accepted links to handlers by address
+///
+/// The address of a local [`Sender`](crate::link::Sender) is taken from its source while the
+/// address of a local [`Receiver`](crate::link::Receiver) is taken from its target. Links whose
+/// address doesn't match any route are passed to the fallback handler. If there is no fallback
+/// handler, the link will be closed with an `amqp:not-found` error.
+#[derive(Default, Clone)]
+pub struct LinkRouter {
+    routes: HashMap<String, Arc<dyn LinkHandler>>,
+    fallback: Option<Arc<dyn LinkHandler>>,
+}
+
+impl std::fmt::Debug for LinkRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LinkRouter")
+            .field("routes", &self.routes.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl LinkRouter {
+    /// Creates an empty router
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a handler for links attached to `address`
+    pub fn route(mut self, address: impl Into<String>, handler: impl LinkHandler) -> Self {
+        self.routes.insert(address.into(), Arc::new(handler));
+        self
+    }
+
+    /// Sets the handler for links that don't match any route
+    pub fn fallback(mut self, handler: impl LinkHandler) -> Self {
+        self.fallback = Some(Arc::new(handler));
+        self
+    }
+
+    /// Finds the handler for the link address
+    pub fn handler(&self, address: Option<&str>) -> Option<&Arc<dyn LinkHandler>> {
+        address
+            .and_then(|address| self.routes.get(address))
+            .or(self.fallback.as_ref())
+    }
+
+    /// Spawns the matching handler for the link onto `tasks` or closes the link if there is no
+    /// handler
+    pub fn dispatch(&self, link: LinkEndpoint, tasks: &mut JoinSet<()>) {
+        match self.handler(link_address(&link)) {
+            Some(handler) => {
+                tasks.spawn(handler.call(link));
+            }
+            None => {
+                let error = definitions::Error::new(
+                    AmqpError::NotFound,
+                    Some(String::from("No handler is found for the link address")),
+                    None,
+                );
+                tasks.spawn(async move {
+                    let _ = match link {
+                        LinkEndpoint::Sender(sender) => sender.close_with_error(error).await,
+                        LinkEndpoint::Receiver(receiver) => receiver.close_with_error(error).await,
+                    };
+                });
+            }
+        }
+    }
+}
+
+/// Returns the address used for routing the link
+///
+/// This is the source address for a local sender and the target address for a local receiver.
+pub fn link_address(link: &LinkEndpoint) -> Option<&str> {
+    match link {
+        LinkEndpoint::Sender(sender) => sender
+            .source()
+            .as_ref()
+            .and_then(|source: &Source| source.address.as_deref()),
+        LinkEndpoint::Receiver(receiver) => receiver
+            .target()
+            .as_ref()
+            .and_then(|target: &Target| target.address.as_deref()),
+    }
+}
+
+/// Performs the TLS/SASL/AMQP negotiation on an incoming stream
+///
+/// This is implemented for all the [`ConnectionAcceptor`] variants and allows the [`Server`] to be
+/// generic over the TLS and SASL acceptors.
+pub trait AcceptConnection: Send + Sync + 'static {
+    /// Accepts an incoming connection
+    fn accept_connection<Io>(
+        &self,
+        stream: Io,
+    ) -> impl Future<Output = Result<ListenerConnectionHandle, OpenError>> + Send + '_
+    where
+        Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static;
+}
+
+impl AcceptConnection for ConnectionAcceptor<(), ()> {
+    fn accept_connection<Io>(
+        &self,
+        stream: Io,
+    ) -> impl Future<Output = Result<ListenerConnectionHandle, OpenError>> + Send + '_
+    where
+        Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static,
+    {
+        self.accept(stream)
+    }
+}
+
+impl<Sasl> AcceptConnection for ConnectionAcceptor<(), Sasl>
+where
+    Sasl: SaslAcceptor + Send + Sync + 'static,
+{
+    fn accept_connection<Io>(
+        &self,
+        stream: Io,
+    ) -> impl Future<Output = Result<ListenerConnectionHandle, OpenError>> + Send + '_
+    where
+        Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static,
+    {
+        self.accept(stream)
+    }
+}
+
+cfg_native_tls! {
+    impl AcceptConnection for ConnectionAcceptor<tokio_native_tls::TlsAcceptor, ()> {
+        fn accept_connection<Io>(
+            &self,
+            stream: Io,
+        ) -> impl Future<Output = Result<ListenerConnectionHandle, OpenError>> + Send + '_
+        where
+            Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static,
+        {
+            self.accept(stream)
+        }
+    }
+
+    impl<Sasl> AcceptConnection for ConnectionAcceptor<tokio_native_tls::TlsAcceptor, Sasl>
+    where
+        Sasl: SaslAcceptor + Send + Sync + 'static,
+    {
+        fn accept_connection<Io>(
+            &self,
+            stream: Io,
+        ) -> impl Future<Output = Result<ListenerConnectionHandle, OpenError>> + Send + '_
+        where
+            Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static,
+        {
+            self.accept(stream)
+        }
+    }
+}
+
+cfg_rustls! {
+    impl AcceptConnection for ConnectionAcceptor<tokio_rustls::TlsAcceptor, ()> {
+        fn accept_connection<Io>(
+            &self,
+            stream: Io,
+        ) -> impl Future<Output = Result<ListenerConnectionHandle, OpenError>> + Send + '_
+        where
+            Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static,
+        {
+            self.accept(stream)
+        }
+    }
+
+    impl<Sasl> AcceptConnection for ConnectionAcceptor<tokio_rustls::TlsAcceptor, Sasl>
+    where
+        Sasl: SaslAcceptor + Send + Sync + 'static,
+    {
+        fn accept_connection<Io>(
+            &self,
+            stream: Io,
+        ) -> impl Future<Output = Result<ListenerConnectionHandle, OpenError>> + Send + '_
+        where
+            Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static,
+        {
+            self.accept(stream)
+        }
+    }
+}
+
+/// A server that accepts incoming connections, sessions and links
+///
+/// # Default configuration
+///
+/// | Field | Default Value |
+/// |-------|---------------|
+/// |`session_acceptor`| [`SessionAcceptor::default()`] |
+/// |`link_acceptor`| [`LinkAcceptor::default()`] |
+/// |`router`| [`LinkRouter::default()`] |
+/// |`max_connections`| `None` |
+/// |`shutdown_timeout`| [`DEFAULT_SHUTDOWN_TIMEOUT`] |
+///
+/// # Connection limit
+///
+/// When `max_connections` is reached, the server stops accepting from the `TcpListener` until
+/// one of the existing connections is closed.
+///
+/// # Graceful shutdown
+///
+/// Once the shutdown signal passed to [`serve_with_shutdown`](#method.serve_with_shutdown)
+/// resolves, the server stops accepting new connections and closes all open connections with
+/// an `amqp:connection:forced` error. A connection is only considered closed once the tasks of
+/// its sessions and link handlers have finished. Connections that do not finish within
+/// `shutdown_timeout` are dropped, which aborts their sessions and link handlers.
+#[derive(Debug)]
+pub struct Server<C, FS, FT>
+where
+    FS: Fn(Source) -> Option<Source>,
+    FT: Fn(Target) -> Option<Target>,
+{
+    /// Acceptor for incoming connections
+    pub connection_acceptor: C,
+
+    /// Acceptor for incoming sessions
+    pub session_acceptor: SessionAcceptor,
+
+    /// Acceptor for incoming links
+    pub link_acceptor: LinkAcceptor<FS, FT>,
+
+    /// Dispatches the accepted links
+    pub router: LinkRouter,
+
+    /// Maximum number of concurrent connections
+    pub max_connections: Option<usize>,
+
+    /// Time to wait for connections to close during a graceful shutdown
+    pub shutdown_timeout: Duration,
+}
+
+impl<C> Server<C, fn(Source) -> Option<Source>, fn(Target) -> Option<Target>>
+where
+    C: AcceptConnection,
+{
+    /// Creates a server with the connection acceptor and default configuration
+    pub fn new(connection_acceptor: C) -> Self {
+        Self {
+            connection_acceptor,
+            session_acceptor: SessionAcceptor::default(),
+            link_acceptor: LinkAcceptor::default(),
+            router: LinkRouter::default(),
+            max_connections: None,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+        }
+    }
+}
+
+impl<C, FS, FT> Server<C, FS, FT>
+where
+    C: AcceptConnection,
+    FS: Fn(Source) -> Option<Source> + Send + Sync + 'static,
+    FT: Fn(Target) -> Option<Target> + Send + Sync + 'static,
+{
+    /// Sets the session acceptor
+    pub fn session_acceptor(mut self, session_acceptor: SessionAcceptor) -> Self {
+        self.session_acceptor = session_acceptor;
+        self
+    }
+
+    /// Sets the link acceptor
+    pub fn link_acceptor<FS2, FT2>(
+        self,
+        link_acceptor: LinkAcceptor<FS2, FT2>,
+    ) -> Server<C, FS2, FT2>
+    where
+        FS2: Fn(Source) -> Option<Source>,
+        FT2: Fn(Target) -> Option<Target>,
+    {
+        Server {
+            connection_acceptor: self.connection_acceptor,
+            session_acceptor: self.session_acceptor,
+            link_acceptor,
+            router: self.router,
+            max_connections: self.max_connections,
+            shutdown_timeout: self.shutdown_timeout,
+        }
+    }
+
+    /// Sets the link router
+    pub fn router(mut self, router: LinkRouter) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// Adds a handler for links attached to `address`
+    pub fn route(mut self, address: impl Into<String>, handler: impl LinkHandler) -> Self {
+        self.router = self.router.route(address, handler);
+        self
+    }
+
+    /// Sets the handler for links that don't match any route
+    pub fn fallback(mut self, handler: impl LinkHandler) -> Self {
+        self.router = self.router.fallback(handler);
+        self
+    }
+
+    /// Sets the maximum number of concurrent connections
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = Some(max);
+        self
+    }
+
+    /// Sets the time to wait for connections to close during a graceful shutdown
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Serves a connection that is already accepted until it is closed and the tasks of its
+    /// sessions and link handlers have finished
+    ///
+    /// This allows the server to drive connections that are not established over a
+    /// `TcpListener`, eg. an in-memory duplex stream.
+    pub async fn serve_connection(self, connection: ListenerConnectionHandle) {
+        let shared = Arc::new(Shared {
+            connection_acceptor: self.connection_acceptor,
+            session_acceptor: self.session_acceptor,
+            link_acceptor: self.link_acceptor,
+            router: self.router,
+        });
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        serve_connection(&shared, connection, shutdown_rx).await
+    }
+
+    /// Serves incoming connections until the listener is dropped
+    pub async fn serve(self, listener: TcpListener) {
+        self.serve_with_shutdown(listener, std::future::pending())
+            .await
+    }
+
+    /// Serves incoming connections until `signal` resolves, and then closes all open
+    /// connections with `amqp:connection:forced`
+    pub async fn serve_with_shutdown<F>(self, listener: TcpListener, signal: F)
+    where
+        F: Future<Output = ()>,
+    {
+        let shared = Arc::new(Shared {
+            connection_acceptor: self.connection_acceptor,
+            session_acceptor: self.session_acceptor,
+            link_acceptor: self.link_acceptor,
+            router: self.router,
+        });
+        let limit = self
+            .max_connections
+            .map(|max| Arc::new(Semaphore::new(max)));
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let mut connections = JoinSet::new();
+
+        tokio::pin!(signal);
+        loop {
+            let permit = match &limit {
+                Some(limit) => tokio::select! {
+                    _ = &mut signal => break,
+                    permit = limit.clone().acquire_owned() => match permit {
+                        Ok(permit) => Some(permit),
+                        Err(_) => break,
+                    },
+                },
+                None => None,
+            };
+
+            let stream = tokio::select! {
+                _ = &mut signal => break,
+                // Reap the finished connection tasks
+                Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+                result = listener.accept() => match result {
+                    Ok((stream, _addr)) => stream,
+                    Err(_error) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!(accept_error = ?_error);
+                        #[cfg(feature = "log")]
+                        log::error!("accept_error = {:?}", _error);
+                        continue;
+                    }
+                },
+            };
+
+            connections.spawn(connection_main(
+                shared.clone(),
+                stream,
+                permit,
+                shutdown_rx.clone(),
+            ));
+        }
+
+        // Stop accepting and close all open connections
+        drop(listener);
+        let _ = shutdown_tx.send(true);
+        let drain = async { while connections.join_next().await.is_some() {} };
+        if tokio::time::timeout(self.shutdown_timeout, drain)
+            .await
+            .is_err()
+        {
+            connections.shutdown().await;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Shared<C, FS, FT>
+where
+    FS: Fn(Source) -> Option<Source>,
+    FT: Fn(Target) -> Option<Target>,
+{
+    connection_acceptor: C,
+    session_acceptor: SessionAcceptor,
+    link_acceptor: LinkAcceptor<FS, FT>,
+    router: LinkRouter,
+}
+
+async fn connection_main<C, FS, FT>(
+    shared: Arc<Shared<C, FS, FT>>,
+    stream: TcpStream,
+    _permit: Option<OwnedSemaphorePermit>,
+    mut shutdown: watch::Receiver<bool>,
+) where
+    C: AcceptConnection,
+    FS: Fn(Source) -> Option<Source> + Send + Sync + 'static,
+    FT: Fn(Target) -> Option<Target> + Send + Sync + 'static,
+{
+    let connection = tokio::select! {
+        _ = wait_for_shutdown(&mut shutdown) => return,
+        result = shared.connection_acceptor.accept_connection(stream) => match result {
+            Ok(connection) => connection,
+            Err(_error) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(open_error = ?_error);
+                #[cfg(feature = "log")]
+                log::error!("open_error = {:?}", _error);
+                return;
+            }
+        },
+    };
+
+    serve_connection(&shared, connection, shutdown).await
+}
+
+async fn serve_connection<C, FS, FT>(
+    shared: &Arc<Shared<C, FS, FT>>,
+    mut connection: ListenerConnectionHandle,
+    mut shutdown: watch::Receiver<bool>,
+) where
+    FS: Fn(Source) -> Option<Source> + Send + Sync + 'static,
+    FT: Fn(Target) -> Option<Target> + Send + Sync + 'static,
+    C: Send + Sync + 'static,
+{
+    let mut sessions = JoinSet::new();
+    loop {
+        tokio::select! {
+            _ = wait_for_shutdown(&mut shutdown) => {
+                let error = definitions::Error::new(
+                    ConnectionError::ConnectionForced,
+                    Some(String::from(SHUTDOWN_DESCRIPTION)),
+                    None,
+                );
+                let _ = connection.close_with_error(error).await;
+                break;
+            }
+            // Reap the finished session tasks
+            Some(_) = sessions.join_next(), if !sessions.is_empty() => {}
+            incoming_session = connection.next_incoming_session() => {
+                let incoming_session = match incoming_session {
+                    Some(incoming_session) => incoming_session,
+                    None => {
+                        let _ = connection.on_close().await;
+                        break;
+                    }
+                };
+                match shared
+                    .session_acceptor
+                    .accept_incoming_session(incoming_session, &mut connection)
+                    .await
+                {
+                    Ok(session) => {
+                        sessions.spawn(session_main(shared.clone(), session));
+                    }
+                    Err(_error) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!(begin_error = ?_error);
+                        #[cfg(feature = "log")]
+                        log::error!("begin_error = {:?}", _error);
+                    }
+                }
+            }
+        }
+    }
+
+    while sessions.join_next().await.is_some() {}
+}
+
+async fn wait_for_shutdown(shutdown: &mut watch::Receiver<bool>) {
+    // The returned guard is not `Send` and must not be held across await points
+    if shutdown.wait_for(|is_shutdown| *is_shutdown).await.is_err() {
+        // The server is dropped without signalling a shutdown
+        std::future::pending::<()>().await;
+    }
+}
+
+async fn session_main<C, FS, FT>(shared: Arc<Shared<C, FS, FT>>, mut session: ListenerSessionHandle)
+where
+    FS: Fn(Source) -> Option<Source>,
+    FT: Fn(Target) -> Option<Target>,
+{
+    let mut handlers = JoinSet::new();
+    loop {
+        let remote_attach = tokio::select! {
+            // Reap the finished link handlers
+            Some(_) = handlers.join_next(), if !handlers.is_empty() => continue,
+            remote_attach = session.next_incoming_attach() => match remote_attach {
+                Some(remote_attach) => remote_attach,
+                None => break,
+            },
+        };
+        match shared
+            .link_acceptor
+            .accept_incoming_attach(remote_attach, &mut session)
+            .await
+        {
+            Ok(link) => shared.router.dispatch(link, &mut handlers),
+            Err(_error) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(attach_error = ?_error);
+                #[cfg(feature = "log")]
+                log::error!("attach_error = {:?}", _error);
+            }
+        }
+    }
+
+    let _ = session.on_end().await;
+    while handlers.join_next().await.is_some() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use fe2o3_amqp_types::definitions::{AmqpError, ConnectionError};
+    use tokio::{
+        io::{AsyncRead, AsyncWrite},
+        net::{TcpListener, TcpStream},
+        sync::mpsc,
+    };
+
+    use crate::{
+        acceptor::{ConnectionAcceptor, LinkEndpoint, ListenerConnectionHandle},
+        connection::{Error as ConnectionStateError, OpenError},
+        link::DetachError,
+        Connection, Sender, Session,
+    };
+
+    use super::{AcceptConnection, Server};
+
+    /// Reports whether the first connection has been closed whenever a connection is accepted
+    struct ObservingAcceptor {
+        inner: ConnectionAcceptor<(), ()>,
+        first_closed: Arc<AtomicBool>,
+        accepted: mpsc::UnboundedSender<bool>,
+    }
+
+    impl AcceptConnection for ObservingAcceptor {
+        fn accept_connection<Io>(
+            &self,
+            stream: Io,
+        ) -> impl Future<Output = Result<ListenerConnectionHandle, OpenError>> + Send + '_
+        where
+            Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static,
+        {
+            let _ = self.accepted.send(self.first_closed.load(Ordering::SeqCst));
+            self.inner.accept(stream)
+        }
+    }
+
+    #[tokio::test]
+    async fn serve_dispatches_links_and_forces_close_on_shutdown() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let handler_finished = Arc::new(AtomicBool::new(false));
+        let finished = handler_finished.clone();
+        let server = Server::new(ConnectionAcceptor::new("test-server")).route(
+            "q1",
+            move |link: LinkEndpoint| {
+                let tx = tx.clone();
+                let finished = finished.clone();
+                async move {
+                    if let LinkEndpoint::Receiver(mut receiver) = link {
+                        let delivery = receiver.recv::<String>().await.unwrap();
+                        receiver.accept(&delivery).await.unwrap();
+                        tx.send(delivery.body().clone()).await.unwrap();
+                        // Runs until the link is stopped by the shutdown
+                        while receiver.recv::<String>().await.is_ok() {}
+                    }
+                    finished.store(true, Ordering::SeqCst);
+                }
+            },
+        );
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(server.serve_with_shutdown(listener, async {
+            let _ = shutdown_rx.await;
+        }));
+
+        let url = format!("amqp://{}", addr);
+        let mut connection = Connection::open("test-client", &url[..]).await.unwrap();
+        let mut session = Session::begin(&mut connection).await.unwrap();
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+        sender.send("hello").await.unwrap();
+        assert_eq!(rx.recv().await.unwrap(), "hello");
+
+        // Links with unknown address are closed with `amqp:not-found`
+        let mut unknown = Sender::attach(&mut session, "unknown", "q2").await.unwrap();
+        match unknown.on_detach().await {
+            DetachError::RemoteClosedWithError(error) => {
+                assert_eq!(error.condition, AmqpError::NotFound.into());
+            }
+            other => panic!("Unexpected detach error {:?}", other),
+        }
+
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap();
+        // The server only returns once the link handlers have finished
+        assert!(handler_finished.load(Ordering::SeqCst));
+
+        match connection.on_close().await {
+            Err(ConnectionStateError::RemoteClosedWithError(error)) => {
+                assert_eq!(error.condition, ConnectionError::ConnectionForced.into());
+            }
+            other => panic!("Unexpected outcome {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn serve_limits_concurrent_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let first_closed = Arc::new(AtomicBool::new(false));
+        let (accepted_tx, mut accepted) = mpsc::unbounded_channel();
+        let acceptor = ObservingAcceptor {
+            inner: ConnectionAcceptor::new("test-server"),
+            first_closed: first_closed.clone(),
+            accepted: accepted_tx,
+        };
+        let server = Server::new(acceptor).max_connections(1);
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(server.serve_with_shutdown(listener, async {
+            let _ = shutdown_rx.await;
+        }));
+
+        let url = format!("amqp://{}", addr);
+        let mut first = Connection::open("first", &url[..]).await.unwrap();
+        assert!(!accepted.recv().await.unwrap());
+
+        // The second connection waits in the backlog of the listener
+        let stream = TcpStream::connect(addr).await.unwrap();
+        first_closed.store(true, Ordering::SeqCst);
+        first.close().await.unwrap();
+
+        let mut second = Connection::builder()
+            .container_id("second")
+            .open_with_stream(stream)
+            .await
+            .unwrap();
+        // The second connection is only accepted once the first one is closed
+        assert!(accepted.recv().await.unwrap());
+        second.close().await.unwrap();
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+}
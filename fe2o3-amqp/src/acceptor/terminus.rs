@@ -0,0 +1,344 @@
+//! Retention of terminus state for links accepted by a [`LinkAcceptor`](super::LinkAcceptor)
+//!
+//! A [`TerminusRegistry`] keeps the local terminus of an accepted link after the link endpoint
+//! goes away so that a remote peer reattaching a link with the same name from the same
+//! container resumes the link (eg. a durable subscription) instead of getting a fresh one.
+//!
+//! What is retained follows the `durable` field of the local terminus (the source of a local
+//! sender or the target of a local receiver)
+//!
+//! | `durable` | Retained state |
+//! |-----------|----------------|
+//! | [`TerminusDurability::None`] | nothing |
+//! | [`TerminusDurability::Configuration`] | the source and target |
+//! | [`TerminusDurability::UnsettledState`] | the source, target and unsettled map |
+//!
+//! and the retained state expires `timeout` seconds after the event selected by the
+//! `expiry_policy` of the local terminus
+//!
+//! | `expiry_policy` | Expiry timer starts when |
+//! |-----------------|--------------------------|
+//! | [`TerminusExpiryPolicy::LinkDetach`] | the link detaches or the accepted link endpoint is dropped |
+//! | [`TerminusExpiryPolicy::SessionEnd`] | the session of the link ends |
+//! | [`TerminusExpiryPolicy::ConnectionClose`] | the connection of the link closes |
+//! | [`TerminusExpiryPolicy::Never`] | never |
+//!
+//! A link whose terminus is destroyed (ie. closed with a closing detach) should be removed
+//! from the registry with [`TerminusRegistry::remove`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp::acceptor::{LinkAcceptor, terminus::TerminusRegistry};
+//!
+//! let registry = TerminusRegistry::new();
+//! let link_acceptor = LinkAcceptor::builder()
+//!     .terminus_registry(registry.clone())
+//!     .build();
+//! ```
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use fe2o3_amqp_types::{
+    definitions::Role,
+    messaging::{Source, Target, TerminusDurability, TerminusExpiryPolicy},
+};
+use parking_lot::Mutex;
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    control::{ConnectionControl, SessionControl},
+    link::{ArcReceiverUnsettledMap, ArcSenderUnsettledMap, LinkFrame},
+};
+
+/// Identifies a retained terminus
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct TerminusKey {
+    /// Container id of the remote peer
+    pub container_id: String,
+
+    /// Name of the link
+    pub name: String,
+
+    /// Role of the local link endpoint
+    pub role: Role,
+}
+
+/// The unsettled map of a retained terminus
+#[derive(Debug, Clone)]
+pub(crate) enum RetainedUnsettled {
+    Sender(ArcSenderUnsettledMap),
+    Receiver(ArcReceiverUnsettledMap),
+}
+
+/// The state of a terminus that can be resumed
+#[derive(Debug, Clone)]
+pub(crate) struct RetainedTerminus {
+    pub source: Option<Source>,
+    pub target: Option<Target>,
+    pub unsettled: Option<RetainedUnsettled>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    /// Incremented whenever the terminus is resumed so that an outdated expiry timer does
+    /// not remove the entry of the resumed link
+    generation: u64,
+
+    /// Whether the link endpoint using this terminus is still alive
+    attached: bool,
+
+    terminus: RetainedTerminus,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    next_generation: u64,
+    entries: HashMap<TerminusKey, Entry>,
+}
+
+/// Terminus state retained per remote container id and link name
+///
+/// The registry is cheap to clone and clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct TerminusRegistry {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl TerminusRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of retained termini, including the termini of links that are still attached
+    pub fn len(&self) -> usize {
+        self.inner.lock().entries.len()
+    }
+
+    /// Whether no terminus is retained
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().entries.is_empty()
+    }
+
+    /// Whether a terminus is retained for the link `name` from container `container_id`
+    ///
+    /// `role` is the role of the local link endpoint
+    pub fn contains(&self, container_id: &str, name: &str, role: Role) -> bool {
+        self.inner.lock().entries.contains_key(&TerminusKey {
+            container_id: container_id.to_string(),
+            name: name.to_string(),
+            role,
+        })
+    }
+
+    /// Removes the retained terminus of a link. Returns whether a terminus was removed
+    ///
+    /// `role` is the role of the local link endpoint
+    pub fn remove(&self, container_id: &str, name: &str, role: Role) -> bool {
+        self.inner
+            .lock()
+            .entries
+            .remove(&TerminusKey {
+                container_id: container_id.to_string(),
+                name: name.to_string(),
+                role,
+            })
+            .is_some()
+    }
+
+    /// Removes all retained termini
+    pub fn clear(&self) {
+        self.inner.lock().entries.clear()
+    }
+
+    /// Takes the terminus of a link that is no longer attached
+    ///
+    /// The terminus of a link that is still attached is not handed out, and the new link
+    /// starts with a fresh terminus
+    pub(crate) fn take_detached(&self, key: &TerminusKey) -> Option<RetainedTerminus> {
+        let mut inner = self.inner.lock();
+        match inner.entries.get(key) {
+            Some(entry) if !entry.attached => inner.entries.remove(key).map(|e| e.terminus),
+            _ => None,
+        }
+    }
+
+    /// Retains the terminus of a newly attached link and watches the link, its session and
+    /// its connection to expire the terminus according to `expiry_policy` and `timeout`
+    pub(crate) fn retain(&self, key: TerminusKey, terminus: RetainedTerminus, watch: LinkWatch) {
+        let (durable, expiry_policy, timeout) = match key.role {
+            Role::Sender => terminus
+                .source
+                .as_ref()
+                .map(|s| (s.durable.clone(), s.expiry_policy.clone(), s.timeout)),
+            Role::Receiver => terminus
+                .target
+                .as_ref()
+                .map(|t| (t.durable.clone(), t.expiry_policy.clone(), t.timeout)),
+        }
+        .unwrap_or_default();
+
+        let terminus = match durable {
+            TerminusDurability::None => return,
+            TerminusDurability::Configuration => RetainedTerminus {
+                unsettled: None,
+                ..terminus
+            },
+            TerminusDurability::UnsettledState => terminus,
+        };
+
+        let generation = {
+            let mut inner = self.inner.lock();
+            inner.next_generation += 1;
+            let generation = inner.next_generation;
+            let entry = Entry {
+                generation,
+                attached: true,
+                terminus,
+            };
+            inner.entries.insert(key.clone(), entry);
+            generation
+        };
+
+        let registry = self.clone();
+        tokio::spawn(async move {
+            watch.link_detached().await;
+            registry.on_link_detached(&key, generation);
+
+            // Holding on to the link sender would keep the link channel open while waiting
+            // for the terminus to expire
+            let LinkWatch {
+                link,
+                session,
+                connection,
+                ..
+            } = watch;
+            drop(link);
+
+            match expiry_policy {
+                TerminusExpiryPolicy::LinkDetach => {}
+                TerminusExpiryPolicy::SessionEnd => session.closed().await,
+                TerminusExpiryPolicy::ConnectionClose => match &connection {
+                    Some(connection) => connection.closed().await,
+                    None => session.closed().await,
+                },
+                TerminusExpiryPolicy::Never => return,
+            }
+
+            tokio::time::sleep(Duration::from_secs(u64::from(timeout))).await;
+            registry.expire(&key, generation);
+        });
+    }
+
+    fn on_link_detached(&self, key: &TerminusKey, generation: u64) {
+        if let Some(entry) = self.inner.lock().entries.get_mut(key) {
+            if entry.generation == generation {
+                entry.attached = false;
+            }
+        }
+    }
+
+    fn expire(&self, key: &TerminusKey, generation: u64) {
+        let mut inner = self.inner.lock();
+        if matches!(inner.entries.get(key), Some(entry) if entry.generation == generation) {
+            inner.entries.remove(key);
+        }
+    }
+}
+
+/// Channels that tell when a link endpoint, its session and its connection are gone
+#[derive(Debug)]
+pub(crate) struct LinkWatch {
+    /// Closed when the link endpoint is dropped
+    pub link: mpsc::Sender<LinkFrame>,
+
+    /// Cancelled when the session stops routing frames to the link, ie. when the link detaches
+    pub detached: Option<CancellationToken>,
+    pub session: mpsc::Sender<SessionControl>,
+    pub connection: Option<mpsc::Sender<ConnectionControl>>,
+}
+
+impl LinkWatch {
+    /// Returns when the link detaches, the link endpoint is dropped or its session has stopped
+    pub(crate) async fn link_detached(&self) {
+        let detached = async {
+            match &self.detached {
+                Some(detached) => detached.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = self.link.closed() => {},
+            _ = detached => {},
+            _ = self.session.closed() => {},
+        }
+    }
+}
+
+/// Context for retaining the terminus of a link being accepted
+#[derive(Debug)]
+pub(crate) struct Retention {
+    pub registry: TerminusRegistry,
+    pub key: TerminusKey,
+    pub connection: Option<mpsc::Sender<ConnectionControl>>,
+
+    /// The terminus to resume, if any
+    pub resumed: Option<RetainedTerminus>,
+}
+
+impl Retention {
+    /// Looks up the retained terminus of the link `name` attaching on the session
+    ///
+    /// Returns `None` if the container id of the remote peer is not known
+    pub(crate) async fn lookup(
+        registry: &TerminusRegistry,
+        session: &mpsc::Sender<SessionControl>,
+        name: &str,
+        role: Role,
+    ) -> Option<Self> {
+        let (tx, rx) = oneshot::channel();
+        session
+            .send(SessionControl::GetRemoteContainerId(tx))
+            .await
+            .ok()?;
+        let container_id = rx.await.ok()??;
+
+        let (tx, rx) = oneshot::channel();
+        let connection = match session.send(SessionControl::GetConnectionControl(tx)).await {
+            Ok(_) => rx.await.ok(),
+            Err(_) => None,
+        };
+
+        let key = TerminusKey {
+            container_id,
+            name: name.to_string(),
+            role,
+        };
+        let resumed = registry.take_detached(&key);
+        Some(Self {
+            registry: registry.clone(),
+            key,
+            connection,
+            resumed,
+        })
+    }
+
+    /// Retains the terminus of the accepted link
+    pub(crate) fn retain(
+        self,
+        terminus: RetainedTerminus,
+        link: mpsc::Sender<LinkFrame>,
+        detached: CancellationToken,
+        session: mpsc::Sender<SessionControl>,
+    ) {
+        let watch = LinkWatch {
+            link,
+            detached: Some(detached),
+            session,
+            connection: self.connection,
+        };
+        self.registry.retain(self.key, terminus, watch)
+    }
+}
@@ -21,6 +21,10 @@ cfg_not_wasm32! {
     use tokio::net::TcpStream;
 }
 
+cfg_proxy! {
+    use super::proxy::Proxy;
+}
+
 use crate::{
     connection::{Connection, ConnectionState},
     control::ConnectionControl,
@@ -148,6 +152,13 @@ pub struct Builder<'a, Mode, Tls> {
     /// actual TLS handshake
     pub alt_tls_estab: bool,
 
+    /// Proxy used to establish the TCP stream in [`open`](#method.open)
+    ///
+    /// The stream supplied to `open_with_stream` is used as is.
+    #[cfg_attr(docsrs, doc(cfg(feature = "proxy")))]
+    #[cfg(all(feature = "proxy", not(target_arch = "wasm32")))]
+    pub proxy: Option<Proxy>,
+
     // type state marker
     marker: PhantomData<Mode>,
 }
@@ -284,6 +295,8 @@ impl<Mode> Builder<'_, Mode, ()> {
             buffer_size: DEFAULT_OUTGOING_BUFFER_SIZE,
             sasl_profile: None,
             alt_tls_estab: false,
+            #[cfg(all(feature = "proxy", not(target_arch = "wasm32")))]
+            proxy: None,
 
             marker: PhantomData,
         }
@@ -317,6 +330,8 @@ impl<'a, Tls> Builder<'a, mode::ConnectorNoId, Tls> {
             buffer_size: self.buffer_size,
             sasl_profile: self.sasl_profile,
             alt_tls_estab: self.alt_tls_estab,
+            #[cfg(all(feature = "proxy", not(target_arch = "wasm32")))]
+            proxy: self.proxy,
 
             marker: PhantomData,
         }
@@ -367,6 +382,8 @@ impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
                 buffer_size: self.buffer_size,
                 sasl_profile: self.sasl_profile,
                 alt_tls_estab: self.alt_tls_estab,
+                #[cfg(all(feature = "proxy", not(target_arch = "wasm32")))]
+                proxy: self.proxy,
 
                 marker: PhantomData,
             }
@@ -431,6 +448,8 @@ impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
                     buffer_size: self.buffer_size,
                     sasl_profile: self.sasl_profile,
                     alt_tls_estab: self.alt_tls_estab,
+                    #[cfg(all(feature = "proxy", not(target_arch = "wasm32")))]
+                    proxy: self.proxy,
 
                     marker: PhantomData,
                 }
@@ -595,6 +614,40 @@ impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
         self.alt_tls_estab = value;
         self
     }
+
+    cfg_proxy! {
+        /// Proxy used to establish the TCP stream in `open()`
+        ///
+        /// A tunnel to the host and port in the url is established through the proxy before TLS
+        /// and SASL negotiation. Use [`Proxy::FromEnv`] to honor the `HTTPS_PROXY`, `ALL_PROXY`
+        /// and `NO_PROXY` environment variables.
+        pub fn proxy(mut self, proxy: impl Into<Option<Proxy>>) -> Self {
+            self.proxy = proxy.into();
+            self
+        }
+    }
+}
+
+cfg_not_wasm32! {
+    impl<Mode, Tls> Builder<'_, Mode, Tls> {
+        async fn connect_tcp_stream(&self, url: &Url) -> Result<TcpStream, OpenError> {
+            #[cfg(feature = "proxy")]
+            if let Some(proxy) = &self.proxy {
+                let host = url
+                    .host_str()
+                    .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+                    .ok_or(OpenError::InvalidDomain)?;
+                let port = url
+                    .port()
+                    .or_else(|| default_port(url.scheme()))
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No port"))?;
+                return proxy.connect(host, port).await.map_err(Into::into);
+            }
+
+            let addr = url.socket_addrs(|| default_port(url.scheme()))?;
+            TcpStream::connect(&*addr).await.map_err(Into::into) // std::io::Error
+        }
+    }
 }
 
 impl<Tls> Builder<'_, mode::ConnectorWithId, Tls> {
@@ -908,8 +961,7 @@ cfg_not_wasm32! {
                 self.sasl_profile = Some(profile);
             }
 
-            let addr = url.socket_addrs(|| default_port(url.scheme()))?;
-            let stream = TcpStream::connect(&*addr).await?; // std::io::Error
+            let stream = self.connect_tcp_stream(&url).await?;
 
             self.open_with_stream(stream).await
         }
@@ -1181,8 +1233,7 @@ cfg_not_wasm32! {
                     self.sasl_profile = Some(profile);
                 }
 
-                let addr = url.socket_addrs(|| default_port(url.scheme()))?;
-                let stream = TcpStream::connect(&*addr).await?; // std::io::Error
+                let stream = self.connect_tcp_stream(&url).await?;
 
                 self.open_with_stream(stream).await
             }
@@ -1314,8 +1365,7 @@ cfg_not_wasm32! {
                     self.sasl_profile = Some(profile);
                 }
 
-                let addr = url.socket_addrs(|| default_port(url.scheme()))?;
-                let stream = TcpStream::connect(&*addr).await?; // std::io::Error
+                let stream = self.connect_tcp_stream(&url).await?;
 
                 self.open_with_stream(stream).await
             }
@@ -399,6 +399,20 @@ where
                     log::error!("{:?}", error);
                 }
             }
+            #[cfg(feature = "acceptor")]
+            ConnectionControl::GetRemoteContainerId(resp) => {
+                let container_id = self
+                    .connection
+                    .remote_open()
+                    .map(|open| open.container_id.clone());
+                #[allow(unused_variables)]
+                if let Err(error) = resp.send(container_id) {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(?error);
+                    #[cfg(feature = "log")]
+                    log::error!("{:?}", error);
+                }
+            }
         }
 
         match self.connection.local_state() {
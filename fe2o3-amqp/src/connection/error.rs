@@ -63,7 +63,7 @@ pub enum OpenError {
     NotImplemented(Option<String>),
 
     /// Decode error
-    #[error("Decode error")]
+    #[error("Decode error: {0}")]
     DecodeError(String),
 
     /// Transport error
@@ -77,6 +77,16 @@ pub enum OpenError {
     /// Remote peer closed connection with error during openning process
     #[error("Remote peer closed connection with error {}", .0)]
     RemoteClosedWithError(definitions::Error),
+
+    /// Error establishing a tunnel through the proxy
+    #[cfg_attr(docsrs, doc(cfg(feature = "proxy")))]
+    #[cfg(all(feature = "proxy", not(target_arch = "wasm32")))]
+    #[error(transparent)]
+    ProxyError(#[from] ProxyError),
+}
+
+cfg_proxy! {
+    pub use fe2o3_amqp_proxy::ProxyError;
 }
 
 impl From<NegotiationError> for OpenError {
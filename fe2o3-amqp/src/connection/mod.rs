@@ -40,6 +40,10 @@ mod error;
 pub mod heartbeat;
 pub use error::*;
 
+cfg_proxy! {
+    pub mod proxy;
+}
+
 /// Default max-frame-size.
 ///
 /// Please note that this is different from `MaxFrameSize::default()`.
@@ -546,6 +550,11 @@ impl endpoint::Connection for Connection {
         &self.local_open
     }
 
+    #[cfg(feature = "acceptor")]
+    fn remote_open(&self) -> Option<&Open> {
+        self.remote_open.as_ref()
+    }
+
     fn allocate_session(
         &mut self,
         tx: Sender<SessionIncomingItem>,
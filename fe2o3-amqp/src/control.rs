@@ -31,6 +31,8 @@ pub(crate) enum ConnectionControl {
     },
     DeallocateSession(OutgoingChannel),
     GetMaxFrameSize(oneshot::Sender<usize>),
+    #[cfg(feature = "acceptor")]
+    GetRemoteContainerId(oneshot::Sender<Option<String>>),
 }
 
 impl std::fmt::Display for ConnectionControl {
@@ -43,6 +45,8 @@ impl std::fmt::Display for ConnectionControl {
             } => write!(f, "AllocateSession"),
             Self::DeallocateSession(id) => write!(f, "DeallocateSession({})", id.0),
             Self::GetMaxFrameSize(_) => write!(f, "GetMaxFrameSize"),
+            #[cfg(feature = "acceptor")]
+            Self::GetRemoteContainerId(_) => write!(f, "GetRemoteContainerId"),
         }
     }
 }
@@ -65,6 +69,10 @@ pub(crate) enum SessionControl {
     Disposition(Disposition),
     CloseConnectionWithError((ConnectionError, Option<String>)),
     GetMaxFrameSize(oneshot::Sender<usize>),
+    #[cfg(feature = "acceptor")]
+    GetRemoteContainerId(oneshot::Sender<Option<String>>),
+    #[cfg(feature = "acceptor")]
+    GetConnectionControl(oneshot::Sender<Sender<ConnectionControl>>),
 
     // Transaction related controls
     #[cfg(feature = "transaction")]
@@ -104,6 +112,10 @@ impl std::fmt::Display for SessionControl {
             SessionControl::Disposition(_) => write!(f, "Disposition"),
             SessionControl::CloseConnectionWithError(_) => write!(f, "CloseConnectionWithError"),
             SessionControl::GetMaxFrameSize(_) => write!(f, "GetMaxFrameSize"),
+            #[cfg(feature = "acceptor")]
+            SessionControl::GetRemoteContainerId(_) => write!(f, "GetRemoteContainerId"),
+            #[cfg(feature = "acceptor")]
+            SessionControl::GetConnectionControl(_) => write!(f, "GetConnectionControl"),
 
             #[cfg(feature = "transaction")]
             SessionControl::AllocateTransactionId { .. } => write!(f, "AllocateTransactionId"),
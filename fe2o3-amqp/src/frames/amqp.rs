@@ -10,7 +10,7 @@ use tokio_util::codec::{Decoder, Encoder};
 
 use crate::Payload;
 
-use super::{Error, FRAME_TYPE_AMQP};
+use super::{body_name, Error, FRAME_TYPE_AMQP};
 
 /// AMQP frame
 #[derive(Debug)]
@@ -239,21 +239,31 @@ impl Decoder for FrameDecoder {
         let body = if src.is_empty() {
             FrameBody::Empty
         } else {
-            let reader = IoReader::new(src.reader());
+            let name = body_name(src);
+            let body = src.split().freeze();
+            let mut buf = body.clone();
+            let reader = IoReader::new((&mut buf).reader());
             let mut deserializer = Deserializer::new(reader);
-            let performative: Performative = Deserialize::deserialize(&mut deserializer)?;
+            let performative: Performative = match Deserialize::deserialize(&mut deserializer) {
+                Ok(performative) => performative,
+                Err(err) => {
+                    // Decoding again from the bytes recovers the key of the map entry that
+                    // failed to decode, which is already consumed from the io reader
+                    let err = serde_amqp::from_bytes::<Performative>(&body)
+                        .err()
+                        .unwrap_or(err);
+                    return Err(Error::decode(err, name));
+                }
+            };
 
             match performative {
                 Performative::Open(performative) => FrameBody::Open(performative),
                 Performative::Begin(performative) => FrameBody::Begin(performative),
                 Performative::Attach(performative) => FrameBody::Attach(performative),
-                Performative::Transfer(performative) => {
-                    let payload = src.split().into();
-                    FrameBody::Transfer {
-                        performative,
-                        payload,
-                    }
-                }
+                Performative::Transfer(performative) => FrameBody::Transfer {
+                    performative,
+                    payload: buf,
+                },
                 Performative::Flow(performative) => FrameBody::Flow(performative),
                 Performative::Disposition(performative) => FrameBody::Disposition(performative),
                 Performative::Detach(performative) => FrameBody::Detach(performative),
@@ -356,4 +366,98 @@ mod tests {
         let mut src = BytesMut::from(&[0x02, 0x00, 0x00, 0x00][..]);
         let _frame = decoder.decode(&mut src).unwrap();
     }
+
+    #[test]
+    fn test_decode_error_is_located() {
+        use fe2o3_amqp_types::{definitions::Role, messaging::Source, performatives::Attach};
+        use serde_amqp::{format_code::EncodingCodes, to_vec};
+
+        use crate::frames::Error;
+
+        let attach = Attach {
+            name: "link".into(),
+            handle: 0.into(),
+            role: Role::Receiver,
+            snd_settle_mode: Default::default(),
+            rcv_settle_mode: Default::default(),
+            source: Some(Box::new(Source::builder().address("q1").timeout(7).build())),
+            target: None,
+            unsettled: None,
+            incomplete_unsettled: false,
+            initial_delivery_count: None,
+            max_message_size: None,
+            offered_capabilities: None,
+            desired_capabilities: None,
+            properties: None,
+        };
+        let body = to_vec(&attach).unwrap();
+        // Replace the timeout of the source with a boolean
+        let value = [EncodingCodes::SmallUint as u8, 7];
+        let offset = body.windows(2).rposition(|w| w == value).unwrap();
+
+        let mut src = BytesMut::from(&[0x02, 0x00, 0x00, 0x00][..]);
+        src.extend_from_slice(&body[..offset]);
+        src.extend_from_slice(&[EncodingCodes::BooleanTrue as u8]);
+        src.extend_from_slice(&body[offset + 2..]);
+
+        let mut decoder = FrameDecoder {};
+        match decoder.decode(&mut src) {
+            Err(Error::LocatedDecodeError {
+                path,
+                offset: located_offset,
+                ..
+            }) => {
+                assert_eq!(path, "attach.source.timeout");
+                assert_eq!(located_offset, offset);
+            }
+            other => panic!("Expecting a located decode error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_error_is_located_by_map_key() {
+        use fe2o3_amqp_types::{
+            definitions::{Fields, Role},
+            performatives::Attach,
+            primitives::Value,
+        };
+        use serde_amqp::{format_code::EncodingCodes, to_vec};
+
+        use crate::frames::Error;
+
+        let mut properties = Fields::new();
+        properties.insert("key".into(), Value::Uint(7));
+        let attach = Attach {
+            name: "link".into(),
+            handle: 0.into(),
+            role: Role::Receiver,
+            snd_settle_mode: Default::default(),
+            rcv_settle_mode: Default::default(),
+            source: None,
+            target: None,
+            unsettled: None,
+            incomplete_unsettled: false,
+            initial_delivery_count: None,
+            max_message_size: None,
+            offered_capabilities: None,
+            desired_capabilities: None,
+            properties: Some(properties),
+        };
+        let body = to_vec(&attach).unwrap();
+        // Replace the format code of the property value with an invalid one
+        let value = [EncodingCodes::SmallUint as u8, 7];
+        let offset = body.windows(2).rposition(|w| w == value).unwrap();
+
+        let mut src = BytesMut::from(&[0x02, 0x00, 0x00, 0x00][..]);
+        src.extend_from_slice(&body);
+        src[4 + offset] = 0x01;
+
+        let mut decoder = FrameDecoder {};
+        match decoder.decode(&mut src) {
+            Err(Error::LocatedDecodeError { path, .. }) => {
+                assert_eq!(path, r#"attach.properties["key"]"#);
+            }
+            other => panic!("Expecting a located decode error, found {:?}", other),
+        }
+    }
 }
@@ -1,5 +1,7 @@
 use std::io;
 
+use serde_amqp::error::Segment;
+
 /// Errors associated with frame encoder and decoder
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -8,19 +10,58 @@ pub enum Error {
     Io(#[from] io::Error),
 
     /// AMQP error: decode error
-    #[error("Decode Error")]
+    #[error("Decode Error: {0}")]
     DecodeError(String),
 
+    /// AMQP error: decode error of a value nested in the frame body
+    #[error("Decode Error: {source} at {path} (byte offset {offset})")]
+    LocatedDecodeError {
+        /// Path to the value that failed to decode, eg. `attach.source.filter["selector"]`
+        path: String,
+
+        /// Offset of the value that failed to decode from the start of the frame body
+        offset: usize,
+
+        /// The error that occurred
+        source: serde_amqp::Error,
+    },
+
     /// AMQP error: not implemented
     #[error("AmqpError: NotImplemented")]
     NotImplemented,
 }
 
-impl From<serde_amqp::Error> for Error {
-    fn from(err: serde_amqp::Error) -> Self {
+impl Error {
+    /// Creates a decode error of the frame body, with `body_name` prepended to the path of the
+    /// value that failed to decode
+    pub(crate) fn decode(err: serde_amqp::Error, body_name: Option<&str>) -> Self {
         match err {
-            serde_amqp::Error::Io(e) => Self::Io(e),
-            other => Self::DecodeError(other.to_string()),
+            serde_amqp::Error::Located {
+                path,
+                offset,
+                source,
+            } if !matches!(source.inner(), serde_amqp::Error::Io(_)) => {
+                let path = match (body_name, path.segments().first()) {
+                    (Some(name), Some(Segment::Field(_))) => format!("{}.{}", name, path),
+                    (Some(name), _) => format!("{}{}", name, path),
+                    (None, _) => path.to_string(),
+                };
+                Self::LocatedDecodeError {
+                    path,
+                    offset,
+                    source: *source,
+                }
+            }
+            err => match err.into_inner() {
+                serde_amqp::Error::Io(e) => Self::Io(e),
+                other => Self::DecodeError(other.to_string()),
+            },
         }
     }
 }
+
+impl From<serde_amqp::Error> for Error {
+    fn from(err: serde_amqp::Error) -> Self {
+        Self::decode(err, None)
+    }
+}
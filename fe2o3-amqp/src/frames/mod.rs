@@ -11,3 +11,34 @@ pub const FRAME_TYPE_SASL: u8 = 0x01;
 
 mod error;
 pub use error::Error;
+
+/// Name of the frame body from the descriptor code, which is used to locate the decode errors
+fn body_name(body: &[u8]) -> Option<&'static str> {
+    let code = match body {
+        [0x00, 0x53, code, ..] => *code as u64,
+        [0x00, 0x80, code @ ..] if code.len() >= 8 => {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&code[..8]);
+            u64::from_be_bytes(bytes)
+        }
+        _ => return None,
+    };
+    let name = match code {
+        0x10 => "open",
+        0x11 => "begin",
+        0x12 => "attach",
+        0x13 => "flow",
+        0x14 => "transfer",
+        0x15 => "disposition",
+        0x16 => "detach",
+        0x17 => "end",
+        0x18 => "close",
+        0x40 => "sasl-mechanisms",
+        0x41 => "sasl-init",
+        0x42 => "sasl-challenge",
+        0x43 => "sasl-response",
+        0x44 => "sasl-outcome",
+        _ => return None,
+    };
+    Some(name)
+}
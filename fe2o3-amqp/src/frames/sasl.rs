@@ -9,7 +9,7 @@ use fe2o3_amqp_types::sasl::{SaslChallenge, SaslInit, SaslMechanisms, SaslOutcom
 use serde_amqp::read::IoReader;
 use tokio_util::codec::{Decoder, Encoder};
 
-use super::{Error, FRAME_TYPE_SASL};
+use super::{body_name, Error, FRAME_TYPE_SASL};
 
 /// SASL frame
 #[derive(Debug)]
@@ -74,9 +74,11 @@ impl Decoder for FrameCodec {
             return Err(Error::NotImplemented);
         }
 
+        let name = body_name(src);
         let reader = IoReader::new(src.reader());
         let mut deserializer = Deserializer::new(reader);
-        let frame: Frame = Deserialize::deserialize(&mut deserializer)?;
+        let frame: Frame =
+            Deserialize::deserialize(&mut deserializer).map_err(|err| Error::decode(err, name))?;
         Ok(Some(frame))
     }
 }
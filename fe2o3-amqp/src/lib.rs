@@ -27,6 +27,7 @@
 //! |`"acceptor"`| enables `ConnectionAcceptor`, `SessionAcceptor`, and `LinkAcceptor`|
 //! |`"transaction"`| enables `Controller`, `Transaction`, `OwnedTransaction` and `control_link_acceptor` |
 //! |`"scram"`| enables SCRAM auth |
+//! |`"proxy"`| enables HTTP CONNECT and SOCKS5 proxy tunneling |
 //! |`"tracing"`| enables logging with `tracing` |
 //! |`"log"`| enables logging with `log` |
 //!
@@ -7,7 +7,7 @@ use std::{
 
 use fe2o3_amqp_types::{
     definitions::{Fields, ReceiverSettleMode, SenderSettleMode, SequenceNo},
-    messaging::{Source, Target, TargetArchetype},
+    messaging::{DeliveryState, Source, Target, TargetArchetype},
     primitives::{Symbol, Ulong},
 };
 use parking_lot::RwLock;
@@ -22,14 +22,16 @@ use crate::{
 };
 
 use super::{
-    receiver::{CreditMode, ReceiverInner},
+    delivery::UnsettledMessage,
+    receiver::{CreditMode, ExpiredMessagePolicy, ReceiverInner},
     role,
     sender::SenderInner,
     state::{LinkFlowState, LinkFlowStateInner, LinkState},
+    store::{LinkRecord, StoredDelivery},
     target_archetype::VerifyTargetArchetype,
     ArcUnsettledMap, Receiver, ReceiverAttachError, ReceiverFlowState, ReceiverLink,
     ReceiverRelayFlowState, Sender, SenderAttachError, SenderFlowState, SenderLink,
-    SenderRelayFlowState,
+    SenderRelayFlowState, UnsettledMap,
 };
 
 cfg_transaction! {
@@ -112,6 +114,15 @@ pub struct Builder<Role, T, NameState, SS, TS> {
     /// `false`
     pub auto_accept: bool,
 
+    /// How the receiver handles messages that have expired when they arrive
+    ///
+    /// This field has no effect on Sender
+    ///
+    /// # Default
+    ///
+    /// [`ExpiredMessagePolicy::Deliver`]
+    pub expired_message_policy: ExpiredMessagePolicy,
+
     /// Whether to verify the `source` field of the incoming Attach frame
     ///
     /// Default to true
@@ -122,6 +133,13 @@ pub struct Builder<Role, T, NameState, SS, TS> {
     /// Default to true
     pub verify_incoming_target: bool,
 
+    /// Whether a receiver accepts an incoming source that doesn't carry all the desired filters.
+    /// The filters actually in place can then be inspected with
+    /// [`Receiver::accepted_filters`](crate::Receiver::accepted_filters)
+    ///
+    /// Default to false
+    pub allow_unsupported_filters: bool,
+
     // Type state markers
     role: PhantomData<Role>,
     name_state: PhantomData<NameState>,
@@ -151,12 +169,47 @@ impl<Role, T> Default for Builder<Role, T, WithoutName, WithoutSource, WithoutTa
             target_state: PhantomData,
 
             auto_accept: false,
+            expired_message_policy: Default::default(),
             verify_incoming_source: true,
             verify_incoming_target: true,
+            allow_unsupported_filters: false,
         }
     }
 }
 
+impl<Role> Builder<Role, Target, WithName, WithSource, WithTarget> {
+    /// Creates a builder with the settings of a persisted link and returns the unsettled
+    /// deliveries of the record
+    pub(crate) fn from_record(record: LinkRecord) -> (Self, Vec<StoredDelivery>) {
+        let builder = Self {
+            name: record.name,
+            snd_settle_mode: record.snd_settle_mode,
+            rcv_settle_mode: record.rcv_settle_mode,
+            source: record.source,
+            target: record.target,
+            initial_delivery_count: record.delivery_count,
+            max_message_size: Default::default(),
+            offered_capabilities: Default::default(),
+            desired_capabilities: Default::default(),
+            properties: Default::default(),
+
+            buffer_size: DEFAULT_OUTGOING_BUFFER_SIZE,
+            credit_mode: Default::default(),
+            role: PhantomData,
+            name_state: PhantomData,
+            source_state: PhantomData,
+            target_state: PhantomData,
+
+            auto_accept: false,
+            expired_message_policy: Default::default(),
+            verify_incoming_source: true,
+            verify_incoming_target: true,
+            allow_unsupported_filters: false,
+        };
+        (builder, record.unsettled)
+    }
+}
+
 impl<T> Builder<role::SenderMarker, T, WithoutName, WithSource, WithoutTarget> {
     pub(crate) fn new() -> Self {
         Builder::<role::SenderMarker, T, _, _, _>::default().source(Source::builder().build())
@@ -178,6 +231,14 @@ impl<T, NameState, SS, TS> Builder<role::ReceiverMarker, T, NameState, SS, TS> {
         self.auto_accept = value;
         self
     }
+
+    /// Sets how the receiver handles messages that have expired when they arrive
+    ///
+    /// Default value: [`ExpiredMessagePolicy::Deliver`]
+    pub fn expired_message_policy(mut self, policy: ExpiredMessagePolicy) -> Self {
+        self.expired_message_policy = policy;
+        self
+    }
 }
 
 impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
@@ -203,8 +264,11 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: self.target_state,
 
             auto_accept: self.auto_accept,
+
+            expired_message_policy: self.expired_message_policy,
             verify_incoming_source: self.verify_incoming_source,
             verify_incoming_target: self.verify_incoming_target,
+            allow_unsupported_filters: self.allow_unsupported_filters,
         }
     }
 
@@ -230,8 +294,11 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: self.target_state,
 
             auto_accept: self.auto_accept,
+
+            expired_message_policy: self.expired_message_policy,
             verify_incoming_source: self.verify_incoming_source,
             verify_incoming_target: self.verify_incoming_target,
+            allow_unsupported_filters: self.allow_unsupported_filters,
         }
     }
 
@@ -257,8 +324,11 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: self.target_state,
 
             auto_accept: self.auto_accept,
+
+            expired_message_policy: self.expired_message_policy,
             verify_incoming_source: self.verify_incoming_source,
             verify_incoming_target: self.verify_incoming_target,
+            allow_unsupported_filters: self.allow_unsupported_filters,
         }
     }
 
@@ -296,8 +366,11 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: self.target_state,
 
             auto_accept: self.auto_accept,
+
+            expired_message_policy: self.expired_message_policy,
             verify_incoming_source: self.verify_incoming_source,
             verify_incoming_target: self.verify_incoming_target,
+            allow_unsupported_filters: self.allow_unsupported_filters,
         }
     }
 
@@ -326,8 +399,11 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: PhantomData,
 
             auto_accept: self.auto_accept,
+
+            expired_message_policy: self.expired_message_policy,
             verify_incoming_source: self.verify_incoming_source,
             verify_incoming_target: self.verify_incoming_target,
+            allow_unsupported_filters: self.allow_unsupported_filters,
         }
     }
 
@@ -357,8 +433,11 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
                 target_state: PhantomData,
 
                 auto_accept: self.auto_accept,
+
+                expired_message_policy: self.expired_message_policy,
                 verify_incoming_source: self.verify_incoming_source,
                 verify_incoming_target: self.verify_incoming_target,
+                allow_unsupported_filters: self.allow_unsupported_filters,
             }
         }
     }
@@ -420,11 +499,16 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
     pub(crate) fn create_link<C, M>(
         self,
         unsettled: ArcUnsettledMap<M>,
-        output_handle: OutputHandle,
+        output_handle: Option<OutputHandle>,
         flow_state_consumer: C,
         // state_code: Arc<AtomicU8>,
     ) -> Link<Role, T, C, M> {
-        let local_state = LinkState::Unattached;
+        // A link without an output handle is restored from a persisted record and can only be
+        // resumed
+        let local_state = match output_handle {
+            Some(_) => LinkState::Unattached,
+            None => LinkState::Detached,
+        };
 
         let max_message_size = self.max_message_size.unwrap_or(0);
 
@@ -434,7 +518,7 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             local_state,
             // state_code,
             name: self.name,
-            output_handle: Some(output_handle),
+            output_handle,
             input_handle: None,
             snd_settle_mode: self.snd_settle_mode,
             rcv_settle_mode: self.rcv_settle_mode,
@@ -451,6 +535,7 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             unsettled,
             verify_incoming_source: self.verify_incoming_source,
             verify_incoming_target: self.verify_incoming_target,
+            allow_unsupported_filters: self.allow_unsupported_filters,
         }
     }
 }
@@ -475,6 +560,17 @@ impl<T, NameState, SS, TS> Builder<role::ReceiverMarker, T, NameState, SS, TS> {
         self.credit_mode = credit_mode;
         self
     }
+
+    /// Set whether the receiver accepts an incoming source that doesn't carry all the desired
+    /// filters instead of failing the attach with
+    /// [`ReceiverAttachError::DesiredFilterNotSupported`](crate::link::ReceiverAttachError::DesiredFilterNotSupported)
+    ///
+    /// The rest of the incoming source is still verified unless `verify_incoming_source` is set
+    /// to false
+    pub fn allow_unsupported_filters(mut self, allow: bool) -> Self {
+        self.allow_unsupported_filters = allow;
+        self
+    }
 }
 
 impl Builder<role::SenderMarker, Target, WithName, WithSource, WithTarget> {
@@ -541,7 +637,7 @@ where
         let link_relay = LinkRelay::new_sender(incoming_tx, producer, unsettled.clone());
         let output_handle =
             session::allocate_link(&session.control, self.name.clone(), link_relay).await?;
-        let mut link = self.create_link(unsettled, output_handle, consumer);
+        let mut link = self.create_link(unsettled, Some(output_handle), consumer);
 
         match link
             .exchange_attach(&session.outgoing, &mut incoming_rx, &session.control, false)
@@ -582,6 +678,30 @@ where
         };
         Ok(inner)
     }
+
+    /// Creates the inner state of a detached sender holding a restored unsettled map
+    ///
+    /// The link is allocated on the session when it is resumed
+    pub(crate) fn detached_inner<R>(
+        mut self,
+        session: &SessionHandle<R>,
+        unsettled: UnsettledMap<UnsettledMessage>,
+    ) -> SenderInner<SenderLink<T>> {
+        let buffer_size = self.buffer_size;
+        // The relay flow state is taken from the link when the link relay is re-created
+        let (_, consumer) = self.create_flow_state_containers();
+        let (_, incoming) = mpsc::channel::<LinkIncomingItem>(buffer_size);
+        let unsettled = Arc::new(RwLock::new(Some(unsettled)));
+        let link = self.create_link(unsettled, None, consumer);
+
+        SenderInner {
+            link,
+            buffer_size,
+            session: session.control.clone(),
+            outgoing: session.outgoing.clone(),
+            incoming,
+        }
+    }
 }
 
 impl Builder<role::ReceiverMarker, Target, WithName, WithSource, WithTarget> {
@@ -643,6 +763,7 @@ where
         let (relay_flow_state, flow_state) = self.create_flow_state_containers();
         let unsettled = Arc::new(RwLock::new(None));
         let auto_accept = self.auto_accept;
+        let expired_message_policy = self.expired_message_policy;
 
         let link_relay = LinkRelay::new_receiver(
             incoming_tx,
@@ -654,7 +775,7 @@ where
         // Any error here will be on the Session level and thus it should immediately return with an error
         let output_handle =
             session::allocate_link(&session.control, self.name.clone(), link_relay).await?;
-        let mut link = self.create_link(unsettled, output_handle, flow_state);
+        let mut link = self.create_link(unsettled, Some(output_handle), flow_state);
 
         match link
             .exchange_attach(&session.outgoing, &mut incoming_rx, &session.control, false)
@@ -680,6 +801,7 @@ where
             credit_mode,
             processed: AtomicU32::new(0),
             auto_accept,
+            expired_message_policy,
             session: session.control.clone(),
             outgoing,
             incoming: incoming_rx,
@@ -692,6 +814,37 @@ where
 
         Ok(inner)
     }
+
+    /// Creates the inner state of a detached receiver holding a restored unsettled map
+    ///
+    /// The link is allocated on the session when it is resumed
+    pub(crate) fn detached_inner<R>(
+        mut self,
+        session: &SessionHandle<R>,
+        unsettled: UnsettledMap<Option<DeliveryState>>,
+    ) -> ReceiverInner<ReceiverLink<T>> {
+        let buffer_size = self.buffer_size;
+        let credit_mode = self.credit_mode.clone();
+        let auto_accept = self.auto_accept;
+        let expired_message_policy = self.expired_message_policy;
+        let (_, flow_state) = self.create_flow_state_containers();
+        let (_, incoming) = mpsc::channel::<LinkIncomingItem>(buffer_size);
+        let unsettled = Arc::new(RwLock::new(Some(unsettled)));
+        let link = self.create_link(unsettled, None, flow_state);
+
+        ReceiverInner {
+            link,
+            buffer_size,
+            credit_mode,
+            processed: AtomicU32::new(0),
+            auto_accept,
+            expired_message_policy,
+            session: session.control.clone(),
+            outgoing: session.outgoing.clone(),
+            incoming,
+            incomplete_transfer: None,
+        }
+    }
 }
 
 cfg_transaction! {
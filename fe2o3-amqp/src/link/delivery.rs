@@ -516,6 +516,6 @@ mod tests {
     fn test_from_custom_type_into_sendable() {
         let value = Foo {};
         let sendable = Sendable::from(value);
-        assert_eq!(sendable.message.body, Data(Binary::from("Foo")));
+        assert_eq!(sendable.message.body, Data::from(Binary::from("Foo")));
     }
 }
@@ -294,6 +294,7 @@ impl<'a> TryFrom<&'a ReceiverAttachError> for definitions::Error {
             ReceiverAttachError::DynamicNodePropertiesIsSomeWhenDynamicIsFalse => {
                 AmqpError::InvalidField.into()
             }
+            ReceiverAttachError::DesiredFilterNotSupported(_) => AmqpError::NotImplemented.into(),
             _ => return Err(value),
         };
 
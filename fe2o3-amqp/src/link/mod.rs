@@ -21,6 +21,7 @@ pub use sender::Sender;
 use serde::Serialize;
 use serde_amqp::ser::Serializer;
 use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::DropGuard;
 
 use crate::{
     control::SessionControl,
@@ -55,6 +56,7 @@ mod sender_link;
 pub(crate) mod shared_inner;
 mod source;
 pub(crate) mod state;
+pub mod store;
 pub mod target_archetype;
 
 /// Default amount of link credit
@@ -211,6 +213,7 @@ pub(crate) struct Link<R, T, F, M> {
 
     pub(crate) verify_incoming_source: bool,
     pub(crate) verify_incoming_target: bool,
+    pub(crate) allow_unsupported_filters: bool,
 }
 
 impl<R, T, F, M> Link<R, T, F, M>
@@ -530,6 +533,9 @@ pub(crate) enum LinkRelay<O> {
         flow_state: SenderRelayFlowState,
         unsettled: ArcSenderUnsettledMap,
         receiver_settle_mode: ReceiverSettleMode,
+        // Dropped together with the relay when the session stops routing frames to the link,
+        // ie. when the link is detached
+        detached: Option<DropGuard>,
     },
     Receiver {
         tx: mpsc::Sender<LinkIncomingItem>,
@@ -538,6 +544,7 @@ pub(crate) enum LinkRelay<O> {
         unsettled: ArcReceiverUnsettledMap,
         receiver_settle_mode: ReceiverSettleMode,
         more: bool,
+        detached: Option<DropGuard>,
     },
 }
 
@@ -553,6 +560,7 @@ impl LinkRelay<()> {
             flow_state,
             unsettled,
             receiver_settle_mode: Default::default(),
+            detached: None,
         }
     }
 
@@ -569,6 +577,7 @@ impl LinkRelay<()> {
             unsettled,
             receiver_settle_mode,
             more: false,
+            detached: None,
         }
     }
 
@@ -579,6 +588,7 @@ impl LinkRelay<()> {
                 flow_state,
                 unsettled,
                 receiver_settle_mode,
+                detached,
                 ..
             } => LinkRelay::Sender {
                 tx,
@@ -586,6 +596,7 @@ impl LinkRelay<()> {
                 flow_state,
                 unsettled,
                 receiver_settle_mode,
+                detached,
             },
             LinkRelay::Receiver {
                 tx,
@@ -593,6 +604,7 @@ impl LinkRelay<()> {
                 unsettled,
                 receiver_settle_mode,
                 more,
+                detached,
                 ..
             } => LinkRelay::Receiver {
                 tx,
@@ -601,6 +613,7 @@ impl LinkRelay<()> {
                 unsettled,
                 receiver_settle_mode,
                 more,
+                detached,
             },
         }
     }
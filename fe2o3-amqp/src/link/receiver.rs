@@ -3,11 +3,13 @@
 use std::sync::atomic::{AtomicU32, Ordering};
 
 use fe2o3_amqp_types::{
-    definitions::{self, DeliveryTag, Fields, SequenceNo},
+    definitions::{self, DeliveryTag, ErrorCondition, Fields, Role, SequenceNo},
     messaging::{
-        Accepted, Address, DeliveryState, FromBody, Modified, Rejected, Released, Source, Target,
+        Accepted, Address, DeliveryState, FilterSet, FromBody, Modified, Rejected, Released,
+        Source, Target,
     },
     performatives::{Attach, Detach, Transfer},
+    primitives::Symbol,
 };
 use tokio::sync::mpsc;
 
@@ -31,10 +33,11 @@ use super::{
     receiver_link::count_number_of_sections_and_offset,
     role,
     shared_inner::{LinkEndpointInner, LinkEndpointInnerDetach, LinkEndpointInnerReattach},
+    store::{LinkRecord, StoreError, StoredDelivery, UnsettledStore},
     ArcReceiverUnsettledMap, DetachThenResumeReceiverError, DispositionError, FlowError,
     IllegalLinkStateError, LinkFrame, LinkRelay, LinkStateError, ReceiverAttachError,
     ReceiverAttachExchange, ReceiverFlowState, ReceiverLink, ReceiverResumeError,
-    ReceiverResumeErrorKind, ReceiverTransferError, RecvError, DEFAULT_CREDIT,
+    ReceiverResumeErrorKind, ReceiverTransferError, RecvError, UnsettledMap, DEFAULT_CREDIT,
 };
 
 cfg_transaction! {
@@ -65,6 +68,31 @@ impl Default for CreditMode {
     }
 }
 
+/// Error condition of the [`Rejected`] outcome of a message rejected by
+/// [`ExpiredMessagePolicy::Reject`]
+pub const MESSAGE_EXPIRED: &str = "amqp:message-expired";
+
+/// How the receiver handles a message that has expired when it arrives
+///
+/// The expiry time of a message is given by [`Message::expiry_time`]
+///
+/// [`Message::expiry_time`]: fe2o3_amqp_types::messaging::Message::expiry_time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExpiredMessagePolicy {
+    /// Expired messages are returned by `recv` like any other message
+    #[default]
+    Deliver,
+
+    /// Expired messages are not returned by `recv` and are settled with
+    /// `Modified { delivery_failed: true, undeliverable_here: true }` so that the sender can
+    /// dead-letter them
+    Drop,
+
+    /// Expired messages are not returned by `recv` and are rejected with the error condition
+    /// [`MESSAGE_EXPIRED`]
+    Reject,
+}
+
 /// An AMQP1.0 receiver
 ///
 /// # Attach a new receiver with default configurations
@@ -99,6 +127,7 @@ impl Default for CreditMode {
 /// |`buffer_size`| `u16::MAX` |
 /// |`role`| `role::Sender` |
 /// |`auto_accept`|`false`|
+/// |`expired_message_policy`|[`ExpiredMessagePolicy::Deliver`]|
 ///
 /// # Customize configuration with [`builder::Builder`]
 ///
@@ -157,6 +186,16 @@ impl Receiver {
         self.inner.auto_accept = value;
     }
 
+    /// Get how the receiver handles messages that have expired when they arrive
+    pub fn expired_message_policy(&self) -> ExpiredMessagePolicy {
+        self.inner.expired_message_policy
+    }
+
+    /// Set how the receiver handles messages that have expired when they arrive
+    pub fn set_expired_message_policy(&mut self, policy: ExpiredMessagePolicy) {
+        self.inner.expired_message_policy = policy;
+    }
+
     /// Get a reference to the link's source field
     pub fn source(&self) -> &Option<Source> {
         &self.inner.link.source
@@ -167,6 +206,27 @@ impl Receiver {
         &mut self.inner.link.source
     }
 
+    /// Get the filters accepted by the remote sender, ie. the filter set of the source in the
+    /// attach frame sent by the remote peer
+    ///
+    /// The attach fails with [`ReceiverAttachError::DesiredFilterNotSupported`] if a desired
+    /// filter is missing, unless the builder is configured with `allow_unsupported_filters(true)`,
+    /// in which case the accepted filters can be inspected to fall back gracefully
+    pub fn accepted_filters(&self) -> Option<&FilterSet> {
+        self.inner
+            .link
+            .source
+            .as_ref()
+            .and_then(|source| source.filter.as_ref())
+    }
+
+    /// Whether the filter `key` is accepted by the remote sender
+    pub fn is_filter_accepted(&self, key: &str) -> bool {
+        self.accepted_filters()
+            .map(|filters| filters.contains_key(&Symbol::from(key)))
+            .unwrap_or(false)
+    }
+
     /// Get a reference to the link's target field
     pub fn target(&self) -> &Option<Target> {
         &self.inner.link.target
@@ -177,6 +237,21 @@ impl Receiver {
         &mut self.inner.link.target
     }
 
+    /// Take a snapshot of the link name, terminus, delivery count and unsettled deliveries
+    pub fn snapshot(&self) -> LinkRecord {
+        self.inner.snapshot()
+    }
+
+    /// Save a snapshot of the link into `store`
+    ///
+    /// See [`store`](crate::link::store) for when to persist the link
+    pub fn persist<S>(&self, store: &S) -> Result<(), StoreError>
+    where
+        S: UnsettledStore + ?Sized,
+    {
+        store.save(&self.inner.snapshot())
+    }
+
     /// Get a reference to the link's properties field in the op
     pub fn properties<F, O>(&self, op: F) -> O
     where
@@ -213,6 +288,7 @@ impl Receiver {
     /// |`buffer_size`| `u16::MAX` |
     /// |`role`| `role::Sender` |
     /// |`auto_accept`|`false`|
+    /// |`expired_message_policy`|[`ExpiredMessagePolicy::Deliver`]|
     ///  
     /// # Example
     ///
@@ -642,6 +718,7 @@ pub(crate) struct ReceiverInner<L: endpoint::ReceiverLink> {
     pub(crate) credit_mode: CreditMode,
     pub(crate) processed: AtomicU32, // SequenceNo,
     pub(crate) auto_accept: bool,
+    pub(crate) expired_message_policy: ExpiredMessagePolicy,
 
     // Control sender to the session
     pub(crate) session: mpsc::Sender<SessionControl>,
@@ -703,6 +780,7 @@ where
             // This only controls whether a multi-transfer delivery id
             // will be added to sessions map
             more: false,
+            detached: None,
         }
     }
 
@@ -936,13 +1014,7 @@ where
                         section_offset,
                     )?;
 
-                    // Auto accept the message and leave settled to be determined based on rcv_settle_mode
-                    if self.auto_accept {
-                        self.dispose(&delivery, None, Accepted {}.into()).await?;
-                        // cancel safe
-                    }
-
-                    Ok(Some(delivery))
+                    self.on_delivery(delivery).await // cancel safe
                 } else {
                     // The new Transfer belongs to the buffered incomplete transfer
                     self.on_complete_transfer(transfer, payload).await // cancel safe
@@ -990,6 +1062,44 @@ where
             }
         };
 
+        self.on_delivery(delivery).await // cancel safe
+    }
+
+    /// Settles an expired delivery according to `expired_message_policy` and auto accepts the
+    /// other deliveries if `auto_accept` is set. Returns `None` if the delivery is not yielded
+    ///
+    /// # Cancel safety
+    ///
+    /// This is cancel safe because all internal `.await` point(s) are cancel safe
+    async fn on_delivery<T>(
+        &mut self,
+        delivery: Delivery<T>,
+    ) -> Result<Option<Delivery<T>>, RecvError> {
+        if self.expired_message_policy != ExpiredMessagePolicy::Deliver
+            && delivery
+                .message()
+                .is_expired_at(&crate::util::now_timestamp())
+        {
+            let state = match self.expired_message_policy {
+                ExpiredMessagePolicy::Reject => {
+                    let error = definitions::Error::new(
+                        ErrorCondition::Custom(Symbol::from(MESSAGE_EXPIRED)),
+                        Some(String::from("The message has expired")),
+                        None,
+                    );
+                    Rejected { error: Some(error) }.into()
+                }
+                _ => Modified {
+                    delivery_failed: Some(true),
+                    undeliverable_here: Some(true),
+                    message_annotations: None,
+                }
+                .into(),
+            };
+            self.dispose(&delivery, None, state).await?; // cancel safe
+            return Ok(None);
+        }
+
         // Auto accept the message and leave settled to be determined based on rcv_settle_mode
         if self.auto_accept {
             self.dispose(&delivery, None, Accepted {}.into()).await?; // cancel safe
@@ -1166,6 +1276,38 @@ impl ReceiverInner<ReceiverLink<Target>> {
     }
 }
 
+impl ReceiverInner<ReceiverLink<Target>> {
+    fn snapshot(&self) -> LinkRecord {
+        let unsettled = self
+            .link
+            .unsettled
+            .read()
+            .as_ref()
+            .map(|map| {
+                map.iter()
+                    .map(|(delivery_tag, state)| StoredDelivery {
+                        delivery_tag: delivery_tag.clone(),
+                        state: state.clone(),
+                        message_format: 0,
+                        payload: None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        LinkRecord {
+            name: self.link.name.clone(),
+            role: Role::Receiver,
+            snd_settle_mode: self.link.snd_settle_mode.clone(),
+            rcv_settle_mode: self.link.rcv_settle_mode.clone(),
+            source: self.link.source.clone(),
+            target: self.link.target.clone(),
+            delivery_count: self.link.flow_state.as_ref().delivery_count(),
+            unsettled,
+        }
+    }
+}
+
 /// A detached receiver
 ///
 /// # Example
@@ -1303,6 +1445,41 @@ impl DetachedReceiver {
         &mut self.inner.link.target
     }
 
+    /// Take a snapshot of the link name, terminus, delivery count and unsettled deliveries
+    pub fn snapshot(&self) -> LinkRecord {
+        self.inner.snapshot()
+    }
+
+    /// Save a snapshot of the link into `store`
+    ///
+    /// See [`store`](crate::link::store) for when to persist the link
+    pub fn persist<S>(&self, store: &S) -> Result<(), StoreError>
+    where
+        S: UnsettledStore + ?Sized,
+    {
+        store.save(&self.inner.snapshot())
+    }
+
+    /// Rebuild a detached receiver from a persisted [`LinkRecord`] on `session`
+    ///
+    /// The restored receiver holds the delivery states of the record, and they are exchanged
+    /// with the remote peer when the receiver is resumed with [`resume`](#method.resume). The
+    /// restored receiver starts without link credit, so credit needs to be issued with
+    /// [`Receiver::set_credit`] after resuming.
+    pub fn restore<R>(session: &SessionHandle<R>, record: LinkRecord) -> Result<Self, StoreError> {
+        record.expect_role(Role::Receiver)?;
+
+        let (builder, deliveries) =
+            builder::Builder::<role::ReceiverMarker, Target, _, _, _>::from_record(record);
+        let unsettled: UnsettledMap<Option<DeliveryState>> = deliveries
+            .into_iter()
+            .map(|delivery| (delivery.delivery_tag, delivery.state))
+            .collect();
+
+        let inner = builder.detached_inner(session, unsettled);
+        Ok(Self { inner })
+    }
+
     async fn resume_inner(
         mut self,
         is_reattaching: bool,
@@ -1,6 +1,6 @@
 use fe2o3_amqp_types::{
     definitions::{Fields, Handle},
-    messaging::{message::DecodeIntoMessage, FromBody},
+    messaging::FromBody,
 };
 use serde_amqp::format_code::EncodingCodes;
 
@@ -158,7 +158,7 @@ where
         let (result, mode) = if settled_by_sender {
             // If the message is pre-settled, there is no need to
             // add to the unsettled map and no need to reply to the Sender
-            let result = T::decode_message_from_reader(payload.into_reader());
+            let result = payload.decode_message::<T>();
             (result, None)
         } else {
             // If the message is being sent settled by the sender, the value of this
@@ -177,7 +177,7 @@ where
                 None => None,
             };
 
-            let result = T::decode_message_from_reader(payload.into_reader());
+            let result = payload.decode_message::<T>();
 
             let state = DeliveryState::Received(Received {
                 section_number, // What is section number?
@@ -666,6 +666,9 @@ where
             .ok_or(ReceiverAttachError::IncomingSourceIsNone)?;
         if self.verify_incoming_source {
             if let Some(local_source) = &self.source {
+                if !self.allow_unsupported_filters {
+                    source::verify_filter(&local_source.filter, &remote_source.filter)?;
+                }
                 local_source.verify_as_receiver(&remote_source)?;
             }
         }
@@ -884,7 +887,10 @@ where
             | ReceiverAttachError::InitialDeliveryCountIsNone
             | ReceiverAttachError::SourceAddressIsNoneWhenDynamicIsTrue
             | ReceiverAttachError::TargetAddressIsSomeWhenDynamicIsTrue
-            | ReceiverAttachError::DynamicNodePropertiesIsSomeWhenDynamicIsFalse => {
+            | ReceiverAttachError::DynamicNodePropertiesIsSomeWhenDynamicIsFalse
+            // The receiving endpoint MUST check that the filter in place meets its needs and
+            // take responsibility for detaching if it does not
+            | ReceiverAttachError::DesiredFilterNotSupported(_) => {
                 match (&attach_error).try_into() {
                     Ok(error) => match self.send_detach(writer, true, Some(error)).await {
                         Ok(_) => recv_detach(self, reader, attach_error).await,
@@ -893,7 +899,6 @@ where
                     Err(_) => attach_error,
                 }
             }
-            _ => attach_error,
         }
     }
 }
@@ -9,7 +9,7 @@ cfg_not_wasm32! {
 }
 
 use fe2o3_amqp_types::{
-    definitions::{self, DeliveryTag, Fields, MessageFormat, SenderSettleMode},
+    definitions::{self, DeliveryTag, Fields, MessageFormat, Role, SenderSettleMode},
     messaging::{
         message::__private::Serializable, Address, DeliveryState, Outcome, SerializableBody,
         Source, Target,
@@ -34,9 +34,10 @@ use super::{
     shared_inner::{
         recv_remote_detach, LinkEndpointInner, LinkEndpointInnerDetach, LinkEndpointInnerReattach,
     },
+    store::{LinkRecord, StoreError, StoredDelivery, UnsettledStore},
     ArcSenderUnsettledMap, DetachThenResumeSenderError, LinkFrame, LinkRelay, LinkStateError,
     SendError, SenderAttachError, SenderAttachExchange, SenderFlowState, SenderLink,
-    SenderResumeError, SenderResumeErrorKind,
+    SenderResumeError, SenderResumeErrorKind, UnsettledMap,
 };
 
 #[cfg(docsrs)]
@@ -143,6 +144,21 @@ impl Sender {
         &mut self.inner.link.target
     }
 
+    /// Take a snapshot of the link name, terminus, delivery count and unsettled deliveries
+    pub fn snapshot(&self) -> LinkRecord {
+        self.inner.snapshot()
+    }
+
+    /// Save a snapshot of the link into `store`
+    ///
+    /// See [`store`](crate::link::store) for when to persist the link
+    pub fn persist<S>(&self, store: &S) -> Result<(), StoreError>
+    where
+        S: UnsettledStore + ?Sized,
+    {
+        store.save(&self.inner.snapshot())
+    }
+
     /// Get a reference to the link's properties field in the op
     pub fn properties<F, O>(&self, op: F) -> O
     where
@@ -159,6 +175,30 @@ impl Sender {
         self.inner.link.properties_mut(op)
     }
 
+    /// Returns a future that resolves when the link has credit to send at least one message
+    ///
+    /// The returned future doesn't borrow the sender and is cancel safe.
+    #[cfg(all(feature = "acceptor", not(target_arch = "wasm32")))]
+    pub(crate) fn credit_available(
+        &self,
+    ) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let notifier = self.inner.link.flow_state.notifier.clone();
+        let state = self.inner.link.flow_state.state().clone();
+        async move {
+            loop {
+                let notified = notifier.notified();
+                tokio::pin!(notified);
+                // Register interest before checking the credit so that a flow that
+                // arrives in between is not missed
+                notified.as_mut().enable();
+                if state.link_credit() > 0 {
+                    return;
+                }
+                notified.await;
+            }
+        }
+    }
+
     /// Attach the sender link to a session with default configuration
     /// with the `name` and `target` address set to the specified values
     ///
@@ -545,6 +585,7 @@ where
             // TODO: what else to do during re-attaching
             unsettled: self.link.unsettled().clone(),
             receiver_settle_mode: self.link.rcv_settle_mode().clone(),
+            detached: None,
         }
     }
 
@@ -611,6 +652,38 @@ where
     }
 }
 
+impl SenderInner<SenderLink<Target>> {
+    fn snapshot(&self) -> LinkRecord {
+        let unsettled = self
+            .link
+            .unsettled
+            .read()
+            .as_ref()
+            .map(|map| {
+                map.iter()
+                    .map(|(delivery_tag, message)| StoredDelivery {
+                        delivery_tag: delivery_tag.clone(),
+                        state: message.state.clone(),
+                        message_format: message.message_format,
+                        payload: Some(message.payload.clone()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        LinkRecord {
+            name: self.link.name.clone(),
+            role: Role::Sender,
+            snd_settle_mode: self.link.snd_settle_mode.clone(),
+            rcv_settle_mode: self.link.rcv_settle_mode.clone(),
+            source: self.link.source.clone(),
+            target: self.link.target.clone(),
+            delivery_count: self.link.flow_state.as_ref().delivery_count(),
+            unsettled,
+        }
+    }
+}
+
 impl<L> SenderInner<L>
 where
     L: endpoint::SenderLink<
@@ -711,6 +784,25 @@ where
 }
 
 impl SenderInner<SenderLink<Target>> {
+    /// Resumes the unsettled deliveries of a retained terminus on a link accepted by a
+    /// [`LinkAcceptor`](crate::acceptor::LinkAcceptor)
+    #[cfg(feature = "acceptor")]
+    pub(crate) async fn resume_deliveries(
+        &mut self,
+        resuming_deliveries: Vec<(DeliveryTag, ResumingDelivery)>,
+    ) -> Result<(), SendError> {
+        let mut resend_buf = Vec::new();
+        for (delivery_tag, resuming) in resuming_deliveries {
+            self.handle_resuming_delivery(delivery_tag, resuming, &mut resend_buf)
+                .await?;
+        }
+
+        for unsettled_message in resend_buf.drain(..) {
+            self.resend(unsettled_message).await?;
+        }
+        Ok(())
+    }
+
     /// Resumes a delivery with the given state and payload.
     ///
     /// The resume operation should not replace the unsettled map entry.
@@ -990,6 +1082,8 @@ impl SenderInner<SenderLink<Target>> {
                     // Upon completion of this reduction of state, the two parties MUST suspend and
                     // re-attempt to resume the link.
                     self.detach_with_error(None).await?;
+                    // Detaching releases the output handle, which is needed again to re-attach
+                    self.reallocate_output_handle().await?;
                 }
             }
         }
@@ -1052,6 +1146,48 @@ impl DetachedSender {
         &mut self.inner.link.target
     }
 
+    /// Take a snapshot of the link name, terminus, delivery count and unsettled deliveries
+    pub fn snapshot(&self) -> LinkRecord {
+        self.inner.snapshot()
+    }
+
+    /// Save a snapshot of the link into `store`
+    ///
+    /// See [`store`](crate::link::store) for when to persist the link
+    pub fn persist<S>(&self, store: &S) -> Result<(), StoreError>
+    where
+        S: UnsettledStore + ?Sized,
+    {
+        store.save(&self.inner.snapshot())
+    }
+
+    /// Rebuild a detached sender from a persisted [`LinkRecord`] on `session`
+    ///
+    /// The restored sender holds the unsettled deliveries of the record, and they are
+    /// exchanged with the remote peer when the sender is resumed with
+    /// [`resume`](#method.resume). The outcome of a delivery sent before the restart is not
+    /// reported back to the application.
+    pub fn restore<R>(session: &SessionHandle<R>, record: LinkRecord) -> Result<Self, StoreError> {
+        record.expect_role(Role::Sender)?;
+
+        let (builder, deliveries) =
+            builder::Builder::<role::SenderMarker, Target, _, _, _>::from_record(record);
+        let mut unsettled = UnsettledMap::new();
+        for delivery in deliveries {
+            let payload = delivery
+                .payload
+                .ok_or_else(|| StoreError::MissingPayload(delivery.delivery_tag.clone()))?;
+            // Nobody is waiting for the outcome of a delivery sent before the restart
+            let (sender, _) = oneshot::channel();
+            let message =
+                UnsettledMessage::new(payload, delivery.state, delivery.message_format, sender);
+            unsettled.insert(delivery.delivery_tag, message);
+        }
+
+        let inner = builder.detached_inner(session, unsettled);
+        Ok(Self::new(inner))
+    }
+
     async fn resume_inner(mut self, is_reattaching: bool) -> Result<Sender, SenderResumeError> {
         try_as_sender!(
             self,
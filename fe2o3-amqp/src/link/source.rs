@@ -25,15 +25,8 @@ impl VerifySource for Source {
     }
 
     fn verify_as_receiver(&self, other: &Self) -> Result<(), ReceiverAttachError> {
-        // The receiving endpoint sets its desired filter, the sending endpoint
-        // sets the filter actually in place (including any filters defaulted at the node). The receiving endpoint
-        // MUST check that the filter in place meets its needs and take responsibility for detaching if it does
-        // not.
-        //
-        // This does NOT check if the value is the same because some brokers uses the draft version of the
-        // spec where the value is not a described type.
-        verify_filter(&self.filter, &other.filter)?;
-
+        // The filter set is checked separately by `verify_filter` so that the receiver can opt in
+        // to accept the filters actually in place
         if other.dynamic && other.address.is_none() {
             // When set to true by the sending link endpoint this field indicates creation of a dynamically created
             // node. In this case the address field will contain the address of the created node
@@ -47,7 +40,13 @@ impl VerifySource for Source {
     }
 }
 
-fn verify_filter(
+/// The receiving endpoint sets its desired filter, the sending endpoint sets the filter actually
+/// in place (including any filters defaulted at the node). The receiving endpoint MUST check that
+/// the filter in place meets its needs and take responsibility for detaching if it does not.
+///
+/// This does NOT check if the value is the same because some brokers uses the draft version of the
+/// spec where the value is not a described type.
+pub(crate) fn verify_filter(
     desired: &Option<FilterSet>,
     supported: &Option<FilterSet>,
 ) -> Result<(), DesiredFilterNotSupported> {
@@ -254,6 +254,10 @@ impl<R> LinkFlowState<R> {
         self.lock.read().initial_delivery_count
     }
 
+    pub fn delivery_count(&self) -> SequenceNo {
+        self.lock.read().delivery_count
+    }
+
     pub fn initial_delivery_count_mut(&self, f: impl Fn(u32) -> u32) {
         let mut guard = self.lock.write();
         let new = f(guard.initial_delivery_count);
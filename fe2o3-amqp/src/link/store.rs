@@ -0,0 +1,550 @@
+//! Persistent storage of link state for durable link recovery
+//!
+//! [`DetachedSender::resume`](crate::link::sender::DetachedSender::resume) and
+//! [`DetachedReceiver::resume`](crate::link::receiver::DetachedReceiver::resume) only work
+//! within one process because the unsettled map of a link lives in memory. An
+//! [`UnsettledStore`] keeps a [`LinkRecord`] (the link name, the terminus, the delivery count
+//! and the unsettled deliveries) outside of the process so that a link can be rebuilt after a
+//! restart with
+//! [`DetachedSender::restore`](crate::link::sender::DetachedSender::restore) or
+//! [`DetachedReceiver::restore`](crate::link::receiver::DetachedReceiver::restore) and then
+//! resumed with the correct `unsettled` map.
+//!
+//! Persisting is explicit. The application decides when a snapshot is taken, typically right
+//! after a message is sent (or received) and after it is settled. A snapshot that still holds
+//! a delivery that has been settled since is harmless for at-least-once delivery; the remote
+//! peer simply does not know the delivery tag anymore and the delivery is resent.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp::link::store::{FileStore, UnsettledStore};
+//! use fe2o3_amqp::types::definitions::Role;
+//!
+//! let store = FileStore::new("./link-state")?;
+//!
+//! // Before the crash
+//! let outcome = sender.send("hello").await?;
+//! sender.persist(&store)?;
+//!
+//! // After a restart
+//! if let Some(record) = store.load(Role::Sender, "durable-sender")? {
+//!     let detached = DetachedSender::restore(&session, record)?;
+//!     let sender = detached.resume().await?;
+//! }
+//! ```
+
+use std::io;
+
+use fe2o3_amqp_types::{
+    definitions::{
+        DeliveryTag, MessageFormat, ReceiverSettleMode, Role, SenderSettleMode, SequenceNo,
+    },
+    messaging::{message::LazyMessage, DeliveryState, Source, Target},
+};
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+
+use crate::Payload;
+
+cfg_not_wasm32! {
+    use std::{
+        path::{Path, PathBuf},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    /// Distinguishes the temporary files of concurrent saves within one process
+    static TMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+}
+
+/// Errors associated with persisting or restoring a link
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    /// Error reading or writing the underlying storage
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// Error encoding or decoding a record
+    #[error(transparent)]
+    Codec(#[from] serde_amqp::Error),
+
+    /// The record belongs to a link of a different role
+    #[error("Expecting a record of a {expected:?} link but found {found:?}")]
+    RoleMismatch {
+        /// The role of the link being restored
+        expected: Role,
+
+        /// The role found in the record
+        found: Role,
+    },
+
+    /// A sender record holds an unsettled delivery without its payload
+    #[error("Unsettled delivery {:?} has no payload", .0)]
+    MissingPayload(DeliveryTag),
+}
+
+/// An unsettled delivery of a persisted link
+#[derive(Debug, Clone)]
+pub struct StoredDelivery {
+    /// The delivery tag
+    pub delivery_tag: DeliveryTag,
+
+    /// The last known delivery state
+    pub state: Option<DeliveryState>,
+
+    /// The message format of the delivery
+    pub message_format: MessageFormat,
+
+    /// The encoded message
+    ///
+    /// This is only recorded on the sending side, where it is needed to resend or resume the
+    /// delivery
+    pub payload: Option<Payload>,
+}
+
+impl StoredDelivery {
+    /// Returns the payload as a [`LazyMessage`] whose sections can be read without decoding
+    /// the message
+    pub fn lazy_message(&self) -> Option<LazyMessage> {
+        self.payload.clone().map(LazyMessage::new)
+    }
+}
+
+/// A snapshot of the state needed to resume a link
+#[derive(Debug, Clone)]
+pub struct LinkRecord {
+    /// The name of the link
+    pub name: String,
+
+    /// The role of the local link endpoint
+    pub role: Role,
+
+    /// Settlement policy of the sender
+    pub snd_settle_mode: SenderSettleMode,
+
+    /// Settlement policy of the receiver
+    pub rcv_settle_mode: ReceiverSettleMode,
+
+    /// The source of the link
+    pub source: Option<Source>,
+
+    /// The target of the link
+    pub target: Option<Target>,
+
+    /// The delivery count of the link
+    ///
+    /// The delivery tags of a sender are derived from the delivery count, so a restored sender
+    /// continues from this value to not reuse the tags of the unsettled deliveries
+    pub delivery_count: SequenceNo,
+
+    /// The unsettled deliveries in the order they were sent or received
+    pub unsettled: Vec<StoredDelivery>,
+}
+
+impl LinkRecord {
+    /// Encode the record into bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>, serde_amqp::Error> {
+        serde_amqp::to_vec(&RecordRepr::from(self))
+    }
+
+    /// Decode a record from bytes produced by [`to_bytes`](#method.to_bytes)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, serde_amqp::Error> {
+        serde_amqp::from_slice::<RecordRepr>(bytes).map(Into::into)
+    }
+
+    pub(crate) fn expect_role(&self, expected: Role) -> Result<(), StoreError> {
+        if self.role == expected {
+            Ok(())
+        } else {
+            Err(StoreError::RoleMismatch {
+                expected,
+                found: self.role.clone(),
+            })
+        }
+    }
+}
+
+/// Storage for [`LinkRecord`]s
+///
+/// Records are keyed by the role and the name of the link. Implementations must replace any
+/// existing record on [`save`](#tymethod.save) so that a partially written record never
+/// overwrites a complete one.
+pub trait UnsettledStore: Send + Sync {
+    /// Save a record, replacing the previous record of the same link
+    fn save(&self, record: &LinkRecord) -> Result<(), StoreError>;
+
+    /// Load the record of a link. Returns `Ok(None)` if nothing was saved for the link
+    fn load(&self, role: Role, name: &str) -> Result<Option<LinkRecord>, StoreError>;
+
+    /// Remove the record of a link. Removing a record that does not exist is not an error
+    fn remove(&self, role: Role, name: &str) -> Result<(), StoreError>;
+}
+
+cfg_not_wasm32! {
+    /// An [`UnsettledStore`] that keeps one file per link in a directory
+    ///
+    /// The file of a link is named after the role and the SHA-256 hash of the link name, so that
+    /// names of any length or content map to a valid file name. The name itself is kept in the
+    /// record and checked on [`load`](UnsettledStore::load).
+    ///
+    /// A record is written to a uniquely named temporary file which then replaces the previous
+    /// record with a rename. The directory is synced after the rename (on unix) so that the
+    /// new record survives a crash.
+    #[derive(Debug, Clone)]
+    pub struct FileStore {
+        dir: PathBuf,
+    }
+
+    impl FileStore {
+        /// Creates a store in `dir`, creating the directory if it does not exist
+        pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+            let dir = dir.into();
+            std::fs::create_dir_all(&dir)?;
+            Ok(Self { dir })
+        }
+
+        /// The directory holding the records
+        pub fn dir(&self) -> &Path {
+            &self.dir
+        }
+
+        /// Link names may contain any character and be arbitrarily long, so the file name holds
+        /// the hex encoded SHA-256 hash of the name
+        fn path(&self, role: &Role, name: &str) -> PathBuf {
+            use sha2::{Digest, Sha256};
+            use std::fmt::Write;
+
+            let prefix = match role {
+                Role::Sender => "sender",
+                Role::Receiver => "receiver",
+            };
+            let digest = Sha256::digest(name.as_bytes());
+            let mut file_name = String::with_capacity(prefix.len() + 1 + digest.len() * 2 + 5);
+            file_name.push_str(prefix);
+            file_name.push('-');
+            for byte in digest.iter() {
+                let _ = write!(file_name, "{:02x}", byte);
+            }
+            file_name.push_str(".amqp");
+            self.dir.join(file_name)
+        }
+
+        fn tmp_path(&self, path: &Path) -> PathBuf {
+            let id = TMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+            let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+            file_name.push(format!(".{}.{}.tmp", std::process::id(), id));
+            self.dir.join(file_name)
+        }
+
+        fn write_and_rename(&self, bytes: &[u8], tmp: &Path, path: &Path) -> io::Result<()> {
+            use std::io::Write;
+
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(tmp)?;
+            file.write_all(bytes)?;
+            file.sync_all()?;
+            std::fs::rename(tmp, path)?;
+
+            // Persist the rename itself
+            #[cfg(unix)]
+            std::fs::File::open(&self.dir)?.sync_all()?;
+            Ok(())
+        }
+    }
+
+    impl UnsettledStore for FileStore {
+        fn save(&self, record: &LinkRecord) -> Result<(), StoreError> {
+            let bytes = record.to_bytes()?;
+            let path = self.path(&record.role, &record.name);
+            let tmp = self.tmp_path(&path);
+            if let Err(err) = self.write_and_rename(&bytes, &tmp, &path) {
+                let _ = std::fs::remove_file(&tmp);
+                return Err(err.into());
+            }
+            Ok(())
+        }
+
+        /// A record whose name or role doesn't match is not the record of the link and is
+        /// reported as missing
+        fn load(&self, role: Role, name: &str) -> Result<Option<LinkRecord>, StoreError> {
+            match std::fs::read(self.path(&role, name)) {
+                Ok(bytes) => {
+                    let record = LinkRecord::from_bytes(&bytes)?;
+                    if record.name == name && record.role == role {
+                        Ok(Some(record))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        }
+
+        fn remove(&self, role: Role, name: &str) -> Result<(), StoreError> {
+            match std::fs::remove_file(self.path(&role, name)) {
+                Ok(()) => Ok(()),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+                Err(err) => Err(err.into()),
+            }
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                Wire format                                 */
+/* -------------------------------------------------------------------------- */
+
+#[derive(Serialize, Deserialize)]
+struct DeliveryRepr {
+    delivery_tag: DeliveryTag,
+    state: Option<DeliveryState>,
+    message_format: MessageFormat,
+    payload: Option<ByteBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordRepr {
+    name: String,
+    role: Role,
+    snd_settle_mode: SenderSettleMode,
+    rcv_settle_mode: ReceiverSettleMode,
+    source: Option<Source>,
+    target: Option<Target>,
+    delivery_count: SequenceNo,
+    unsettled: Vec<DeliveryRepr>,
+}
+
+impl From<&LinkRecord> for RecordRepr {
+    fn from(record: &LinkRecord) -> Self {
+        Self {
+            name: record.name.clone(),
+            role: record.role.clone(),
+            snd_settle_mode: record.snd_settle_mode.clone(),
+            rcv_settle_mode: record.rcv_settle_mode.clone(),
+            source: record.source.clone(),
+            target: record.target.clone(),
+            delivery_count: record.delivery_count,
+            unsettled: record
+                .unsettled
+                .iter()
+                .map(|delivery| DeliveryRepr {
+                    delivery_tag: delivery.delivery_tag.clone(),
+                    state: delivery.state.clone(),
+                    message_format: delivery.message_format,
+                    payload: delivery
+                        .payload
+                        .as_ref()
+                        .map(|payload| ByteBuf::from(payload.to_vec())),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<RecordRepr> for LinkRecord {
+    fn from(repr: RecordRepr) -> Self {
+        Self {
+            name: repr.name,
+            role: repr.role,
+            snd_settle_mode: repr.snd_settle_mode,
+            rcv_settle_mode: repr.rcv_settle_mode,
+            source: repr.source,
+            target: repr.target,
+            delivery_count: repr.delivery_count,
+            unsettled: repr
+                .unsettled
+                .into_iter()
+                .map(|delivery| StoredDelivery {
+                    delivery_tag: delivery.delivery_tag,
+                    state: delivery.state,
+                    message_format: delivery.message_format,
+                    payload: delivery.payload.map(|buf| Payload::from(buf.into_vec())),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{
+        definitions::{ReceiverSettleMode, Role, SenderSettleMode},
+        messaging::{Accepted, DeliveryState, Received, Source, Target, TerminusDurability},
+        primitives::Binary,
+    };
+
+    use crate::Payload;
+
+    use super::{LinkRecord, StoreError, StoredDelivery};
+
+    fn sender_record() -> LinkRecord {
+        LinkRecord {
+            name: "durable/sender-1".into(),
+            role: Role::Sender,
+            snd_settle_mode: SenderSettleMode::Unsettled,
+            rcv_settle_mode: ReceiverSettleMode::Second,
+            source: Some(
+                Source::builder()
+                    .durable(TerminusDurability::UnsettledState)
+                    .build(),
+            ),
+            target: Some(Target::builder().address("q1").build()),
+            delivery_count: 4,
+            unsettled: vec![
+                StoredDelivery {
+                    delivery_tag: Binary::from(vec![0, 0, 0, 1]),
+                    state: None,
+                    message_format: 0,
+                    payload: Some(Payload::from_static(&[0x00, 0x53, 0x77, 0x40])),
+                },
+                StoredDelivery {
+                    delivery_tag: Binary::from(vec![0, 0, 0, 2]),
+                    state: Some(DeliveryState::Received(Received {
+                        section_number: 1,
+                        section_offset: 7,
+                    })),
+                    message_format: 0,
+                    payload: Some(Payload::from_static(&[0x00, 0x53, 0x77, 0xa1, 0x00])),
+                },
+                StoredDelivery {
+                    delivery_tag: Binary::from(vec![0, 0, 0, 3]),
+                    state: Some(DeliveryState::Accepted(Accepted {})),
+                    message_format: 0,
+                    payload: Some(Payload::new()),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn link_record_round_trips() {
+        let record = sender_record();
+        let bytes = record.to_bytes().unwrap();
+        let decoded = LinkRecord::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.name, record.name);
+        assert_eq!(decoded.role, Role::Sender);
+        assert_eq!(decoded.delivery_count, 4);
+        assert_eq!(decoded.unsettled.len(), 3);
+        assert_eq!(
+            decoded.unsettled[1].delivery_tag,
+            record.unsettled[1].delivery_tag
+        );
+        assert!(matches!(
+            decoded.unsettled[1].state,
+            Some(DeliveryState::Received(Received {
+                section_number: 1,
+                section_offset: 7
+            }))
+        ));
+        assert_eq!(decoded.unsettled[1].payload, record.unsettled[1].payload);
+        let lazy = decoded.unsettled[0].lazy_message().unwrap();
+        assert_eq!(lazy.sections().count(), 1);
+        assert!(lazy.header().unwrap().is_none());
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn receiver_record_without_payload_round_trips() {
+        let record = LinkRecord {
+            name: "receiver".into(),
+            role: Role::Receiver,
+            snd_settle_mode: SenderSettleMode::Mixed,
+            rcv_settle_mode: ReceiverSettleMode::First,
+            source: None,
+            target: None,
+            delivery_count: 0,
+            unsettled: vec![StoredDelivery {
+                delivery_tag: Binary::from(vec![9]),
+                state: None,
+                message_format: 0,
+                payload: None,
+            }],
+        };
+        let bytes = record.to_bytes().unwrap();
+        let decoded = LinkRecord::from_bytes(&bytes).unwrap();
+        assert!(decoded.source.is_none());
+        assert!(decoded.unsettled[0].payload.is_none());
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn expect_role_reports_mismatch() {
+        let record = sender_record();
+        assert!(record.expect_role(Role::Sender).is_ok());
+        assert!(matches!(
+            record.expect_role(Role::Receiver),
+            Err(StoreError::RoleMismatch {
+                expected: Role::Receiver,
+                found: Role::Sender
+            })
+        ));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn file_store_saves_loads_and_removes() {
+        use super::{FileStore, UnsettledStore};
+
+        let dir = std::env::temp_dir().join(format!(
+            "fe2o3-amqp-store-test-{}-{}",
+            std::process::id(),
+            line!()
+        ));
+        let store = FileStore::new(&dir).unwrap();
+        let record = sender_record();
+
+        assert!(store.load(Role::Sender, &record.name).unwrap().is_none());
+        store.save(&record).unwrap();
+        let loaded = store.load(Role::Sender, &record.name).unwrap().unwrap();
+        assert_eq!(loaded.to_bytes().unwrap(), record.to_bytes().unwrap());
+        // Records of the other role are kept apart
+        assert!(store.load(Role::Receiver, &record.name).unwrap().is_none());
+
+        // Saving again replaces the previous record
+        let mut updated = record.clone();
+        updated.unsettled.truncate(1);
+        store.save(&updated).unwrap();
+        let loaded = store.load(Role::Sender, &record.name).unwrap().unwrap();
+        assert_eq!(loaded.unsettled.len(), 1);
+
+        store.remove(Role::Sender, &record.name).unwrap();
+        assert!(store.load(Role::Sender, &record.name).unwrap().is_none());
+        store.remove(Role::Sender, &record.name).unwrap();
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn file_store_hashes_long_link_names() {
+        use super::{FileStore, UnsettledStore};
+
+        let dir = std::env::temp_dir().join(format!(
+            "fe2o3-amqp-store-test-{}-{}",
+            std::process::id(),
+            line!()
+        ));
+        let store = FileStore::new(&dir).unwrap();
+        let mut record = sender_record();
+        // Longer than the file name limit of most file systems if it were hex encoded
+        record.name = "durable/sender/".repeat(100);
+
+        store.save(&record).unwrap();
+        store.save(&record).unwrap();
+        let loaded = store.load(Role::Sender, &record.name).unwrap().unwrap();
+        assert_eq!(loaded.name, record.name);
+
+        // Only the record is left behind, the temporary files are renamed
+        let file_names: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(file_names.len(), 1);
+        assert_eq!(file_names[0].len(), "sender-".len() + 64 + ".amqp".len());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
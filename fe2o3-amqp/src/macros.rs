@@ -81,3 +81,15 @@ macro_rules! cfg_scram {
         )*
     }
 }
+
+/// Proxy tunneling is not supported in wasm32 targets
+macro_rules! cfg_proxy {
+    ($($item:item)*) => {
+        $(
+            #[cfg_attr(docsrs, doc(cfg(feature = "proxy")))]
+            #[cfg(not(target_arch = "wasm32"))]
+            #[cfg(feature = "proxy")]
+            $item
+        )*
+    }
+}
@@ -281,6 +281,18 @@ where
                     .await
                     .map_err(|_| SessionInnerError::IllegalConnectionState)?;
             }
+            #[cfg(feature = "acceptor")]
+            SessionControl::GetRemoteContainerId(resp) => {
+                self.conn_control
+                    .send(ConnectionControl::GetRemoteContainerId(resp))
+                    .await
+                    .map_err(|_| SessionInnerError::IllegalConnectionState)?;
+            }
+            #[cfg(feature = "acceptor")]
+            SessionControl::GetConnectionControl(resp) => {
+                // The requester may have stopped waiting
+                let _ = resp.send(self.conn_control.clone());
+            }
 
             #[cfg(feature = "transaction")]
             SessionControl::AllocateTransactionId { resp } => {
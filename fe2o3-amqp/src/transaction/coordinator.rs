@@ -53,6 +53,7 @@ impl Default for ControlLinkAcceptor {
                 credit_mode: Default::default(),
                 target_capabilities: None,
                 auto_accept: false,
+                expired_message_policy: Default::default(),
                 on_dynamic_target: unreachable_dynamic_coordinator,
                 target_marker: std::marker::PhantomData,
 
@@ -73,7 +74,7 @@ impl ControlLinkAcceptor {
         outgoing: mpsc::Sender<LinkFrame>,
     ) -> Result<TxnCoordinator, ReceiverAttachError> {
         self.inner
-            .accept_incoming_attach_inner(&self.shared, remote_attach, control, outgoing)
+            .accept_incoming_attach_inner(&self.shared, remote_attach, control, outgoing, None)
             .await
             .map(|inner| TxnCoordinator {
                 inner,
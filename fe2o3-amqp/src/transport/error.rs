@@ -21,7 +21,7 @@ pub enum Error {
     IdleTimeoutElapsed,
 
     /// Decode error
-    #[error("Decode error")]
+    #[error("Decode error: {0}")]
     DecodeError(String),
 
     /// Not implemented
@@ -37,6 +37,7 @@ impl From<serde_amqp::Error> for Error {
     fn from(err: serde_amqp::Error) -> Self {
         match err {
             serde_amqp::Error::Io(e) => Self::Io(e),
+            err if matches!(err.inner(), serde_amqp::Error::Io(_)) => Self::from(err.into_inner()),
             other => Self::DecodeError(other.to_string()),
         }
     }
@@ -47,6 +48,11 @@ impl From<frames::Error> for Error {
         match err {
             frames::Error::Io(io) => Self::Io(io),
             frames::Error::DecodeError(val) => Self::DecodeError(val),
+            frames::Error::LocatedDecodeError {
+                path,
+                offset,
+                source,
+            } => Self::DecodeError(format!("{} at {} (byte offset {})", source, path, offset)),
             frames::Error::NotImplemented => Self::NotImplemented(None),
         }
     }
@@ -63,7 +69,7 @@ pub enum NegotiationError {
     #[error("Invalid domain")]
     InvalidDomain,
 
-    #[error("Decode error")]
+    #[error("Decode error: {0}")]
     DecodeError(String),
 
     #[error("Not implemented")]
@@ -91,6 +97,11 @@ impl From<frames::Error> for NegotiationError {
         match err {
             frames::Error::Io(err) => Self::Io(err),
             frames::Error::DecodeError(val) => Self::DecodeError(val),
+            frames::Error::LocatedDecodeError {
+                path,
+                offset,
+                source,
+            } => Self::DecodeError(format!("{} at {} (byte offset {})", source, path, offset)),
             frames::Error::NotImplemented => Self::NotImplemented(None),
         }
     }
@@ -2,9 +2,11 @@
 
 use bytes::Buf;
 use fe2o3_amqp_types::definitions::DeliveryNumber;
-use fe2o3_amqp_types::messaging::DeliveryState;
+use fe2o3_amqp_types::messaging::message::DecodeIntoMessage;
+use fe2o3_amqp_types::messaging::{DeliveryState, Message};
+use fe2o3_amqp_types::primitives::Timestamp;
 use futures_util::Future;
-use serde_amqp::read::{IoReader, SliceReader};
+use serde_amqp::read::{BytesReader, IoReader};
 use std::io;
 use std::ops::Deref;
 use std::slice::Iter;
@@ -49,6 +51,14 @@ cfg_not_wasm32! {
 
     type DelayImpl = tokio::time::Sleep;
 
+    /// The current time as an AMQP timestamp
+    pub(crate) fn now_timestamp() -> Timestamp {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        Timestamp::from_milliseconds(now.as_millis() as i64)
+    }
+
     impl Delay for tokio::time::Sleep {
         type Instant = tokio::time::Instant;
 
@@ -75,6 +85,14 @@ cfg_wasm32! {
 
     type DelayImpl = wasmtimer::tokio::Sleep;
 
+    /// The current time as an AMQP timestamp
+    pub(crate) fn now_timestamp() -> Timestamp {
+        let now = wasmtimer::std::SystemTime::now()
+            .duration_since(wasmtimer::std::UNIX_EPOCH)
+            .unwrap_or_default();
+        Timestamp::from_milliseconds(now.as_millis() as i64)
+    }
+
     impl Delay for wasmtimer::tokio::Sleep {
         type Instant = wasmtimer::std::Instant;
 
@@ -169,13 +187,29 @@ pub(crate) trait IntoReader<'a> {
     type Reader: serde_amqp::read::Read<'a>;
 
     fn into_reader(self) -> Self::Reader;
+
+    fn decode_message<T>(self) -> Result<Message<T>, T::DecodeError>
+    where
+        Self: Sized,
+        T: DecodeIntoMessage,
+    {
+        T::decode_message_from_reader(self.into_reader())
+    }
 }
 
 impl<'a> IntoReader<'a> for &'a Payload {
-    type Reader = SliceReader<'a>;
+    type Reader = BytesReader<'a>;
 
     fn into_reader(self) -> Self::Reader {
-        SliceReader::new(self)
+        BytesReader::new(self)
+    }
+
+    /// A `Data` body shares the buffer of the payload
+    fn decode_message<T>(self) -> Result<Message<T>, T::DecodeError>
+    where
+        T: DecodeIntoMessage,
+    {
+        T::decode_message_from_bytes(self)
     }
 }
 
@@ -1 +1,677 @@
+//! Tests sending and receiving messages with the in-memory broker
 
+#![cfg(all(feature = "acceptor", not(target_arch = "wasm32")))]
+
+use fe2o3_amqp::{
+    acceptor::broker::Broker, types::definitions::SenderSettleMode, Connection, Receiver, Sender,
+    Session,
+};
+use fe2o3_amqp_types::messaging::Message;
+
+#[tokio::test]
+async fn broker_send_receive() {
+    let broker = Broker::new();
+    let handle = broker.start().await.unwrap();
+
+    let mut connection = Connection::open("test-connection", &handle.url()[..])
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+    let mut sender = Sender::attach(&mut session, "test-sender", "test-queue")
+        .await
+        .unwrap();
+    let mut receiver = Receiver::attach(&mut session, "test-receiver", "test-queue")
+        .await
+        .unwrap();
+
+    let message = Message::from("test-message");
+    let outcome = sender.send(message).await.unwrap();
+    outcome.accepted_or("Not accepted").unwrap();
+
+    let received = receiver.recv::<String>().await.unwrap();
+    receiver.accept(&received).await.unwrap();
+    assert_eq!(received.body(), "test-message");
+
+    sender.close().await.unwrap();
+    receiver.close().await.unwrap();
+    session.close().await.unwrap();
+    connection.close().await.unwrap();
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn broker_send_receive_large_content() {
+    let broker = Broker::new();
+    let stream = broker.connect_duplex(64 * 1024);
+
+    let mut connection = Connection::builder()
+        .container_id("test-connection")
+        .open_with_stream(stream)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+    let mut sender = Sender::attach(&mut session, "test-sender", "test-queue")
+        .await
+        .unwrap();
+    let mut receiver = Receiver::attach(&mut session, "test-receiver", "test-queue")
+        .await
+        .unwrap();
+
+    let message = Message::from("test-message".repeat(100_000));
+    let outcome = sender.send(message).await.unwrap();
+    outcome.accepted_or("Not accepted").unwrap();
+
+    let received = receiver.recv::<String>().await.unwrap();
+    receiver.accept(&received).await.unwrap();
+    assert_eq!(received.body(), &"test-message".repeat(100_000));
+
+    sender.close().await.unwrap();
+    receiver.close().await.unwrap();
+    session.close().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn broker_pre_settled_sender() {
+    let broker = Broker::new();
+    let stream = broker.connect_duplex(64 * 1024);
+
+    let mut connection = Connection::builder()
+        .container_id("test-connection")
+        .open_with_stream(stream)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+    let mut sender = Sender::builder()
+        .name("test-sender")
+        .target("test-queue")
+        .sender_settle_mode(SenderSettleMode::Settled)
+        .attach(&mut session)
+        .await
+        .unwrap();
+    let mut receiver = Receiver::attach(&mut session, "test-receiver", "test-queue")
+        .await
+        .unwrap();
+
+    for i in 0..10 {
+        sender.send(i).await.unwrap();
+    }
+    for i in 0..10 {
+        let received = receiver.recv::<i32>().await.unwrap();
+        receiver.accept(&received).await.unwrap();
+        assert_eq!(received.body(), &i);
+    }
+
+    sender.close().await.unwrap();
+    receiver.close().await.unwrap();
+    session.close().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn broker_released_message_is_redelivered() {
+    let broker = Broker::new();
+    let stream = broker.connect_duplex(64 * 1024);
+
+    let mut connection = Connection::builder()
+        .container_id("test-connection")
+        .open_with_stream(stream)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+    let mut sender = Sender::attach(&mut session, "test-sender", "test-queue")
+        .await
+        .unwrap();
+    sender.send("test-message").await.unwrap();
+
+    let mut receiver = Receiver::attach(&mut session, "test-receiver", "test-queue")
+        .await
+        .unwrap();
+    let received = receiver.recv::<String>().await.unwrap();
+    receiver.release(&received).await.unwrap();
+
+    let received = receiver.recv::<String>().await.unwrap();
+    receiver.accept(&received).await.unwrap();
+    assert_eq!(received.body(), "test-message");
+
+    sender.close().await.unwrap();
+    receiver.close().await.unwrap();
+    session.close().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn broker_restored_sender_resends_unsettled_delivery() {
+    use fe2o3_amqp::link::{
+        sender::DetachedSender,
+        store::{FileStore, LinkRecord, StoredDelivery, UnsettledStore},
+    };
+    use fe2o3_amqp_types::{
+        definitions::{ReceiverSettleMode, Role},
+        messaging::{message::__private::Serializable, Source, Target, TerminusDurability},
+        primitives::Binary,
+    };
+
+    // A record left behind by a sender that crashed before its delivery was settled
+    let payload = serde_amqp::to_vec(&Serializable(Message::from("persisted"))).unwrap();
+    let record = LinkRecord {
+        name: "durable-sender".into(),
+        role: Role::Sender,
+        snd_settle_mode: SenderSettleMode::Unsettled,
+        rcv_settle_mode: ReceiverSettleMode::First,
+        source: Some(
+            Source::builder()
+                .durable(TerminusDurability::UnsettledState)
+                .build(),
+        ),
+        target: Some(Target::builder().address("test-queue").build()),
+        delivery_count: 2,
+        unsettled: vec![StoredDelivery {
+            delivery_tag: Binary::from(vec![0, 0, 0, 1]),
+            state: None,
+            message_format: 0,
+            payload: Some(payload.into()),
+        }],
+    };
+    let dir = std::env::temp_dir().join(format!("fe2o3-amqp-listener-{}", std::process::id()));
+    let store = FileStore::new(&dir).unwrap();
+    store.save(&record).unwrap();
+
+    let broker = Broker::new();
+    let stream = broker.connect_duplex(64 * 1024);
+    let mut connection = Connection::builder()
+        .container_id("test-connection")
+        .open_with_stream(stream)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+
+    let record = store.load(Role::Sender, "durable-sender").unwrap().unwrap();
+    let detached = DetachedSender::restore(&session, record).unwrap();
+    // New deliveries must not reuse the tags of the restored unsettled deliveries
+    assert_eq!(detached.snapshot().delivery_count, 2);
+    let mut sender = detached.resume().await.unwrap();
+    sender.send("after restore").await.unwrap();
+
+    let mut receiver = Receiver::attach(&mut session, "test-receiver", "test-queue")
+        .await
+        .unwrap();
+    let received = receiver.recv::<String>().await.unwrap();
+    receiver.accept(&received).await.unwrap();
+    assert_eq!(received.body(), "persisted");
+    let received = receiver.recv::<String>().await.unwrap();
+    receiver.accept(&received).await.unwrap();
+    assert_eq!(received.body(), "after restore");
+
+    sender.persist(&store).unwrap();
+    store.remove(Role::Sender, "durable-sender").unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+
+    sender.close().await.unwrap();
+    receiver.close().await.unwrap();
+    session.close().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn link_acceptor_resumes_retained_terminus() {
+    use fe2o3_amqp::acceptor::{
+        ConnectionAcceptor, LinkAcceptor, LinkEndpoint, SessionAcceptor, TerminusRegistry,
+    };
+    use fe2o3_amqp_types::{
+        definitions::Role,
+        messaging::{Source, TerminusDurability, TerminusExpiryPolicy},
+    };
+
+    let registry = TerminusRegistry::new();
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+    let link_acceptor = LinkAcceptor::builder()
+        .terminus_registry(registry.clone())
+        .build();
+    let server = tokio::spawn(async move {
+        let mut connection = ConnectionAcceptor::new("test-server")
+            .accept(server_io)
+            .await
+            .unwrap();
+        let mut session = SessionAcceptor::new()
+            .accept(&mut connection)
+            .await
+            .unwrap();
+
+        let mut addresses = Vec::new();
+        for _ in 0..2 {
+            let mut sender = match link_acceptor.accept(&mut session).await.unwrap() {
+                LinkEndpoint::Sender(sender) => sender,
+                LinkEndpoint::Receiver(_) => panic!("expecting a local sender"),
+            };
+            addresses.push(sender.source().clone().and_then(|s| s.address));
+            sender.on_detach().await;
+            sender.detach().await.unwrap();
+        }
+        (connection, session, addresses)
+    });
+
+    let mut connection = Connection::builder()
+        .container_id("test-client")
+        .open_with_stream(client_io)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+
+    let receiver = Receiver::builder()
+        .name("durable-subscription")
+        .source(
+            Source::builder()
+                .address("first-queue")
+                .durable(TerminusDurability::Configuration)
+                .expiry_policy(TerminusExpiryPolicy::Never)
+                .build(),
+        )
+        .attach(&mut session)
+        .await
+        .unwrap();
+    receiver.detach().await.unwrap();
+
+    // The terminus is handed out only after the accepted link is dropped
+    assert!(registry.contains("test-client", "durable-subscription", Role::Sender));
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let receiver = Receiver::builder()
+        .name("durable-subscription")
+        .source(Source::builder().address("second-queue").build())
+        .attach(&mut session)
+        .await
+        .unwrap();
+    receiver.detach().await.unwrap();
+
+    let (_server_connection, _server_session, addresses) = server.await.unwrap();
+    assert_eq!(
+        addresses,
+        vec![Some("first-queue".into()), Some("first-queue".into())]
+    );
+    assert!(registry.contains("test-client", "durable-subscription", Role::Sender));
+
+    session.end().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn link_acceptor_expires_terminus_on_link_detach() {
+    use fe2o3_amqp::acceptor::{
+        ConnectionAcceptor, LinkAcceptor, LinkEndpoint, SessionAcceptor, TerminusRegistry,
+    };
+    use fe2o3_amqp_types::{
+        definitions::Role,
+        messaging::{Source, TerminusDurability, TerminusExpiryPolicy},
+    };
+
+    let registry = TerminusRegistry::new();
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+    let link_acceptor = LinkAcceptor::builder()
+        .terminus_registry(registry.clone())
+        .build();
+    let server = tokio::spawn(async move {
+        let mut connection = ConnectionAcceptor::new("test-server")
+            .accept(server_io)
+            .await
+            .unwrap();
+        let mut session = SessionAcceptor::new()
+            .accept(&mut connection)
+            .await
+            .unwrap();
+        // The detached endpoint is kept alive, the expiry timer starts on the detach
+        let detached = match link_acceptor.accept(&mut session).await.unwrap() {
+            LinkEndpoint::Sender(mut sender) => {
+                sender.on_detach().await;
+                sender.detach().await.unwrap()
+            }
+            LinkEndpoint::Receiver(_) => panic!("expecting a local sender"),
+        };
+        (connection, session, detached)
+    });
+
+    let mut connection = Connection::builder()
+        .container_id("test-client")
+        .open_with_stream(client_io)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+
+    let receiver = Receiver::builder()
+        .name("short-lived")
+        .source(
+            Source::builder()
+                .address("test-queue")
+                .durable(TerminusDurability::Configuration)
+                .expiry_policy(TerminusExpiryPolicy::LinkDetach)
+                .timeout(0)
+                .build(),
+        )
+        .attach(&mut session)
+        .await
+        .unwrap();
+    assert!(registry.contains("test-client", "short-lived", Role::Sender));
+    receiver.detach().await.unwrap();
+
+    let (_server_connection, _server_session, _detached) = server.await.unwrap();
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        while !registry.is_empty() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    session.end().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn link_acceptor_deletes_dynamic_node_without_links() {
+    use fe2o3_amqp::acceptor::{
+        dynamic_node::DynamicNodeRegistry, ConnectionAcceptor, LinkAcceptor, LinkEndpoint,
+        SessionAcceptor,
+    };
+    use fe2o3_amqp_types::messaging::{DeleteOnNoLinks, Source};
+    use tokio::sync::mpsc;
+
+    let (deleted_tx, mut deleted_rx) = mpsc::unbounded_channel();
+    let registry = DynamicNodeRegistry::new(move |address, _policy| {
+        let _ = deleted_tx.send(address.to_string());
+    });
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+    let link_acceptor = LinkAcceptor::builder()
+        .on_dynamic_source(|mut source: Source| {
+            source.address = Some(String::from("dynamic-queue"));
+            Some(source)
+        })
+        .dynamic_node_registry(registry.clone())
+        .build();
+    let server = tokio::spawn(async move {
+        let mut connection = ConnectionAcceptor::new("test-server")
+            .accept(server_io)
+            .await
+            .unwrap();
+        let mut session = SessionAcceptor::new()
+            .accept(&mut connection)
+            .await
+            .unwrap();
+
+        let mut senders = Vec::new();
+        for _ in 0..2 {
+            match link_acceptor.accept(&mut session).await.unwrap() {
+                LinkEndpoint::Sender(sender) => senders.push(sender),
+                LinkEndpoint::Receiver(_) => panic!("expecting a local sender"),
+            }
+        }
+        for mut sender in senders {
+            sender.on_detach().await;
+            sender.close().await.unwrap();
+        }
+        (connection, session)
+    });
+
+    let mut connection = Connection::builder()
+        .container_id("test-client")
+        .open_with_stream(client_io)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+
+    let dynamic = Receiver::builder()
+        .name("dynamic-receiver")
+        .source(
+            Source::builder()
+                .dynamic(true)
+                .add_lifetime_policy(DeleteOnNoLinks {})
+                .build(),
+        )
+        .attach(&mut session)
+        .await
+        .unwrap();
+    let address = dynamic.source().clone().and_then(|s| s.address);
+    assert_eq!(address.as_deref(), Some("dynamic-queue"));
+
+    let other = Receiver::attach(&mut session, "other-receiver", "dynamic-queue")
+        .await
+        .unwrap();
+    assert_eq!(registry.link_count("dynamic-queue"), Some(2));
+
+    // The node outlives the link that created it while another link is attached
+    dynamic.close().await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(registry.contains("dynamic-queue"));
+    assert!(deleted_rx.try_recv().is_err());
+
+    other.close().await.unwrap();
+    let deleted = tokio::time::timeout(std::time::Duration::from_secs(5), deleted_rx.recv())
+        .await
+        .unwrap();
+    assert_eq!(deleted.as_deref(), Some("dynamic-queue"));
+    assert!(registry.is_empty());
+
+    let (_server_connection, _server_session) = server.await.unwrap();
+    session.end().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn link_acceptor_strips_unsupported_filters() {
+    use fe2o3_amqp::acceptor::{
+        ConnectionAcceptor, FilterRegistry, LinkAcceptor, LinkEndpoint, SessionAcceptor,
+    };
+    use fe2o3_amqp::link::ReceiverAttachError;
+    use fe2o3_amqp_ext::filters::{NoLocalFilter, SelectorFilter, Xquery};
+    use fe2o3_amqp_types::messaging::Source;
+
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+    let link_acceptor = LinkAcceptor::builder()
+        .supported_filters(FilterRegistry::new().with_filter(
+            SelectorFilter::descriptor_name(),
+            SelectorFilter::descriptor_code(),
+        ))
+        .build();
+    let server = tokio::spawn(async move {
+        let mut connection = ConnectionAcceptor::new("test-server")
+            .accept(server_io)
+            .await
+            .unwrap();
+        let mut session = SessionAcceptor::new()
+            .accept(&mut connection)
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            match link_acceptor.accept(&mut session).await.unwrap() {
+                LinkEndpoint::Sender(mut sender) => {
+                    sender.on_detach().await;
+                    let _ = sender.detach().await;
+                }
+                LinkEndpoint::Receiver(_) => panic!("expecting a local sender"),
+            }
+        }
+        (connection, session)
+    });
+
+    let mut connection = Connection::builder()
+        .container_id("test-client")
+        .open_with_stream(client_io)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+
+    let source = Source::builder()
+        .address("test-queue")
+        .add_to_filter("selector", SelectorFilter::new("color = 'red'"))
+        .add_to_filter("no-local", NoLocalFilter(vec![]))
+        .add_to_filter("xquery", Xquery::new("/a"))
+        // A legacy filter is not identified by its key even if it is a supported descriptor name
+        .add_to_filter_using_legacy_format(SelectorFilter::descriptor_name(), "color = 'blue'")
+        .build();
+
+    // The desired filters are verified by default
+    let result = Receiver::builder()
+        .name("verified")
+        .source(source.clone())
+        .attach(&mut session)
+        .await;
+    match result {
+        Err(ReceiverAttachError::DesiredFilterNotSupported(err)) => {
+            assert_eq!(err.not_supported.len(), 3);
+        }
+        other => panic!("expecting DesiredFilterNotSupported, found {:?}", other),
+    }
+
+    let receiver = Receiver::builder()
+        .name("fallback")
+        .source(source)
+        .allow_unsupported_filters(true)
+        .attach(&mut session)
+        .await
+        .unwrap();
+    assert!(receiver.is_filter_accepted("selector"));
+    assert!(!receiver.is_filter_accepted("no-local"));
+    assert!(!receiver.is_filter_accepted("apache.org:selector-filter:string"));
+    assert_eq!(receiver.accepted_filters().map(|f| f.len()), Some(1));
+    receiver.detach().await.unwrap();
+
+    let (_server_connection, _server_session) = server.await.unwrap();
+    session.end().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn link_acceptor_keeps_filters_by_default() {
+    use fe2o3_amqp::acceptor::{ConnectionAcceptor, LinkAcceptor, LinkEndpoint, SessionAcceptor};
+    use fe2o3_amqp_ext::filters::{NoLocalFilter, SelectorFilter};
+    use fe2o3_amqp_types::messaging::Source;
+
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+    let server = tokio::spawn(async move {
+        let mut connection = ConnectionAcceptor::new("test-server")
+            .accept(server_io)
+            .await
+            .unwrap();
+        let mut session = SessionAcceptor::new()
+            .accept(&mut connection)
+            .await
+            .unwrap();
+        match LinkAcceptor::new().accept(&mut session).await.unwrap() {
+            LinkEndpoint::Sender(mut sender) => {
+                sender.on_detach().await;
+                let _ = sender.detach().await;
+            }
+            LinkEndpoint::Receiver(_) => panic!("expecting a local sender"),
+        }
+        (connection, session)
+    });
+
+    let mut connection = Connection::builder()
+        .container_id("test-client")
+        .open_with_stream(client_io)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+
+    let source = Source::builder()
+        .address("test-queue")
+        .add_to_filter("selector", SelectorFilter::new("color = 'red'"))
+        .add_to_filter("no-local", NoLocalFilter(vec![]))
+        .add_to_filter_using_legacy_format("legacy", "value")
+        .build();
+    let receiver = Receiver::builder()
+        .name("default")
+        .source(source)
+        .attach(&mut session)
+        .await
+        .unwrap();
+    assert!(receiver.is_filter_accepted("selector"));
+    assert!(receiver.is_filter_accepted("no-local"));
+    assert!(receiver.is_filter_accepted("legacy"));
+    receiver.detach().await.unwrap();
+
+    let (_server_connection, _server_session) = server.await.unwrap();
+    session.end().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn receiver_rejects_expired_messages() {
+    use fe2o3_amqp::acceptor::{ConnectionAcceptor, LinkAcceptor, LinkEndpoint, SessionAcceptor};
+    use fe2o3_amqp::link::receiver::{ExpiredMessagePolicy, MESSAGE_EXPIRED};
+    use fe2o3_amqp_types::{
+        definitions::ErrorCondition,
+        messaging::{Outcome, Properties},
+        primitives::{Symbol, Timestamp},
+    };
+
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+    let server = tokio::spawn(async move {
+        let mut connection = ConnectionAcceptor::new("test-server")
+            .accept(server_io)
+            .await
+            .unwrap();
+        let mut session = SessionAcceptor::new()
+            .accept(&mut connection)
+            .await
+            .unwrap();
+        let mut sender = match LinkAcceptor::new().accept(&mut session).await.unwrap() {
+            LinkEndpoint::Sender(sender) => sender,
+            LinkEndpoint::Receiver(_) => panic!("expecting a local sender"),
+        };
+
+        let expired = Message::builder()
+            .properties(
+                Properties::builder()
+                    .absolute_expiry_time(Timestamp::from_milliseconds(1))
+                    .build(),
+            )
+            .value("expired")
+            .build();
+        let outcome = sender.send(expired).await.unwrap();
+        match outcome {
+            Outcome::Rejected(rejected) => {
+                let condition = rejected.error.map(|e| e.condition);
+                assert_eq!(
+                    condition,
+                    Some(ErrorCondition::Custom(Symbol::from(MESSAGE_EXPIRED)))
+                );
+            }
+            other => panic!("expecting Rejected, found {:?}", other),
+        }
+        let outcome = sender.send("live").await.unwrap();
+        assert!(outcome.is_accepted());
+
+        sender.on_detach().await;
+        sender.detach().await.unwrap();
+        (connection, session)
+    });
+
+    let mut connection = Connection::builder()
+        .container_id("test-client")
+        .open_with_stream(client_io)
+        .await
+        .unwrap();
+    let mut session = Session::begin(&mut connection).await.unwrap();
+
+    let mut receiver = Receiver::builder()
+        .name("expiring")
+        .source("test-queue")
+        .expired_message_policy(ExpiredMessagePolicy::Reject)
+        .attach(&mut session)
+        .await
+        .unwrap();
+    let delivery = receiver.recv::<String>().await.unwrap();
+    assert_eq!(delivery.body(), "live");
+    receiver.accept(&delivery).await.unwrap();
+    receiver.detach().await.unwrap();
+
+    let (_server_connection, _server_session) = server.await.unwrap();
+    session.end().await.unwrap();
+    connection.close().await.unwrap();
+}
@@ -8,6 +8,13 @@ pub const DESCRIBED_MAP: &str = "AMQP1.0_DESCRIBED_MAP";
 #[doc(hidden)]
 pub const DESCRIPTOR: &str = "AMQP1.0_DESCRIPTOR";
 
+// Field names used by the derive macros to serialize the entries captured by a trailing rest
+// field of a struct with the map encoding
+#[doc(hidden)]
+pub const REST_MAP_KEY: &str = "AMQP1.0_REST_MAP_KEY";
+#[doc(hidden)]
+pub const REST_MAP_VALUE: &str = "AMQP1.0_REST_MAP_VALUE";
+
 #[doc(hidden)]
 pub const UNTAGGED_ENUM: &str = "FE2O3_AMQP_UNTAGGED";
 
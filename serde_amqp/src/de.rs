@@ -1,10 +1,12 @@
 //! Deserializer implementation
 
+use alloc::{string::String, vec::Vec};
+use bytes::Bytes;
+use core::convert::TryInto;
 use serde::{
     de::{self},
     Deserialize,
 };
-use std::convert::TryInto;
 
 use crate::{
     __constants::{
@@ -12,19 +14,21 @@ use crate::{
         DESCRIPTOR, LAZY_VALUE, SYMBOL, SYMBOL_REF, TIMESTAMP, TRANSPARENT_VEC, UUID, VALUE,
     },
     descriptor::PeekDescriptor,
-    error::Error,
+    error::{Error, Segment},
     fixed_width::{DECIMAL128_WIDTH, DECIMAL32_WIDTH, DECIMAL64_WIDTH, UUID_WIDTH},
     format::{
         OFFSET_ARRAY32, OFFSET_ARRAY8, OFFSET_LIST32, OFFSET_LIST8, OFFSET_MAP32, OFFSET_MAP8,
     },
     format_code::EncodingCodes,
-    read::{IoReader, Read, SliceReader},
+    read::{BytesReader, Read, SliceReader},
     util::{EnumType, NonNativeType, PeekTypeCode, SequenceType, StructEncoding},
 };
 
 /// Deserialize an instance of type T from an IO stream
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
 pub fn from_reader<T: de::DeserializeOwned>(reader: impl std::io::Read) -> Result<T, Error> {
-    let reader = IoReader::new(reader);
+    let reader = crate::read::IoReader::new(reader);
     let mut de = Deserializer::new(reader);
     T::deserialize(&mut de)
 }
@@ -36,6 +40,18 @@ pub fn from_slice<'de, T: de::Deserialize<'de>>(slice: &'de [u8]) -> Result<T, E
     T::deserialize(&mut de)
 }
 
+/// Deserialize an instance of type T from [`Bytes`]
+///
+/// The `Bytes`-backed types (ie. [`BinaryBytes`](crate::primitives::BinaryBytes),
+/// [`StrBytes`](crate::primitives::StrBytes) and [`SymbolBytes`](crate::primitives::SymbolBytes))
+/// only share the buffer of `bytes` when they are deserialized with
+/// [`Shared`](crate::primitives::Shared).
+pub fn from_bytes<'de, T: de::Deserialize<'de>>(bytes: &'de Bytes) -> Result<T, Error> {
+    let reader = BytesReader::new(bytes);
+    let mut de = Deserializer::new(reader);
+    T::deserialize(&mut de)
+}
+
 /// A structure that deserializes AMQP1.0 binary encoded values into rust types
 #[derive(Debug)]
 pub struct Deserializer<R> {
@@ -101,7 +117,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             }
             EncodingCodes::BooleanTrue => Ok(true),
             EncodingCodes::BooleanFalse => Ok(false),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("boolean", code)),
         }
     }
 
@@ -119,7 +135,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                     .and_then(|b| b.ok_or_else(|| Error::unexpected_eof("Expecting i8")))?;
                 Ok(byte as i8)
             }
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("byte", code)),
         }
     }
 
@@ -134,7 +150,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 .read_const_bytes()
                 .map(i16::from_be_bytes)
                 .map_err(Into::into),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("short", code)),
         }
     }
 
@@ -153,7 +169,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 b.map(|signed| signed as i8 as i32)
                     .ok_or_else(|| Error::unexpected_eof("Expecting i32"))
             }),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("int", code)),
         }
     }
 
@@ -172,7 +188,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 b.map(|signed| signed as i8 as i64)
                     .ok_or_else(|| Error::unexpected_eof("Expecting i64"))
             }),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("long", code)),
         }
     }
 
@@ -187,7 +203,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 .next()
                 .map_err(Into::into)
                 .and_then(|b| b.ok_or_else(|| Error::unexpected_eof("Expecting u8"))),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("ubyte", code)),
         }
     }
 
@@ -202,7 +218,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 .read_const_bytes()
                 .map(u16::from_be_bytes)
                 .map_err(Into::into),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("ushort", code)),
         }
     }
 
@@ -222,7 +238,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                     .map(|byte| byte as u32)
             }),
             EncodingCodes::Uint0 => Ok(0),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("uint", code)),
         }
     }
 
@@ -244,7 +260,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 Ok(byte as u64)
             }
             EncodingCodes::Ulong0 => Ok(0),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("ulong", code)),
         }
     }
 
@@ -258,7 +274,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 let bytes = self.reader.read_const_bytes()?;
                 Ok(f32::from_be_bytes(bytes))
             }
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("float", code)),
         }
     }
 
@@ -272,7 +288,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 let bytes = self.reader.read_const_bytes()?;
                 Ok(f64::from_be_bytes(bytes))
             }
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("double", code)),
         }
     }
 
@@ -287,7 +303,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 let n = u32::from_be_bytes(bytes);
                 char::from_u32(n).ok_or(Error::InvalidValue)
             }
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("char", code)),
         }
     }
 
@@ -329,7 +345,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             EncodingCodes::Str32 => self
                 .read_string()
                 .ok_or_else(|| Error::unexpected_eof("Expecting str32"))?,
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("string", code)),
         }
     }
 
@@ -345,7 +361,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             EncodingCodes::Sym32 => self
                 .read_string()
                 .ok_or_else(|| Error::unexpected_eof("Expecting sym32"))?,
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("symbol", code)),
         }
     }
 
@@ -367,7 +383,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 let len = u32::from_be_bytes(len_bytes);
                 self.reader.read_bytes(len as usize).map_err(Into::into)
             }
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("binary", code)),
         }
     }
 
@@ -389,7 +405,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             EncodingCodes::Decimal128 => self
                 .reader
                 .forward_read_bytes_with_hint(DECIMAL128_WIDTH, visitor),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("decimal", code)),
         }
     }
 
@@ -405,7 +421,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             EncodingCodes::Uuid => self
                 .reader
                 .forward_read_bytes_with_hint(UUID_WIDTH, visitor),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("uuid", code)),
         }
     }
 
@@ -419,7 +435,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 let bytes = self.reader.read_const_bytes()?;
                 Ok(i64::from_be_bytes(bytes))
             }
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("timestamp", code)),
         }
     }
 
@@ -430,7 +446,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             .ok_or_else(|| Error::unexpected_eof("parse_unit"))??
         {
             EncodingCodes::Null => Ok(()),
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("null", code)),
         }
     }
 
@@ -460,7 +476,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                     .reader
                     .peek_bytes(3 + size)?
                     .ok_or_else(|| Error::unexpected_eof(""))?;
-                let slice = std::str::from_utf8(&_buf[3..])?;
+                let slice = core::str::from_utf8(&_buf[3..])?;
                 visitor.visit_str(slice)
             }
             EncodingCodes::Sym32 => {
@@ -478,7 +494,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                     .reader
                     .peek_bytes(6 + size)?
                     .ok_or_else(|| Error::unexpected_eof(""))?;
-                let slice = std::str::from_utf8(&_buf[6..])?;
+                let slice = core::str::from_utf8(&_buf[6..])?;
                 visitor.visit_str(slice)
             }
             EncodingCodes::Ulong0 => visitor.visit_u64(0),
@@ -506,7 +522,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 let value = u64::from_be_bytes(bytes);
                 visitor.visit_u64(value)
             }
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("descriptor", code)),
         }
     }
 }
@@ -712,7 +728,7 @@ where
             EncodingCodes::Str32 | EncodingCodes::Sym32 => {
                 self.reader.read_const_bytes().map(u32::from_be_bytes)? as usize
             }
-            _ => return Err(Error::InvalidFormatCode),
+            code => return Err(Error::unexpected_format_code("string or symbol", code)),
         };
         self.reader.forward_read_str(len, visitor)
     }
@@ -762,7 +778,7 @@ where
                     EncodingCodes::Vbin32 => {
                         self.reader.read_const_bytes().map(u32::from_be_bytes)? as usize
                     }
-                    _ => return Err(Error::InvalidFormatCode),
+                    code => return Err(Error::unexpected_format_code("binary", code)),
                 };
                 self.reader.forward_read_bytes_with_hint(len, visitor)
             }
@@ -949,7 +965,7 @@ where
                 self.elem_format_code = None;
                 visitor.visit_seq(ListAccess::new(self, len, count))
             }
-            _ => Err(Error::InvalidFormatCode),
+            code => Err(Error::unexpected_format_code("array or list", code)),
         }
     }
 
@@ -1000,7 +1016,7 @@ where
                 self.elem_format_code = None;
                 (size, count)
             }
-            _ => return Err(Error::InvalidFormatCode),
+            code => return Err(Error::unexpected_format_code("list", code)),
         };
 
         if count != len {
@@ -1047,7 +1063,7 @@ where
 
                 (size, count)
             }
-            _ => return Err(Error::InvalidFormatCode),
+            code => return Err(Error::unexpected_format_code("map", code)),
         };
 
         // // AMQP map count includes both key and value, should be halfed
@@ -1069,7 +1085,7 @@ where
             visitor.visit_seq(DescribedAccess::basic(self, len as u32))
         } else if name == DESCRIBED_LIST {
             self.struct_encoding = StructEncoding::DescribedList;
-            visitor.visit_seq(DescribedAccess::list(self))
+            visitor.visit_seq(DescribedAccess::named_list(self, &[]))
         } else {
             match self
                 .get_elem_code_or_peek_byte()
@@ -1098,7 +1114,7 @@ where
             visitor.visit_seq(DescribedAccess::basic(self, fields.len() as u32))
         } else if name == DESCRIBED_LIST {
             self.struct_encoding = StructEncoding::DescribedList;
-            visitor.visit_seq(DescribedAccess::list(self))
+            visitor.visit_seq(DescribedAccess::named_list(self, fields))
         } else if name == DESCRIBED_MAP {
             self.struct_encoding = StructEncoding::DescribedMap;
             visitor.visit_map(DescribedAccess::map(self))
@@ -1114,7 +1130,10 @@ where
                 }
                 EncodingCodes::Map32 | EncodingCodes::Map8 => self.deserialize_map(visitor),
                 EncodingCodes::DescribedType => visitor.visit_seq(DescribedAccess::list(self)),
-                _ => Err(Error::InvalidFormatCode),
+                code => Err(Error::unexpected_format_code(
+                    "list, map or described type",
+                    code,
+                )),
             }
         };
         // Restore
@@ -1232,7 +1251,7 @@ where
                     .try_into()?
                 {
                     EncodingCodes::DescribedType => {}
-                    _ => return Err(Error::InvalidFormatCode),
+                    code => return Err(Error::unexpected_format_code("described type", code)),
                 };
                 // Reset the enum type
                 self.enum_type = EnumType::None;
@@ -1299,6 +1318,7 @@ pub struct ArrayAccess<'a, R> {
     de: &'a mut Deserializer<R>,
     _size: usize,
     count: usize,
+    index: usize,
 }
 
 impl<'a, R> ArrayAccess<'a, R> {
@@ -1307,6 +1327,7 @@ impl<'a, R> ArrayAccess<'a, R> {
             de,
             _size: size,
             count,
+            index: 0,
         }
     }
 }
@@ -1331,7 +1352,12 @@ impl<'de, R: Read<'de>> de::SeqAccess<'de> for ArrayAccess<'_, R> {
             }
             _ => {
                 self.count -= 1;
-                seed.deserialize(self.as_mut()).map(Some)
+                let index = self.index;
+                self.index += 1;
+                let offset = self.de.reader.position();
+                seed.deserialize(self.as_mut())
+                    .map(Some)
+                    .map_err(|err| err.at(Segment::Index(index), offset))
             }
         }
     }
@@ -1343,6 +1369,7 @@ pub struct ListAccess<'a, R> {
     de: &'a mut Deserializer<R>,
     _size: usize,
     count: usize,
+    index: usize,
 }
 
 impl<'a, R> ListAccess<'a, R> {
@@ -1351,6 +1378,7 @@ impl<'a, R> ListAccess<'a, R> {
             de,
             _size: size,
             count,
+            index: 0,
         }
     }
 }
@@ -1372,7 +1400,12 @@ impl<'de, R: Read<'de>> de::SeqAccess<'de> for ListAccess<'_, R> {
             0 => Ok(None),
             _ => {
                 self.count -= 1;
-                seed.deserialize(self.as_mut()).map(Some)
+                let index = self.index;
+                self.index += 1;
+                let offset = self.de.reader.position();
+                seed.deserialize(self.as_mut())
+                    .map(Some)
+                    .map_err(|err| err.at(Segment::Index(index), offset))
             }
         }
     }
@@ -1383,11 +1416,16 @@ impl<'de, R: Read<'de>> de::SeqAccess<'de> for ListAccess<'_, R> {
 pub struct TransparentVecAccess<'a, R> {
     de: &'a mut Deserializer<R>,
     cached: Option<PeekTypeCode>,
+    index: usize,
 }
 
 impl<'a, R> TransparentVecAccess<'a, R> {
     pub(crate) fn new(de: &'a mut Deserializer<R>) -> Self {
-        Self { de, cached: None }
+        Self {
+            de,
+            cached: None,
+            index: 0,
+        }
     }
 }
 
@@ -1431,7 +1469,12 @@ impl<'de, R: Read<'de>> de::SeqAccess<'de> for TransparentVecAccess<'_, R> {
             None => return Ok(None),
         }
 
-        seed.deserialize(self.as_mut()).map(Some)
+        let index = self.index;
+        self.index += 1;
+        let offset = self.de.reader.position();
+        seed.deserialize(self.as_mut())
+            .map(Some)
+            .map_err(|err| err.at(Segment::Index(index), offset))
     }
 }
 
@@ -1441,6 +1484,9 @@ pub struct MapAccess<'a, R> {
     de: &'a mut Deserializer<R>,
     _size: usize,
     count: usize,
+    index: usize,
+    // Offset of the key of the current entry
+    key_offset: Option<usize>,
 }
 
 impl<'a, R> MapAccess<'a, R> {
@@ -1449,10 +1495,54 @@ impl<'a, R> MapAccess<'a, R> {
             de,
             _size: size,
             count,
+            index: 0,
+            key_offset: None,
         }
     }
 }
 
+impl<'de, R: Read<'de>> MapAccess<'_, R> {
+    /// Records the offset of the key of the next entry
+    fn start_key(&mut self) -> usize {
+        let offset = self.de.reader.position();
+        // The key of an entry is not encoded with its format code if it comes from an array
+        self.key_offset = match self.de.elem_format_code {
+            Some(_) => None,
+            None => Some(offset),
+        };
+        offset
+    }
+
+    /// The segment of the value of the current entry
+    ///
+    /// This is only called when the value failed to decode. The key is recovered from the
+    /// consumed bytes if the reader still holds them.
+    fn value_segment(&self, index: usize) -> Segment {
+        self.key_offset
+            .and_then(|offset| self.de.reader.consumed_since(offset))
+            .and_then(str_key)
+            .map(Segment::Key)
+            .unwrap_or(Segment::Entry(index))
+    }
+}
+
+/// Decodes an encoded string or symbol
+fn str_key(bytes: &[u8]) -> Option<String> {
+    let (width, len) = match EncodingCodes::try_from(*bytes.first()?).ok()? {
+        EncodingCodes::Str8 | EncodingCodes::Sym8 => (1, *bytes.get(1)? as usize),
+        EncodingCodes::Str32 | EncodingCodes::Sym32 => {
+            let len = bytes.get(1..5)?;
+            (
+                4,
+                u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize,
+            )
+        }
+        _ => return None,
+    };
+    let key = bytes.get(1 + width..1 + width + len)?;
+    Some(String::from_utf8_lossy(key).into_owned())
+}
+
 impl<R> AsMut<Deserializer<R>> for MapAccess<'_, R> {
     fn as_mut(&mut self) -> &mut Deserializer<R> {
         self.de
@@ -1470,7 +1560,11 @@ impl<'de, R: Read<'de>> de::MapAccess<'de> for MapAccess<'_, R> {
             0 => Ok(None),
             _ => {
                 self.count -= 1;
-                seed.deserialize(self.as_mut()).map(Some)
+                let offset = self.start_key();
+                let index = self.index;
+                seed.deserialize(self.as_mut())
+                    .map(Some)
+                    .map_err(|err| err.at(Segment::Entry(index), offset))
             }
         }
     }
@@ -1480,7 +1574,11 @@ impl<'de, R: Read<'de>> de::MapAccess<'de> for MapAccess<'_, R> {
         V: de::DeserializeSeed<'de>,
     {
         self.count -= 1;
+        let index = self.index;
+        self.index += 1;
+        let offset = self.de.reader.position();
         seed.deserialize(self.as_mut())
+            .map_err(|err| err.at(self.value_segment(index), offset))
     }
 
     fn next_entry_seed<K, V>(
@@ -1497,8 +1595,16 @@ impl<'de, R: Read<'de>> de::MapAccess<'de> for MapAccess<'_, R> {
             _ => {
                 // AMQP map count includes both key and value
                 self.count -= 2;
-                let key = kseed.deserialize(self.as_mut())?;
-                let val = vseed.deserialize(self.as_mut())?;
+                let index = self.index;
+                self.index += 1;
+                let offset = self.start_key();
+                let key = kseed
+                    .deserialize(self.as_mut())
+                    .map_err(|err| err.at(Segment::Entry(index), offset))?;
+                let offset = self.de.reader.position();
+                let val = vseed
+                    .deserialize(self.as_mut())
+                    .map_err(|err| err.at(self.value_segment(index), offset))?;
                 Ok(Some((key, val)))
             }
         }
@@ -1575,6 +1681,9 @@ pub struct DescribedAccess<'a, R> {
     de: &'a mut Deserializer<R>,
     counter: u32,
     field_count: u32,
+    // Names of the fields following the descriptor. The elements are located by their position
+    // if the names are not available, and are not located if this is `None`.
+    fields: Option<&'static [&'static str]>,
 }
 
 impl<'a, 'de, R: Read<'de>> DescribedAccess<'a, R> {
@@ -1585,6 +1694,17 @@ impl<'a, 'de, R: Read<'de>> DescribedAccess<'a, R> {
             de,
             field_count: 1,
             counter: 0,
+            fields: None,
+        }
+    }
+
+    /// The first of `fields` is the descriptor
+    pub(crate) fn named_list(de: &'a mut Deserializer<R>, fields: &'static [&'static str]) -> Self {
+        Self {
+            de,
+            field_count: 1,
+            counter: 0,
+            fields: Some(fields),
         }
     }
 
@@ -1593,6 +1713,7 @@ impl<'a, 'de, R: Read<'de>> DescribedAccess<'a, R> {
             de,
             field_count,
             counter: 0,
+            fields: None,
         }
     }
 
@@ -1601,6 +1722,20 @@ impl<'a, 'de, R: Read<'de>> DescribedAccess<'a, R> {
             de,
             field_count: 1,
             counter: 0,
+            fields: None,
+        }
+    }
+
+    /// The segment of the element at `counter`, the descriptor is not located
+    fn segment(&self, counter: u32) -> Option<Segment> {
+        let fields = self.fields?;
+        let counter = counter as usize;
+        match counter {
+            0 => None,
+            _ => match fields.get(counter) {
+                Some(name) => Some(Segment::Field(name)),
+                None => Some(Segment::Index(counter - 1)),
+            },
         }
     }
 
@@ -1689,6 +1824,7 @@ impl<'de, R: Read<'de>> de::SeqAccess<'de> for DescribedAccess<'_, R> {
             None => return Ok(None),
         };
         let code = byte.try_into()?;
+        let offset = self.de.reader.position();
         let result = match code {
             EncodingCodes::DescribedType => {
                 let result = seed.deserialize(self.as_mut()).map(Some);
@@ -1704,6 +1840,10 @@ impl<'de, R: Read<'de>> de::SeqAccess<'de> for DescribedAccess<'_, R> {
             }
             _ => seed.deserialize(self.as_mut()).map(Some),
         };
+        let result = result.map_err(|err| match self.segment(self.counter) {
+            Some(segment) => err.at(segment, offset),
+            None => err,
+        });
 
         self.counter += 1;
 
@@ -1733,8 +1873,11 @@ impl<'de, R: Read<'de>> de::MapAccess<'de> for DescribedAccess<'_, R> {
                 Ok(None)
             }
             EncodingCodes::DescribedType => {
-                self.de.enum_type = EnumType::Descriptor;
+                // Restore the enum type afterwards so that the following keys are not treated
+                // as descriptors
+                let enum_type = core::mem::replace(&mut self.de.enum_type, EnumType::Descriptor);
                 let result = seed.deserialize(self.as_mut()).map(Some);
+                self.de.enum_type = enum_type;
                 if self.counter == 0 {
                     if let StructEncoding::DescribedMap = self.de.struct_encoding {
                         self.field_count += self.consume_map_header()?;
@@ -2623,4 +2766,99 @@ mod tests {
         let buf = to_vec(&expected).unwrap();
         assert_eq_from_reader_vs_expected(&buf, expected);
     }
+
+    #[test]
+    fn test_unexpected_format_code() {
+        use crate::Error;
+
+        let buf = [EncodingCodes::Str8 as u8, 0x01, b'a'];
+        let err = from_slice::<u32>(&buf).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnexpectedFormatCode {
+                expected: "uint",
+                found: EncodingCodes::Str8
+            }
+        ));
+        assert!(err.path().is_none());
+    }
+
+    #[test]
+    fn test_error_path_in_list() {
+        use crate::{ser::to_vec, Value};
+
+        let buf = to_vec(&vec![Value::Uint(1), Value::String("a".to_string())]).unwrap();
+        let err = from_slice::<Vec<u32>>(&buf).unwrap_err();
+        assert_eq!(err.path().unwrap().to_string(), "[1]");
+        // list8 header is 3 bytes, followed by a smalluint
+        assert_eq!(err.offset(), Some(3 + 2));
+
+        let err = from_reader::<Vec<u32>>(&buf[..]).unwrap_err();
+        assert_eq!(err.path().unwrap().to_string(), "[1]");
+        assert_eq!(err.offset(), Some(3 + 2));
+    }
+
+    #[cfg(feature = "serde_amqp_derive")]
+    #[test]
+    fn test_error_path_in_described_list() {
+        use std::collections::BTreeMap;
+
+        use crate as serde_amqp;
+        use crate::macros::{DeserializeComposite, SerializeComposite};
+        use crate::ser::to_vec;
+        use crate::Error;
+
+        #[derive(Debug, SerializeComposite, DeserializeComposite)]
+        #[amqp_contract(code = "00:13", encoding = "list", rename_all = "kebab-case")]
+        struct Source<T> {
+            address: String,
+            filter: BTreeMap<String, T>,
+        }
+
+        #[derive(Debug, SerializeComposite, DeserializeComposite)]
+        #[amqp_contract(code = "00:12", encoding = "list", rename_all = "kebab-case")]
+        struct Attach<T> {
+            name: String,
+            source: Source<T>,
+        }
+
+        let mut filter = BTreeMap::new();
+        filter.insert("selector".to_string(), "1=1".to_string());
+        let attach = Attach {
+            name: "link".to_string(),
+            source: Source {
+                address: "q1".to_string(),
+                filter,
+            },
+        };
+        let buf = to_vec(&attach).unwrap();
+        let value = [EncodingCodes::Str8 as u8, 3, b'1', b'=', b'1'];
+        let expected_offset = buf.windows(value.len()).position(|w| w == value).unwrap();
+
+        let err = from_slice::<Attach<u32>>(&buf).unwrap_err();
+        assert_eq!(
+            err.path().unwrap().to_string(),
+            r#"source.filter["selector"]"#
+        );
+        assert_eq!(err.offset(), Some(expected_offset));
+        assert!(matches!(
+            err.inner(),
+            Error::UnexpectedFormatCode {
+                expected: "uint",
+                found: EncodingCodes::Str8
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                r#"Invalid format code: expecting uint, found Str8:0xa1 at source.filter["selector"] (byte offset {})"#,
+                expected_offset
+            )
+        );
+
+        // The key is already consumed from the io reader when the value fails to decode
+        let err = from_reader::<Attach<u32>>(&buf[..]).unwrap_err();
+        assert_eq!(err.path().unwrap().to_string(), "source.filter[#0]");
+        assert_eq!(err.offset(), Some(expected_offset));
+    }
 }
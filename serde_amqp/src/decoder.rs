@@ -0,0 +1,313 @@
+//! Incremental decoder that can be fed chunks of bytes
+//!
+//! AMQP1.0 encoded values are self-delimiting, so the [`Decoder`] only needs to inspect the format
+//! codes and size prefixes to tell whether a complete value has been buffered. Instead of failing
+//! with an unexpected EOF, [`Decoder::decode`] returns [`Decoded::NeedMore`] with the number of
+//! bytes that are still missing.
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde_amqp::{to_vec, Decoded, Decoder};
+//!
+//! let buf = to_vec(&vec![1i32, 2, 3]).unwrap();
+//! let (head, tail) = buf.split_at(3);
+//!
+//! let mut decoder = Decoder::new();
+//! decoder.feed(head);
+//! assert!(matches!(decoder.decode::<Vec<i32>>(), Ok(Decoded::NeedMore(_))));
+//!
+//! decoder.feed(tail);
+//! match decoder.decode::<Vec<i32>>().unwrap() {
+//!     Decoded::Value(value) => assert_eq!(value, vec![1, 2, 3]),
+//!     Decoded::NeedMore(_) => unreachable!(),
+//! }
+//! ```
+
+use bytes::{Buf, BytesMut};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    format::{Category, IsDescribed},
+    format_code::EncodingCodes,
+    from_slice, Error,
+};
+
+/// The result of [`Decoder::decode`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decoded<T> {
+    /// A complete value has been decoded
+    Value(T),
+
+    /// The buffered bytes do not contain a complete value yet. The number is the minimum number of
+    /// bytes that must be fed before the next attempt can make progress.
+    NeedMore(usize),
+}
+
+/// A resumable decoder that buffers chunks of bytes until a complete value can be decoded
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buf: BytesMut,
+}
+
+impl Decoder {
+    /// Creates an empty decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty decoder with at least the given buffer capacity
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: BytesMut::with_capacity(capacity),
+        }
+    }
+
+    /// Appends a chunk of bytes to the buffer
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Number of bytes that are buffered but not decoded yet
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Whether there is no buffered byte
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Returns the minimum number of bytes needed before the next value can be decoded, or `None`
+    /// if a complete value is buffered
+    pub fn needed(&self) -> Result<Option<usize>, Error> {
+        match encoded_len(&self.buf)? {
+            EncodedLen::Complete(_) => Ok(None),
+            EncodedLen::Incomplete(needed) => Ok(Some(needed)),
+        }
+    }
+
+    /// Attempts to decode the next value from the buffered bytes
+    ///
+    /// The bytes of the value are removed from the buffer only if a complete value is buffered.
+    /// An error is returned if the buffered bytes are not a valid encoding of `T`, in which case
+    /// the bytes of the value are discarded.
+    pub fn decode<T: DeserializeOwned>(&mut self) -> Result<Decoded<T>, Error> {
+        match encoded_len(&self.buf)? {
+            EncodedLen::Complete(len) => {
+                let bytes = self.buf.split_to(len);
+                from_slice(&bytes).map(Decoded::Value)
+            }
+            EncodedLen::Incomplete(needed) => Ok(Decoded::NeedMore(needed)),
+        }
+    }
+
+    /// Discards the next `n` buffered bytes
+    pub fn advance(&mut self, n: usize) {
+        self.buf.advance(n.min(self.buf.len()))
+    }
+
+    /// Consumes the decoder and returns the bytes that are buffered but not decoded
+    pub fn into_inner(self) -> BytesMut {
+        self.buf
+    }
+}
+
+/// Deserialize an instance of type T from an async IO stream
+///
+/// The reader is never read past the end of the encoded value, so the remaining bytes can be
+/// used to decode the following values.
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn from_async_reader<T, R>(mut reader: R) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    // The size prefixes come from the reader, so the buffer only grows by the bytes that have
+    // actually been read
+    const CHUNK_SIZE: usize = 8 * 1024;
+
+    let mut decoder = Decoder::new();
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    loop {
+        match decoder.decode()? {
+            Decoded::Value(value) => return Ok(value),
+            Decoded::NeedMore(needed) => {
+                let chunk = &mut chunk[..needed.min(CHUNK_SIZE)];
+                reader.read_exact(chunk).await?;
+                decoder.feed(chunk);
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum EncodedLen {
+    Complete(usize),
+    Incomplete(usize),
+}
+
+/// Find the length of the first encoded value in `buf`
+///
+/// Described values are measured iteratively so that a long run of described constructors
+/// cannot overflow the stack.
+pub(crate) fn encoded_len(buf: &[u8]) -> Result<EncodedLen, Error> {
+    let mut offset = 0;
+    // Number of values whose length still needs to be added. A described constructor replaces
+    // one value by its descriptor and its value.
+    let mut pending = 1usize;
+    while pending > 0 {
+        let code: EncodingCodes = match buf.get(offset) {
+            Some(code) => (*code).try_into()?,
+            None => return Ok(EncodedLen::Incomplete(offset + 1 - buf.len())),
+        };
+
+        let len = match Category::try_from(code) {
+            Ok(Category::Fixed(width)) => 1 + width,
+            Ok(Category::Variable(width))
+            | Ok(Category::Compound(width))
+            | Ok(Category::Array(width)) => {
+                let header = offset + 1 + width;
+                if buf.len() < header {
+                    return Ok(EncodedLen::Incomplete(header - buf.len()));
+                }
+                let size = match width {
+                    1 => buf[offset + 1] as usize,
+                    _ => u32::from_be_bytes([
+                        buf[offset + 1],
+                        buf[offset + 2],
+                        buf[offset + 3],
+                        buf[offset + 4],
+                    ]) as usize,
+                };
+                1 + width + size
+            }
+            Err(IsDescribed) => {
+                offset += 1;
+                pending += 1;
+                continue;
+            }
+        };
+        offset += len;
+        pending -= 1;
+    }
+
+    match offset > buf.len() {
+        true => Ok(EncodedLen::Incomplete(offset - buf.len())),
+        false => Ok(EncodedLen::Complete(offset)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        described::Described, descriptor::Descriptor, primitives::OrderedMap, to_vec, Value,
+    };
+
+    use super::{encoded_len, Decoded, Decoder, EncodedLen};
+
+    #[test]
+    fn encoded_len_of_values() {
+        let values = vec![
+            Value::Null,
+            Value::Bool(true),
+            Value::Uint(7),
+            Value::Ulong(u64::MAX),
+            Value::String(String::from("hello")),
+            Value::String("a".repeat(300)),
+            Value::List(vec![Value::Int(1), Value::Symbol("a".into())]),
+            Value::Map(OrderedMap::from_iter([(Value::Int(1), Value::Bool(false))])),
+            Value::Described(Box::new(Described {
+                descriptor: Descriptor::Code(0x70),
+                value: Value::List(vec![Value::Bool(true)]),
+            })),
+        ];
+
+        for value in values {
+            let buf = to_vec(&value).unwrap();
+            assert_eq!(encoded_len(&buf).unwrap(), EncodedLen::Complete(buf.len()));
+            for i in 0..buf.len() {
+                assert!(matches!(
+                    encoded_len(&buf[..i]).unwrap(),
+                    EncodedLen::Incomplete(n) if n > 0 && i + n <= buf.len()
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn encoded_len_of_described_constructor_run() {
+        let buf = vec![0x00; 1_000_000];
+        assert_eq!(encoded_len(&buf).unwrap(), EncodedLen::Incomplete(1));
+    }
+
+    #[test]
+    fn decode_byte_by_byte() {
+        let expected = Value::List(vec![Value::String("a".repeat(300)), Value::Long(-1)]);
+        let buf = to_vec(&expected).unwrap();
+
+        let mut decoder = Decoder::new();
+        let mut decoded = None;
+        for byte in &buf {
+            assert!(decoded.is_none());
+            decoder.feed(&[*byte]);
+            if let Decoded::Value(value) = decoder.decode::<Value>().unwrap() {
+                decoded = Some(value);
+            }
+        }
+        assert_eq!(decoded, Some(expected));
+        assert!(decoder.is_empty());
+    }
+
+    #[test]
+    fn decode_consecutive_values() {
+        let mut buf = to_vec(&1u32).unwrap();
+        buf.extend(to_vec(&String::from("second")).unwrap());
+
+        let mut decoder = Decoder::new();
+        decoder.feed(&buf[..buf.len() - 1]);
+        assert_eq!(decoder.decode::<u32>().unwrap(), Decoded::Value(1));
+        assert_eq!(decoder.decode::<String>().unwrap(), Decoded::NeedMore(1));
+        decoder.feed(&buf[buf.len() - 1..]);
+        assert_eq!(
+            decoder.decode::<String>().unwrap(),
+            Decoded::Value(String::from("second"))
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn from_async_reader_does_not_read_past_value() {
+        use super::from_async_reader;
+
+        let mut buf = to_vec(&vec![1i64, 2, 3]).unwrap();
+        buf.extend(to_vec(&true).unwrap());
+
+        let (mut client, server) = tokio::io::duplex(4);
+        let writer = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            client.write_all(&buf).await.unwrap();
+        });
+
+        let mut server = server;
+        let value: Vec<i64> = from_async_reader(&mut server).await.unwrap();
+        assert_eq!(value, vec![1, 2, 3]);
+        let value: bool = from_async_reader(&mut server).await.unwrap();
+        assert!(value);
+        writer.await.unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn from_async_reader_does_not_trust_size_prefix() {
+        use super::from_async_reader;
+
+        // A binary whose size prefix is u32::MAX followed by only a few bytes
+        let buf: &[u8] = &[0xb0, 0xff, 0xff, 0xff, 0xff, 1, 2, 3];
+        let result: Result<Vec<u8>, _> = from_async_reader(buf).await;
+        assert!(matches!(result, Err(crate::Error::Io(_))));
+    }
+}
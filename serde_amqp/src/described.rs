@@ -1,6 +1,6 @@
 //! Definition of `Described<T>` type
 
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 use serde::{de, ser};
 
@@ -57,7 +57,7 @@ struct Visitor<'de, T> {
 impl<'de, T: de::Deserialize<'de>> de::Visitor<'de> for Visitor<'de, T> {
     type Value = Described<T>;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("struct Described")
     }
 
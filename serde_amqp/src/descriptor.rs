@@ -2,6 +2,7 @@
 
 use crate::__constants::DESCRIPTOR;
 use crate::primitives::Symbol;
+use alloc::string::String;
 
 /// Descriptor of a described type
 ///
@@ -27,7 +28,7 @@ pub enum Descriptor {
     Code(u64),
 }
 
-use std::convert::TryInto;
+use core::convert::TryInto;
 
 use serde::de::{self, VariantAccess};
 use serde::ser::Serialize;
@@ -63,7 +64,7 @@ struct FieldVisitor {}
 impl de::Visitor<'_> for FieldVisitor {
     type Value = Field;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("variant identifier")
     }
 
@@ -98,7 +99,7 @@ struct DescriptorVisitor {}
 impl<'de> de::Visitor<'de> for DescriptorVisitor {
     type Value = Descriptor;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("enum Descriptor")
     }
 
@@ -143,7 +144,7 @@ struct PeekDescriptorVisitor {}
 impl de::Visitor<'_> for PeekDescriptorVisitor {
     type Value = PeekDescriptor;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("enum Descriptor")
     }
 
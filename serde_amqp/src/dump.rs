@@ -0,0 +1,664 @@
+//! Annotated dump of AMQP 1.0 encoded bytes for diagnostics
+//!
+//! [`dump`] walks over encoded values without deserializing them into any type and prints one
+//! line per constructor with the byte offset, the encoded bytes, the format code, the size and
+//! count prefixes of compound values, and the value itself. Descriptors are resolved against the
+//! descriptors defined in the AMQP 1.0 specification, and more can be registered with
+//! [`Dumper::descriptor`].
+//!
+//! Malformed input doesn't stop the dump. Everything before the first error is printed, followed
+//! by a line marked with `!!` at the offset where the error occurred. Values nested deeper than
+//! [`MAX_DEPTH`] and arrays whose count of zero-width elements exceeds the bytes of the array
+//! are reported as errors, so that the output stays proportional to the input.
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde_amqp::{described::Described, descriptor::Descriptor, dump, Value};
+//!
+//! let value = Described {
+//!     descriptor: Descriptor::Code(0x73),
+//!     value: Value::List(vec![Value::String("abc".into()), Value::Null, Value::Uint(7)]),
+//! };
+//! let buf = serde_amqp::to_vec(&value).unwrap();
+//! assert_eq!(
+//!     dump::dump(&buf),
+//!     "\
+//! 000000  00                        Described
+//! 000001  53 73                       descriptor: SmallUlong 0x0000_0000:0x0000_0073 (amqp:properties:list)
+//! 000003  c0 09 03                    value: List8 size 9 count 3
+//! 000006  a1 03 61 62 63                [0] Str8 len 3 \"abc\"
+//! 00000b  40                            [1] Null
+//! 00000c  52 07                         [2] SmallUint 7
+//! "
+//! );
+//! ```
+
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+};
+use core::fmt::Write;
+
+use crate::{
+    format_code::EncodingCodes,
+    primitives::{Dec128, Dec32, Dec64, Uuid},
+    Error,
+};
+
+/// The descriptors of the message sections, as `(code, name)`
+pub mod sections {
+    /// Descriptor of the `header` section
+    pub const HEADER: (u64, &str) = (0x70, "amqp:header:list");
+    /// Descriptor of the `delivery-annotations` section
+    pub const DELIVERY_ANNOTATIONS: (u64, &str) = (0x71, "amqp:delivery-annotations:map");
+    /// Descriptor of the `message-annotations` section
+    pub const MESSAGE_ANNOTATIONS: (u64, &str) = (0x72, "amqp:message-annotations:map");
+    /// Descriptor of the `properties` section
+    pub const PROPERTIES: (u64, &str) = (0x73, "amqp:properties:list");
+    /// Descriptor of the `application-properties` section
+    pub const APPLICATION_PROPERTIES: (u64, &str) = (0x74, "amqp:application-properties:map");
+    /// Descriptor of the `data` section
+    pub const DATA: (u64, &str) = (0x75, "amqp:data:binary");
+    /// Descriptor of the `amqp-sequence` section
+    pub const AMQP_SEQUENCE: (u64, &str) = (0x76, "amqp:amqp-sequence:list");
+    /// Descriptor of the `amqp-value` section
+    pub const AMQP_VALUE: (u64, &str) = (0x77, "amqp:amqp-value:*");
+    /// Descriptor of the `footer` section
+    pub const FOOTER: (u64, &str) = (0x78, "amqp:footer:map");
+}
+
+/// The descriptors defined in the AMQP 1.0 specification and the filters registered by
+/// Apache Qpid that are commonly found on the wire
+pub const KNOWN_DESCRIPTORS: &[(u64, &str)] = &[
+    // Transport
+    (0x10, "amqp:open:list"),
+    (0x11, "amqp:begin:list"),
+    (0x12, "amqp:attach:list"),
+    (0x13, "amqp:flow:list"),
+    (0x14, "amqp:transfer:list"),
+    (0x15, "amqp:disposition:list"),
+    (0x16, "amqp:detach:list"),
+    (0x17, "amqp:end:list"),
+    (0x18, "amqp:close:list"),
+    (0x1d, "amqp:error:list"),
+    // Messaging
+    (0x23, "amqp:received:list"),
+    (0x24, "amqp:accepted:list"),
+    (0x25, "amqp:rejected:list"),
+    (0x26, "amqp:released:list"),
+    (0x27, "amqp:modified:list"),
+    (0x28, "amqp:source:list"),
+    (0x29, "amqp:target:list"),
+    (0x2b, "amqp:delete-on-close:list"),
+    (0x2c, "amqp:delete-on-no-links:list"),
+    (0x2d, "amqp:delete-on-no-messages:list"),
+    (0x2e, "amqp:delete-on-no-links-or-messages:list"),
+    sections::HEADER,
+    sections::DELIVERY_ANNOTATIONS,
+    sections::MESSAGE_ANNOTATIONS,
+    sections::PROPERTIES,
+    sections::APPLICATION_PROPERTIES,
+    sections::DATA,
+    sections::AMQP_SEQUENCE,
+    sections::AMQP_VALUE,
+    sections::FOOTER,
+    // Transactions
+    (0x30, "amqp:coordinator:list"),
+    (0x31, "amqp:declare:list"),
+    (0x32, "amqp:discharge:list"),
+    (0x33, "amqp:declared:list"),
+    (0x34, "amqp:transactional-state:list"),
+    // Security
+    (0x40, "amqp:sasl-mechanisms:list"),
+    (0x41, "amqp:sasl-init:list"),
+    (0x42, "amqp:sasl-challenge:list"),
+    (0x43, "amqp:sasl-response:list"),
+    (0x44, "amqp:sasl-outcome:list"),
+    // Filters
+    (
+        0x0000_468c_0000_0000,
+        "apache.org:legacy-amqp-direct-binding:string",
+    ),
+    (
+        0x0000_468c_0000_0001,
+        "apache.org:legacy-amqp-topic-binding:string",
+    ),
+    (
+        0x0000_468c_0000_0002,
+        "apache.org:legacy-amqp-headers-binding:map",
+    ),
+    (0x0000_468c_0000_0003, "apache.org:no-local-filter:list"),
+    (0x0000_468c_0000_0004, "apache.org:selector-filter:string"),
+];
+
+/// Number of bytes shown on each line before the bytes are elided
+const HEX_BYTES: usize = 8;
+
+/// Number of bytes of a binary value shown in the annotation before the bytes are elided
+const BINARY_BYTES: usize = 32;
+
+/// Maximum nesting depth of described and compound values that is printed, or transcoded by
+/// the `json` module
+pub const MAX_DEPTH: usize = 128;
+
+/// Dumps the encoded values in `buf` with the descriptors in [`KNOWN_DESCRIPTORS`]
+///
+/// `buf` may hold any number of concatenated values, eg. the sections of a message.
+pub fn dump(buf: &[u8]) -> String {
+    Dumper::new().dump(buf)
+}
+
+/// Dumps encoded AMQP values with a configurable set of descriptor names
+#[derive(Debug, Clone)]
+pub struct Dumper {
+    names: BTreeMap<u64, Cow<'static, str>>,
+}
+
+impl Default for Dumper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dumper {
+    /// Creates a dumper that resolves the descriptors in [`KNOWN_DESCRIPTORS`]
+    pub fn new() -> Self {
+        let names = KNOWN_DESCRIPTORS
+            .iter()
+            .map(|(code, name)| (*code, Cow::Borrowed(*name)))
+            .collect();
+        Self { names }
+    }
+
+    /// Registers the symbolic name of a descriptor code, eg. a vendor specific filter
+    pub fn descriptor(mut self, code: u64, name: impl Into<Cow<'static, str>>) -> Self {
+        self.names.insert(code, name.into());
+        self
+    }
+
+    /// Dumps the encoded values in `buf`
+    ///
+    /// `buf` may hold any number of concatenated values, eg. the sections of a message.
+    pub fn dump(&self, buf: &[u8]) -> String {
+        let mut printer = Printer {
+            dumper: self,
+            out: String::new(),
+        };
+        let mut cursor = Cursor { buf, pos: 0 };
+        while cursor.pos < buf.len() {
+            if let Err(err) = printer.value(&mut cursor, 0, "", false) {
+                let end = buf.len().min(cursor.pos + HEX_BYTES);
+                printer.line(cursor.pos, &buf[cursor.pos..end], 0, &format!("!! {err}"));
+                break;
+            }
+        }
+        printer.out
+    }
+
+    fn name_of(&self, code: u64) -> Option<&str> {
+        self.names.get(&code).map(|name| name.as_ref())
+    }
+
+    fn code_of(&self, name: &str) -> Option<u64> {
+        self.names
+            .iter()
+            .find(|(_, value)| *value == name)
+            .map(|(code, _)| *code)
+    }
+}
+
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.buf.len() - self.pos < n {
+            return Err(Error::unexpected_eof("Expecting more bytes"));
+        }
+        let bytes = &self.buf[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn read_len(&mut self, code: &EncodingCodes) -> Result<usize, Error> {
+        use EncodingCodes as C;
+
+        match code {
+            C::Vbin8 | C::Str8 | C::Sym8 | C::List8 | C::Map8 | C::Array8 => {
+                Ok(self.read::<1>()?[0] as usize)
+            }
+            _ => Ok(u32::from_be_bytes(self.read()?) as usize),
+        }
+    }
+
+    /// A cursor over the next `size` bytes that keeps the offsets of the whole buffer
+    ///
+    /// The cursor is cut short if there are fewer bytes left, so that the elements of a
+    /// truncated compound value are still printed until the end of the buffer.
+    fn limit(&self, size: usize) -> Cursor<'a> {
+        let end = self.buf.len().min(self.pos.saturating_add(size));
+        Cursor {
+            buf: &self.buf[..end],
+            pos: self.pos,
+        }
+    }
+}
+
+struct Printer<'d> {
+    dumper: &'d Dumper,
+    out: String,
+}
+
+impl Printer<'_> {
+    fn line(&mut self, offset: usize, bytes: &[u8], depth: usize, text: &str) {
+        let _ = write!(self.out, "{offset:06x}  ");
+        let mut hex = String::with_capacity(HEX_BYTES * 3 + 2);
+        for b in bytes.iter().take(HEX_BYTES) {
+            let _ = write!(hex, "{b:02x} ");
+        }
+        if bytes.len() > HEX_BYTES {
+            hex.push_str("..");
+        }
+        let _ = write!(self.out, "{hex:<width$}", width = HEX_BYTES * 3 + 2);
+        for _ in 0..depth {
+            self.out.push_str("  ");
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn value(
+        &mut self,
+        cursor: &mut Cursor<'_>,
+        depth: usize,
+        label: &str,
+        is_descriptor: bool,
+    ) -> Result<(), Error> {
+        check_depth(depth)?;
+        let start = cursor.pos;
+        let code = self.constructor(cursor)?;
+        if code == EncodingCodes::DescribedType {
+            self.line(
+                start,
+                &cursor.buf[start..cursor.pos],
+                depth,
+                &format!("{label}Described"),
+            );
+            self.value(cursor, depth + 1, "descriptor: ", true)?;
+            return self.value(cursor, depth + 1, "value: ", false);
+        }
+        self.body(cursor, start, code, depth, label, is_descriptor)
+    }
+
+    fn constructor(&mut self, cursor: &mut Cursor<'_>) -> Result<EncodingCodes, Error> {
+        let start = cursor.pos;
+        let byte = cursor.read::<1>()?[0];
+        let code = EncodingCodes::try_from(byte);
+        if code.is_err() {
+            cursor.pos = start;
+        }
+        code
+    }
+
+    /// Prints the value that follows the constructor `code` at `start`
+    fn body(
+        &mut self,
+        cursor: &mut Cursor<'_>,
+        start: usize,
+        code: EncodingCodes,
+        depth: usize,
+        label: &str,
+        is_descriptor: bool,
+    ) -> Result<(), Error> {
+        use EncodingCodes as C;
+
+        match code {
+            C::List8 | C::List32 | C::Map8 | C::Map32 | C::Array8 | C::Array32 => {
+                let size = cursor.read_len(&code)?;
+                let mut inner = cursor.limit(size);
+                // Bytes of the array that are actually present in the input
+                let len = inner.buf.len() - inner.pos;
+                let count = inner.read_len(&code)?;
+                let header = &cursor.buf[start..inner.pos];
+                self.line(
+                    start,
+                    header,
+                    depth,
+                    &format!("{label}{code:?} size {size} count {count}"),
+                );
+
+                let result = match code {
+                    C::Array8 | C::Array32 => self.elements(&mut inner, depth + 1, len, count),
+                    _ => (0..count).try_for_each(|i| {
+                        let label = match code {
+                            C::Map8 | C::Map32 if i % 2 == 0 => format!("key[{}]: ", i / 2),
+                            C::Map8 | C::Map32 => format!("value[{}]: ", i / 2),
+                            _ => format!("[{i}] "),
+                        };
+                        self.value(&mut inner, depth + 1, &label, false)
+                    }),
+                };
+                cursor.pos = inner.pos;
+                result?;
+
+                let left = inner.buf.len() - inner.pos;
+                if left > 0 {
+                    let end = inner.buf.len().min(inner.pos + HEX_BYTES);
+                    self.line(
+                        inner.pos,
+                        &inner.buf[inner.pos..end],
+                        depth + 1,
+                        &format!("!! {left} bytes left over after the last element"),
+                    );
+                    cursor.pos = inner.buf.len();
+                }
+            }
+            _ => {
+                let text = self.scalar(&code, cursor, is_descriptor)?;
+                let bytes = &cursor.buf[start..cursor.pos];
+                self.line(start, bytes, depth, &format!("{label}{code:?}{text}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the elements of an array that share the constructor in front of the first element
+    fn elements(
+        &mut self,
+        cursor: &mut Cursor<'_>,
+        depth: usize,
+        len: usize,
+        count: usize,
+    ) -> Result<(), Error> {
+        check_depth(depth)?;
+        let start = cursor.pos;
+        let mut code = self.constructor(cursor)?;
+        let mut constructor_depth = depth;
+        if code == EncodingCodes::DescribedType {
+            self.line(
+                start,
+                &cursor.buf[start..cursor.pos],
+                depth,
+                "element constructor: Described",
+            );
+            self.value(cursor, depth + 1, "descriptor: ", true)?;
+            constructor_depth += 1;
+            code = self.constructor(cursor)?;
+        }
+        let pos = cursor.pos - 1;
+        self.line(
+            pos,
+            &cursor.buf[pos..cursor.pos],
+            constructor_depth,
+            &format!("element constructor: {code:?}"),
+        );
+
+        // Elements without a body don't consume any bytes, so a bogus count would otherwise
+        // print one line per element without ever running out of input
+        if is_zero_width(&code) && count > len {
+            return Err(Error::Message(format!(
+                "Array count {count} of {code:?} elements exceeds the {len} bytes of the array"
+            )));
+        }
+
+        for i in 0..count {
+            let start = cursor.pos;
+            self.body(
+                cursor,
+                start,
+                code.clone(),
+                depth,
+                &format!("[{i}] "),
+                false,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Formats the value of a primitive type, which is preceded by a space
+    fn scalar(
+        &self,
+        code: &EncodingCodes,
+        cursor: &mut Cursor<'_>,
+        is_descriptor: bool,
+    ) -> Result<String, Error> {
+        use EncodingCodes as C;
+
+        let text = match code {
+            C::Null | C::List0 => String::new(),
+            C::BooleanTrue => " true".to_string(),
+            C::BooleanFalse => " false".to_string(),
+            C::Boolean => match cursor.read::<1>()?[0] {
+                0x00 => " false".to_string(),
+                0x01 => " true".to_string(),
+                _ => return Err(Error::InvalidValue),
+            },
+            C::Ubyte => format!(" {}", u8::from_be_bytes(cursor.read()?)),
+            C::Ushort => format!(" {}", u16::from_be_bytes(cursor.read()?)),
+            C::Uint0 => " 0".to_string(),
+            C::SmallUint => format!(" {}", u8::from_be_bytes(cursor.read()?)),
+            C::Uint => format!(" {}", u32::from_be_bytes(cursor.read()?)),
+            C::Ulong0 | C::SmallUlong | C::Ulong => {
+                let value = match code {
+                    C::Ulong0 => 0,
+                    C::SmallUlong => u8::from_be_bytes(cursor.read()?) as u64,
+                    _ => u64::from_be_bytes(cursor.read()?),
+                };
+                match is_descriptor {
+                    true => match self.dumper.name_of(value) {
+                        Some(name) => format!(" {} ({name})", descriptor_code(value)),
+                        None => format!(" {}", descriptor_code(value)),
+                    },
+                    false => format!(" {value}"),
+                }
+            }
+            C::Byte => format!(" {}", i8::from_be_bytes(cursor.read()?)),
+            C::Short => format!(" {}", i16::from_be_bytes(cursor.read()?)),
+            C::SmallInt => format!(" {}", i8::from_be_bytes(cursor.read()?)),
+            C::Int => format!(" {}", i32::from_be_bytes(cursor.read()?)),
+            C::SmallLong => format!(" {}", i8::from_be_bytes(cursor.read()?)),
+            C::Long => format!(" {}", i64::from_be_bytes(cursor.read()?)),
+            C::Float => format!(" {:?}", f32::from_be_bytes(cursor.read()?)),
+            C::Double => format!(" {:?}", f64::from_be_bytes(cursor.read()?)),
+            C::Decimal32 => format!(" {}", Dec32::from(cursor.read::<4>()?)),
+            C::Decimal64 => format!(" {}", Dec64::from(cursor.read::<8>()?)),
+            C::Decimal128 => format!(" {}", Dec128::from(cursor.read::<16>()?)),
+            C::Char => {
+                let code_point = u32::from_be_bytes(cursor.read()?);
+                let c = char::from_u32(code_point).ok_or(Error::InvalidValue)?;
+                format!(" {c:?}")
+            }
+            C::Timestamp => format!(" {}", i64::from_be_bytes(cursor.read()?)),
+            C::Uuid => format!(" {:x}", Uuid::from(cursor.read::<16>()?)),
+            C::Vbin8 | C::Vbin32 => {
+                let len = cursor.read_len(code)?;
+                let bytes = cursor.take(len)?;
+                let mut text = format!(" len {len} 0x");
+                for b in bytes.iter().take(BINARY_BYTES) {
+                    let _ = write!(text, "{b:02x}");
+                }
+                if len > BINARY_BYTES {
+                    text.push_str("..");
+                }
+                text
+            }
+            C::Str8 | C::Str32 | C::Sym8 | C::Sym32 => {
+                let len = cursor.read_len(code)?;
+                let s = core::str::from_utf8(cursor.take(len)?)?;
+                match self.dumper.code_of(s) {
+                    Some(value) if is_descriptor => {
+                        format!(" len {len} {s:?} ({})", descriptor_code(value))
+                    }
+                    _ => format!(" len {len} {s:?}"),
+                }
+            }
+            C::DescribedType
+            | C::List8
+            | C::List32
+            | C::Map8
+            | C::Map32
+            | C::Array8
+            | C::Array32 => return Err(Error::InvalidFormatCode),
+        };
+        Ok(text)
+    }
+}
+
+/// Formats a descriptor code as `domain-id:descriptor-id` like in the specification
+pub(crate) fn check_depth(depth: usize) -> Result<(), Error> {
+    match depth > MAX_DEPTH {
+        true => Err(Error::Message(format!(
+            "Nesting depth exceeds the maximum of {MAX_DEPTH}"
+        ))),
+        false => Ok(()),
+    }
+}
+
+/// Whether the value is fully encoded by the format code
+fn is_zero_width(code: &EncodingCodes) -> bool {
+    use EncodingCodes as C;
+
+    matches!(
+        code,
+        C::Null | C::BooleanTrue | C::BooleanFalse | C::Uint0 | C::Ulong0 | C::List0
+    )
+}
+
+fn descriptor_code(value: u64) -> String {
+    format!(
+        "0x{:04x}_{:04x}:0x{:04x}_{:04x}",
+        value >> 48,
+        (value >> 32) & 0xffff,
+        (value >> 16) & 0xffff,
+        value & 0xffff
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        described::Described,
+        descriptor::Descriptor,
+        primitives::{Array, OrderedMap, Symbol},
+        to_vec, Value,
+    };
+
+    use super::{dump, Dumper, MAX_DEPTH};
+
+    #[test]
+    fn test_dump_map_and_array() {
+        let mut map = OrderedMap::new();
+        map.insert(Value::Symbol(Symbol::from("key")), Value::Long(-1));
+        let value = Value::List(vec![
+            Value::Map(map),
+            Value::Array(Array::from(vec![Value::Uint(1), Value::Uint(300)])),
+        ]);
+        let buf = to_vec(&value).unwrap();
+        let expected = "\
+000000  c0 17 02                  List8 size 23 count 2
+000003  c1 08 02                    [0] Map8 size 8 count 2
+000006  a3 03 6b 65 79                key[0]: Sym8 len 3 \"key\"
+00000b  55 ff                         value[0]: SmallLong -1
+00000d  e0 0a 02                    [1] Array8 size 10 count 2
+000010  70                            element constructor: Uint
+000011  00 00 00 01                   [0] Uint 1
+000015  00 00 01 2c                   [1] Uint 300
+";
+        assert_eq!(dump(&buf), expected);
+    }
+
+    #[test]
+    fn test_dump_symbol_descriptor() {
+        let value = Described {
+            descriptor: Descriptor::Name(Symbol::from("amqp:accepted:list")),
+            value: Value::List(vec![]),
+        };
+        let buf = to_vec(&value).unwrap();
+        let dumped = dump(&buf);
+        assert!(dumped
+            .contains("descriptor: Sym8 len 18 \"amqp:accepted:list\" (0x0000_0000:0x0000_0024)"));
+        assert!(dumped.contains("value: List0"));
+    }
+
+    #[test]
+    fn test_dump_custom_descriptor() {
+        let value = Described {
+            descriptor: Descriptor::Code(0x0000_0137_0000_0001),
+            value: Value::String("x".into()),
+        };
+        let buf = to_vec(&value).unwrap();
+        let dumped = Dumper::new()
+            .descriptor(0x0000_0137_0000_0001, "com.example:filter:string")
+            .dump(&buf);
+        assert!(dumped.contains("Ulong 0x0000_0137:0x0000_0001 (com.example:filter:string)"));
+    }
+
+    #[test]
+    fn test_dump_truncated_input() {
+        let buf = to_vec(&Value::List(vec![Value::Bool(true), Value::Uint(1000)])).unwrap();
+        let dumped = dump(&buf[..buf.len() - 2]);
+        let lines: Vec<&str> = dumped.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains("List8 size 7 count 2"));
+        assert!(lines[1].contains("[0] BooleanTrue true"));
+        assert!(lines[2].starts_with("000005"));
+        assert!(lines[2].contains("!! "));
+    }
+
+    #[test]
+    fn test_dump_invalid_format_code() {
+        let dumped = dump(&[0x40, 0xff, 0x01]);
+        assert_eq!(
+            dumped,
+            "000000  40                        Null\n000001  ff 01                     !! Invalid format code\n"
+        );
+    }
+
+    #[test]
+    fn test_dump_array_count_of_zero_width_elements() {
+        let dumped = dump(&[0xf0, 0x00, 0x00, 0x00, 0x05, 0x01, 0x31, 0x2d, 0x00, 0x40]);
+        let lines: Vec<&str> = dumped.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains("element constructor: Null"));
+        assert!(lines[2].contains("!! Message Array count 20000000 of Null elements exceeds"));
+
+        // A count that fits in the array size is printed as usual
+        let dumped = dump(&[0xe0, 0x02, 0x02, 0x40]);
+        assert!(dumped.contains("[1] Null"));
+
+        // The size prefix is not trusted either
+        let dumped = dump(&[0xf0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xf0, 0x40]);
+        assert!(dumped.contains("exceeds the 5 bytes of the array"));
+    }
+
+    #[test]
+    fn test_dump_nesting_depth() {
+        let mut buf = vec![0x00; 1_000_000];
+        buf.push(0x40);
+        let dumped = dump(&buf);
+        assert_eq!(dumped.lines().count(), MAX_DEPTH + 2);
+        assert!(dumped.ends_with("!! Message Nesting depth exceeds the maximum of 128\n"));
+    }
+
+    #[test]
+    fn test_dump_concatenated_values() {
+        let mut buf = to_vec(&1u8).unwrap();
+        buf.extend(to_vec(&"hi").unwrap());
+        let dumped = dump(&buf);
+        assert_eq!(
+            dumped,
+            "000000  50 01                     Ubyte 1\n000002  a1 02 68 69               Str8 len 2 \"hi\"\n"
+        );
+    }
+}
@@ -1,60 +1,221 @@
 //! Custom error
 
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Display;
 use serde::{de, ser};
-use std::fmt::Display;
+
+use crate::{format_code::EncodingCodes, io};
 
 // pub type Result<T> = core::result::Result<T, Error>;
 
 /// Custom serialization/deserialization errors
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug)]
 pub enum Error {
     /// Custom error with message
-    #[error("Message {0}")]
     Message(String),
 
     /// IO error
-    #[error("IO {0}")]
-    Io(std::io::Error),
+    Io(io::Error),
 
     /// Invalid format code
-    #[error("Invalid format code")]
     InvalidFormatCode,
 
     /// Invalid value
-    #[error("Invalid value")]
     InvalidValue,
 
     /// Found invalid UTF-8 encoding
-    #[error("Invalid UTF-8 encoding")]
     InvalidUtf8Encoding,
 
     /// Sequence type length mismatch
-    #[error("Sequence length mismatch")]
     SequenceLengthMismatch,
 
     /// Length is invalid
-    #[error("Invalid length")]
     InvalidLength,
+
+    /// Found a format code that doesn't match the expected type
+    UnexpectedFormatCode {
+        /// Name of the expected type
+        expected: &'static str,
+
+        /// The format code found in the input
+        found: EncodingCodes,
+    },
+
+    /// An error that occurred while decoding a value nested in a compound or described type
+    Located {
+        /// Path from the outermost value to the value that failed to decode
+        path: Path,
+
+        /// Offset of the value that failed to decode from the start of the input
+        offset: usize,
+
+        /// The error that occurred
+        source: Box<Error>,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Message(msg) => write!(f, "Message {}", msg),
+            Self::Io(err) => write!(f, "IO {}", err),
+            Self::InvalidFormatCode => write!(f, "Invalid format code"),
+            Self::InvalidValue => write!(f, "Invalid value"),
+            Self::InvalidUtf8Encoding => write!(f, "Invalid UTF-8 encoding"),
+            Self::SequenceLengthMismatch => write!(f, "Sequence length mismatch"),
+            Self::InvalidLength => write!(f, "Invalid length"),
+            Self::UnexpectedFormatCode { expected, found } => write!(
+                f,
+                "Invalid format code: expecting {}, found {}",
+                expected, found
+            ),
+            Self::Located {
+                path,
+                offset,
+                source,
+            } => write!(f, "{} at {} (byte offset {})", source, path, offset),
+        }
+    }
+}
+
+impl crate::StdError for Error {
+    fn source(&self) -> Option<&(dyn crate::StdError + 'static)> {
+        match self {
+            Self::Located { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 impl Error {
     pub(crate) fn too_long() -> Self {
-        let io_err = std::io::Error::new(std::io::ErrorKind::Other, "Too long");
+        let io_err = io::Error::new(io::ErrorKind::Other, "Too long");
         Self::Io(io_err)
     }
 
-    pub(crate) fn unexpected_eof(
-        error: impl Into<Box<dyn std::error::Error + Send + Sync>>,
-    ) -> Self {
-        let io_err = std::io::Error::new(std::io::ErrorKind::UnexpectedEof, error);
+    pub(crate) fn unexpected_eof(error: impl Into<Box<dyn crate::StdError + Send + Sync>>) -> Self {
+        let io_err = io::Error::new(io::ErrorKind::UnexpectedEof, error);
         Self::Io(io_err)
     }
+
+    pub(crate) fn unexpected_format_code(expected: &'static str, found: EncodingCodes) -> Self {
+        Self::UnexpectedFormatCode { expected, found }
+    }
+
+    /// Adds the segment of the value that failed to decode in front of the path
+    pub(crate) fn at(self, segment: Segment, offset: usize) -> Self {
+        match self {
+            Self::Located {
+                mut path,
+                offset,
+                source,
+            } => {
+                path.0.insert(0, segment);
+                Self::Located {
+                    path,
+                    offset,
+                    source,
+                }
+            }
+            source => Self::Located {
+                path: Path(vec![segment]),
+                offset,
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// Path to the value that failed to decode if available
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Located { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Offset of the value that failed to decode from the start of the input if available
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::Located { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Get a reference to the error without the location
+    pub fn inner(&self) -> &Error {
+        match self {
+            Self::Located { source, .. } => source,
+            _ => self,
+        }
+    }
+
+    /// Consumes the error and returns the error without the location
+    pub fn into_inner(self) -> Error {
+        match self {
+            Self::Located { source, .. } => *source,
+            _ => self,
+        }
+    }
+}
+
+/// Path from the outermost value to a nested value
+///
+/// The path is displayed like `source.filter["selector"]`, where the named fields of a described
+/// list are separated by `.`, and the elements of a list or an array are indexed by their
+/// position. Map entries are indexed by their key if the key is a string or a symbol, and by
+/// their position (eg. `[#2]`) otherwise. The key is only known when decoding from a slice or
+/// [`Bytes`](bytes::Bytes) because it is recovered from the input once the value of the entry
+/// has failed to decode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Path(Vec<Segment>);
+
+impl Path {
+    /// Get the segments of the path
+    pub fn segments(&self) -> &[Segment] {
+        &self.0
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                Segment::Field(name) if i == 0 => write!(f, "{}", name)?,
+                Segment::Field(name) => write!(f, ".{}", name)?,
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+                Segment::Key(key) => write!(f, "[{:?}]", key)?,
+                Segment::Entry(index) => write!(f, "[#{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A segment of a [`Path`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// A named field of a described type
+    Field(&'static str),
+
+    /// Position of an element in a list or an array, or of an unnamed field of a described type
+    Index(usize),
+
+    /// Key of a map entry
+    Key(String),
+
+    /// Position of a map entry whose key is not a string or a symbol
+    Entry(usize),
 }
 
 impl ser::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
-        T: std::fmt::Display,
+        T: core::fmt::Display,
     {
         Self::Message(msg.to_string())
     }
@@ -69,20 +230,20 @@ impl de::Error for Error {
     }
 }
 
-impl From<std::io::Error> for Error {
-    fn from(err: std::io::Error) -> Self {
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
         Self::Io(err)
     }
 }
 
-impl From<std::string::FromUtf8Error> for Error {
-    fn from(_: std::string::FromUtf8Error) -> Self {
+impl From<alloc::string::FromUtf8Error> for Error {
+    fn from(_: alloc::string::FromUtf8Error) -> Self {
         Error::InvalidUtf8Encoding
     }
 }
 
-impl From<std::str::Utf8Error> for Error {
-    fn from(_: std::str::Utf8Error) -> Self {
+impl From<core::str::Utf8Error> for Error {
+    fn from(_: core::str::Utf8Error) -> Self {
         Error::InvalidUtf8Encoding
     }
 }
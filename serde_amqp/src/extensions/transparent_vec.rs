@@ -1,6 +1,7 @@
 //! Implement transparent vec
 
-use std::{
+use alloc::vec::Vec;
+use core::{
     marker::PhantomData,
     ops::{Deref, DerefMut},
 };
@@ -63,7 +64,7 @@ impl<T> DerefMut for TransparentVec<T> {
 impl<T> IntoIterator for TransparentVec<T> {
     type Item = T;
 
-    type IntoIter = std::vec::IntoIter<T>;
+    type IntoIter = alloc::vec::IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
@@ -73,7 +74,7 @@ impl<T> IntoIterator for TransparentVec<T> {
 impl<'a, T> IntoIterator for &'a TransparentVec<T> {
     type Item = &'a T;
 
-    type IntoIter = std::slice::Iter<'a, T>;
+    type IntoIter = core::slice::Iter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.iter()
@@ -83,7 +84,7 @@ impl<'a, T> IntoIterator for &'a TransparentVec<T> {
 impl<'a, T> IntoIterator for &'a mut TransparentVec<T> {
     type Item = &'a mut T;
 
-    type IntoIter = std::slice::IterMut<'a, T>;
+    type IntoIter = core::slice::IterMut<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.iter_mut()
@@ -116,7 +117,7 @@ struct Visitor<T> {
 impl<'de, T: de::Deserialize<'de>> de::Visitor<'de> for Visitor<T> {
     type Value = TransparentVec<T>;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("struct Array")
     }
 
@@ -1,6 +1,6 @@
 //! Encoding codes of AMQP types
 
-use std::{convert::TryFrom, fmt::Display};
+use core::{convert::TryFrom, fmt::Display};
 
 use crate::error::Error;
 
@@ -89,7 +89,7 @@ pub enum EncodingCodes {
 }
 
 impl Display for EncodingCodes {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:?}:0x{:x}", self, self.clone() as u8)
     }
 }
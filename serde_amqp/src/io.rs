@@ -0,0 +1,118 @@
+//! Minimal IO abstractions
+//!
+//! With the `"std"` feature, these are re-exports from `std::io`. Without the `"std"` feature, a
+//! minimal [`Write`] trait and [`Error`] type are provided in place of `std::io` so that the
+//! serializer can be used on `no_std` targets with `alloc`.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::core_io::{Error, ErrorKind, Write};
+
+#[cfg(not(feature = "std"))]
+mod core_io {
+    use alloc::{boxed::Box, vec::Vec};
+    use core::fmt::Display;
+
+    /// General categories of IO errors, a subset of `std::io::ErrorKind`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        /// An operation could not be completed because the end of the input was reached
+        /// prematurely
+        UnexpectedEof,
+
+        /// A write returned `Ok(0)`
+        WriteZero,
+
+        /// Data not valid for the operation were encountered
+        InvalidData,
+
+        /// Any error that doesn't fall under the other kinds
+        Other,
+    }
+
+    impl ErrorKind {
+        fn as_str(&self) -> &'static str {
+            match self {
+                ErrorKind::UnexpectedEof => "unexpected end of file",
+                ErrorKind::WriteZero => "write zero",
+                ErrorKind::InvalidData => "invalid data",
+                ErrorKind::Other => "other error",
+            }
+        }
+    }
+
+    /// The error type of the minimal IO abstractions
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        error: Option<Box<dyn core::error::Error + Send + Sync>>,
+    }
+
+    impl Error {
+        /// Creates a new IO error from a kind and an arbitrary error payload
+        pub fn new<E>(kind: ErrorKind, error: E) -> Self
+        where
+            E: Into<Box<dyn core::error::Error + Send + Sync>>,
+        {
+            Self {
+                kind,
+                error: Some(error.into()),
+            }
+        }
+
+        /// Returns the kind of the error
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self {
+            Self { kind, error: None }
+        }
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match &self.error {
+                Some(error) => Display::fmt(error, f),
+                None => f.write_str(self.kind.as_str()),
+            }
+        }
+    }
+
+    impl core::error::Error for Error {}
+
+    /// A minimal version of `std::io::Write`
+    pub trait Write {
+        /// Writes a buffer into this writer, returning how many bytes were written
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error>;
+
+        /// Attempts to write an entire buffer into this writer
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<(), Error> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => return Err(Error::from(ErrorKind::WriteZero)),
+                    n => buf = &buf[n..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            (**self).write(buf)
+        }
+    }
+}
@@ -0,0 +1,1221 @@
+//! Type-preserving transcoding between the AMQP 1.0 encoding and JSON
+//!
+//! Unlike the conversion from `serde_json::Value` into [`Value`](crate::Value), the JSON
+//! representation defined here keeps the AMQP type and the encoding of every value, so that
+//! [`encode`] reproduces exactly the bytes that were given to [`decode`]. This allows encoded
+//! messages to be dumped, inspected or edited as JSON and then re-injected.
+//!
+//! # Format
+//!
+//! Every value is a JSON object with a single key naming the AMQP type, eg. `{"ubyte": 1}` or
+//! `{"symbol": "amqp:accepted:list"}`. When a value doesn't use the smallest encoding of its type,
+//! the name of the encoding in the specification is kept under the `"encoding"` key, eg.
+//! `{"uint": 0, "encoding": "uint"}` instead of the default `uint0`.
+//!
+//! | AMQP type | JSON value |
+//! |-----------|------------|
+//! | `null` | `null` |
+//! | `boolean` | `true` or `false` |
+//! | `ubyte`, `ushort`, `uint`, `ulong`, `byte`, `short`, `int`, `long` | number |
+//! | `float`, `double` | number, `"NaN"`, `"Infinity"` or `"-Infinity"`, or the bits in hex (eg. `"0x7fc00001"`) for other NaNs |
+//! | `decimal32`, `decimal64`, `decimal128` | string of the decimal (eg. `"1.50"`), or the bits in hex for non-canonical encodings |
+//! | `char` | string of a single character |
+//! | `timestamp` | number of milliseconds since the unix epoch |
+//! | `uuid` | hyphenated string |
+//! | `binary` | hex string |
+//! | `string`, `symbol` | string |
+//! | `list` | array of values |
+//! | `map` | array of `[key, value]` pairs in the encoded order |
+//! | `array` | object with the element `"type"` and the `"values"` without the type key, and optionally the element `"encoding"` and the `"descriptor"` of described elements |
+//! | `described` | object with the `"descriptor"` and the `"value"` |
+//!
+//! The elements of an `array` share one constructor, which by default is the widest encoding of
+//! the element type (eg. `uint` rather than `smalluint`) just like the serializer.
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde_amqp::{json, primitives::Symbol, to_vec};
+//!
+//! let buf = to_vec(&(Symbol::from("amqp"), 1u8, 1i64)).unwrap();
+//! let value = json::decode(&buf).unwrap();
+//! assert_eq!(
+//!     value.to_string(),
+//!     r#"{"list":[{"symbol":"amqp"},{"ubyte":1},{"long":1}]}"#
+//! );
+//! assert_eq!(json::encode(&value).unwrap(), buf);
+//! ```
+
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    dump::check_depth,
+    error::Error,
+    format_code::EncodingCodes,
+    primitives::{Dec128, Dec32, Dec64},
+};
+
+const TYPE: &str = "type";
+const ENCODING: &str = "encoding";
+const DESCRIPTOR: &str = "descriptor";
+const VALUE: &str = "value";
+const VALUES: &str = "values";
+const DESCRIBED: &str = "described";
+
+/// All encoding codes except the described type constructor
+const CODES: [EncodingCodes; 39] = [
+    EncodingCodes::Null,
+    EncodingCodes::Boolean,
+    EncodingCodes::BooleanTrue,
+    EncodingCodes::BooleanFalse,
+    EncodingCodes::Ubyte,
+    EncodingCodes::Ushort,
+    EncodingCodes::Uint,
+    EncodingCodes::SmallUint,
+    EncodingCodes::Uint0,
+    EncodingCodes::Ulong,
+    EncodingCodes::SmallUlong,
+    EncodingCodes::Ulong0,
+    EncodingCodes::Byte,
+    EncodingCodes::Short,
+    EncodingCodes::Int,
+    EncodingCodes::SmallInt,
+    EncodingCodes::Long,
+    EncodingCodes::SmallLong,
+    EncodingCodes::Float,
+    EncodingCodes::Double,
+    EncodingCodes::Decimal32,
+    EncodingCodes::Decimal64,
+    EncodingCodes::Decimal128,
+    EncodingCodes::Char,
+    EncodingCodes::Timestamp,
+    EncodingCodes::Uuid,
+    EncodingCodes::Vbin8,
+    EncodingCodes::Vbin32,
+    EncodingCodes::Str8,
+    EncodingCodes::Str32,
+    EncodingCodes::Sym8,
+    EncodingCodes::Sym32,
+    EncodingCodes::List0,
+    EncodingCodes::List8,
+    EncodingCodes::List32,
+    EncodingCodes::Map8,
+    EncodingCodes::Map32,
+    EncodingCodes::Array8,
+    EncodingCodes::Array32,
+];
+
+/// Transcodes a single encoded AMQP value into its JSON representation
+///
+/// Returns an error if `buf` doesn't contain exactly one valid encoded value or if described and
+/// compound values are nested deeper than [`MAX_DEPTH`](crate::dump::MAX_DEPTH).
+pub fn decode(buf: &[u8]) -> Result<Value, Error> {
+    let mut cursor = Cursor(buf);
+    let value = decode_value(&mut cursor, 0)?;
+    cursor.finish()?;
+    Ok(value)
+}
+
+/// Transcodes concatenated encoded AMQP values, eg. the sections of a message, into their JSON
+/// representations
+pub fn decode_all(buf: &[u8]) -> Result<Vec<Value>, Error> {
+    let mut cursor = Cursor(buf);
+    let mut values = Vec::new();
+    while !cursor.0.is_empty() {
+        values.push(decode_value(&mut cursor, 0)?);
+    }
+    Ok(values)
+}
+
+/// Encodes the JSON representation of an AMQP value
+///
+/// Returns an error if described and compound values are nested deeper than
+/// [`MAX_DEPTH`](crate::dump::MAX_DEPTH).
+pub fn encode(value: &Value) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    encode_value(value, &mut buf, 0)?;
+    Ok(buf)
+}
+
+/// Encodes the JSON representations of AMQP values one after another, eg. the sections of a
+/// message
+pub fn encode_all<'a>(values: impl IntoIterator<Item = &'a Value>) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    for value in values {
+        encode_value(value, &mut buf, 0)?;
+    }
+    Ok(buf)
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                   Decode                                   */
+/* -------------------------------------------------------------------------- */
+
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < n {
+            return Err(Error::unexpected_eof("Expecting more bytes"));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn read_len(&mut self, width: usize) -> Result<usize, Error> {
+        match width {
+            1 => Ok(self.read::<1>()?[0] as usize),
+            _ => Ok(u32::from_be_bytes(self.read()?) as usize),
+        }
+    }
+
+    fn finish(&self) -> Result<(), Error> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(Error::InvalidLength),
+        }
+    }
+}
+
+fn decode_value(cursor: &mut Cursor<'_>, depth: usize) -> Result<Value, Error> {
+    check_depth(depth)?;
+    let code = cursor.read::<1>()?[0];
+    if code == EncodingCodes::DescribedType as u8 {
+        let mut described = Map::new();
+        described.insert(DESCRIPTOR.into(), decode_value(cursor, depth + 1)?);
+        described.insert(VALUE.into(), decode_value(cursor, depth + 1)?);
+        return Ok(tagged(DESCRIBED, Value::Object(described), None));
+    }
+
+    let code = EncodingCodes::try_from(code)?;
+    let body = Body::read(&code, cursor)?;
+    let encoding = (code != body.smallest_code()).then(|| encoding_name(&code));
+    Ok(tagged(type_name(&code), body.to_json(depth)?, encoding))
+}
+
+fn tagged(ty: &str, value: Value, encoding: Option<&str>) -> Value {
+    let mut map = Map::new();
+    map.insert(ty.into(), value);
+    if let Some(encoding) = encoding {
+        map.insert(ENCODING.into(), Value::String(encoding.into()));
+    }
+    Value::Object(map)
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                   Encode                                   */
+/* -------------------------------------------------------------------------- */
+
+fn encode_value(value: &Value, buf: &mut Vec<u8>, depth: usize) -> Result<(), Error> {
+    check_depth(depth)?;
+    let (ty, inner, encoding) = untag(value)?;
+    if ty == DESCRIBED {
+        if encoding.is_some() {
+            return Err(invalid(DESCRIBED, value));
+        }
+        let described = inner.as_object().ok_or_else(|| invalid(DESCRIBED, inner))?;
+        buf.push(EncodingCodes::DescribedType as u8);
+        encode_value(field(described, DESCRIPTOR)?, buf, depth + 1)?;
+        return encode_value(field(described, VALUE)?, buf, depth + 1);
+    }
+
+    let body = Body::from_json(ty, inner, depth)?;
+    let code = match encoding {
+        Some(name) => code_by_name(ty, name)?,
+        None => body.smallest_code(),
+    };
+    buf.push(code.clone() as u8);
+    body.write(&code, buf)
+}
+
+/// Splits the JSON representation into the type, the untagged value and the encoding
+fn untag(value: &Value) -> Result<(&str, &Value, Option<&str>), Error> {
+    let map = value
+        .as_object()
+        .ok_or_else(|| Error::Message(format!("Expecting a tagged AMQP value, found {value}")))?;
+    let encoding = match map.get(ENCODING) {
+        Some(Value::String(encoding)) => Some(encoding.as_str()),
+        Some(_) => return Err(invalid(ENCODING, value)),
+        None => None,
+    };
+    let mut entries = map.iter().filter(|(key, _)| key.as_str() != ENCODING);
+    match (entries.next(), entries.next()) {
+        (Some((ty, inner)), None) => Ok((ty, inner, encoding)),
+        _ => Err(Error::Message(format!(
+            "Expecting a single AMQP type, found {value}"
+        ))),
+    }
+}
+
+fn field<'a>(map: &'a Map<String, Value>, key: &str) -> Result<&'a Value, Error> {
+    map.get(key)
+        .ok_or_else(|| Error::Message(format!("Missing field \"{key}\"")))
+}
+
+fn invalid(ty: &str, value: &Value) -> Error {
+    Error::Message(format!("Invalid {ty}: {value}"))
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                    Body                                    */
+/* -------------------------------------------------------------------------- */
+
+/// The value of an encoded AMQP value without the constructor
+///
+/// Compound values keep the encoded content (ie. the elements) after the count.
+enum Body<'a> {
+    Null,
+    Boolean(bool),
+    Ubyte(u8),
+    Ushort(u16),
+    Uint(u32),
+    Ulong(u64),
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Decimal32([u8; 4]),
+    Decimal64([u8; 8]),
+    Decimal128([u8; 16]),
+    Char(char),
+    Timestamp(i64),
+    Uuid([u8; 16]),
+    Binary(Cow<'a, [u8]>),
+    String(Cow<'a, str>),
+    Symbol(Cow<'a, str>),
+    List {
+        count: usize,
+        content: Cow<'a, [u8]>,
+    },
+    Map {
+        count: usize,
+        content: Cow<'a, [u8]>,
+    },
+    Array {
+        count: usize,
+        content: Cow<'a, [u8]>,
+    },
+}
+
+impl<'a> Body<'a> {
+    fn read(code: &EncodingCodes, cursor: &mut Cursor<'a>) -> Result<Self, Error> {
+        use EncodingCodes as C;
+
+        let body = match code {
+            C::DescribedType => return Err(Error::InvalidFormatCode),
+            C::Null => Body::Null,
+            C::BooleanTrue => Body::Boolean(true),
+            C::BooleanFalse => Body::Boolean(false),
+            C::Boolean => match cursor.read::<1>()?[0] {
+                0x00 => Body::Boolean(false),
+                0x01 => Body::Boolean(true),
+                _ => return Err(Error::InvalidValue),
+            },
+            C::Ubyte => Body::Ubyte(u8::from_be_bytes(cursor.read()?)),
+            C::Ushort => Body::Ushort(u16::from_be_bytes(cursor.read()?)),
+            C::Uint0 => Body::Uint(0),
+            C::SmallUint => Body::Uint(u8::from_be_bytes(cursor.read()?) as u32),
+            C::Uint => Body::Uint(u32::from_be_bytes(cursor.read()?)),
+            C::Ulong0 => Body::Ulong(0),
+            C::SmallUlong => Body::Ulong(u8::from_be_bytes(cursor.read()?) as u64),
+            C::Ulong => Body::Ulong(u64::from_be_bytes(cursor.read()?)),
+            C::Byte => Body::Byte(i8::from_be_bytes(cursor.read()?)),
+            C::Short => Body::Short(i16::from_be_bytes(cursor.read()?)),
+            C::SmallInt => Body::Int(i8::from_be_bytes(cursor.read()?) as i32),
+            C::Int => Body::Int(i32::from_be_bytes(cursor.read()?)),
+            C::SmallLong => Body::Long(i8::from_be_bytes(cursor.read()?) as i64),
+            C::Long => Body::Long(i64::from_be_bytes(cursor.read()?)),
+            C::Float => Body::Float(f32::from_be_bytes(cursor.read()?)),
+            C::Double => Body::Double(f64::from_be_bytes(cursor.read()?)),
+            C::Decimal32 => Body::Decimal32(cursor.read()?),
+            C::Decimal64 => Body::Decimal64(cursor.read()?),
+            C::Decimal128 => Body::Decimal128(cursor.read()?),
+            C::Char => {
+                let code_point = u32::from_be_bytes(cursor.read()?);
+                Body::Char(char::from_u32(code_point).ok_or(Error::InvalidValue)?)
+            }
+            C::Timestamp => Body::Timestamp(i64::from_be_bytes(cursor.read()?)),
+            C::Uuid => Body::Uuid(cursor.read()?),
+            C::Vbin8 | C::Vbin32 => {
+                let len = cursor.read_len(width(code))?;
+                Body::Binary(Cow::Borrowed(cursor.take(len)?))
+            }
+            C::Str8 | C::Str32 | C::Sym8 | C::Sym32 => {
+                let len = cursor.read_len(width(code))?;
+                let s = Cow::Borrowed(std::str::from_utf8(cursor.take(len)?)?);
+                match code {
+                    C::Str8 | C::Str32 => Body::String(s),
+                    _ => Body::Symbol(s),
+                }
+            }
+            C::List0 => Body::List {
+                count: 0,
+                content: Cow::Borrowed(&[]),
+            },
+            C::List8 | C::List32 | C::Map8 | C::Map32 | C::Array8 | C::Array32 => {
+                let width = width(code);
+                let size = cursor.read_len(width)?;
+                let mut inner = Cursor(cursor.take(size)?);
+                let count = inner.read_len(width)?;
+                let content = Cow::Borrowed(inner.0);
+                match code {
+                    C::List8 | C::List32 => Body::List { count, content },
+                    C::Map8 | C::Map32 => Body::Map { count, content },
+                    _ => Body::Array { count, content },
+                }
+            }
+        };
+        Ok(body)
+    }
+
+    /// Writes the value without the constructor, assuming the constructor is `code`
+    fn write(&self, code: &EncodingCodes, buf: &mut Vec<u8>) -> Result<(), Error> {
+        use EncodingCodes as C;
+
+        match (self, code) {
+            (Body::Null, C::Null)
+            | (Body::Boolean(true), C::BooleanTrue)
+            | (Body::Boolean(false), C::BooleanFalse)
+            | (Body::Uint(0), C::Uint0)
+            | (Body::Ulong(0), C::Ulong0) => {}
+            (Body::Boolean(value), C::Boolean) => buf.push(*value as u8),
+            (Body::Ubyte(value), C::Ubyte) => buf.push(*value),
+            (Body::Ushort(value), C::Ushort) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Uint(value), C::SmallUint) if *value <= u8::MAX as u32 => buf.push(*value as u8),
+            (Body::Uint(value), C::Uint) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Ulong(value), C::SmallUlong) if *value <= u8::MAX as u64 => {
+                buf.push(*value as u8)
+            }
+            (Body::Ulong(value), C::Ulong) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Byte(value), C::Byte) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Short(value), C::Short) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Int(value), C::SmallInt) if i8::try_from(*value).is_ok() => {
+                buf.push(*value as i8 as u8)
+            }
+            (Body::Int(value), C::Int) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Long(value), C::SmallLong) if i8::try_from(*value).is_ok() => {
+                buf.push(*value as i8 as u8)
+            }
+            (Body::Long(value), C::Long) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Float(value), C::Float) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Double(value), C::Double) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Decimal32(bytes), C::Decimal32) => buf.extend_from_slice(bytes),
+            (Body::Decimal64(bytes), C::Decimal64) => buf.extend_from_slice(bytes),
+            (Body::Decimal128(bytes), C::Decimal128) => buf.extend_from_slice(bytes),
+            (Body::Char(value), C::Char) => buf.extend_from_slice(&(*value as u32).to_be_bytes()),
+            (Body::Timestamp(value), C::Timestamp) => buf.extend_from_slice(&value.to_be_bytes()),
+            (Body::Uuid(bytes), C::Uuid) => buf.extend_from_slice(bytes),
+            (Body::Binary(bytes), C::Vbin8 | C::Vbin32) => {
+                write_len(width(code), bytes.len(), buf)?;
+                buf.extend_from_slice(bytes);
+            }
+            (Body::String(s), C::Str8 | C::Str32) | (Body::Symbol(s), C::Sym8 | C::Sym32) => {
+                write_len(width(code), s.len(), buf)?;
+                buf.extend_from_slice(s.as_bytes());
+            }
+            (Body::List { count: 0, content }, C::List0) if content.is_empty() => {}
+            (Body::List { count, content }, C::List8 | C::List32)
+            | (Body::Map { count, content }, C::Map8 | C::Map32)
+            | (Body::Array { count, content }, C::Array8 | C::Array32) => {
+                let width = width(code);
+                write_len(width, width + content.len(), buf)?;
+                write_len(width, *count, buf)?;
+                buf.extend_from_slice(content);
+            }
+            _ => {
+                return Err(Error::Message(format!(
+                    "{} cannot be encoded as {}",
+                    self.type_name(),
+                    encoding_name(code)
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// The smallest encoding of the value
+    fn smallest_code(&self) -> EncodingCodes {
+        use EncodingCodes as C;
+
+        let fits_u8 = |len: usize| len <= u8::MAX as usize;
+        match self {
+            Body::Null => C::Null,
+            Body::Boolean(true) => C::BooleanTrue,
+            Body::Boolean(false) => C::BooleanFalse,
+            Body::Ubyte(_) => C::Ubyte,
+            Body::Ushort(_) => C::Ushort,
+            Body::Uint(0) => C::Uint0,
+            Body::Uint(value) if *value <= u8::MAX as u32 => C::SmallUint,
+            Body::Uint(_) => C::Uint,
+            Body::Ulong(0) => C::Ulong0,
+            Body::Ulong(value) if *value <= u8::MAX as u64 => C::SmallUlong,
+            Body::Ulong(_) => C::Ulong,
+            Body::Byte(_) => C::Byte,
+            Body::Short(_) => C::Short,
+            Body::Int(value) if i8::try_from(*value).is_ok() => C::SmallInt,
+            Body::Int(_) => C::Int,
+            Body::Long(value) if i8::try_from(*value).is_ok() => C::SmallLong,
+            Body::Long(_) => C::Long,
+            Body::Float(_) => C::Float,
+            Body::Double(_) => C::Double,
+            Body::Decimal32(_) => C::Decimal32,
+            Body::Decimal64(_) => C::Decimal64,
+            Body::Decimal128(_) => C::Decimal128,
+            Body::Char(_) => C::Char,
+            Body::Timestamp(_) => C::Timestamp,
+            Body::Uuid(_) => C::Uuid,
+            Body::Binary(bytes) if fits_u8(bytes.len()) => C::Vbin8,
+            Body::Binary(_) => C::Vbin32,
+            Body::String(s) if fits_u8(s.len()) => C::Str8,
+            Body::String(_) => C::Str32,
+            Body::Symbol(s) if fits_u8(s.len()) => C::Sym8,
+            Body::Symbol(_) => C::Sym32,
+            Body::List { count: 0, content } if content.is_empty() => C::List0,
+            Body::List { count, content } if fits_u8(*count) && fits_u8(1 + content.len()) => {
+                C::List8
+            }
+            Body::List { .. } => C::List32,
+            Body::Map { count, content } if fits_u8(*count) && fits_u8(1 + content.len()) => {
+                C::Map8
+            }
+            Body::Map { .. } => C::Map32,
+            Body::Array { count, content } if fits_u8(*count) && fits_u8(1 + content.len()) => {
+                C::Array8
+            }
+            Body::Array { .. } => C::Array32,
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name(&self.smallest_code())
+    }
+
+    /// `depth` is the nesting depth of the value that this is the body of
+    fn to_json(&self, depth: usize) -> Result<Value, Error> {
+        let value = match self {
+            Body::Null => Value::Null,
+            Body::Boolean(value) => Value::Bool(*value),
+            Body::Ubyte(value) => Value::from(*value),
+            Body::Ushort(value) => Value::from(*value),
+            Body::Uint(value) => Value::from(*value),
+            Body::Ulong(value) => Value::from(*value),
+            Body::Byte(value) => Value::from(*value),
+            Body::Short(value) => Value::from(*value),
+            Body::Int(value) => Value::from(*value),
+            Body::Long(value) => Value::from(*value),
+            Body::Float(value) => f32_to_json(*value),
+            Body::Double(value) => f64_to_json(*value),
+            Body::Decimal32(bytes) => decimal_to_json::<Dec32, 4>(*bytes),
+            Body::Decimal64(bytes) => decimal_to_json::<Dec64, 8>(*bytes),
+            Body::Decimal128(bytes) => decimal_to_json::<Dec128, 16>(*bytes),
+            Body::Char(value) => Value::String(value.to_string()),
+            Body::Timestamp(value) => Value::from(*value),
+            Body::Uuid(bytes) => Value::String(uuid_to_string(bytes)),
+            Body::Binary(bytes) => Value::String(to_hex(bytes)),
+            Body::String(s) | Body::Symbol(s) => Value::String(s.to_string()),
+            Body::List { count, content } => {
+                let mut cursor = Cursor(content);
+                let items = (0..*count)
+                    .map(|_| decode_value(&mut cursor, depth + 1))
+                    .collect::<Result<Vec<_>, _>>()?;
+                cursor.finish()?;
+                Value::Array(items)
+            }
+            Body::Map { count, content } => {
+                if count % 2 != 0 {
+                    return Err(Error::InvalidLength);
+                }
+                let mut cursor = Cursor(content);
+                let entries = (0..count / 2)
+                    .map(|_| {
+                        let key = decode_value(&mut cursor, depth + 1)?;
+                        let value = decode_value(&mut cursor, depth + 1)?;
+                        Ok(Value::Array(vec![key, value]))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                cursor.finish()?;
+                Value::Array(entries)
+            }
+            Body::Array { count, content } => array_to_json(*count, content, depth + 1)?,
+        };
+        Ok(value)
+    }
+
+    /// `depth` is the nesting depth of the value that this is the body of
+    fn from_json(ty: &str, value: &'a Value, depth: usize) -> Result<Self, Error> {
+        let body = match ty {
+            "null" => match value {
+                Value::Null => Body::Null,
+                _ => return Err(invalid(ty, value)),
+            },
+            "boolean" => Body::Boolean(value.as_bool().ok_or_else(|| invalid(ty, value))?),
+            "ubyte" => Body::Ubyte(integer(ty, value)?),
+            "ushort" => Body::Ushort(integer(ty, value)?),
+            "uint" => Body::Uint(integer(ty, value)?),
+            "ulong" => Body::Ulong(integer(ty, value)?),
+            "byte" => Body::Byte(integer(ty, value)?),
+            "short" => Body::Short(integer(ty, value)?),
+            "int" => Body::Int(integer(ty, value)?),
+            "long" => Body::Long(integer(ty, value)?),
+            "float" => Body::Float(f32_from_json(value).ok_or_else(|| invalid(ty, value))?),
+            "double" => Body::Double(f64_from_json(value).ok_or_else(|| invalid(ty, value))?),
+            "decimal32" => Body::Decimal32(decimal_from_json::<Dec32, 4>(ty, value)?),
+            "decimal64" => Body::Decimal64(decimal_from_json::<Dec64, 8>(ty, value)?),
+            "decimal128" => Body::Decimal128(decimal_from_json::<Dec128, 16>(ty, value)?),
+            "char" => {
+                let mut chars = value.as_str().map(str::chars).into_iter().flatten();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Body::Char(c),
+                    _ => return Err(invalid(ty, value)),
+                }
+            }
+            "timestamp" => Body::Timestamp(integer(ty, value)?),
+            "uuid" => Body::Uuid(
+                value
+                    .as_str()
+                    .and_then(uuid_from_str)
+                    .ok_or_else(|| invalid(ty, value))?,
+            ),
+            "binary" => Body::Binary(Cow::Owned(
+                value
+                    .as_str()
+                    .and_then(from_hex)
+                    .ok_or_else(|| invalid(ty, value))?,
+            )),
+            "string" => Body::String(Cow::Borrowed(str_from_json(ty, value)?)),
+            "symbol" => Body::Symbol(Cow::Borrowed(str_from_json(ty, value)?)),
+            "list" => {
+                let items = value.as_array().ok_or_else(|| invalid(ty, value))?;
+                let mut content = Vec::new();
+                for item in items {
+                    encode_value(item, &mut content, depth + 1)?;
+                }
+                Body::List {
+                    count: items.len(),
+                    content: Cow::Owned(content),
+                }
+            }
+            "map" => {
+                let entries = value.as_array().ok_or_else(|| invalid(ty, value))?;
+                let mut content = Vec::new();
+                for entry in entries {
+                    match entry.as_array().map(Vec::as_slice) {
+                        Some([key, value]) => {
+                            encode_value(key, &mut content, depth + 1)?;
+                            encode_value(value, &mut content, depth + 1)?;
+                        }
+                        _ => return Err(invalid("map entry", entry)),
+                    }
+                }
+                Body::Map {
+                    count: entries.len() * 2,
+                    content: Cow::Owned(content),
+                }
+            }
+            "array" => array_from_json(value, depth + 1)?,
+            _ => return Err(Error::Message(format!("Unknown AMQP type \"{ty}\""))),
+        };
+        Ok(body)
+    }
+}
+
+/// `depth` is the nesting depth of the elements
+fn array_to_json(count: usize, content: &[u8], depth: usize) -> Result<Value, Error> {
+    check_depth(depth)?;
+    let mut cursor = Cursor(content);
+    let mut array = Map::new();
+
+    let mut code = cursor.read::<1>()?[0];
+    if code == EncodingCodes::DescribedType as u8 {
+        array.insert(DESCRIPTOR.into(), decode_value(&mut cursor, depth)?);
+        code = cursor.read::<1>()?[0];
+    }
+    let code = EncodingCodes::try_from(code)?;
+    let ty = type_name(&code);
+    let values = (0..count)
+        .map(|_| Body::read(&code, &mut cursor)?.to_json(depth))
+        .collect::<Result<Vec<_>, _>>()?;
+    cursor.finish()?;
+
+    array.insert(TYPE.into(), Value::String(ty.into()));
+    if code != widest_code(ty)? {
+        array.insert(ENCODING.into(), Value::String(encoding_name(&code).into()));
+    }
+    array.insert(VALUES.into(), Value::Array(values));
+    Ok(Value::Object(array))
+}
+
+/// `depth` is the nesting depth of the elements
+fn array_from_json(value: &Value, depth: usize) -> Result<Body<'static>, Error> {
+    check_depth(depth)?;
+    let array = value.as_object().ok_or_else(|| invalid("array", value))?;
+    let ty = field(array, TYPE)?
+        .as_str()
+        .ok_or_else(|| invalid("array", value))?;
+    let code = match array.get(ENCODING) {
+        Some(encoding) => {
+            let encoding = encoding.as_str().ok_or_else(|| invalid("array", value))?;
+            code_by_name(ty, encoding)?
+        }
+        None => widest_code(ty)?,
+    };
+    let values = field(array, VALUES)?
+        .as_array()
+        .ok_or_else(|| invalid("array", value))?;
+
+    let mut content = Vec::new();
+    if let Some(descriptor) = array.get(DESCRIPTOR) {
+        content.push(EncodingCodes::DescribedType as u8);
+        encode_value(descriptor, &mut content, depth)?;
+    }
+    content.push(code.clone() as u8);
+    for value in values {
+        Body::from_json(ty, value, depth)?.write(&code, &mut content)?;
+    }
+    Ok(Body::Array {
+        count: values.len(),
+        content: Cow::Owned(content),
+    })
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                  Encodings                                 */
+/* -------------------------------------------------------------------------- */
+
+fn width(code: &EncodingCodes) -> usize {
+    use EncodingCodes as C;
+
+    match code {
+        C::Vbin8 | C::Str8 | C::Sym8 | C::List8 | C::Map8 | C::Array8 => 1,
+        _ => 4,
+    }
+}
+
+fn write_len(width: usize, len: usize, buf: &mut Vec<u8>) -> Result<(), Error> {
+    match width {
+        1 => buf.push(u8::try_from(len).map_err(|_| Error::InvalidLength)?),
+        _ => buf.extend_from_slice(
+            &u32::try_from(len)
+                .map_err(|_| Error::too_long())?
+                .to_be_bytes(),
+        ),
+    }
+    Ok(())
+}
+
+fn type_name(code: &EncodingCodes) -> &'static str {
+    use EncodingCodes as C;
+
+    match code {
+        C::DescribedType => DESCRIBED,
+        C::Null => "null",
+        C::Boolean | C::BooleanTrue | C::BooleanFalse => "boolean",
+        C::Ubyte => "ubyte",
+        C::Ushort => "ushort",
+        C::Uint | C::SmallUint | C::Uint0 => "uint",
+        C::Ulong | C::SmallUlong | C::Ulong0 => "ulong",
+        C::Byte => "byte",
+        C::Short => "short",
+        C::Int | C::SmallInt => "int",
+        C::Long | C::SmallLong => "long",
+        C::Float => "float",
+        C::Double => "double",
+        C::Decimal32 => "decimal32",
+        C::Decimal64 => "decimal64",
+        C::Decimal128 => "decimal128",
+        C::Char => "char",
+        C::Timestamp => "timestamp",
+        C::Uuid => "uuid",
+        C::Vbin8 | C::Vbin32 => "binary",
+        C::Str8 | C::Str32 => "string",
+        C::Sym8 | C::Sym32 => "symbol",
+        C::List0 | C::List8 | C::List32 => "list",
+        C::Map8 | C::Map32 => "map",
+        C::Array8 | C::Array32 => "array",
+    }
+}
+
+/// The name of the encoding in the specification
+fn encoding_name(code: &EncodingCodes) -> &'static str {
+    use EncodingCodes as C;
+
+    match code {
+        C::Boolean => "boolean",
+        C::BooleanTrue => "true",
+        C::BooleanFalse => "false",
+        C::Uint0 => "uint0",
+        C::SmallUint => "smalluint",
+        C::Ulong0 => "ulong0",
+        C::SmallUlong => "smallulong",
+        C::SmallInt => "smallint",
+        C::SmallLong => "smalllong",
+        C::Float | C::Double | C::Decimal32 | C::Decimal64 | C::Decimal128 => "ieee-754",
+        C::Char => "utf32",
+        C::Timestamp => "ms64",
+        C::Vbin8 => "vbin8",
+        C::Vbin32 => "vbin32",
+        C::Str8 => "str8-utf8",
+        C::Str32 => "str32-utf8",
+        C::Sym8 => "sym8",
+        C::Sym32 => "sym32",
+        C::List0 => "list0",
+        C::List8 => "list8",
+        C::List32 => "list32",
+        C::Map8 => "map8",
+        C::Map32 => "map32",
+        C::Array8 => "array8",
+        C::Array32 => "array32",
+        _ => type_name(code),
+    }
+}
+
+fn code_by_name(ty: &str, encoding: &str) -> Result<EncodingCodes, Error> {
+    CODES
+        .iter()
+        .find(|code| type_name(code) == ty && encoding_name(code) == encoding)
+        .cloned()
+        .ok_or_else(|| Error::Message(format!("Unknown encoding \"{encoding}\" of {ty}")))
+}
+
+/// The widest encoding of the type, which is the default for the elements of an array
+fn widest_code(ty: &str) -> Result<EncodingCodes, Error> {
+    use EncodingCodes as C;
+
+    let code = match ty {
+        "boolean" => C::Boolean,
+        "uint" => C::Uint,
+        "ulong" => C::Ulong,
+        "int" => C::Int,
+        "long" => C::Long,
+        "binary" => C::Vbin32,
+        "string" => C::Str32,
+        "symbol" => C::Sym32,
+        "list" => C::List32,
+        "map" => C::Map32,
+        "array" => C::Array32,
+        _ => CODES
+            .iter()
+            .find(|code| type_name(code) == ty)
+            .cloned()
+            .ok_or_else(|| Error::Message(format!("Unknown AMQP type \"{ty}\"")))?,
+    };
+    Ok(code)
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                   Values                                   */
+/* -------------------------------------------------------------------------- */
+
+fn integer<T>(ty: &str, value: &Value) -> Result<T, Error>
+where
+    T: TryFrom<u64> + TryFrom<i64>,
+{
+    let n = match value {
+        Value::Number(n) => n,
+        _ => return Err(invalid(ty, value)),
+    };
+    let result = match (n.as_u64(), n.as_i64()) {
+        (Some(n), _) => T::try_from(n).ok(),
+        (None, Some(n)) => T::try_from(n).ok(),
+        (None, None) => None,
+    };
+    result.ok_or_else(|| invalid(ty, value))
+}
+
+fn str_from_json<'a>(ty: &str, value: &'a Value) -> Result<&'a str, Error> {
+    value.as_str().ok_or_else(|| invalid(ty, value))
+}
+
+macro_rules! impl_float_json {
+    ($to:ident, $from:ident, $ty:ty, $width:literal) => {
+        fn $to(value: $ty) -> Value {
+            if value.is_nan() {
+                return match value.to_bits() == <$ty>::NAN.to_bits() {
+                    true => Value::String("NaN".into()),
+                    false => Value::String(format!("0x{}", to_hex(&value.to_be_bytes()))),
+                };
+            }
+            if value.is_infinite() {
+                return match value.is_sign_positive() {
+                    true => Value::String("Infinity".into()),
+                    false => Value::String("-Infinity".into()),
+                };
+            }
+
+            // The shortest representation is preferred if it reads back into the same value
+            let shortest = value
+                .to_string()
+                .parse::<f64>()
+                .ok()
+                .filter(|n| (*n as $ty).to_bits() == value.to_bits());
+            let n = shortest.unwrap_or(value as f64);
+            Number::from_f64(n)
+                .map(Value::Number)
+                .unwrap_or(Value::Null)
+        }
+
+        fn $from(value: &Value) -> Option<$ty> {
+            match value {
+                Value::Number(n) => n.as_f64().map(|n| n as $ty),
+                Value::String(s) => match s.as_str() {
+                    "NaN" => Some(<$ty>::NAN),
+                    "Infinity" => Some(<$ty>::INFINITY),
+                    "-Infinity" => Some(<$ty>::NEG_INFINITY),
+                    s => {
+                        let bytes: [u8; $width] = hex_bits(s)?;
+                        Some(<$ty>::from_be_bytes(bytes))
+                    }
+                },
+                _ => None,
+            }
+        }
+    };
+}
+
+impl_float_json!(f32_to_json, f32_from_json, f32, 4);
+impl_float_json!(f64_to_json, f64_from_json, f64, 8);
+
+fn decimal_to_json<D, const N: usize>(bytes: [u8; N]) -> Value
+where
+    D: From<[u8; N]> + Into<[u8; N]> + Display + FromStr,
+{
+    let s = D::from(bytes).to_string();
+    match s.parse::<D>().map(Into::into) {
+        Ok(parsed) if parsed == bytes => Value::String(s),
+        _ => Value::String(format!("0x{}", to_hex(&bytes))),
+    }
+}
+
+fn decimal_from_json<D, const N: usize>(ty: &str, value: &Value) -> Result<[u8; N], Error>
+where
+    D: Into<[u8; N]> + FromStr,
+{
+    let s = value.as_str().ok_or_else(|| invalid(ty, value))?;
+    let bytes = match s.starts_with("0x") {
+        true => hex_bits(s),
+        false => s.parse::<D>().ok().map(Into::into),
+    };
+    bytes.ok_or_else(|| invalid(ty, value))
+}
+
+fn uuid_to_string(bytes: &[u8; 16]) -> String {
+    format!(
+        "{}-{}-{}-{}-{}",
+        to_hex(&bytes[0..4]),
+        to_hex(&bytes[4..6]),
+        to_hex(&bytes[6..8]),
+        to_hex(&bytes[8..10]),
+        to_hex(&bytes[10..16])
+    )
+}
+
+fn uuid_from_str(s: &str) -> Option<[u8; 16]> {
+    let hyphens = [8, 13, 18, 23];
+    let is_hyphenated = s.len() == 36
+        && s.char_indices()
+            .all(|(i, c)| (c == '-') == hyphens.contains(&i));
+    if !is_hyphenated {
+        return None;
+    }
+    from_hex(&s.replace('-', ""))?.try_into().ok()
+}
+
+/// Parses the bits of a fixed width value from a `0x` prefixed hex string
+fn hex_bits<const N: usize>(s: &str) -> Option<[u8; N]> {
+    from_hex(s.strip_prefix("0x")?)?.try_into().ok()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+            let _ = write!(s, "{b:02x}");
+            s
+        })
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => Some((hex_digit(*high)? << 4) | hex_digit(*low)?),
+            _ => None,
+        })
+        .collect()
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        described::Described,
+        descriptor::Descriptor,
+        primitives::{Array, Dec64, Symbol, Timestamp, Uuid},
+        to_vec, Value,
+    };
+
+    use super::{decode, decode_all, encode, encode_all};
+
+    fn assert_round_trip(buf: &[u8]) -> serde_json::Value {
+        let value = decode(buf).unwrap();
+        assert_eq!(encode(&value).unwrap(), buf, "{value}");
+
+        // The textual form reads back into the same bytes
+        let text = value.to_string();
+        let value2: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(encode(&value2).unwrap(), buf, "{text}");
+        value
+    }
+
+    #[test]
+    fn test_round_trip_primitives() {
+        let values = vec![
+            Value::Null,
+            Value::Bool(true),
+            Value::Bool(false),
+            Value::Ubyte(u8::MAX),
+            Value::Ushort(u16::MAX),
+            Value::Uint(0),
+            Value::Uint(7),
+            Value::Uint(u32::MAX),
+            Value::Ulong(0),
+            Value::Ulong(7),
+            Value::Ulong(u64::MAX),
+            Value::Byte(i8::MIN),
+            Value::Short(i16::MIN),
+            Value::Int(-1),
+            Value::Int(i32::MIN),
+            Value::Long(-1),
+            Value::Long(i64::MIN),
+            Value::Float(0.1f32.into()),
+            Value::Float(f32::MIN_POSITIVE.into()),
+            Value::Float((-0.0f32).into()),
+            Value::Float(f32::NAN.into()),
+            Value::Float(f32::NEG_INFINITY.into()),
+            Value::Double(0.1.into()),
+            Value::Double(f64::INFINITY.into()),
+            Value::Decimal64("1.50".parse::<Dec64>().unwrap()),
+            Value::Char('🦀'),
+            Value::Timestamp(Timestamp::from_milliseconds(-1)),
+            Value::Uuid(Uuid::from([7u8; 16])),
+            Value::Binary(vec![0u8, 1, 254, 255].into()),
+            Value::Binary(vec![1u8; 300].into()),
+            Value::String("amqp".into()),
+            Value::String("a".repeat(300)),
+            Value::Symbol("amqp:accepted:list".into()),
+        ];
+
+        for value in values {
+            assert_round_trip(&to_vec(&value).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_round_trip_compound() {
+        let values = vec![
+            Value::List(vec![]),
+            Value::List(vec![Value::Int(1), Value::Symbol("a".into())]),
+            Value::List(vec![Value::String("a".repeat(300))]),
+            Value::Map(
+                vec![
+                    (Value::Symbol("b".into()), Value::Null),
+                    (Value::Symbol("a".into()), Value::Uint(1)),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+            Value::Array(Array::from(vec![Value::Int(1), Value::Int(2)])),
+            Value::Array(Array::from(vec![Value::Symbol("a".into())])),
+            Value::Described(Box::new(Described {
+                descriptor: Descriptor::Code(0x70),
+                value: Value::List(vec![Value::Bool(true), Value::Ubyte(4)]),
+            })),
+            Value::Described(Box::new(Described {
+                descriptor: Descriptor::Name(Symbol::from("amqp:data:binary")),
+                value: Value::Binary(vec![1, 2, 3].into()),
+            })),
+        ];
+
+        for value in values {
+            assert_round_trip(&to_vec(&value).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        let value = Value::Described(Box::new(Described {
+            descriptor: Descriptor::Code(0x13),
+            value: Value::List(vec![
+                Value::Char('a'),
+                Value::Uuid(Uuid::from([0xab; 16])),
+                Value::Binary(vec![0xca, 0xfe].into()),
+                Value::Array(Array::from(vec![Value::Long(1)])),
+            ]),
+        }));
+        let json = decode(&to_vec(&value).unwrap()).unwrap();
+        let expected = json!({
+            "described": {
+                "descriptor": {"ulong": 0x13},
+                "value": {"list": [
+                    {"char": "a"},
+                    {"uuid": "abababab-abab-abab-abab-abababababab"},
+                    {"binary": "cafe"},
+                    {"array": {"type": "long", "values": [1]}},
+                ]},
+            }
+        });
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_keeps_non_default_encodings() {
+        let buf = [
+            0xd0, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x04, // list32
+            0x70, 0x00, 0x00, 0x00, 0x00, // uint 0
+            0x56, 0x01, // boolean true
+            0xb1, 0x00, 0x00, 0x00, 0x01, b'a', // str32 "a"
+            0xc0, 0x01, 0x00, // empty list8
+        ];
+        let json = assert_round_trip(&buf);
+        let expected = json!({
+            "list": [
+                {"uint": 0, "encoding": "uint"},
+                {"boolean": true, "encoding": "boolean"},
+                {"string": "a", "encoding": "str32-utf8"},
+                {"list": [], "encoding": "list8"},
+            ],
+            "encoding": "list32"
+        });
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_array_element_encodings() {
+        // array8 of two smallint elements with a descriptor
+        let buf = [0xe0, 0x07, 0x02, 0x00, 0x53, 0x13, 0x54, 0x01, 0xff];
+        let json = assert_round_trip(&buf);
+        let expected = json!({
+            "array": {
+                "descriptor": {"ulong": 0x13},
+                "type": "int",
+                "encoding": "smallint",
+                "values": [1, -1],
+            }
+        });
+        assert_eq!(json, expected);
+
+        let value = json!({"array": {"type": "ubyte", "values": [1, 256]}});
+        assert!(encode(&value).is_err());
+    }
+
+    #[test]
+    fn test_non_canonical_floats_and_decimals() {
+        let nan = f32::from_bits(0x7fc0_0001);
+        let buf = to_vec(&nan).unwrap();
+        let json = assert_round_trip(&buf);
+        assert_eq!(json, json!({"float": "0x7fc00001"}));
+
+        // A coefficient larger than the precision of decimal32 is non-canonical
+        let buf = [0x74, 0x6c, 0xff, 0xff, 0xff];
+        let json = assert_round_trip(&buf);
+        assert_eq!(json, json!({"decimal32": "0x6cffffff"}));
+    }
+
+    #[test]
+    fn test_encode_from_hand_written_json() {
+        let value = json!({"map": [
+            [{"symbol": "x-opt-partition"}, {"int": 3}],
+            [{"string": "created"}, {"timestamp": 1_700_000_000_000i64}],
+        ]});
+        let buf = encode(&value).unwrap();
+        let decoded: Value = crate::from_slice(&buf).unwrap();
+        let expected = Value::Map(
+            vec![
+                (Value::Symbol("x-opt-partition".into()), Value::Int(3)),
+                (
+                    Value::String("created".into()),
+                    Value::Timestamp(Timestamp::from_milliseconds(1_700_000_000_000)),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn test_sequence_of_values() {
+        let mut buf = to_vec(&Symbol::from("a")).unwrap();
+        buf.extend(to_vec(&1u64).unwrap());
+        let values = decode_all(&buf).unwrap();
+        assert_eq!(values, vec![json!({"symbol": "a"}), json!({"ulong": 1})]);
+        assert_eq!(encode_all(&values).unwrap(), buf);
+
+        assert!(decode(&buf).is_err());
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let values = vec![
+            json!(1),
+            json!({"uint": -1}),
+            json!({"ubyte": 256}),
+            json!({"int": 1, "long": 1}),
+            json!({"unknown": 1}),
+            json!({"uint": 1, "encoding": "uint0"}),
+            json!({"uint": 1, "encoding": "str8-utf8"}),
+            json!({"char": "ab"}),
+            json!({"uuid": "not a uuid"}),
+            json!({"binary": "abc"}),
+            json!({"map": [[{"null": null}]]}),
+            json!({"described": {"descriptor": {"ulong": 1}}}),
+        ];
+        for value in values {
+            assert!(encode(&value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_encoding() {
+        // truncated
+        assert!(decode(&[0x70, 0x00]).is_err());
+        // list with a size that doesn't match the elements
+        assert!(decode(&[0xc0, 0x03, 0x01, 0x41]).is_err());
+        // invalid utf-8
+        assert!(decode(&[0xa1, 0x01, 0xff]).is_err());
+    }
+
+    #[test]
+    fn test_nesting_depth_is_limited() {
+        // Nested described type constructors
+        assert!(decode(&vec![0x00; 2_000_000]).is_err());
+
+        let mut value = json!({"null": null});
+        for depth in 1..=200 {
+            value = json!({"list": [value]});
+            if depth == 100 {
+                let buf = encode(&value).unwrap();
+                assert_eq!(decode(&buf).unwrap(), value);
+            }
+        }
+        assert!(encode(&value).is_err());
+    }
+}
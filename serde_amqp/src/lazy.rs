@@ -1,12 +1,41 @@
 //! Implements `LazyValue`
+//!
+//! A [`LazyValue`] can be navigated without decoding the whole value. Compound values are skipped
+//! over by their size prefix, and the navigated values share the buffer of the original value.
+//!
+//! ```rust
+//! use serde_amqp::{
+//!     lazy::{to_lazy_value, PathSegment},
+//!     primitives::OrderedMap,
+//!     Value,
+//! };
+//!
+//! let mut map = OrderedMap::new();
+//! map.insert(Value::from("tenant"), Value::from("contoso"));
+//! let value = Value::List(vec![Value::Bool(true), Value::Map(map)]);
+//!
+//! let lazy = to_lazy_value(&value).unwrap();
+//! let tenant = lazy
+//!     .get_path(&[PathSegment::Index(1), PathSegment::Key(Value::from("tenant"))])
+//!     .unwrap()
+//!     .unwrap();
+//! let tenant: String = serde_amqp::from_slice(tenant.as_slice()).unwrap();
+//! assert_eq!(tenant, "contoso");
+//! ```
+
+use alloc::vec::Vec;
+use core::ops::Range;
 
 use bytes::Bytes;
 use serde::{de::Visitor, Deserialize, Serialize};
 
 use crate::{
-    read::{read_described_bytes, read_primitive_bytes_or_else, Read},
-    Error,
     __constants::LAZY_VALUE,
+    decoder::{encoded_len, EncodedLen},
+    descriptor::Descriptor,
+    format_code::EncodingCodes,
+    read::{read_described_bytes, read_primitive_bytes_or_else, Read},
+    Error, Value,
 };
 
 /// Serialize a value into a [`LazyValue`].
@@ -53,6 +82,251 @@ impl LazyValue {
     pub fn into_bytes(self) -> Bytes {
         self.0
     }
+
+    /// Whether the value is a described type
+    pub fn is_described(&self) -> bool {
+        self.0.first() == Some(&(EncodingCodes::DescribedType as u8))
+    }
+
+    /// Decodes the descriptor if the value is a described type
+    pub fn descriptor(&self) -> Result<Option<Descriptor>, Error> {
+        let (descriptor, _) = match self.described_parts()? {
+            Some(parts) => parts,
+            None => return Ok(None),
+        };
+        match crate::from_slice(&self.0[descriptor])? {
+            Value::Ulong(code) => Ok(Some(Descriptor::Code(code))),
+            Value::Symbol(name) => Ok(Some(Descriptor::Name(name))),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+
+    /// Returns the value of a described type without the descriptor, or `None` if the value is
+    /// not a described type
+    pub fn described_value(&self) -> Result<Option<LazyValue>, Error> {
+        Ok(self
+            .described_parts()?
+            .map(|(_, value)| LazyValue(self.0.slice(value))))
+    }
+
+    /// Returns the element at `index`, or `None` if the value is not a list or the index is out
+    /// of bounds
+    pub fn get_index(&self, index: usize) -> Result<Option<LazyValue>, Error> {
+        match self.elements(false)? {
+            Some(mut elements) => elements.nth(index).transpose(),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the value associated with `key`, or `None` if the value is not a map or the key
+    /// is not found
+    ///
+    /// Only the keys are inspected. The values are skipped over by their size prefix.
+    pub fn get_key<K>(&self, key: &K) -> Result<Option<LazyValue>, Error>
+    where
+        K: Serialize + ?Sized,
+    {
+        let target = crate::to_vec(&key)?;
+        let mut elements = match self.elements(true)? {
+            Some(elements) => elements,
+            None => return Ok(None),
+        };
+        while let Some(entry_key) = elements.next().transpose()? {
+            let entry_value = elements.next().transpose()?.ok_or(Error::InvalidLength)?;
+            if keys_eq(entry_key.as_slice(), &target)? {
+                return Ok(Some(entry_value));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Follows the `path` from this value, or returns `None` if any of the segments cannot be
+    /// found
+    pub fn get_path<'p>(
+        &self,
+        path: impl IntoIterator<Item = &'p PathSegment>,
+    ) -> Result<Option<LazyValue>, Error> {
+        let mut current = self.clone();
+        for segment in path {
+            let next = match segment {
+                PathSegment::Described => current.described_value()?,
+                PathSegment::Index(index) => current.get_index(*index)?,
+                PathSegment::Key(key) => current.get_key(key)?,
+            };
+            match next {
+                Some(next) => current = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(current))
+    }
+
+    /// Returns the range of the descriptor and the range of the value if this is a described type
+    fn described_parts(&self) -> Result<Option<DescribedParts>, Error> {
+        if !self.is_described() {
+            return Ok(None);
+        }
+        let value_start = 1 + complete_len(&self.0[1..])?;
+        let value_end = value_start + complete_len(&self.0[value_start..])?;
+        Ok(Some((1..value_start, value_start..value_end)))
+    }
+
+    /// Returns an iterator over the encoded elements if this is a list (or a map if `map` is
+    /// true). The keys and values of a map are returned as separate elements.
+    fn elements(&self, map: bool) -> Result<Option<Elements<'_>>, Error> {
+        let code = match self.0.first() {
+            Some(code) => EncodingCodes::try_from(*code)?,
+            None => return Err(Error::unexpected_eof("Empty lazy value")),
+        };
+        let eof = || Error::unexpected_eof("Incomplete compound header");
+        let (start, end, count) = match (code, map) {
+            (EncodingCodes::List0, false) => (1, 1, 0),
+            (EncodingCodes::List8, false) | (EncodingCodes::Map8, true) => {
+                let header = self.0.get(1..3).ok_or_else(eof)?;
+                (3, 2 + header[0] as usize, header[1] as usize)
+            }
+            (EncodingCodes::List32, false) | (EncodingCodes::Map32, true) => {
+                let header = self.0.get(1..9).ok_or_else(eof)?;
+                let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+                let count = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+                (9, 5 + size as usize, count as usize)
+            }
+            _ => return Ok(None),
+        };
+        if end < start || end > self.0.len() {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Some(Elements {
+            bytes: &self.0,
+            pos: start,
+            end,
+            remaining: count,
+        }))
+    }
+}
+
+impl TryFrom<Bytes> for LazyValue {
+    type Error = Error;
+
+    /// The bytes must contain exactly one encoded value
+    fn try_from(bytes: Bytes) -> Result<Self, Self::Error> {
+        match complete_len(&bytes)? == bytes.len() {
+            true => Ok(LazyValue(bytes)),
+            false => Err(Error::InvalidLength),
+        }
+    }
+}
+
+/// The range of the descriptor and the range of the value of a described type
+type DescribedParts = (Range<usize>, Range<usize>);
+
+/// A segment of the path that is followed by [`LazyValue::get_path`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    /// The value of a described type
+    Described,
+
+    /// The element at the index of a list
+    Index(usize),
+
+    /// The value associated with the key in a map
+    Key(Value),
+}
+
+/// An iterator over the concatenated encoded values in a buffer, eg. the sections of a message
+///
+/// Each value is returned as a [`LazyValue`] that shares the buffer.
+#[derive(Debug, Clone)]
+pub struct LazyValues {
+    bytes: Bytes,
+}
+
+impl LazyValues {
+    /// Creates an iterator over the encoded values in `bytes`
+    pub fn new(bytes: Bytes) -> Self {
+        Self { bytes }
+    }
+}
+
+impl Iterator for LazyValues {
+    type Item = Result<LazyValue, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        match complete_len(&self.bytes) {
+            Ok(len) => Some(Ok(LazyValue(self.bytes.split_to(len)))),
+            Err(err) => {
+                self.bytes.clear();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+struct Elements<'a> {
+    bytes: &'a Bytes,
+    pos: usize,
+    end: usize,
+    remaining: usize,
+}
+
+impl Iterator for Elements<'_> {
+    type Item = Result<LazyValue, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let result = complete_len(&self.bytes[self.pos..self.end]).map(|len| {
+            let value = LazyValue(self.bytes.slice(self.pos..self.pos + len));
+            self.pos += len;
+            value
+        });
+        if result.is_err() {
+            self.remaining = 0;
+        }
+        Some(result)
+    }
+}
+
+/// Length of the first encoded value in `buf`, which must be complete
+fn complete_len(buf: &[u8]) -> Result<usize, Error> {
+    match encoded_len(buf)? {
+        EncodedLen::Complete(len) => Ok(len),
+        EncodedLen::Incomplete(_) => Err(Error::unexpected_eof("Incomplete encoded value")),
+    }
+}
+
+/// Compares two encoded keys. Variable width values are compared by the content regardless of the
+/// width of the size prefix.
+fn keys_eq(encoded: &[u8], target: &[u8]) -> Result<bool, Error> {
+    if encoded == target {
+        return Ok(true);
+    }
+    match (variable_content(encoded), variable_content(target)) {
+        (Some(lhs), Some(rhs)) => Ok(lhs == rhs),
+        (Some(_), None) | (None, Some(_)) => Ok(false),
+        (None, None) => {
+            Ok(crate::from_slice::<Value>(encoded)? == crate::from_slice::<Value>(target)?)
+        }
+    }
+}
+
+/// Returns the 8-bit width format code and the content of a variable width value
+fn variable_content(buf: &[u8]) -> Option<(u8, &[u8])> {
+    let (code, width) = match EncodingCodes::try_from(*buf.first()?).ok()? {
+        EncodingCodes::Vbin8 => (EncodingCodes::Vbin8, 1),
+        EncodingCodes::Vbin32 => (EncodingCodes::Vbin8, 4),
+        EncodingCodes::Str8 => (EncodingCodes::Str8, 1),
+        EncodingCodes::Str32 => (EncodingCodes::Str8, 4),
+        EncodingCodes::Sym8 => (EncodingCodes::Sym8, 1),
+        EncodingCodes::Sym32 => (EncodingCodes::Sym8, 4),
+        _ => return None,
+    };
+    Some((code as u8, buf.get(1 + width..)?))
 }
 
 impl From<LazyValue> for Bytes {
@@ -81,7 +355,7 @@ struct LazyValueVisitor;
 impl Visitor<'_> for LazyValueVisitor {
     type Value = LazyValue;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("LazyValue")
     }
 
@@ -105,8 +379,13 @@ impl<'de> Deserialize<'de> for LazyValue {
 #[cfg(test)]
 mod tests {
     use crate::{
-        described::Described, descriptor::Descriptor, format_code::EncodingCodes, from_slice,
-        from_value, primitives::Array, read::SliceReader, to_value, to_vec, Value,
+        described::Described,
+        descriptor::Descriptor,
+        format_code::EncodingCodes,
+        from_slice, from_value,
+        primitives::{Array, OrderedMap, Symbol},
+        read::SliceReader,
+        to_value, to_vec, Value,
     };
 
     use super::*;
@@ -242,4 +521,124 @@ mod tests {
         let expected = to_vec(&src).unwrap();
         assert_eq!(lazy.as_slice(), &expected);
     }
+
+    fn nested_value() -> Value {
+        let mut map = OrderedMap::new();
+        map.insert(Value::from("first"), Value::List(vec![Value::Uint(1); 100]));
+        map.insert(Value::from("tenant"), Value::from("contoso"));
+        map.insert(Value::Symbol("sym".into()), Value::Long(-1));
+        Value::List(vec![
+            Value::Null,
+            Value::Described(Box::new(Described {
+                descriptor: Descriptor::Code(0x74),
+                value: Value::Map(map),
+            })),
+        ])
+    }
+
+    #[test]
+    fn test_get_index() {
+        let lazy = to_lazy_value(&nested_value()).unwrap();
+        let first = lazy.get_index(0).unwrap().unwrap();
+        assert_eq!(first.as_slice(), &[EncodingCodes::Null as u8]);
+        assert!(lazy.get_index(2).unwrap().is_none());
+
+        let empty = to_lazy_value(&Value::List(vec![])).unwrap();
+        assert!(empty.get_index(0).unwrap().is_none());
+
+        // Not a list
+        let lazy = to_lazy_value(&1u32).unwrap();
+        assert!(lazy.get_index(0).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_described_value() {
+        let lazy = to_lazy_value(&nested_value()).unwrap();
+        let section = lazy.get_index(1).unwrap().unwrap();
+        assert!(section.is_described());
+        assert_eq!(section.descriptor().unwrap(), Some(Descriptor::Code(0x74)));
+
+        let map = section.described_value().unwrap().unwrap();
+        assert!(!map.is_described());
+        assert!(map.descriptor().unwrap().is_none());
+        assert!(map.described_value().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_key() {
+        let lazy = to_lazy_value(&nested_value()).unwrap();
+        let map = lazy
+            .get_path(&[PathSegment::Index(1), PathSegment::Described])
+            .unwrap()
+            .unwrap();
+
+        let tenant = map.get_key("tenant").unwrap().unwrap();
+        let tenant: String = from_slice(tenant.as_slice()).unwrap();
+        assert_eq!(tenant, "contoso");
+
+        let sym = map.get_key(&Symbol::from("sym")).unwrap().unwrap();
+        let sym: i64 = from_slice(sym.as_slice()).unwrap();
+        assert_eq!(sym, -1);
+
+        // A string key does not match a symbol key
+        assert!(map.get_key("sym").unwrap().is_none());
+        assert!(map.get_key("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_key_with_wide_encoding() {
+        // A str32 encoded key
+        let mut bytes = vec![EncodingCodes::Map8 as u8, 0, 2];
+        bytes.extend([EncodingCodes::Str32 as u8, 0, 0, 0, 6]);
+        bytes.extend(b"tenant");
+        bytes.extend([EncodingCodes::BooleanTrue as u8]);
+        bytes[1] = (bytes.len() - 2) as u8;
+
+        let lazy = LazyValue::try_from(Bytes::from(bytes)).unwrap();
+        let value = lazy.get_key("tenant").unwrap().unwrap();
+        assert_eq!(value.as_slice(), &[EncodingCodes::BooleanTrue as u8]);
+    }
+
+    #[test]
+    fn test_navigated_value_shares_buffer() {
+        let lazy = to_lazy_value(&nested_value()).unwrap();
+        let path = [
+            PathSegment::Index(1),
+            PathSegment::Described,
+            PathSegment::Key(Value::from("tenant")),
+        ];
+        let tenant = lazy.get_path(&path).unwrap().unwrap();
+
+        let start = lazy.as_slice().as_ptr() as usize;
+        let ptr = tenant.as_slice().as_ptr() as usize;
+        assert!(ptr > start && ptr + tenant.as_slice().len() <= start + lazy.as_slice().len());
+    }
+
+    #[test]
+    fn test_lazy_values() {
+        let mut bytes = to_vec(&1u32).unwrap();
+        bytes.extend(to_vec(&nested_value()).unwrap());
+        bytes.extend(to_vec(&"last").unwrap());
+
+        let values: Vec<LazyValue> = LazyValues::new(Bytes::from(bytes))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(
+            from_slice::<Value>(values[1].as_slice()).unwrap(),
+            nested_value()
+        );
+        assert_eq!(from_slice::<String>(values[2].as_slice()).unwrap(), "last");
+
+        let mut truncated = LazyValues::new(Bytes::from(vec![EncodingCodes::Uint as u8, 0]));
+        assert!(truncated.next().unwrap().is_err());
+        assert!(truncated.next().is_none());
+    }
+
+    #[test]
+    fn test_try_from_bytes() {
+        let bytes = to_vec(&nested_value()).unwrap();
+        assert!(LazyValue::try_from(Bytes::from(bytes.clone())).is_ok());
+        assert!(LazyValue::try_from(Bytes::from(bytes[..bytes.len() - 1].to_vec())).is_err());
+    }
 }
@@ -1,4 +1,5 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs, missing_debug_implementations)]
 
 //! A serde implementation of AMQP1.0 protocol and the primitive types.
@@ -19,7 +20,13 @@
 //! Deserialization:
 //!
 //! - [`from_slice`]
-//! - [`from_reader`]
+//! - [`from_bytes`], which borrows from [`Bytes`](bytes::Bytes) just like [`from_slice`]
+//! - [`from_reader`] (requires feature `"std"`)
+//! - [`Decoder`], which can be fed chunks of bytes as they arrive
+//! - [`from_async_reader`] (requires feature `"tokio"`)
+//!
+//! Encoded bytes can be inspected without knowing their type with [`dump::dump`], which prints an
+//! annotated dump of every constructor, size prefix, descriptor and value.
 //!
 //! # Primitive types
 //!
@@ -98,17 +105,22 @@
 //! # Feature flag
 //!
 //! ```toml
-//! default = []
+//! default = ["std"]
 //! ```
 //!
 //! | Feature | Description |
 //! |---------|-------------|
+//! |`"std"`| enables the use of the standard library, [`from_reader`] and [`read::IoReader`]. Without it, the crate is `no_std` and only depends on `core` and `alloc`, and mod [`io`] provides a minimal `Write` trait and `Error` type in place of `std::io` |
 //! |`"derive"`| enables [`SerializeComposite` and `DeserializeComposite`](#serializecomposite-and-deserializecomposite) |
 //! |`"extensions"`| enables `extensions` mod (see [Extensions](#extensions)), added since "0.4.5" |
 //! |`"time"`| enables conversion of `Timestamp` from/to `time::Duration` and `time::OffsetDateTime`, added since "0.5.1" |
 //! |`"chrono"`| enables conversion of `Timestamp` from/to `chrono::Duration` and `chrono::DateTime`, added since "0.5.1" |
 //! |`"chrono-preview"`| a temporary feature that removes the use of deprecated APIs in `chrono` crate |
 //! |`"uuid"`| enables conversion of `Uuid` from/to `uuid::Uuid`, added since "0.5.1" |
+//! |`"tokio"`| enables [`from_async_reader`] for `tokio::io::AsyncRead` |
+//! |`"json"`| enables conversion from `serde_json::Value` to [`Value`] and the type-preserving JSON transcoding in mod `json` |
+//! |`"rust_decimal"`| enables conversion of `Dec32`, `Dec64` and `Dec128` from/to `rust_decimal::Decimal` |
+//! |`"bigdecimal"`| enables conversion of `Dec32`, `Dec64` and `Dec128` from/to `bigdecimal::BigDecimal` |
 //!
 //! ## `SerializeComposite` and `DeserializeComposite`
 //!
@@ -245,13 +257,27 @@
 //! 1. `TransparentVec` - a thin wrapper around `Vec` that is serialized/deserialized as a sequence
 //!    of elements `Vec` is treated as an AMQP `List` in the core spec
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub(crate) use std::error::Error as StdError;
+
+#[cfg(not(feature = "std"))]
+pub(crate) use core::error::Error as StdError;
+
 // Public mods
 pub mod de;
+pub mod decoder;
 pub mod described;
 pub mod descriptor;
+pub mod dump;
 pub mod error;
 pub mod fixed_width;
 pub mod format_code;
+pub mod io;
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+#[cfg(feature = "json")]
+pub mod json;
 pub mod lazy;
 pub mod primitives;
 pub mod read;
@@ -269,6 +295,12 @@ pub mod extensions;
 #[path = "constants.rs"]
 pub mod __constants;
 
+// Used by derive macros so that the generated code doesn't depend on `std`
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec::Vec;
+}
+
 // Private mods
 mod util;
 
@@ -277,7 +309,14 @@ mod format;
 
 pub use serde;
 
-pub use de::{from_reader, from_slice};
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use de::from_reader;
+pub use de::{from_bytes, from_slice};
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub use decoder::from_async_reader;
+pub use decoder::{Decoded, Decoder};
 pub use error::Error;
 pub use ser::to_vec;
 pub use size_ser::serialized_size;
@@ -1,4 +1,5 @@
-use std::{
+use alloc::{vec, vec::Vec};
+use core::{
     marker::PhantomData,
     ops::{Deref, DerefMut},
 };
@@ -73,7 +74,7 @@ impl<T: ser::Serialize> ser::Serialize for Array<T> {
 impl<T> IntoIterator for Array<T> {
     type Item = T;
 
-    type IntoIter = std::vec::IntoIter<T>;
+    type IntoIter = alloc::vec::IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
@@ -83,7 +84,7 @@ impl<T> IntoIterator for Array<T> {
 impl<'a, T> IntoIterator for &'a Array<T> {
     type Item = &'a T;
 
-    type IntoIter = std::slice::Iter<'a, T>;
+    type IntoIter = core::slice::Iter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.iter()
@@ -93,7 +94,7 @@ impl<'a, T> IntoIterator for &'a Array<T> {
 impl<'a, T> IntoIterator for &'a mut Array<T> {
     type Item = &'a mut T;
 
-    type IntoIter = std::slice::IterMut<'a, T>;
+    type IntoIter = core::slice::IterMut<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.iter_mut()
@@ -117,7 +118,7 @@ struct FieldVisitor {}
 impl de::Visitor<'_> for FieldVisitor {
     type Value = Field;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("Single or Multiple identifier for Array")
     }
 
@@ -151,7 +152,7 @@ struct Visitor<T> {
 impl<'de, T: de::Deserialize<'de>> de::Visitor<'de> for Visitor<T> {
     type Value = Array<T>;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("struct Array")
     }
 
@@ -1,4 +1,4 @@
-use std::fmt::{LowerHex, UpperHex};
+use core::fmt::{LowerHex, UpperHex};
 
 use serde::{de, Serialize};
 
@@ -34,7 +34,7 @@ impl<'de> de::Deserialize<'de> for BinaryRef<'de> {
 }
 
 impl LowerHex for BinaryRef<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         for byte in self.0 {
             write!(f, "{:x}", byte)?;
         }
@@ -43,7 +43,7 @@ impl LowerHex for BinaryRef<'_> {
 }
 
 impl UpperHex for BinaryRef<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         for byte in self.0 {
             write!(f, "{:X}", byte)?;
         }
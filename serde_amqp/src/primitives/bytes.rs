@@ -0,0 +1,561 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{borrow::Borrow, fmt::Display, marker::PhantomData, ops::Deref};
+
+use bytes::Bytes;
+use serde::{
+    de::{self, DeserializeSeed, Visitor},
+    Serialize,
+};
+
+use crate::__constants::SYMBOL_REF;
+
+use super::{Binary, Symbol};
+
+/// A [`DeserializeSeed`] for the `Bytes`-backed types ([`BinaryBytes`], [`StrBytes`] and
+/// [`SymbolBytes`]) that shares the buffer of `source` via reference counting
+///
+/// The content is only shared if the deserializer borrows it from `source` (eg. with
+/// [`BytesReader`](crate::read::BytesReader)), and it is copied otherwise. Deserializing
+/// these types without the seed always copies.
+///
+/// # Example
+///
+/// ```rust
+/// use bytes::Bytes;
+/// use serde::de::DeserializeSeed;
+/// use serde_amqp::{de::Deserializer, primitives::{BinaryBytes, Shared}, read::BytesReader, to_vec};
+///
+/// let buf = Bytes::from(to_vec(&BinaryBytes::from(vec![1u8; 64])).unwrap());
+/// let mut de = Deserializer::new(BytesReader::new(&buf));
+/// let binary = Shared::<BinaryBytes>::new(&buf).deserialize(&mut de).unwrap();
+/// assert_eq!(&binary[..], &[1u8; 64][..]);
+/// ```
+#[derive(Debug)]
+pub struct Shared<'s, T> {
+    source: &'s Bytes,
+    marker: PhantomData<T>,
+}
+
+impl<'s, T> Shared<'s, T> {
+    /// Creates a seed that shares the buffer of `source`
+    pub fn new(source: &'s Bytes) -> Self {
+        Self {
+            source,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Shared<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Shared<'_, T> {}
+
+/// Returns a [`Bytes`] that shares the buffer of `source` if `subset` is borrowed from it,
+/// otherwise the bytes are copied
+fn share(source: Option<&Bytes>, subset: &[u8]) -> Bytes {
+    match source {
+        Some(source) if contains(source, subset) => source.slice_ref(subset),
+        _ => Bytes::copy_from_slice(subset),
+    }
+}
+
+fn contains(source: &[u8], subset: &[u8]) -> bool {
+    let start = source.as_ptr() as usize;
+    let end = start + source.len();
+    let sub_start = subset.as_ptr() as usize;
+    sub_start >= start && sub_start + subset.len() <= end
+}
+
+/// An AMQP Binary backed by [`Bytes`]
+///
+/// When deserialized with [`Shared`], the content shares the buffer of the input via reference
+/// counting instead of being copied.
+#[derive(Debug, Clone, Default, Eq, PartialOrd, Ord, Hash)]
+pub struct BinaryBytes(pub Bytes);
+
+impl BinaryBytes {
+    /// Consumes the wrapper and returns the inner [`Bytes`]
+    pub fn into_inner(self) -> Bytes {
+        self.0
+    }
+}
+
+impl Deref for BinaryBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for BinaryBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<Rhs> PartialEq<Rhs> for BinaryBytes
+where
+    Rhs: ?Sized + AsRef<[u8]>,
+{
+    fn eq(&self, other: &Rhs) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl From<Bytes> for BinaryBytes {
+    fn from(value: Bytes) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Vec<u8>> for BinaryBytes {
+    fn from(value: Vec<u8>) -> Self {
+        Self(Bytes::from(value))
+    }
+}
+
+impl From<Binary> for BinaryBytes {
+    fn from(value: Binary) -> Self {
+        Self(Bytes::from(value.into_vec()))
+    }
+}
+
+impl From<BinaryBytes> for Binary {
+    fn from(value: BinaryBytes) -> Self {
+        Binary::from(Vec::from(value.0))
+    }
+}
+
+impl Serialize for BinaryBytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+struct BinaryBytesVisitor<'s> {
+    source: Option<&'s Bytes>,
+}
+
+impl<'de> Visitor<'de> for BinaryBytesVisitor<'_> {
+    type Value = BinaryBytes;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("binary")
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(BinaryBytes(share(self.source, v)))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(BinaryBytes(Bytes::copy_from_slice(v)))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(BinaryBytes(Bytes::from(v)))
+    }
+}
+
+impl<'de> de::Deserialize<'de> for BinaryBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(BinaryBytesVisitor { source: None })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Shared<'_, BinaryBytes> {
+    type Value = BinaryBytes;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(BinaryBytesVisitor {
+            source: Some(self.source),
+        })
+    }
+}
+
+/// An AMQP String backed by [`Bytes`] that is guaranteed to be valid UTF-8
+///
+/// When deserialized with [`Shared`], the content shares the buffer of the input via reference
+/// counting instead of being copied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StrBytes(Bytes);
+
+impl StrBytes {
+    /// Creates a `StrBytes` from a static str without copying
+    pub const fn from_static(s: &'static str) -> Self {
+        Self(Bytes::from_static(s.as_bytes()))
+    }
+
+    /// Creates a `StrBytes` from bytes that are valid UTF-8
+    pub fn from_utf8(bytes: Bytes) -> Result<Self, core::str::Utf8Error> {
+        core::str::from_utf8(&bytes)?;
+        Ok(Self(bytes))
+    }
+
+    /// Get a reference to the str
+    pub fn as_str(&self) -> &str {
+        // SAFETY: The content is validated as UTF-8 in all constructors
+        unsafe { core::str::from_utf8_unchecked(&self.0) }
+    }
+
+    /// Consumes the wrapper and returns the underlying UTF-8 encoded [`Bytes`]
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+}
+
+impl Deref for StrBytes {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for StrBytes {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for StrBytes {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Display for StrBytes {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<String> for StrBytes {
+    fn from(value: String) -> Self {
+        Self(Bytes::from(value.into_bytes()))
+    }
+}
+
+impl From<&str> for StrBytes {
+    fn from(value: &str) -> Self {
+        Self(Bytes::copy_from_slice(value.as_bytes()))
+    }
+}
+
+impl From<StrBytes> for String {
+    fn from(value: StrBytes) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+impl Serialize for StrBytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+struct StrBytesVisitor<'s> {
+    source: Option<&'s Bytes>,
+}
+
+impl<'de> Visitor<'de> for StrBytesVisitor<'_> {
+    type Value = StrBytes;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("string")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(StrBytes(share(self.source, v.as_bytes())))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(StrBytes::from(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(StrBytes::from(v))
+    }
+}
+
+impl<'de> de::Deserialize<'de> for StrBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StrBytesVisitor { source: None })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Shared<'_, StrBytes> {
+    type Value = StrBytes;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StrBytesVisitor {
+            source: Some(self.source),
+        })
+    }
+}
+
+/// An AMQP Symbol backed by [`Bytes`]
+///
+/// When deserialized with [`Shared`], the content shares the buffer of the input via reference
+/// counting instead of being copied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SymbolBytes(pub StrBytes);
+
+impl SymbolBytes {
+    /// Creates a `SymbolBytes` from a static str without copying
+    pub const fn from_static(s: &'static str) -> Self {
+        Self(StrBytes::from_static(s))
+    }
+
+    /// Get a reference to the str
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Deref for SymbolBytes {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SymbolBytes {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Display for SymbolBytes {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for SymbolBytes {
+    fn from(value: &str) -> Self {
+        Self(StrBytes::from(value))
+    }
+}
+
+impl From<String> for SymbolBytes {
+    fn from(value: String) -> Self {
+        Self(StrBytes::from(value))
+    }
+}
+
+impl From<Symbol> for SymbolBytes {
+    fn from(value: Symbol) -> Self {
+        Self(StrBytes::from(value.0))
+    }
+}
+
+impl From<SymbolBytes> for Symbol {
+    fn from(value: SymbolBytes) -> Self {
+        Symbol::from(value.as_str())
+    }
+}
+
+impl Serialize for SymbolBytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(SYMBOL_REF, self.as_str())
+    }
+}
+
+struct SymbolBytesVisitor<'s> {
+    source: Option<&'s Bytes>,
+}
+
+impl<'de> Visitor<'de> for SymbolBytesVisitor<'_> {
+    type Value = SymbolBytes;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("symbol")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        StrBytesVisitor {
+            source: self.source,
+        }
+        .visit_borrowed_str(v)
+        .map(SymbolBytes)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(SymbolBytes::from(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(SymbolBytes::from(v))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_str(StrBytesVisitor {
+                source: self.source,
+            })
+            .map(SymbolBytes)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for SymbolBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(SYMBOL_REF, SymbolBytesVisitor { source: None })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Shared<'_, SymbolBytes> {
+    type Value = SymbolBytes;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(
+            SYMBOL_REF,
+            SymbolBytesVisitor {
+                source: Some(self.source),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use serde::de::DeserializeSeed;
+
+    use crate::{
+        de::Deserializer,
+        from_bytes, from_slice,
+        primitives::{Binary, Symbol},
+        read::BytesReader,
+        to_vec,
+    };
+
+    use super::{contains as is_within, BinaryBytes, Shared, StrBytes, SymbolBytes};
+
+    fn from_bytes_shared<'de, T>(buf: &'de Bytes) -> T
+    where
+        for<'s> Shared<'s, T>: DeserializeSeed<'de, Value = T>,
+    {
+        let mut de = Deserializer::new(BytesReader::new(buf));
+        Shared::<T>::new(buf).deserialize(&mut de).unwrap()
+    }
+
+    #[test]
+    fn test_binary_bytes_shares_buffer() {
+        let expected = Binary::from(vec![7u8; 1024]);
+        let buf = Bytes::from(to_vec(&expected).unwrap());
+
+        let binary: BinaryBytes = from_bytes_shared(&buf);
+        assert_eq!(&binary[..], &expected[..]);
+        assert!(is_within(&buf, &binary));
+
+        assert_eq!(to_vec(&binary).unwrap(), &buf[..]);
+    }
+
+    #[test]
+    fn test_str_bytes_shares_buffer() {
+        let expected = String::from("amqp");
+        let buf = Bytes::from(to_vec(&expected).unwrap());
+
+        let s: StrBytes = from_bytes_shared(&buf);
+        assert_eq!(s.as_str(), expected);
+        assert!(is_within(&buf, s.as_bytes()));
+
+        assert_eq!(to_vec(&s).unwrap(), &buf[..]);
+    }
+
+    #[test]
+    fn test_symbol_bytes_shares_buffer() {
+        let expected = Symbol::from("amqp:symbol");
+        let buf = Bytes::from(to_vec(&expected).unwrap());
+
+        let s: SymbolBytes = from_bytes_shared(&buf);
+        assert_eq!(s.as_str(), expected.as_str());
+        assert!(is_within(&buf, s.as_bytes()));
+
+        assert_eq!(to_vec(&s).unwrap(), &buf[..]);
+    }
+
+    #[test]
+    fn test_deserialize_without_seed_copies() {
+        let expected = Binary::from(vec![7u8; 1024]);
+        let buf = Bytes::from(to_vec(&expected).unwrap());
+
+        let binary: BinaryBytes = from_bytes(&buf).unwrap();
+        assert_eq!(&binary[..], &expected[..]);
+        assert!(!is_within(&buf, &binary));
+    }
+
+    #[test]
+    fn test_from_slice_copies() {
+        let expected = vec![BinaryBytes::from(vec![1, 2, 3])];
+        let buf = to_vec(&expected).unwrap();
+        let decoded: Vec<BinaryBytes> = from_slice(&buf).unwrap();
+        assert_eq!(decoded, expected);
+    }
+}
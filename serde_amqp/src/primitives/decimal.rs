@@ -1,6 +1,22 @@
 //! Custom structs that hold bytes for decimal types
+//!
+//! The bytes are interpreted with the Binary Integer Decimal (BID) encoding defined in IEEE
+//! 754-2008. Equality, ordering and hashing are by value, eg. `1.0` equals `1.00`, positive and
+//! negative zeros are equal, and all NaNs are equal to each other and greater than any other value
+//! so that the decimal types can still be used as keys of a map.
+//!
+//! Conversions are exact. A value that cannot be represented without rounding is rejected with
+//! [`Error::InvalidValue`]. Arithmetic can be done after converting into `rust_decimal::Decimal`
+//! (feature `"rust_decimal"`) or `bigdecimal::BigDecimal` (feature `"bigdecimal"`).
 
-use std::convert::TryFrom;
+use alloc::string::ToString;
+use core::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
 use serde::de;
 use serde::ser;
@@ -8,6 +24,640 @@ use serde_bytes::Bytes;
 
 use crate::error::Error;
 
+/// The value of a decimal number
+///
+/// The derived `PartialEq` compares the representation, eg. `1.0` and `1.00` are not equal. Compare
+/// the decimal types to compare by value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecimalValue {
+    /// A finite number equal to `coefficient * 10^exponent`, negated if `negative` is true
+    Finite {
+        /// Whether the number is negative. Zero may also be negative.
+        negative: bool,
+
+        /// The integer coefficient
+        coefficient: u128,
+
+        /// The power of ten
+        exponent: i32,
+    },
+
+    /// Positive or negative infinity
+    Infinite {
+        /// Whether this is the negative infinity
+        negative: bool,
+    },
+
+    /// Not a number
+    NaN {
+        /// The sign bit
+        negative: bool,
+
+        /// Whether this is a signaling NaN
+        signaling: bool,
+    },
+}
+
+impl DecimalValue {
+    /// Whether the value is finite
+    pub fn is_finite(&self) -> bool {
+        matches!(self, DecimalValue::Finite { .. })
+    }
+
+    /// Whether the value is positive or negative infinity
+    pub fn is_infinite(&self) -> bool {
+        matches!(self, DecimalValue::Infinite { .. })
+    }
+
+    /// Whether the value is not a number
+    pub fn is_nan(&self) -> bool {
+        matches!(self, DecimalValue::NaN { .. })
+    }
+
+    /// Whether the sign bit is set
+    pub fn is_sign_negative(&self) -> bool {
+        match self {
+            DecimalValue::Finite { negative, .. }
+            | DecimalValue::Infinite { negative }
+            | DecimalValue::NaN { negative, .. } => *negative,
+        }
+    }
+
+    /// Returns the canonical member of the values that are equal to this value
+    fn normalize(self) -> Self {
+        match self {
+            DecimalValue::Finite { coefficient: 0, .. } => DecimalValue::Finite {
+                negative: false,
+                coefficient: 0,
+                exponent: 0,
+            },
+            DecimalValue::Finite {
+                negative,
+                mut coefficient,
+                mut exponent,
+            } => {
+                while coefficient % 10 == 0 {
+                    coefficient /= 10;
+                    exponent += 1;
+                }
+                DecimalValue::Finite {
+                    negative,
+                    coefficient,
+                    exponent,
+                }
+            }
+            DecimalValue::Infinite { .. } => self,
+            DecimalValue::NaN { .. } => DecimalValue::NaN {
+                negative: false,
+                signaling: false,
+            },
+        }
+    }
+
+    /// Compares by value. NaNs are equal to each other and greater than any other value.
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        use DecimalValue::*;
+
+        match (self, other) {
+            (NaN { .. }, NaN { .. }) => Ordering::Equal,
+            (NaN { .. }, _) => Ordering::Greater,
+            (_, NaN { .. }) => Ordering::Less,
+            (Infinite { negative: lhs }, Infinite { negative: rhs }) => rhs.cmp(lhs),
+            (Infinite { negative }, Finite { .. }) => match negative {
+                true => Ordering::Less,
+                false => Ordering::Greater,
+            },
+            (Finite { .. }, Infinite { negative }) => match negative {
+                true => Ordering::Greater,
+                false => Ordering::Less,
+            },
+            (
+                Finite {
+                    negative: lhs_negative,
+                    coefficient: lhs_coefficient,
+                    exponent: lhs_exponent,
+                },
+                Finite {
+                    negative: rhs_negative,
+                    coefficient: rhs_coefficient,
+                    exponent: rhs_exponent,
+                },
+            ) => {
+                let signum = |negative: bool, coefficient: u128| match (coefficient, negative) {
+                    (0, _) => 0,
+                    (_, true) => -1,
+                    (_, false) => 1,
+                };
+                let lhs_signum = signum(*lhs_negative, *lhs_coefficient);
+                let rhs_signum = signum(*rhs_negative, *rhs_coefficient);
+                match lhs_signum.cmp(&rhs_signum) {
+                    Ordering::Equal if lhs_signum == 0 => Ordering::Equal,
+                    Ordering::Equal => {
+                        let ordering = cmp_magnitude(
+                            *lhs_coefficient,
+                            *lhs_exponent,
+                            *rhs_coefficient,
+                            *rhs_exponent,
+                        );
+                        match lhs_signum < 0 {
+                            true => ordering.reverse(),
+                            false => ordering,
+                        }
+                    }
+                    ordering => ordering,
+                }
+            }
+        }
+    }
+}
+
+/// Compares the magnitudes of two non-zero finite numbers
+fn cmp_magnitude(lhs: u128, lhs_exponent: i32, rhs: u128, rhs_exponent: i32) -> Ordering {
+    let lhs_digits = digits(lhs) as i32;
+    let rhs_digits = digits(rhs) as i32;
+    let lhs_adjusted = lhs_exponent + lhs_digits - 1;
+    let rhs_adjusted = rhs_exponent + rhs_digits - 1;
+    match lhs_adjusted.cmp(&rhs_adjusted) {
+        // Pad the coefficient with fewer digits so both have the same number of digits
+        Ordering::Equal => match lhs_digits.cmp(&rhs_digits) {
+            Ordering::Less => (lhs * pow10((rhs_digits - lhs_digits) as u32)).cmp(&rhs),
+            _ => lhs.cmp(&(rhs * pow10((lhs_digits - rhs_digits) as u32))),
+        },
+        ordering => ordering,
+    }
+}
+
+fn digits(coefficient: u128) -> u32 {
+    match coefficient {
+        0 => 1,
+        _ => coefficient.ilog10() + 1,
+    }
+}
+
+const fn pow10(exp: u32) -> u128 {
+    10u128.pow(exp)
+}
+
+impl Display for DecimalValue {
+    /// Formats with the scientific notation of the General Decimal Arithmetic specification, which
+    /// is the same as `java.math.BigDecimal::toString`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_sign_negative() && !self.is_nan() {
+            f.write_str("-")?;
+        }
+        let (coefficient, exponent) = match self {
+            DecimalValue::NaN {
+                signaling: true, ..
+            } => return f.write_str("sNaN"),
+            DecimalValue::NaN { .. } => return f.write_str("NaN"),
+            DecimalValue::Infinite { .. } => return f.write_str("Infinity"),
+            DecimalValue::Finite {
+                coefficient,
+                exponent,
+                ..
+            } => (*coefficient, *exponent),
+        };
+
+        let digits = coefficient.to_string();
+        let len = digits.len() as i64;
+        let adjusted = exponent as i64 + len - 1;
+        if exponent <= 0 && adjusted >= -6 {
+            let point = len + exponent as i64;
+            match point {
+                _ if exponent == 0 => f.write_str(&digits),
+                p if p > 0 => {
+                    let (int, frac) = digits.split_at(p as usize);
+                    write!(f, "{}.{}", int, frac)
+                }
+                p => write!(f, "0.{}{}", "0".repeat((-p) as usize), digits),
+            }
+        } else {
+            let (first, rest) = digits.split_at(1);
+            f.write_str(first)?;
+            if !rest.is_empty() {
+                write!(f, ".{}", rest)?;
+            }
+            write!(
+                f,
+                "E{}{}",
+                if adjusted >= 0 { "+" } else { "-" },
+                adjusted.abs()
+            )
+        }
+    }
+}
+
+impl FromStr for DecimalValue {
+    type Err = Error;
+
+    /// Parses a number in plain or scientific notation, eg. `-12.50` or `1.25E+3`, or one of
+    /// `Infinity`, `Inf`, `NaN` and `sNaN` (case insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+
+        if unsigned.eq_ignore_ascii_case("infinity") || unsigned.eq_ignore_ascii_case("inf") {
+            return Ok(DecimalValue::Infinite { negative });
+        }
+        if unsigned.eq_ignore_ascii_case("nan") {
+            return Ok(DecimalValue::NaN {
+                negative,
+                signaling: false,
+            });
+        }
+        if unsigned.eq_ignore_ascii_case("snan") {
+            return Ok(DecimalValue::NaN {
+                negative,
+                signaling: true,
+            });
+        }
+
+        let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+            Some(pos) => {
+                let exponent: i32 = unsigned[pos + 1..]
+                    .parse()
+                    .map_err(|_| Error::InvalidValue)?;
+                (&unsigned[..pos], exponent)
+            }
+            None => (unsigned, 0),
+        };
+        let (int, frac) = match mantissa.split_once('.') {
+            Some((int, frac)) => (int, frac),
+            None => (mantissa, ""),
+        };
+        if int.is_empty() && frac.is_empty() {
+            return Err(Error::InvalidValue);
+        }
+
+        let mut coefficient: u128 = 0;
+        for byte in int.bytes().chain(frac.bytes()) {
+            let digit = match byte {
+                b'0'..=b'9' => (byte - b'0') as u128,
+                _ => return Err(Error::InvalidValue),
+            };
+            coefficient = coefficient
+                .checked_mul(10)
+                .and_then(|c| c.checked_add(digit))
+                .ok_or(Error::InvalidValue)?;
+        }
+        let frac_len = i32::try_from(frac.len()).map_err(|_| Error::InvalidValue)?;
+        let exponent = exponent.checked_sub(frac_len).ok_or(Error::InvalidValue)?;
+
+        Ok(DecimalValue::Finite {
+            negative,
+            coefficient,
+            exponent,
+        })
+    }
+}
+
+/// Parameters of a decimal interchange format
+struct Format {
+    width: u32,
+    exponent_bits: u32,
+    precision: u32,
+    bias: i32,
+}
+
+impl Format {
+    const fn max_coefficient(&self) -> u128 {
+        pow10(self.precision) - 1
+    }
+
+    const fn min_exponent(&self) -> i32 {
+        -self.bias
+    }
+
+    const fn max_exponent(&self) -> i32 {
+        3 * (1 << (self.exponent_bits - 2)) - 1 - self.bias
+    }
+
+    /// Number of bits of the coefficient if the two bits after the sign are not both set
+    const fn trailing_bits(&self) -> u32 {
+        self.width - 1 - self.exponent_bits
+    }
+
+    fn decode(&self, bits: u128) -> DecimalValue {
+        let negative = (bits >> (self.width - 1)) & 1 == 1;
+        match (bits >> (self.width - 6)) & 0b11111 {
+            0b11111 => {
+                return DecimalValue::NaN {
+                    negative,
+                    signaling: (bits >> (self.width - 7)) & 1 == 1,
+                }
+            }
+            0b11110 => return DecimalValue::Infinite { negative },
+            _ => {}
+        }
+
+        let trailing = self.trailing_bits();
+        let exponent_mask = mask(self.exponent_bits);
+        let (biased, coefficient) = match (bits >> (self.width - 3)) & 0b11 {
+            0b11 => (
+                (bits >> (trailing - 2)) & exponent_mask,
+                (0b100 << (trailing - 2)) | (bits & mask(trailing - 2)),
+            ),
+            _ => ((bits >> trailing) & exponent_mask, bits & mask(trailing)),
+        };
+        // Non-canonical coefficients are treated as zero
+        let coefficient = match coefficient > self.max_coefficient() {
+            true => 0,
+            false => coefficient,
+        };
+        DecimalValue::Finite {
+            negative,
+            coefficient,
+            exponent: biased as i32 - self.bias,
+        }
+    }
+
+    fn encode(&self, value: DecimalValue) -> Result<u128, Error> {
+        let sign = |negative: bool| (negative as u128) << (self.width - 1);
+        let (negative, mut coefficient, mut exponent) = match value {
+            DecimalValue::NaN {
+                negative,
+                signaling,
+            } => {
+                return Ok(sign(negative)
+                    | (0b11111 << (self.width - 6))
+                    | ((signaling as u128) << (self.width - 7)))
+            }
+            DecimalValue::Infinite { negative } => {
+                return Ok(sign(negative) | (0b11110 << (self.width - 6)))
+            }
+            DecimalValue::Finite {
+                negative,
+                coefficient,
+                exponent,
+            } => (negative, coefficient, exponent),
+        };
+
+        // Remove the trailing zeros that do not fit
+        while coefficient != 0
+            && coefficient % 10 == 0
+            && (coefficient > self.max_coefficient() || exponent < self.min_exponent())
+        {
+            coefficient /= 10;
+            exponent += 1;
+        }
+        if coefficient == 0 {
+            exponent = exponent.clamp(self.min_exponent(), self.max_exponent());
+        }
+        // Pad with trailing zeros if the exponent is too large
+        while exponent > self.max_exponent() && coefficient <= self.max_coefficient() / 10 {
+            coefficient *= 10;
+            exponent -= 1;
+        }
+        if coefficient > self.max_coefficient()
+            || exponent < self.min_exponent()
+            || exponent > self.max_exponent()
+        {
+            return Err(Error::InvalidValue);
+        }
+
+        let biased = (exponent + self.bias) as u128;
+        let trailing = self.trailing_bits();
+        let bits = match coefficient >> trailing {
+            0 => (biased << trailing) | coefficient,
+            _ => {
+                (0b11 << (self.width - 3))
+                    | (biased << (trailing - 2))
+                    | (coefficient & mask(trailing - 2))
+            }
+        };
+        Ok(sign(negative) | bits)
+    }
+}
+
+const fn mask(bits: u32) -> u128 {
+    (1 << bits) - 1
+}
+
+const DEC32_FORMAT: Format = Format {
+    width: 32,
+    exponent_bits: 8,
+    precision: 7,
+    bias: 101,
+};
+
+const DEC64_FORMAT: Format = Format {
+    width: 64,
+    exponent_bits: 10,
+    precision: 16,
+    bias: 398,
+};
+
+const DEC128_FORMAT: Format = Format {
+    width: 128,
+    exponent_bits: 14,
+    precision: 34,
+    bias: 6176,
+};
+
+macro_rules! impl_decimal {
+    ($ty:ident, $format:ident, $bits:ty) => {
+        impl $ty {
+            /// Decodes the value
+            pub fn value(&self) -> DecimalValue {
+                $format.decode(<$bits>::from_be_bytes(self.0) as u128)
+            }
+
+            /// Encodes the value. An error is returned if the value cannot be represented
+            /// exactly.
+            pub fn from_value(value: DecimalValue) -> Result<Self, Error> {
+                let bits = $format.encode(value)? as $bits;
+                Ok(Self(bits.to_be_bytes()))
+            }
+
+            /// Whether the value is finite
+            pub fn is_finite(&self) -> bool {
+                self.value().is_finite()
+            }
+
+            /// Whether the value is positive or negative infinity
+            pub fn is_infinite(&self) -> bool {
+                self.value().is_infinite()
+            }
+
+            /// Whether the value is not a number
+            pub fn is_nan(&self) -> bool {
+                self.value().is_nan()
+            }
+
+            /// Whether the sign bit is set
+            pub fn is_sign_negative(&self) -> bool {
+                self.value().is_sign_negative()
+            }
+        }
+
+        impl TryFrom<DecimalValue> for $ty {
+            type Error = Error;
+
+            fn try_from(value: DecimalValue) -> Result<Self, Self::Error> {
+                Self::from_value(value)
+            }
+        }
+
+        impl From<$ty> for DecimalValue {
+            fn from(value: $ty) -> Self {
+                value.value()
+            }
+        }
+
+        impl Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.value().fmt(f)
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::from_value(s.parse()?)
+            }
+        }
+
+        impl PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for $ty {}
+
+        impl PartialOrd for $ty {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $ty {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.value().total_cmp(&other.value())
+            }
+        }
+
+        impl Hash for $ty {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.value().normalize().hash(state)
+            }
+        }
+
+        #[cfg_attr(docsrs, doc(cfg(feature = "rust_decimal")))]
+        #[cfg(feature = "rust_decimal")]
+        impl TryFrom<$ty> for rust_decimal::Decimal {
+            type Error = Error;
+
+            fn try_from(value: $ty) -> Result<Self, Self::Error> {
+                decimal_value_to_rust_decimal(value.value())
+            }
+        }
+
+        #[cfg_attr(docsrs, doc(cfg(feature = "rust_decimal")))]
+        #[cfg(feature = "rust_decimal")]
+        impl TryFrom<rust_decimal::Decimal> for $ty {
+            type Error = Error;
+
+            fn try_from(value: rust_decimal::Decimal) -> Result<Self, Self::Error> {
+                Self::from_value(DecimalValue::Finite {
+                    negative: value.is_sign_negative(),
+                    coefficient: value.mantissa().unsigned_abs(),
+                    exponent: -(value.scale() as i32),
+                })
+            }
+        }
+
+        #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
+        #[cfg(feature = "bigdecimal")]
+        impl TryFrom<$ty> for bigdecimal::BigDecimal {
+            type Error = Error;
+
+            fn try_from(value: $ty) -> Result<Self, Self::Error> {
+                decimal_value_to_bigdecimal(value.value())
+            }
+        }
+
+        #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
+        #[cfg(feature = "bigdecimal")]
+        impl TryFrom<&bigdecimal::BigDecimal> for $ty {
+            type Error = Error;
+
+            fn try_from(value: &bigdecimal::BigDecimal) -> Result<Self, Self::Error> {
+                Self::from_value(bigdecimal_to_decimal_value(value)?)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "rust_decimal")]
+fn decimal_value_to_rust_decimal(value: DecimalValue) -> Result<rust_decimal::Decimal, Error> {
+    let (negative, mut coefficient, mut exponent) = match value {
+        DecimalValue::Finite {
+            negative,
+            coefficient,
+            exponent,
+        } => (negative, coefficient, exponent),
+        _ => return Err(Error::InvalidValue),
+    };
+    while exponent < -(rust_decimal::Decimal::MAX_SCALE as i32) && coefficient % 10 == 0 {
+        coefficient /= 10;
+        exponent += 1;
+    }
+    let mut mantissa = i128::try_from(coefficient).map_err(|_| Error::InvalidValue)?;
+    if exponent > 0 {
+        let scale = 10i128
+            .checked_pow(exponent as u32)
+            .ok_or(Error::InvalidValue)?;
+        mantissa = mantissa.checked_mul(scale).ok_or(Error::InvalidValue)?;
+        exponent = 0;
+    }
+    if negative {
+        mantissa = -mantissa;
+    }
+    rust_decimal::Decimal::try_from_i128_with_scale(mantissa, (-exponent) as u32)
+        .map_err(|_| Error::InvalidValue)
+}
+
+#[cfg(feature = "bigdecimal")]
+fn decimal_value_to_bigdecimal(value: DecimalValue) -> Result<bigdecimal::BigDecimal, Error> {
+    match value {
+        DecimalValue::Finite {
+            negative,
+            coefficient,
+            exponent,
+        } => {
+            let mut digits = bigdecimal::num_bigint::BigInt::from(coefficient);
+            if negative {
+                digits = -digits;
+            }
+            Ok(bigdecimal::BigDecimal::new(digits, -(exponent as i64)))
+        }
+        _ => Err(Error::InvalidValue),
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+fn bigdecimal_to_decimal_value(value: &bigdecimal::BigDecimal) -> Result<DecimalValue, Error> {
+    use bigdecimal::ToPrimitive;
+
+    let (digits, scale) = value.as_bigint_and_exponent();
+    let (digits, scale) = match digits.magnitude().to_u128() {
+        Some(_) => (digits, scale),
+        None => value.normalized().as_bigint_and_exponent(),
+    };
+    let coefficient = digits.magnitude().to_u128().ok_or(Error::InvalidValue)?;
+    let exponent = i32::try_from(-scale).map_err(|_| Error::InvalidValue)?;
+    Ok(DecimalValue::Finite {
+        negative: digits.sign() == bigdecimal::num_bigint::Sign::Minus,
+        coefficient,
+        exponent,
+    })
+}
+
 mod dec32 {
     // use serde_bytes::ByteBuf;
 
@@ -20,7 +670,7 @@ mod dec32 {
     /// encoding name = "ieee-754", encoding code = 0x74
     /// category = fixed, width = 4
     /// label = "IEEE 754-2008 decimal32 using the Binary Integer Decimal encoding"
-    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[derive(Debug, Clone)]
     pub struct Dec32([u8; DECIMAL32_WIDTH]);
 
     impl Dec32 {
@@ -30,6 +680,8 @@ mod dec32 {
         }
     }
 
+    impl_decimal!(Dec32, DEC32_FORMAT, u32);
+
     impl From<[u8; DECIMAL32_WIDTH]> for Dec32 {
         fn from(val: [u8; DECIMAL32_WIDTH]) -> Self {
             Self(val)
@@ -70,7 +722,7 @@ mod dec32 {
     impl de::Visitor<'_> for Visitor {
         type Value = Dec32;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
             formatter.write_str("struct Dec32")
         }
 
@@ -104,7 +756,7 @@ mod dec64 {
     /// encoding name = "ieee-754", encoding code = 0x84
     /// category = fixed, width = 8
     /// label = "IEEE 754-2008 decimal64 using the Binary Integer Decimal encoding"
-    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[derive(Debug, Clone)]
     pub struct Dec64([u8; DECIMAL64_WIDTH]);
 
     impl Dec64 {
@@ -114,12 +766,20 @@ mod dec64 {
         }
     }
 
+    impl_decimal!(Dec64, DEC64_FORMAT, u64);
+
     impl From<[u8; DECIMAL64_WIDTH]> for Dec64 {
         fn from(val: [u8; DECIMAL64_WIDTH]) -> Self {
             Self(val)
         }
     }
 
+    impl From<Dec64> for [u8; DECIMAL64_WIDTH] {
+        fn from(val: Dec64) -> Self {
+            val.0
+        }
+    }
+
     impl TryFrom<&[u8]> for Dec64 {
         type Error = Error;
 
@@ -148,7 +808,7 @@ mod dec64 {
     impl de::Visitor<'_> for Visitor {
         type Value = Dec64;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
             formatter.write_str("struct Dec64")
         }
 
@@ -182,7 +842,7 @@ mod dec128 {
     /// encoding name = "ieee-754", encoding code = 0x94
     /// category = fixed, width = 16
     /// label = "IEEE 754-2008 decimal128 using the Binary Integer Decimal encoding"
-    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[derive(Debug, Clone)]
     pub struct Dec128([u8; DECIMAL128_WIDTH]);
 
     impl Dec128 {
@@ -192,12 +852,20 @@ mod dec128 {
         }
     }
 
+    impl_decimal!(Dec128, DEC128_FORMAT, u128);
+
     impl From<[u8; DECIMAL128_WIDTH]> for Dec128 {
         fn from(val: [u8; DECIMAL128_WIDTH]) -> Self {
             Self(val)
         }
     }
 
+    impl From<Dec128> for [u8; DECIMAL128_WIDTH] {
+        fn from(val: Dec128) -> Self {
+            val.0
+        }
+    }
+
     impl TryFrom<&[u8]> for Dec128 {
         type Error = Error;
 
@@ -226,7 +894,7 @@ mod dec128 {
     impl de::Visitor<'_> for Visitor {
         type Value = Dec128;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
             formatter.write_str("struct Dec128")
         }
 
@@ -251,3 +919,170 @@ mod dec128 {
 pub use dec128::*;
 pub use dec32::*;
 pub use dec64::*;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{from_slice, to_vec};
+
+    use super::{Dec128, Dec32, Dec64, DecimalValue};
+
+    fn finite(negative: bool, coefficient: u128, exponent: i32) -> DecimalValue {
+        DecimalValue::Finite {
+            negative,
+            coefficient,
+            exponent,
+        }
+    }
+
+    #[test]
+    fn test_decode_known_bits() {
+        let one = Dec32::from(0x3280_0001u32.to_be_bytes());
+        assert_eq!(one.value(), finite(false, 1, 0));
+
+        let max = Dec32::from(0x6cb8_967fu32.to_be_bytes());
+        assert_eq!(max.value(), finite(false, 9_999_999, 0));
+
+        let one = Dec64::from(0x31c0_0000_0000_0001u64.to_be_bytes());
+        assert_eq!(one.value(), finite(false, 1, 0));
+
+        let minus_one_tenth = Dec64::from(0xb1a0_0000_0000_0001u64.to_be_bytes());
+        assert_eq!(minus_one_tenth.value(), finite(true, 1, -1));
+
+        let one = Dec128::from(0x3040_0000_0000_0000_0000_0000_0000_0001u128.to_be_bytes());
+        assert_eq!(one.value(), finite(false, 1, 0));
+    }
+
+    #[test]
+    fn test_encode_known_bits() {
+        let max = Dec32::from_value(finite(false, 9_999_999, 0)).unwrap();
+        assert_eq!(max.into_inner(), 0x6cb8_967fu32.to_be_bytes());
+
+        let one = Dec128::from_value(finite(false, 1, 0)).unwrap();
+        assert_eq!(
+            one.into_inner(),
+            0x3040_0000_0000_0000_0000_0000_0000_0001u128.to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn test_special_values() {
+        for s in ["Infinity", "-Infinity", "NaN", "sNaN"] {
+            let value: Dec64 = s.parse().unwrap();
+            assert_eq!(value.to_string(), s);
+        }
+        assert!("inf".parse::<Dec32>().unwrap().is_infinite());
+        assert!("nan".parse::<Dec128>().unwrap().is_nan());
+        assert!("-1".parse::<Dec32>().unwrap().is_sign_negative());
+    }
+
+    #[test]
+    fn test_display_and_from_str_round_trip() {
+        let cases = [
+            "0",
+            "-0",
+            "1",
+            "-12.50",
+            "0.000001",
+            "1E-7",
+            "1.23E+3",
+            "123456789.0123456789",
+            "9.999999999999999999999999999999999E+6144",
+            "1E-6176",
+        ];
+        for s in cases {
+            let value: Dec128 = s.parse().unwrap();
+            assert_eq!(value.to_string(), s);
+
+            let buf = to_vec(&value).unwrap();
+            let decoded: Dec128 = from_slice(&buf).unwrap();
+            assert_eq!(decoded.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_inexact_is_rejected() {
+        assert!("12345678".parse::<Dec32>().is_err());
+        assert!("1E+200".parse::<Dec32>().is_err());
+        assert!("1E-200".parse::<Dec32>().is_err());
+        assert!("1.2.3".parse::<Dec32>().is_err());
+        assert!("".parse::<Dec32>().is_err());
+        assert!("100000000000000000000000000000000000001E+7000"
+            .parse::<Dec128>()
+            .is_err());
+
+        // Trailing zeros that do not fit are removed
+        let value: Dec32 = "12345670".parse().unwrap();
+        assert_eq!(value.value(), finite(false, 1_234_567, 1));
+
+        // Zeros are padded if the exponent is too large
+        let value: Dec32 = "1E+95".parse().unwrap();
+        assert_eq!(value.value(), finite(false, 100_000, 90));
+    }
+
+    #[test]
+    fn test_eq_and_ord_by_value() {
+        let parse = |s: &str| s.parse::<Dec64>().unwrap();
+
+        assert_eq!(parse("1.0"), parse("1.00"));
+        assert_eq!(parse("-0"), parse("0E+5"));
+        assert_eq!(parse("NaN"), parse("-sNaN"));
+        assert_ne!(parse("1.0"), parse("1.01"));
+
+        let ordered = [
+            "-Infinity",
+            "-1E+3",
+            "-1.5",
+            "0",
+            "0.5",
+            "0.51",
+            "1",
+            "1E+2",
+            "Infinity",
+            "NaN",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+
+        let set: HashSet<Dec64> = ["1", "1.0", "1.000", "10E-1"].map(parse).into();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn test_rust_decimal_conversions() {
+        use rust_decimal::Decimal;
+
+        let decimal: Decimal = "-123.4500".parse().unwrap();
+        let dec128 = Dec128::try_from(decimal).unwrap();
+        assert_eq!(dec128.to_string(), "-123.4500");
+        assert_eq!(Decimal::try_from(dec128).unwrap(), decimal);
+
+        let dec32: Dec32 = "1.5E+3".parse().unwrap();
+        assert_eq!(Decimal::try_from(dec32).unwrap(), Decimal::from(1500));
+
+        let nan: Dec64 = "NaN".parse().unwrap();
+        assert!(Decimal::try_from(nan).is_err());
+        assert!(Dec32::try_from(Decimal::MAX).is_err());
+    }
+
+    #[cfg(feature = "bigdecimal")]
+    #[test]
+    fn test_bigdecimal_conversions() {
+        use bigdecimal::BigDecimal;
+
+        let big: BigDecimal = "-123.4500".parse().unwrap();
+        let dec128 = Dec128::try_from(&big).unwrap();
+        assert_eq!(dec128.to_string(), "-123.4500");
+        assert_eq!(BigDecimal::try_from(dec128).unwrap(), big);
+
+        let dec64: Dec64 = "1.5E+300".parse().unwrap();
+        let big = BigDecimal::try_from(dec64).unwrap();
+        assert_eq!(big, "1.5E+300".parse::<BigDecimal>().unwrap());
+
+        let inf: Dec64 = "Infinity".parse().unwrap();
+        assert!(BigDecimal::try_from(inf).is_err());
+    }
+}
@@ -2,6 +2,7 @@
 
 mod array;
 mod binary_ref;
+mod bytes;
 mod decimal;
 mod map;
 mod symbol;
@@ -11,6 +12,7 @@ mod uuid;
 // to avoid ambiguity
 pub use crate::primitives::array::*;
 pub use crate::primitives::binary_ref::*;
+pub use crate::primitives::bytes::*;
 pub use crate::primitives::decimal::*;
 pub use crate::primitives::map::*;
 pub use crate::primitives::symbol::*;
@@ -18,6 +20,7 @@ pub use crate::primitives::timestamp::*;
 pub use crate::primitives::uuid::*;
 
 // Alias for the primitive types to match those in the spec
+use alloc::vec::Vec;
 use serde_bytes::ByteBuf;
 
 /// Represents a true or false value
@@ -1,4 +1,5 @@
-use std::{
+use alloc::string::String;
+use core::{
     borrow::Borrow,
     ops::{Deref, DerefMut},
 };
@@ -80,7 +81,7 @@ struct SymbolRefVisitor {}
 impl<'de> Visitor<'de> for SymbolRefVisitor {
     type Value = SymbolRef<'de>;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("A borrowed symbol")
     }
 
@@ -88,7 +89,7 @@ impl<'de> Visitor<'de> for SymbolRefVisitor {
     where
         E: de::Error,
     {
-        std::str::from_utf8(v)
+        core::str::from_utf8(v)
             .map(SymbolRef)
             .map_err(|e| de::Error::custom(e))
     }
@@ -209,7 +210,7 @@ struct SymbolVisitor {}
 impl<'de> Visitor<'de> for SymbolVisitor {
     type Value = Symbol;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("struct Symbol")
     }
 
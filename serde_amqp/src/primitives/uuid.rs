@@ -1,6 +1,7 @@
-use std::convert::TryFrom;
-use std::fmt::LowerHex;
-use std::fmt::UpperHex;
+use alloc::string::ToString;
+use core::convert::TryFrom;
+use core::fmt::LowerHex;
+use core::fmt::UpperHex;
 
 use serde::de;
 use serde::ser;
@@ -101,7 +102,7 @@ struct Visitor {}
 impl de::Visitor<'_> for Visitor {
     type Value = Uuid;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("struct Uuid")
     }
 
@@ -123,7 +124,7 @@ impl<'de> de::Deserialize<'de> for Uuid {
 }
 
 impl LowerHex for Uuid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
@@ -137,7 +138,7 @@ impl LowerHex for Uuid {
 }
 
 impl UpperHex for Uuid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
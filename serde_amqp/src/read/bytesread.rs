@@ -0,0 +1,99 @@
+use bytes::Bytes;
+
+use crate::{error::Error, io};
+
+use super::{private, Read, SliceReader};
+
+/// A reader for [`Bytes`]
+///
+/// The bytes and strings are forwarded to the visitor as borrowed slices just like
+/// [`SliceReader`]. The `Bytes`-backed types (ie. [`BinaryBytes`](crate::primitives::BinaryBytes),
+/// [`StrBytes`](crate::primitives::StrBytes) and [`SymbolBytes`](crate::primitives::SymbolBytes))
+/// share the underlying buffer via reference counting instead of copying when they are
+/// deserialized with [`Shared`](crate::primitives::Shared) over the same [`Bytes`].
+#[derive(Debug)]
+pub struct BytesReader<'b> {
+    bytes: &'b Bytes,
+    inner: SliceReader<'b>,
+}
+
+impl<'b> BytesReader<'b> {
+    /// Creates a new bytes reader
+    pub fn new(bytes: &'b Bytes) -> Self {
+        Self {
+            bytes,
+            inner: SliceReader::new(bytes),
+        }
+    }
+
+    /// Get a reference to the buffer being read
+    pub fn bytes(&self) -> &'b Bytes {
+        self.bytes
+    }
+}
+
+impl private::Sealed for BytesReader<'_> {}
+
+impl<'b> Read<'b> for BytesReader<'b> {
+    fn position(&self) -> usize {
+        self.inner.position()
+    }
+
+    fn consumed_since(&self, position: usize) -> Option<&[u8]> {
+        self.inner.consumed_since(position)
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.inner.peek()
+    }
+
+    fn peek_bytes(&mut self, n: usize) -> Result<Option<&[u8]>, io::Error> {
+        self.inner.peek_bytes(n)
+    }
+
+    fn next(&mut self) -> Result<Option<u8>, io::Error> {
+        self.inner.next()
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.inner.read_exact(buf)
+    }
+
+    fn forward_read_bytes_with_hint<V>(&mut self, len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'b>,
+    {
+        self.inner.forward_read_bytes_with_hint(len, visitor)
+    }
+
+    fn forward_read_byte_buf<V>(&mut self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'b>,
+    {
+        self.inner.forward_read_byte_buf(visitor)
+    }
+
+    fn forward_read_str<V>(&mut self, len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'b>,
+    {
+        self.inner.forward_read_str(len, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::{BytesReader, Read};
+
+    #[test]
+    fn test_read_through_bytes_reader() {
+        let bytes = Bytes::from_static(&[0, 1, 2, 3]);
+        let mut reader = BytesReader::new(&bytes);
+        assert_eq!(reader.peek(), Some(0));
+        assert_eq!(reader.next().unwrap(), Some(0));
+        assert_eq!(reader.read_const_bytes::<3>().unwrap(), [1, 2, 3]);
+        assert!(reader.next().unwrap().is_none());
+    }
+}
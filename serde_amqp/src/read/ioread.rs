@@ -10,6 +10,8 @@ pub struct IoReader<R> {
     // an io reader
     reader: R,
     buf: Vec<u8>,
+    // number of bytes read from the io reader
+    read: usize,
 }
 
 impl<R: io::Read> IoReader<R> {
@@ -18,6 +20,7 @@ impl<R: io::Read> IoReader<R> {
         Self {
             reader,
             buf: Vec::new(),
+            read: 0,
         }
     }
 
@@ -34,7 +37,12 @@ impl<R: io::Read> IoReader<R> {
         let l = self.buf.len();
         if l < len {
             self.buf.resize(len, 0);
-            self.reader.read_exact(&mut self.buf[l..])?;
+            if let Err(err) = self.reader.read_exact(&mut self.buf[l..]) {
+                // Don't leave the zero padding in the buffer
+                self.buf.truncate(l);
+                return Err(err);
+            }
+            self.read += len - l;
             Ok(())
         } else {
             Ok(())
@@ -45,6 +53,10 @@ impl<R: io::Read> IoReader<R> {
 impl<R: io::Read> private::Sealed for IoReader<R> {}
 
 impl<'de, R: io::Read + 'de> Read<'de> for IoReader<R> {
+    fn position(&self) -> usize {
+        self.read - self.buf.len()
+    }
+
     fn peek(&mut self) -> Option<u8> {
         match self.buf.first() {
             Some(b) => Some(*b),
@@ -52,6 +64,7 @@ impl<'de, R: io::Read + 'de> Read<'de> for IoReader<R> {
                 let mut buf = [0u8; 1];
                 match self.reader.read_exact(&mut buf) {
                     Ok(_) => {
+                        self.read += 1;
                         self.buf.push(buf[0]);
                         Some(buf[0])
                     }
@@ -77,6 +90,7 @@ impl<'de, R: io::Read + 'de> Read<'de> for IoReader<R> {
             None => {
                 let mut buf = [0u8; 1];
                 self.reader.read_exact(&mut buf)?;
+                self.read += 1;
                 Ok(Some(buf[0]))
             }
         }
@@ -91,6 +105,9 @@ impl<'de, R: io::Read + 'de> Read<'de> for IoReader<R> {
             let result = self.reader.read_exact(&mut buf[l..]);
             // drain the buffer even if the read fails
             self.buf.drain(..l);
+            if result.is_ok() {
+                self.read += n - l;
+            }
             result
         } else {
             buf.copy_from_slice(&self.buf[..n]);
@@ -1,10 +1,16 @@
 //! Custom `Read` trait
 
-use std::io;
+use alloc::{vec, vec::Vec};
 
-use crate::{error::Error, format::Category, format_code::EncodingCodes};
+use crate::{error::Error, format::Category, format_code::EncodingCodes, io};
 
+mod bytesread;
+pub use bytesread::*;
+
+#[cfg(feature = "std")]
 mod ioread;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
 pub use ioread::*;
 
 mod sliceread;
@@ -19,6 +25,16 @@ pub trait Read<'de>: private::Sealed {
     /// Peek the next byte without consuming
     fn peek(&mut self) -> Option<u8>;
 
+    /// Number of bytes consumed so far
+    fn position(&self) -> usize;
+
+    /// The bytes consumed since `position` if the reader still holds them
+    ///
+    /// This is only used to recover the key of a map entry whose value failed to decode
+    fn consumed_since(&self, _position: usize) -> Option<&[u8]> {
+        None
+    }
+
     /// Read the next byte
     fn next(&mut self) -> Result<Option<u8>, io::Error>;
 
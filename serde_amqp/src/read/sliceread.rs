@@ -1,19 +1,21 @@
-use std::io;
-
-use crate::error::Error;
+use crate::{error::Error, io};
 
 use super::{private, read_described_bytes, read_primitive_bytes_or_else, Read};
 
 /// A reader for a slice of bytes
 #[derive(Debug)]
 pub struct SliceReader<'s> {
+    input: &'s [u8],
     slice: &'s [u8],
 }
 
 impl<'s> SliceReader<'s> {
     /// Creates a new slice reader
     pub fn new(slice: &'s [u8]) -> Self {
-        Self { slice }
+        Self {
+            input: slice,
+            slice,
+        }
     }
 
     /// Return a slice of the given length. If the internal slice doesn't have
@@ -31,6 +33,14 @@ impl<'s> SliceReader<'s> {
 impl private::Sealed for SliceReader<'_> {}
 
 impl<'s> Read<'s> for SliceReader<'s> {
+    fn position(&self) -> usize {
+        self.input.len() - self.slice.len()
+    }
+
+    fn consumed_since(&self, position: usize) -> Option<&[u8]> {
+        self.input.get(position..self.position())
+    }
+
     fn peek(&mut self) -> Option<u8> {
         self.slice.first().copied()
     }
@@ -50,7 +60,17 @@ impl<'s> Read<'s> for SliceReader<'s> {
     }
 
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
-        std::io::Read::read_exact(&mut self.slice, buf)
+        if self.slice.len() < buf.len() {
+            self.slice = &[];
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        let (read_slice, remaining) = self.slice.split_at(buf.len());
+        buf.copy_from_slice(read_slice);
+        self.slice = remaining;
+        Ok(())
     }
 
     fn forward_read_bytes_with_hint<V>(&mut self, len: usize, visitor: V) -> Result<V::Value, Error>
@@ -72,7 +92,7 @@ impl<'s> Read<'s> for SliceReader<'s> {
     where
         V: serde::de::Visitor<'s>,
     {
-        let str_slice = std::str::from_utf8(self.get_byte_slice(len)?)?;
+        let str_slice = core::str::from_utf8(self.get_byte_slice(len)?)?;
         visitor.visit_borrowed_str(str_slice)
     }
 }
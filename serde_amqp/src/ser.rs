@@ -1,8 +1,8 @@
 //! Serializer implementation
 
-use std::io::Write;
+use crate::io::Write;
+use alloc::{string::String, vec, vec::Vec};
 
-use bytes::BufMut;
 use serde::{
     ser::{self, SerializeMap},
     Serialize,
@@ -11,7 +11,8 @@ use serde::{
 use crate::{
     __constants::{
         ARRAY, DECIMAL128, DECIMAL32, DECIMAL64, DESCRIBED_BASIC, DESCRIBED_LIST, DESCRIBED_MAP,
-        DESCRIPTOR, LAZY_VALUE, SYMBOL, SYMBOL_REF, TIMESTAMP, TRANSPARENT_VEC, UUID,
+        DESCRIPTOR, LAZY_VALUE, REST_MAP_KEY, REST_MAP_VALUE, SYMBOL, SYMBOL_REF, TIMESTAMP,
+        TRANSPARENT_VEC, UUID,
     },
     error::Error,
     format::{OFFSET_LIST32, OFFSET_LIST8, OFFSET_MAP32, OFFSET_MAP8},
@@ -1081,7 +1082,7 @@ enum MapSerializerState {
 
 impl MapSerializerState {
     fn take(&mut self) -> Self {
-        std::mem::replace(self, MapSerializerState::Init(None))
+        core::mem::replace(self, MapSerializerState::Init(None))
     }
 }
 
@@ -1442,6 +1443,18 @@ impl<'a, W: Write + 'a> ser::SerializeStruct for StructSerializer<'a, W> {
     {
         if key == DESCRIPTOR {
             value.serialize(self.as_mut())
+        } else if key == REST_MAP_KEY {
+            // The key of an entry captured by a trailing rest field. The entry is counted when
+            // its value is serialized
+            match self.se.struct_encoding() {
+                StructEncoding::DescribedMap => {
+                    let mut serializer = Serializer::described_map(&mut self.buf);
+                    value.serialize(&mut serializer)
+                }
+                _ => Err(Error::Message(String::from(
+                    "Rest map entries are only supported with the map encoding",
+                ))),
+            }
         } else {
             self.count += 1;
             match self.se.struct_encoding() {
@@ -1458,7 +1471,9 @@ impl<'a, W: Write + 'a> ser::SerializeStruct for StructSerializer<'a, W> {
                 }
                 StructEncoding::DescribedMap => {
                     let mut serializer = Serializer::described_map(&mut self.buf);
-                    key.serialize(&mut serializer)?;
+                    if key != REST_MAP_VALUE {
+                        key.serialize(&mut serializer)?;
+                    }
                     value.serialize(&mut serializer)
                 }
             }
@@ -1545,19 +1560,16 @@ impl<'a, W: Write + 'a> ser::SerializeTupleVariant for VariantSerializer<'a, W>
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        use bytes::BytesMut;
-        let kv_buf = BytesMut::new();
-        let mut writer = kv_buf.writer();
+        let mut buf = Vec::new();
 
         // Serialize key
-        let mut key_se = Serializer::new(&mut writer);
+        let mut key_se = Serializer::new(&mut buf);
         ser::Serialize::serialize(&self.variant_index, &mut key_se)?;
 
         // Write values
-        write_list(&mut writer, self.num, &self.buf, &self.se.is_array_elem)?;
+        write_list(&mut buf, self.num, &self.buf, &self.se.is_array_elem)?;
 
         // Write entire list
-        let buf = writer.into_inner().freeze();
         // write_list(&mut self.se.writer, 2, &buf, &self.se.is_array_elem)
         write_map(&mut self.se.writer, 2, &buf, &self.se.is_array_elem)
     }
@@ -1,15 +1,17 @@
 //! Serializer that calculates the size of serialized data without actually allocating `Vec<u8>`
 
+use alloc::{vec, vec::Vec};
 use serde::ser::{self, SerializeMap};
 
 use crate::{
-    Error,
     __constants::{
         ARRAY, DECIMAL128, DECIMAL32, DECIMAL64, DESCRIBED_BASIC, DESCRIBED_LIST, DESCRIBED_MAP,
-        DESCRIPTOR, LAZY_VALUE, SYMBOL, SYMBOL_REF, TIMESTAMP, TRANSPARENT_VEC, UUID,
+        DESCRIPTOR, LAZY_VALUE, REST_MAP_KEY, REST_MAP_VALUE, SYMBOL, SYMBOL_REF, TIMESTAMP,
+        TRANSPARENT_VEC, UUID,
     },
     ser::{U32_MAX_MINUS_4, U8_MAX, U8_MAX_MINUS_1},
     util::{FieldRole, IsArrayElement, NonNativeType, SequenceType, StructEncoding},
+    Error,
 };
 
 /// Obtain the serialized size without allocating `Vec<u8>`
@@ -756,6 +758,10 @@ impl ser::SerializeStruct for StructSerializer<'_> {
         if key == DESCRIPTOR {
             self.cumulated_size += value.serialize(&mut *self.se)?;
             Ok(())
+        } else if key == REST_MAP_KEY {
+            let mut serializer = SizeSerializer::described_map();
+            self.cumulated_size += value.serialize(&mut serializer)?;
+            Ok(())
         } else {
             match self.se.struct_encoding() {
                 StructEncoding::None => {
@@ -771,7 +777,9 @@ impl ser::SerializeStruct for StructSerializer<'_> {
                 }
                 StructEncoding::DescribedMap => {
                     let mut serializer = SizeSerializer::described_map();
-                    self.cumulated_size += key.serialize(&mut serializer)?;
+                    if key != REST_MAP_VALUE {
+                        self.cumulated_size += key.serialize(&mut serializer)?;
+                    }
                     self.cumulated_size += value.serialize(&mut serializer)?;
                     Ok(())
                 }
@@ -1,14 +1,21 @@
 //! Value deserializer
 
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 use ordered_float::OrderedFloat;
 use serde::de::{self};
 use serde_bytes::ByteBuf;
 
 use crate::{
     __constants::{
-        ARRAY, DECIMAL128, DECIMAL32, DECIMAL64, DESCRIBED_BASIC, DESCRIPTOR, LAZY_VALUE, SYMBOL,
-        TIMESTAMP, UUID, VALUE,
+        ARRAY, DECIMAL128, DECIMAL32, DECIMAL64, DESCRIBED_BASIC, DESCRIBED_LIST, DESCRIPTOR,
+        LAZY_VALUE, SYMBOL, TIMESTAMP, UUID, VALUE,
     },
+    described::Described,
+    descriptor::Descriptor,
     error::Error,
     format_code::EncodingCodes,
     primitives::OrderedMap,
@@ -117,7 +124,7 @@ struct FieldVisitor {}
 impl de::Visitor<'_> for FieldVisitor {
     type Value = ValueType;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("field of enum Value")
     }
 
@@ -192,7 +199,7 @@ struct ValueVisitor {
 impl<'de> de::Visitor<'de> for ValueVisitor {
     type Value = Value;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("enum Value")
     }
 
@@ -927,27 +934,33 @@ impl<'de> de::Deserializer<'de> for Deserializer {
     #[inline]
     fn deserialize_tuple_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_tuple(len, visitor)
+        match self.value {
+            Value::Described(described) => visit_described(*described, name, visitor),
+            _ => self.deserialize_tuple(len, visitor),
+        }
     }
 
     #[inline]
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_tuple(fields.len(), visitor)
+        match self.value {
+            Value::Described(described) => visit_described(*described, name, visitor),
+            _ => self.deserialize_tuple(fields.len(), visitor),
+        }
     }
 
     #[inline]
@@ -978,10 +991,10 @@ impl<'de> de::Deserializer<'de> for Deserializer {
             self.enum_type = EnumType::Value;
             self.deserialize_any(visitor)
         } else if name == DESCRIPTOR {
-            self.enum_type = EnumType::Descriptor;
-            match &self.value {
-                Value::Symbol(_) => self.deserialize_newtype_struct(SYMBOL, visitor),
-                Value::Ulong(_) => self.deserialize_u64(visitor),
+            match self.value {
+                v @ (Value::Symbol(_) | Value::Ulong(_)) => {
+                    visitor.visit_enum(DescriptorAccess { value: v })
+                }
                 _ => Err(Error::InvalidValue),
             }
         } else if name == ARRAY {
@@ -1012,6 +1025,14 @@ impl<'de> de::Deserializer<'de> for Deserializer {
                 v @ Value::Symbol(_) => visitor.visit_enum(VariantAccess {
                     iter: vec![v].into_iter(),
                 }),
+                // The variant is identified by the descriptor and the whole described value is
+                // the content of the variant, eg. an enum of described composite types
+                Value::Described(described) => {
+                    let identifier = descriptor_to_value(described.descriptor.clone());
+                    visitor.visit_enum(VariantAccess {
+                        iter: vec![identifier, Value::Described(described)].into_iter(),
+                    })
+                }
                 _ => Err(Error::InvalidValue),
             }
         }
@@ -1036,6 +1057,7 @@ impl<'de> de::Deserializer<'de> for Deserializer {
             }
             EnumType::None => match self.value {
                 Value::Uint(v) => visitor.visit_u32(v),
+                Value::Ulong(v) => visitor.visit_u64(v),
                 Value::Symbol(_) => self.deserialize_newtype_struct(SYMBOL, visitor),
                 _ => Err(Error::InvalidValue),
             },
@@ -1081,6 +1103,34 @@ impl<'de> de::SeqAccess<'de> for SeqAccess {
     }
 }
 
+fn descriptor_to_value(descriptor: Descriptor) -> Value {
+    match descriptor {
+        Descriptor::Name(name) => Value::Symbol(name),
+        Descriptor::Code(code) => Value::Ulong(code),
+    }
+}
+
+/// Visits a described value as a sequence of the descriptor followed by the fields of a
+/// described list (`DESCRIBED_LIST`) or by the value itself otherwise
+fn visit_described<'de, V>(
+    described: Described<Value>,
+    name: &'static str,
+    visitor: V,
+) -> Result<V::Value, Error>
+where
+    V: de::Visitor<'de>,
+{
+    let mut elements = vec![descriptor_to_value(described.descriptor)];
+    match described.value {
+        Value::List(fields) if name == DESCRIBED_LIST => elements.extend(fields),
+        value => elements.push(value),
+    }
+    visitor.visit_seq(SeqAccess {
+        iter: elements.into_iter(),
+        seq_type: SeqType::List,
+    })
+}
+
 /// Accssor for map types
 #[derive(Debug)]
 pub struct MapAccess {
@@ -1192,6 +1242,59 @@ impl<'de> de::VariantAccess<'de> for VariantAccess {
     }
 }
 
+/// Accessor for a descriptor, whose variant is identified by the format code of the value
+#[derive(Debug)]
+pub struct DescriptorAccess {
+    value: Value,
+}
+
+impl<'de> de::EnumAccess<'de> for DescriptorAccess {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let code = self.value.format_code();
+        let val = seed.deserialize(de::IntoDeserializer::<Error>::into_deserializer(code))?;
+        Ok((val, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for DescriptorAccess {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(Error::InvalidValue)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(Deserializer::new(self.value))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::InvalidValue)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::InvalidValue)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde::de;
@@ -1398,6 +1501,45 @@ mod tests {
         assert_eq!(value, expected_bar);
     }
 
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_deserialize_described_from_value() {
+        use crate as serde_amqp;
+        use crate::{described::Described, descriptor::Descriptor, primitives::Symbol};
+        use serde_amqp_derive::{DeserializeComposite, SerializeComposite};
+
+        #[derive(Debug, PartialEq, SerializeComposite, DeserializeComposite)]
+        #[amqp_contract(name = "test:foo:list", code = "0x00:0x13", encoding = "list")]
+        struct Foo {
+            is_foo: bool,
+            count: Option<i32>,
+        }
+
+        #[derive(Debug, PartialEq, SerializeComposite, DeserializeComposite)]
+        #[amqp_contract(code = "0x00:0x14", encoding = "basic")]
+        struct Wrapper(Vec<i32>);
+
+        let descriptor: Descriptor = from_value(Value::Ulong(0x13)).unwrap();
+        assert_eq!(descriptor, Descriptor::Code(0x13));
+        let descriptor: Descriptor = from_value(Value::Symbol(Symbol::from("a:b"))).unwrap();
+        assert_eq!(descriptor, Descriptor::Name(Symbol::from("a:b")));
+
+        let value = Value::from(Described {
+            descriptor: Descriptor::Name(Symbol::from("test:foo:list")),
+            value: Value::List(vec![Value::Bool(true)]),
+        });
+        let expected = Foo {
+            is_foo: true,
+            count: None,
+        };
+        assert_eq_from_value_vs_expected(value, expected);
+
+        // The list of a basic encoding is the value itself
+        let wrapper = Wrapper(vec![1, 2]);
+        let value = to_value(&wrapper).unwrap();
+        assert_eq_from_value_vs_expected(value, wrapper);
+    }
+
     #[cfg(feature = "json")]
     #[test]
     fn test_json_bool() {
@@ -1,13 +1,18 @@
 //! Value type for untyped AMQP1.0 data structures.
 
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::hash::{BuildHasher, Hash};
 use indexmap::IndexMap;
 use ordered_float::OrderedFloat;
 use serde::Serialize;
 use serde_bytes::ByteBuf;
-use std::{
-    collections::{BTreeMap, HashMap},
-    hash::Hash,
-};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
 
 use crate::{
     described::Described,
@@ -19,6 +24,8 @@ use crate::{
 pub(crate) mod de;
 pub(crate) mod ser;
 
+pub use de::Deserializer;
+
 /// Primitive type definitions
 ///
 /// An empty message body may be represented as [`Value::Null`].
@@ -409,8 +416,7 @@ where
     V: Into<Value>,
 {
     fn from(map: BTreeMap<K, V>) -> Self {
-        let map: IndexMap<_, _> = map.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
-        Value::Map(OrderedMap::from(map))
+        Value::Map(map.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
     }
 }
 
@@ -561,9 +567,10 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 impl<K, V> TryFrom<Value> for HashMap<K, V>
 where
-    K: TryFrom<Value, Error = Value> + std::hash::Hash + Eq,
+    K: TryFrom<Value, Error = Value> + core::hash::Hash + Eq,
     V: TryFrom<Value, Error = Value>,
 {
     type Error = Value;
@@ -583,10 +590,11 @@ where
     }
 }
 
-impl<K, V> TryFrom<Value> for IndexMap<K, V>
+impl<K, V, S> TryFrom<Value> for IndexMap<K, V, S>
 where
-    K: TryFrom<Value, Error = Value> + std::hash::Hash + Eq,
+    K: TryFrom<Value, Error = Value> + core::hash::Hash + Eq,
     V: TryFrom<Value, Error = Value>,
+    S: BuildHasher + Default,
 {
     type Error = Value;
 
@@ -0,0 +1,121 @@
+#![cfg(feature = "derive")]
+
+use serde_amqp::{from_slice, to_vec, DeserializeComposite, SerializeComposite};
+
+#[derive(Debug, Clone, PartialEq, SerializeComposite, DeserializeComposite)]
+#[amqp_contract(
+    name = "test:first:list",
+    code = "0x0000_462c:0x0000_0001",
+    encoding = "list"
+)]
+struct First {
+    a: u32,
+    b: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, SerializeComposite, DeserializeComposite)]
+#[amqp_contract(
+    name = "test:second:basic",
+    code = "0x0000_462c:0x0000_0002",
+    encoding = "basic"
+)]
+struct Second(bool);
+
+#[derive(Debug, Clone, PartialEq, SerializeComposite, DeserializeComposite)]
+#[amqp_contract(name = "test:third:list", encoding = "list")]
+struct Third {
+    c: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, SerializeComposite, DeserializeComposite)]
+enum Union {
+    #[amqp_contract(name = "test:first:list", code = "0x0000_462c:0x0000_0001")]
+    First(First),
+
+    #[amqp_contract(name = "test:second:basic", code = "0x0000_462c:0x0000_0002")]
+    Second(Second),
+
+    #[amqp_contract(name = "test:third:list")]
+    Third(Third),
+}
+
+#[derive(Debug, Clone, PartialEq, SerializeComposite, DeserializeComposite)]
+#[amqp_contract(
+    name = "test:container:list",
+    code = "0x0000_462c:0x0000_0010",
+    encoding = "list"
+)]
+struct Container {
+    inner: Option<Union>,
+    tail: u8,
+}
+
+#[test]
+fn enum_encodes_same_as_variant() {
+    let first = First {
+        a: 7,
+        b: Some(String::from("hello")),
+    };
+    assert_eq!(
+        to_vec(&Union::First(first.clone())).unwrap(),
+        to_vec(&first).unwrap()
+    );
+
+    let second = Second(true);
+    assert_eq!(
+        to_vec(&Union::Second(second.clone())).unwrap(),
+        to_vec(&second).unwrap()
+    );
+}
+
+#[test]
+fn enum_dispatch_on_descriptor_code() {
+    let value = Union::First(First { a: 7, b: None });
+    let buf = to_vec(&value).unwrap();
+    let decoded: Union = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+
+    let value = Union::Second(Second(false));
+    let buf = to_vec(&value).unwrap();
+    let decoded: Union = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[test]
+fn enum_dispatch_on_descriptor_name() {
+    let value = Union::Third(Third { c: -13 });
+    let buf = to_vec(&value).unwrap();
+    let decoded: Union = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[test]
+fn enum_unknown_descriptor_is_rejected() {
+    #[derive(Debug, SerializeComposite)]
+    #[amqp_contract(code = "0x0000_462c:0x0000_00ff", encoding = "list")]
+    struct Unknown {
+        a: u32,
+    }
+
+    let buf = to_vec(&Unknown { a: 1 }).unwrap();
+    assert!(from_slice::<Union>(&buf).is_err());
+}
+
+#[test]
+fn enum_as_struct_field() {
+    let value = Container {
+        inner: Some(Union::Second(Second(true))),
+        tail: 3,
+    };
+    let buf = to_vec(&value).unwrap();
+    let decoded: Container = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+
+    let value = Container {
+        inner: None,
+        tail: 3,
+    };
+    let buf = to_vec(&value).unwrap();
+    let decoded: Container = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
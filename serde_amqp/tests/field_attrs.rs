@@ -0,0 +1,186 @@
+#![cfg(feature = "derive")]
+
+use serde_amqp::{
+    from_slice, primitives::OrderedMap, to_vec, DeserializeComposite, SerializeComposite, Value,
+};
+
+fn default_priority() -> u8 {
+    4
+}
+
+mod as_string {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        value.to_string().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, SerializeComposite, DeserializeComposite)]
+#[amqp_contract(
+    name = "test:fields:list",
+    code = "0x0000_462c:0x0000_0020",
+    encoding = "list",
+    rename_all = "kebab-case"
+)]
+struct ListFields {
+    id: u32,
+    #[amqp_contract(skip)]
+    cached: Option<String>,
+    #[amqp_contract(default = "default_priority")]
+    priority: u8,
+    #[amqp_contract(with = "as_string")]
+    count: u32,
+    label: Option<String>,
+    #[amqp_contract(rest)]
+    rest: Vec<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, SerializeComposite, DeserializeComposite)]
+#[amqp_contract(
+    name = "test:fields:list",
+    code = "0x0000_462c:0x0000_0020",
+    encoding = "list",
+    rename_all = "kebab-case"
+)]
+struct ListFieldsV2 {
+    id: u32,
+    #[amqp_contract(default = "default_priority")]
+    priority: u8,
+    count: String,
+    label: Option<String>,
+    extra: Option<bool>,
+    more: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, SerializeComposite, DeserializeComposite)]
+#[amqp_contract(
+    name = "test:fields:map",
+    code = "0x0000_462c:0x0000_0021",
+    encoding = "map",
+    rename_all = "kebab-case"
+)]
+struct MapFields {
+    #[amqp_contract(rename = "x-opt-id")]
+    id: u32,
+    #[amqp_contract(default)]
+    flag: bool,
+    #[amqp_contract(skip)]
+    cached: Option<String>,
+    #[amqp_contract(rest)]
+    rest: OrderedMap<Value, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, SerializeComposite, DeserializeComposite)]
+#[amqp_contract(
+    name = "test:fields:map",
+    code = "0x0000_462c:0x0000_0021",
+    encoding = "map",
+    rename_all = "kebab-case"
+)]
+struct MapFieldsV2 {
+    #[amqp_contract(rename = "x-opt-id")]
+    id: u32,
+    #[amqp_contract(default)]
+    flag: bool,
+    unknown: Option<String>,
+}
+
+#[test]
+fn skipped_field_is_not_encoded() {
+    let value = ListFields {
+        id: 1,
+        cached: Some(String::from("cached")),
+        priority: 5,
+        count: 10,
+        label: None,
+        rest: vec![],
+    };
+    let buf = to_vec(&value).unwrap();
+    let decoded: ListFields = from_slice(&buf).unwrap();
+    assert_eq!(decoded.cached, None);
+    assert_eq!(decoded.priority, 5);
+    assert_eq!(decoded.count, 10);
+}
+
+#[test]
+fn default_fn_is_used_for_absent_field() {
+    let value = ListFields {
+        id: 1,
+        cached: None,
+        priority: default_priority(),
+        count: 0,
+        label: None,
+        rest: vec![],
+    };
+    let buf = to_vec(&value).unwrap();
+    let decoded: ListFieldsV2 = from_slice(&buf).unwrap();
+    assert_eq!(decoded.priority, 4);
+
+    let decoded: ListFields = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[test]
+fn with_module_is_used_for_field() {
+    let value = ListFields {
+        id: 1,
+        cached: None,
+        priority: 5,
+        count: 42,
+        label: Some(String::from("label")),
+        rest: vec![],
+    };
+    let buf = to_vec(&value).unwrap();
+    let decoded: ListFieldsV2 = from_slice(&buf).unwrap();
+    assert_eq!(decoded.count, "42");
+}
+
+#[test]
+fn list_rest_captures_trailing_fields() {
+    let newer = ListFieldsV2 {
+        id: 1,
+        priority: 5,
+        count: String::from("3"),
+        label: None,
+        extra: Some(true),
+        more: Some(-1),
+    };
+    let buf = to_vec(&newer).unwrap();
+    let decoded: ListFields = from_slice(&buf).unwrap();
+    assert_eq!(decoded.count, 3);
+    assert_eq!(decoded.rest, vec![Value::Bool(true), Value::Long(-1)]);
+
+    // Round trip keeps the unknown fields
+    let buf = to_vec(&decoded).unwrap();
+    let decoded: ListFieldsV2 = from_slice(&buf).unwrap();
+    assert_eq!(decoded, newer);
+}
+
+#[test]
+fn map_rename_and_rest() {
+    let newer = MapFieldsV2 {
+        id: 7,
+        flag: true,
+        unknown: Some(String::from("hello")),
+    };
+    let buf = to_vec(&newer).unwrap();
+    let decoded: MapFields = from_slice(&buf).unwrap();
+    assert_eq!(decoded.id, 7);
+    assert!(decoded.flag);
+    assert_eq!(decoded.rest.len(), 1);
+    assert_eq!(
+        decoded.rest.get(&Value::String(String::from("unknown"))),
+        Some(&Value::String(String::from("hello")))
+    );
+
+    let buf = to_vec(&decoded).unwrap();
+    assert_eq!(buf.len(), serde_amqp::serialized_size(&decoded).unwrap());
+    let decoded: MapFieldsV2 = from_slice(&buf).unwrap();
+    assert_eq!(decoded, newer);
+}
@@ -1,13 +1,13 @@
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{spanned::Spanned, DeriveInput, Fields};
 
 use crate::{
     util::{
-        convert_to_case, generic_visitor, get_span_of, macro_rules_unwrap_or_default,
-        macro_rules_unwrap_or_none, parse_described_struct_attr, parse_named_field_attrs,
+        generic_visitor, get_span_of, macro_rules_unwrap_or_default, macro_rules_unwrap_or_none,
+        parse_described_struct_attr, parse_described_variants, parse_named_fields,
         where_deserialize,
     },
-    DescribedStructAttr, EncodingType, FieldAttr,
+    DescribedStructAttr, EncodingType, NamedField,
 };
 
 pub(crate) fn expand_deserialize(
@@ -20,7 +20,11 @@ pub(crate) fn expand_deserialize(
         syn::Data::Struct(data) => {
             expand_deserialize_on_datastruct(&attr, ident, generics, data, input)
         }
-        _ => unimplemented!(),
+        syn::Data::Enum(data) => expand_deserialize_on_dataenum(ident, generics, data),
+        syn::Data::Union(_) => Err(syn::Error::new(
+            ident.span(),
+            "DeserializeComposite cannot be derived on union",
+        )),
     }
 }
 
@@ -88,6 +92,157 @@ fn expand_deserialize_on_datastruct(
     }
 }
 
+fn expand_deserialize_on_dataenum(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    data: &syn::DataEnum,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let variants = parse_described_variants(data)?;
+    let expecting = format!("enum {}", ident);
+    let enum_name = ident.to_string();
+
+    let field_variants = variants.iter().map(|variant| {
+        let variant_ident = variant.ident;
+        let cfg_attrs = &variant.cfg_attrs;
+        quote! {
+            #(#cfg_attrs)*
+            #variant_ident,
+        }
+    });
+    let name_arms = variants.iter().filter_map(|variant| {
+        let variant_ident = variant.ident;
+        let cfg_attrs = &variant.cfg_attrs;
+        variant.name.as_ref().map(|name| {
+            quote! {
+                #(#cfg_attrs)*
+                #name => __Field::#variant_ident,
+            }
+        })
+    });
+    let code_arms = variants.iter().filter_map(|variant| {
+        let variant_ident = variant.ident;
+        let cfg_attrs = &variant.cfg_attrs;
+        variant.code.map(|code| {
+            quote! {
+                #(#cfg_attrs)*
+                #code => __Field::#variant_ident,
+            }
+        })
+    });
+    let variant_arms = variants.iter().map(|variant| {
+        let variant_ident = variant.ident;
+        let cfg_attrs = &variant.cfg_attrs;
+        quote! {
+            #(#cfg_attrs)*
+            __Field::#variant_ident => {
+                let __value = __variant.newtype_variant()?;
+                Ok(#ident::#variant_ident(__value))
+            }
+        }
+    });
+    let variant_names = variants.iter().map(|variant| {
+        let cfg_attrs = &variant.cfg_attrs;
+        let name = variant
+            .name
+            .clone()
+            .unwrap_or_else(|| variant.ident.to_string());
+        quote! {
+            #(#cfg_attrs)*
+            #name,
+        }
+    });
+
+    let gen_params = &generics.params;
+    let visitor = generic_visitor(generics);
+    let where_clause = where_deserialize(generics);
+
+    let token = quote! {
+        #[automatically_derived]
+        impl<'de, #gen_params> serde_amqp::serde::de::Deserialize<'de> for #ident<#gen_params> #where_clause {
+            fn deserialize<_D>(deserializer: _D) -> Result<Self, _D::Error>
+            where
+                _D: serde_amqp::serde::de::Deserializer<'de>,
+            {
+                enum __Field {
+                    #(#field_variants)*
+                }
+
+                struct __FieldVisitor {}
+
+                impl<'de> serde_amqp::serde::de::Visitor<'de> for __FieldVisitor {
+                    type Value = __Field;
+
+                    fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                        formatter.write_str("variant identifier")
+                    }
+
+                    fn visit_str<_E>(self, v: &str) -> Result<Self::Value, _E>
+                    where
+                        _E: serde_amqp::serde::de::Error,
+                    {
+                        let val = match v {
+                            #(#name_arms)*
+                            _ => return Err(serde_amqp::serde::de::Error::custom("Wrong symbol value for descriptor")),
+                        };
+                        Ok(val)
+                    }
+
+                    fn visit_u64<_E>(self, v: u64) -> Result<Self::Value, _E>
+                    where
+                        _E: serde_amqp::serde::de::Error,
+                    {
+                        let val = match v {
+                            #(#code_arms)*
+                            _ => {
+                                return Err(serde_amqp::serde::de::Error::custom(format_args!(
+                                    "Wrong code value for descriptor, found {:#x?}",
+                                    v
+                                )))
+                            }
+                        };
+                        Ok(val)
+                    }
+                }
+
+                impl<'de> serde_amqp::serde::de::Deserialize<'de> for __Field {
+                    fn deserialize<_D>(deserializer: _D) -> Result<Self, _D::Error>
+                    where
+                        _D: serde_amqp::serde::de::Deserializer<'de>,
+                    {
+                        deserializer.deserialize_identifier(__FieldVisitor {})
+                    }
+                }
+
+                #visitor
+                impl<'de, #gen_params> serde_amqp::serde::de::Visitor<'de> for Visitor<#gen_params> #where_clause {
+                    type Value = #ident<#gen_params>;
+
+                    fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                        formatter.write_str(#expecting)
+                    }
+
+                    fn visit_enum<_A>(self, data: _A) -> Result<Self::Value, _A::Error>
+                    where
+                        _A: serde_amqp::serde::de::EnumAccess<'de>,
+                    {
+                        use serde_amqp::serde::de::VariantAccess;
+                        let (__field, __variant) = data.variant::<__Field>()?;
+                        match __field {
+                            #(#variant_arms)*
+                        }
+                    }
+                }
+
+                const VARIANTS: &[&str] = &[
+                    #(#variant_names)*
+                ];
+                deserializer.deserialize_enum(#enum_name, VARIANTS, Visitor::new())
+            }
+        }
+    };
+    Ok(token)
+}
+
 fn impl_visit_seq_for_unit_struct(
     ident: &syn::Ident,
     evaluate_descriptor: &proc_macro2::TokenStream,
@@ -147,7 +302,7 @@ fn expand_deserialize_unit_struct(
                 impl<'de> serde_amqp::serde::de::Visitor<'de> for Visitor {
                     type Value = #ident;
 
-                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                         formatter.write_str(#expecting)
                     }
 
@@ -256,7 +411,7 @@ fn expand_deserialize_tuple_struct(
                 impl<'de, #gen_params> serde_amqp::serde::de::Visitor<'de> for Visitor<#gen_params> #where_clause {
                     type Value = #ident<#gen_params>;
 
-                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                         formatter.write_str(#expecting)
                     }
 
@@ -310,60 +465,68 @@ fn expand_deserialize_struct(
             _ => quote!(serde_amqp::__constants::DESCRIBED_MAP),
         },
     };
-    let field_idents: Vec<syn::Ident> = fields
-        .named
-        .iter()
-        .map(|f| f.ident.clone().unwrap())
-        .collect();
-    let field_names: Vec<String> = field_idents
-        .iter()
-        .map(|i| convert_to_case(rename_all, i.to_string(), ctx).unwrap())
-        .collect();
-    let field_types: Vec<&syn::Type> = fields.named.iter().map(|f| &f.ty).collect();
-    let field_attrs = parse_named_field_attrs(fields.named.iter());
-
-    let deserialize_field = impl_deserialize_for_field(&field_idents, &field_names);
-
-    let visit_seq = impl_visit_seq_for_struct(
-        ident,
-        &field_idents,
-        &field_types,
-        &field_attrs,
-        evaluate_descriptor,
-    );
-    let visit_map = match len {
-        0 => quote! {},
-        _ => impl_visit_map(
-            ident,
-            &field_idents,
-            &field_names,
-            &field_types,
-            &field_attrs,
-            evaluate_descriptor,
-        ),
-    };
-
-    let mut n_true: u32 = 0;
-    let mut n_false: u32 = 0;
-    field_attrs.iter().for_each(|a| {
-        if a.default {
-            n_true += 1;
-        } else {
-            n_false += 1;
+    let fields = parse_named_fields(fields, rename_all, ctx)?;
+    if let EncodingType::Basic = encoding {
+        if let Some(f) = fields.iter().find(|f| f.rest) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "rest field is not supported with the basic encoding",
+            ));
         }
+    }
+
+    // Fields that are decoded by name
+    let active_fields: Vec<&NamedField> = fields.iter().filter(|f| !f.skip && !f.rest).collect();
+    let field_idents: Vec<&syn::Ident> = active_fields.iter().map(|f| f.ident).collect();
+    let field_names: Vec<&str> = active_fields.iter().map(|f| &f.name[..]).collect();
+    let has_rest = fields.iter().any(|f| f.rest);
+
+    let deserialize_field = impl_deserialize_for_field(&field_idents, &field_names, has_rest);
+
+    let gen_params = &generics.params;
+    let where_clause = where_deserialize(generics);
+    let deserialize_with_impls = active_fields.iter().filter_map(|f| {
+        f.with.as_ref().map(|path| {
+            let wrapper = deserialize_with_wrapper(f.ident);
+            let ty = f.ty;
+            quote! {
+                #[allow(non_camel_case_types)]
+                struct #wrapper<#gen_params> {
+                    value: #ty,
+                    phantom: ::core::marker::PhantomData<#ident<#gen_params>>,
+                }
+
+                impl<'de, #gen_params> serde_amqp::serde::de::Deserialize<'de> for #wrapper<#gen_params> #where_clause {
+                    fn deserialize<_D>(deserializer: _D) -> Result<Self, _D::Error>
+                    where
+                        _D: serde_amqp::serde::de::Deserializer<'de>,
+                    {
+                        Ok(Self {
+                            value: #path::deserialize(deserializer)?,
+                            phantom: ::core::marker::PhantomData,
+                        })
+                    }
+                }
+            }
+        })
     });
 
-    let unwrap_or_default = match n_true {
-        0 => quote! {},
-        _ => macro_rules_unwrap_or_default(),
+    let visit_seq =
+        impl_visit_seq_for_struct(ident, generics, encoding, &fields, evaluate_descriptor);
+    let visit_map = match fields.iter().all(|f| f.skip) {
+        true => quote! {},
+        false => impl_visit_map(ident, generics, encoding, &fields, evaluate_descriptor),
     };
-    let unwrap_or_none = match n_false {
-        0 => quote! {},
-        _ => macro_rules_unwrap_or_none(),
+
+    let unwrap_or_default = match active_fields.iter().any(|f| f.default.is_some()) {
+        true => macro_rules_unwrap_or_default(),
+        false => quote! {},
+    };
+    let unwrap_or_none = match active_fields.iter().any(|f| f.default.is_none()) {
+        true => macro_rules_unwrap_or_none(),
+        false => quote! {},
     };
-    let gen_params = &generics.params;
     let visitor = generic_visitor(generics);
-    let where_clause = where_deserialize(generics);
 
     let token = quote! {
         #unwrap_or_default
@@ -378,11 +541,13 @@ fn expand_deserialize_struct(
 
                 #deserialize_field
 
+                #( #deserialize_with_impls )*
+
                 #visitor
                 impl<'de, #gen_params> serde_amqp::serde::de::Visitor<'de> for Visitor<#gen_params> #where_clause {
                     type Value = #ident<#gen_params>;
 
-                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                         formatter.write_str(#expecting)
                     }
 
@@ -404,21 +569,51 @@ fn expand_deserialize_struct(
     Ok(token)
 }
 
+fn deserialize_with_wrapper(id: &syn::Ident) -> syn::Ident {
+    format_ident!("__DeserializeWith_{}", id)
+}
+
+/// The value assigned to a skipped field
+fn skipped_field_value(field: &NamedField) -> proc_macro2::TokenStream {
+    let ty = field.ty;
+    match &field.default {
+        Some(default) => default.clone(),
+        None => quote!(<#ty as Default>::default()),
+    }
+}
+
 fn impl_deserialize_for_field(
-    field_idents: &Vec<syn::Ident>,
-    field_names: &Vec<String>,
+    field_idents: &[&syn::Ident],
+    field_names: &[&str],
+    has_rest: bool,
 ) -> proc_macro2::TokenStream {
+    // Unknown keys are captured by the rest field, which needs to look up the field by name
+    let from_name = match has_rest {
+        true => quote! {
+            impl Field {
+                fn from_name(__name: &str) -> Option<Self> {
+                    match __name {
+                        #(#field_names => Some(Self::#field_idents),)*
+                        _ => None,
+                    }
+                }
+            }
+        },
+        false => quote! {},
+    };
+
     quote! {
         #[allow(non_camel_case_types)]
         enum Field {
             #(#field_idents, )*
             // TODO: considering add ignored
         }
+        #from_name
         struct FieldVisitor {}
         impl<'de> serde_amqp::serde::de::Visitor<'de> for FieldVisitor {
             type Value = Field;
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                 formatter.write_str("field identifier")
             }
 
@@ -458,19 +653,51 @@ fn impl_deserialize_for_field(
 
 fn impl_visit_seq_for_struct(
     ident: &syn::Ident,
-    field_idents: &[syn::Ident],
-    field_types: &[&syn::Type],
-    field_attrs: &[FieldAttr],
+    generics: &syn::Generics,
+    encoding: &EncodingType,
+    fields: &[NamedField],
     evaluate_descriptor: &proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
+    let gen_params = &generics.params;
+    let field_idents = fields.iter().map(|f| f.ident);
     let mut field_impls: Vec<proc_macro2::TokenStream> = vec![];
-    for ((id, ty), attr) in field_idents.iter().zip(field_types.iter()).zip(field_attrs) {
-        let token = match attr.default {
-            true => {
-                quote! { unwrap_or_default!(#id, __seq.next_element::<Option<#ty>>()?.unwrap_or_default(), #ty) }
+    for field in fields {
+        let id = field.ident;
+        let ty = field.ty;
+        let token = if field.skip {
+            let value = skipped_field_value(field);
+            quote! { let #id: #ty = #value; }
+        } else if field.rest {
+            match encoding {
+                EncodingType::Map => quote! {
+                    // A map rest field cannot hold the trailing list items
+                    while __seq.next_element::<serde_amqp::Value>()?.is_some() {}
+                    let #id: #ty = <#ty as Default>::default();
+                },
+                _ => quote! {
+                    let mut __rest = serde_amqp::__private::Vec::new();
+                    while let Some(__elem) = __seq.next_element::<serde_amqp::Value>()? {
+                        __rest.push(__elem);
+                    }
+                    let #id: #ty = __rest.into_iter().collect();
+                },
             }
-            false => {
-                quote! { unwrap_or_none!(#id, __seq.next_element()?, #ty) }
+        } else {
+            match (&field.with, &field.default) {
+                (Some(_), Some(default)) => {
+                    let wrapper = deserialize_with_wrapper(id);
+                    quote! { unwrap_or_default!(#id, __seq.next_element::<Option<#wrapper<#gen_params>>>()?.flatten().map(|__w| __w.value), #ty, #default); }
+                }
+                (Some(_), None) => {
+                    let wrapper = deserialize_with_wrapper(id);
+                    quote! { unwrap_or_none!(#id, __seq.next_element::<#wrapper<#gen_params>>()?.map(|__w| __w.value), #ty); }
+                }
+                (None, Some(default)) => {
+                    quote! { unwrap_or_default!(#id, __seq.next_element::<Option<#ty>>()?.unwrap_or_default(), #ty, #default); }
+                }
+                (None, None) => {
+                    quote! { unwrap_or_none!(#id, __seq.next_element()?, #ty); }
+                }
             }
         };
         field_impls.push(token);
@@ -489,7 +716,7 @@ fn impl_visit_seq_for_struct(
             #evaluate_descriptor
 
             // #( unwrap_or_none!(#field_idents, __seq, #field_types); )*
-            #( #field_impls; )*
+            #( #field_impls )*
 
             Ok( #ident{ #(#field_idents, )* } )
         }
@@ -498,20 +725,82 @@ fn impl_visit_seq_for_struct(
 
 fn impl_visit_map(
     ident: &syn::Ident,
-    field_idents: &Vec<syn::Ident>,
-    field_names: &Vec<String>,
-    field_types: &Vec<&syn::Type>,
-    field_attrs: &Vec<FieldAttr>,
+    generics: &syn::Generics,
+    encoding: &EncodingType,
+    fields: &[NamedField],
     evaluate_descriptor: &proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
+    let gen_params = &generics.params;
+    let active_fields: Vec<&NamedField> = fields.iter().filter(|f| !f.skip && !f.rest).collect();
+    let active_idents: Vec<&syn::Ident> = active_fields.iter().map(|f| f.ident).collect();
+    let active_types = active_fields.iter().map(|f| f.ty);
+    let field_idents = fields.iter().map(|f| f.ident);
+
+    let mut assign_values: Vec<proc_macro2::TokenStream> = vec![];
+    for field in active_fields.iter() {
+        let id = field.ident;
+        let name = &field.name;
+        let next_value = match &field.with {
+            Some(_) => {
+                let wrapper = deserialize_with_wrapper(id);
+                quote!(__map.next_value::<#wrapper<#gen_params>>()?.value)
+            }
+            None => quote!(__map.next_value()?),
+        };
+        assign_values.push(quote! {
+            {
+                if #id.is_some() {
+                    return Err(serde_amqp::serde::de::Error::duplicate_field(#name))
+                }
+                #id = Some(#next_value);
+            }
+        });
+    }
+
+    let visit_entries = match fields.iter().any(|f| f.rest) {
+        true => quote! {
+            let mut __rest = serde_amqp::__private::Vec::new();
+            while let Some(__key) = __map.next_key::<serde_amqp::Value>()? {
+                let __field = match &__key {
+                    serde_amqp::Value::String(__s) => Field::from_name(__s),
+                    serde_amqp::Value::Symbol(__s) => Field::from_name(__s.as_str()),
+                    _ => None,
+                };
+                match __field {
+                    #( Some(Field::#active_idents) => #assign_values, )*
+                    None => {
+                        let __value: serde_amqp::Value = __map.next_value()?;
+                        __rest.push((__key, __value));
+                    }
+                }
+            }
+        },
+        false => quote! {
+            while let Some(key) = __map.next_key::<Field>()? {
+                match key {
+                    #( Field::#active_idents => #assign_values, )*
+                }
+            }
+        },
+    };
+
     let mut field_impls: Vec<proc_macro2::TokenStream> = vec![];
-    for ((id, ty), attr) in field_idents.iter().zip(field_types.iter()).zip(field_attrs) {
-        let token = match attr.default {
-            true => {
-                quote! { unwrap_or_default!(#id, #id, #ty) }
+    for field in fields {
+        let id = field.ident;
+        let ty = field.ty;
+        let token = if field.skip {
+            let value = skipped_field_value(field);
+            quote! { let #id: #ty = #value; }
+        } else if field.rest {
+            match encoding {
+                EncodingType::Map => quote! { let #id: #ty = __rest.into_iter().collect(); },
+                // A list rest field cannot hold the unknown map entries
+                _ => quote! { let #id: #ty = <#ty as Default>::default(); },
             }
-            false => {
-                quote! { unwrap_or_none!(#id, #id, #ty); }
+        } else {
+            match &field.default {
+                Some(default) => quote! { unwrap_or_default!(#id, #id, #ty, #default); },
+                None => quote! { unwrap_or_none!(#id, #id, #ty); },
             }
         };
         field_impls.push(token);
@@ -521,7 +810,7 @@ fn impl_visit_map(
         fn visit_map<_A>(self, mut __map: _A)-> Result<Self::Value, _A::Error>
         where _A: serde_amqp::serde::de::MapAccess<'de>
         {
-            #(let mut #field_idents: Option<#field_types> = None;)*
+            #(let mut #active_idents: Option<#active_types> = None;)*
 
             // The first should always be the descriptor
             let __descriptor: serde_amqp::descriptor::Descriptor = match __map.next_key()? {
@@ -531,22 +820,9 @@ fn impl_visit_map(
 
             #evaluate_descriptor
 
-            while let Some(key) = __map.next_key::<Field>()? {
-                match key {
-                    #(
-                        Field::#field_idents => {
-                            if #field_idents.is_some() {
-                                return Err(serde_amqp::serde::de::Error::duplicate_field(#field_names))
-                            }
-                            #field_idents = Some(__map.next_value()?);
-                        },
-                    )*
-                }
-            }
+            #visit_entries
 
-            #(
-                #field_impls;
-            )*
+            #( #field_impls )*
             Ok( #ident{ #(#field_idents, )* } )
         }
     }
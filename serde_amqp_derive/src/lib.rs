@@ -36,6 +36,22 @@
 //! AMQP1.0 `null` primitive (`0x40`). During deserialization, an AMQP1.0 `null` primitive or an
 //! empty field will be decoded as the default value of the type.
 //!
+//! ## Field attributes
+//!
+//! - `#[amqp_contract(default)]`: the field takes `Default::default()` if absent (see above).
+//! - `#[amqp_contract(default = "path")]`: same as `default` but the default value is given by
+//!   calling the function at `path`.
+//! - `#[amqp_contract(skip)]`: the field is neither serialized nor deserialized. It takes its
+//!   `default` value (or `Default::default()`) during deserialization.
+//! - `#[amqp_contract(with = "module")]`: the field is serialized with `module::serialize` and
+//!   deserialized with `module::deserialize`.
+//! - `#[amqp_contract(rename = "name")]`: overrides the name of the field (and `rename_all`) in
+//!   the `"map"` encoding.
+//! - `#[amqp_contract(rest)]`: the last field that captures all the trailing list items
+//!   (`Vec<Value>`) with the `"list"` encoding or all the unknown map entries
+//!   (`OrderedMap<Value, Value>`) with the `"map"` encoding, so that fields appended by a newer
+//!   version of the peer are preserved instead of failing the decoding.
+//!
 //! # Example
 //!
 //! The `"list"` encoding will encode the `Attach` struct as a described list (a descriptor followed
@@ -115,8 +131,26 @@
 //! )]
 //! pub struct ApplicationProperties(pub BTreeMap<String, SimpleValue>);
 //! ```
+//!
+//! # Enums of described types
+//!
+//! The macros can also be derived on an enum whose variants are all newtype variants wrapping a
+//! described type. Each variant must be annotated with the descriptor `name` and/or `code` of the
+//! wrapped type. Serialization simply delegates to the wrapped value, and deserialization
+//! dispatches on the descriptor name or code found in the encoded value.
+//!
+//! ```rust,ignore
+//! #[derive(Debug, Clone, SerializeComposite, DeserializeComposite)]
+//! pub enum Outcome {
+//!     #[amqp_contract(name = "amqp:accepted:list", code = "0x0000_0000:0x0000_0024")]
+//!     Accepted(Accepted),
+//!
+//!     #[amqp_contract(name = "amqp:rejected:list", code = "0x0000_0000:0x0000_0025")]
+//!     Rejected(Rejected),
+//! }
+//! ```
 
-use darling::{FromDeriveInput, FromMeta};
+use darling::{util::Override, FromDeriveInput, FromMeta};
 use quote::quote;
 use syn::DeriveInput;
 
@@ -148,11 +182,27 @@ struct DescribedAttr {
     pub no_descriptor: Option<()>,
 }
 
-#[derive(Debug, darling::FromMeta, PartialEq)]
+#[derive(Debug, Default, darling::FromMeta, PartialEq)]
 struct FieldAttr {
-    // default: syn::Lit
+    /// `default` uses `Default::default()`, `default = "path"` calls the given function
+    #[darling(default)]
+    default: Option<Override<String>>,
+    #[darling(default)]
+    skip: bool,
+    #[darling(default)]
+    with: Option<String>,
     #[darling(default)]
-    default: bool,
+    rename: Option<String>,
+    #[darling(default)]
+    rest: bool,
+}
+
+#[derive(Debug, darling::FromMeta, PartialEq)]
+struct VariantAttr {
+    #[darling(default)]
+    name: Option<String>,
+    #[darling(default)]
+    code: Option<String>,
 }
 
 struct DescribedStructAttr {
@@ -162,10 +212,31 @@ struct DescribedStructAttr {
     rename_field: String,
 }
 
+/// A named field of a struct with its parsed `amqp_contract` attribute
+struct NamedField<'a> {
+    ident: &'a syn::Ident,
+    name: String,
+    ty: &'a syn::Type,
+    /// Expression producing the value used when the field is absent
+    default: Option<proc_macro2::TokenStream>,
+    /// Module providing the custom `serialize` and `deserialize` functions
+    with: Option<syn::Path>,
+    skip: bool,
+    rest: bool,
+}
+
+/// A newtype variant of an enum that wraps a described type
+struct DescribedVariant<'a> {
+    ident: &'a syn::Ident,
+    name: Option<String>,
+    code: Option<u64>,
+    cfg_attrs: Vec<&'a syn::Attribute>,
+}
+
 #[proc_macro_derive(SerializeComposite, attributes(amqp_contract))]
 pub fn derive_serialize_described(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(item as DeriveInput);
-    let impl_ser = ser::expand_serialize(&input).unwrap();
+    let impl_ser = ser::expand_serialize(&input).unwrap_or_else(syn::Error::into_compile_error);
     let output = quote! {
         const _: () = {
             #impl_ser
@@ -177,7 +248,7 @@ pub fn derive_serialize_described(item: proc_macro::TokenStream) -> proc_macro::
 #[proc_macro_derive(DeserializeComposite, attributes(amqp_contract))]
 pub fn derive_deserialize_described(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(item as DeriveInput);
-    let impl_de = de::expand_deserialize(&input).unwrap();
+    let impl_de = de::expand_deserialize(&input).unwrap_or_else(syn::Error::into_compile_error);
     let output = quote! {
         const _:() = {
             #impl_de
@@ -1,14 +1,14 @@
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{DeriveInput, Fields};
 
 use crate::{
     util::{
-        convert_to_case, macro_rules_buffer_if_eq_default, macro_rules_buffer_if_none,
+        macro_rules_buffer_if_eq_default, macro_rules_buffer_if_none,
         macro_rules_buffer_if_none_for_tuple_struct, macro_rules_serialize_if_neq_default,
-        macro_rules_serialize_if_some, parse_described_struct_attr, parse_named_field_attrs,
-        where_serialize,
+        macro_rules_serialize_if_some, parse_described_struct_attr, parse_described_variants,
+        parse_named_fields, where_serialize,
     },
-    DescribedStructAttr, EncodingType, FieldAttr,
+    DescribedStructAttr, EncodingType,
 };
 
 pub(crate) fn expand_serialize(
@@ -21,7 +21,11 @@ pub(crate) fn expand_serialize(
         syn::Data::Struct(data) => {
             expand_serialize_on_datastruct(&amqp_attr, ident, generics, data, input)
         }
-        _ => unimplemented!(),
+        syn::Data::Enum(data) => expand_serialize_on_dataenum(ident, generics, data),
+        syn::Data::Union(_) => Err(syn::Error::new(
+            ident.span(),
+            "SerializeComposite cannot be derived on union",
+        )),
     }
 }
 
@@ -52,7 +56,7 @@ fn expand_serialize_on_datastruct(
                     &amqp_attr.rename_field,
                     fields,
                     ctx,
-                ),
+                )?,
             };
             Ok(token)
         }
@@ -77,6 +81,39 @@ fn expand_serialize_on_datastruct(
     }
 }
 
+fn expand_serialize_on_dataenum(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    data: &syn::DataEnum,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let variants = parse_described_variants(data)?;
+    let arms = variants.iter().map(|variant| {
+        let variant_ident = variant.ident;
+        let cfg_attrs = &variant.cfg_attrs;
+        quote! {
+            #(#cfg_attrs)*
+            Self::#variant_ident(__value) => serde_amqp::serde::ser::Serialize::serialize(__value, serializer),
+        }
+    });
+    let gen_params = &generics.params;
+    let where_clause = where_serialize(generics);
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl<#gen_params> serde_amqp::serde::ser::Serialize for #ident<#gen_params> #where_clause {
+            fn serialize<_S>(&self, serializer: _S) -> Result<_S::Ok, _S::Error>
+            where
+                _S: serde_amqp::serde::ser::Serializer,
+            {
+                // Each variant wraps a described type, which carries its own descriptor
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
 fn expand_serialize_unit_struct(
     ident: &syn::Ident,
     descriptor: &proc_macro2::TokenStream,
@@ -172,7 +209,7 @@ fn expand_serialize_struct(
     rename_all: &str,
     fields: &syn::FieldsNamed,
     ctx: &DeriveInput,
-) -> proc_macro2::TokenStream {
+) -> Result<proc_macro2::TokenStream, syn::Error> {
     let len = fields.named.len();
     let struct_name = match encoding {
         EncodingType::Basic => {
@@ -186,22 +223,29 @@ fn expand_serialize_struct(
         EncodingType::List => quote!(serde_amqp::__constants::DESCRIBED_LIST),
         EncodingType::Map => quote!(serde_amqp::__constants::DESCRIBED_MAP),
     };
-    let field_idents: Vec<syn::Ident> = fields
-        .named
+    let fields = parse_named_fields(fields, rename_all, ctx)?;
+    if let EncodingType::Basic = encoding {
+        if let Some(f) = fields.iter().find(|f| f.rest) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "rest field is not supported with the basic encoding",
+            ));
+        }
+    }
+
+    let has_default = fields
         .iter()
-        .map(|f| f.ident.clone().unwrap())
-        .collect();
-    let field_names: Vec<String> = field_idents
+        .any(|f| !f.skip && !f.rest && f.default.is_some());
+    let has_non_default = fields
         .iter()
-        .map(|i| convert_to_case(rename_all, i.to_string(), ctx).unwrap())
-        .collect();
-    let field_types: Vec<&syn::Type> = fields.named.iter().map(|f| &f.ty).collect();
-    let field_attrs = parse_named_field_attrs(fields.named.iter());
+        .any(|f| !f.skip && !f.rest && f.default.is_none());
     let declarative_macro = match encoding {
         EncodingType::Basic | EncodingType::List => {
-            let buffer_if_none = macro_rules_buffer_if_none();
-
-            let buffer_if_eq_default = match field_attrs.contains(&FieldAttr { default: true }) {
+            let buffer_if_none = match has_non_default {
+                true => macro_rules_buffer_if_none(),
+                false => quote! {},
+            };
+            let buffer_if_eq_default = match has_default {
                 true => macro_rules_buffer_if_eq_default(),
                 false => quote! {},
             };
@@ -220,53 +264,82 @@ fn expand_serialize_struct(
         }
     };
 
-    let mut field_impls: Vec<proc_macro2::TokenStream> = vec![];
-    match encoding {
-        EncodingType::Basic | EncodingType::List => {
-            // for ((id, name), ty) in field_idents
-            for (((id, name), ty), attr) in field_idents
-                .iter()
-                .zip(field_names.iter())
-                .zip(field_types.iter())
-                .zip(field_attrs.iter())
-            {
-                let token = match attr.default {
-                    true => quote! {
-                        buffer_if_eq_default!(state, nulls, &self.#id, #name, #ty);
-                    },
-                    false => quote! {
-                        buffer_if_none!(state, nulls, &self.#id, #name, #ty);
-                    },
-                };
-                field_impls.push(token);
-            }
-        }
-        EncodingType::Map => {
-            for (((id, name), ty), attr) in field_idents
-                .iter()
-                .zip(field_names.iter())
-                .zip(field_types.iter())
-                .zip(field_attrs.iter())
-            {
-                let token = match attr.default {
-                    true => quote! {
-                        serialize_if_neq_default!(state, &self.#id, #name, #ty);
-                    },
-                    false => quote! {
-                        serialize_if_some!(state, &self.#id, #name, #ty);
-                    },
-                };
-                field_impls.push(token);
-            }
-        }
-    }
-
     let where_clause = match generics.params.len() {
         0 => quote! {},
         _ => where_serialize(generics),
     };
 
-    quote! {
+    let gen_params = &generics.params;
+    let mut serialize_with_impls: Vec<proc_macro2::TokenStream> = vec![];
+    let mut field_impls: Vec<proc_macro2::TokenStream> = vec![];
+    for field in fields.iter().filter(|f| !f.skip) {
+        let id = field.ident;
+        let name = &field.name;
+        let ty = field.ty;
+
+        if field.rest {
+            let token = match encoding {
+                EncodingType::Map => quote! {
+                    for (__key, __value) in self.#id.iter() {
+                        state.serialize_field(serde_amqp::__constants::REST_MAP_KEY, __key)?;
+                        state.serialize_field(serde_amqp::__constants::REST_MAP_VALUE, __value)?;
+                    }
+                },
+                _ => quote! {
+                    for __elem in self.#id.iter() {
+                        for field_name in nulls.drain(..) {
+                            state.serialize_field(field_name, &())?;
+                        }
+                        state.serialize_field(#name, __elem)?;
+                    }
+                },
+            };
+            field_impls.push(token);
+            continue;
+        }
+
+        let value = match &field.with {
+            Some(path) => {
+                let wrapper = format_ident!("__SerializeWith_{}", id);
+                serialize_with_impls.push(quote! {
+                    #[allow(non_camel_case_types)]
+                    struct #wrapper<'__a, #gen_params> {
+                        value: &'__a #ty,
+                        phantom: ::core::marker::PhantomData<#ident #generics>,
+                    }
+
+                    impl<'__a, #gen_params> serde_amqp::serde::ser::Serialize for #wrapper<'__a, #gen_params> #where_clause {
+                        fn serialize<_S>(&self, serializer: _S) -> Result<_S::Ok, _S::Error>
+                        where
+                            _S: serde_amqp::serde::ser::Serializer,
+                        {
+                            #path::serialize(self.value, serializer)
+                        }
+                    }
+                });
+                quote!(&#wrapper { value: &self.#id, phantom: ::core::marker::PhantomData })
+            }
+            None => quote!(&self.#id),
+        };
+
+        let token = match (encoding, &field.default) {
+            (EncodingType::Basic | EncodingType::List, Some(default)) => quote! {
+                buffer_if_eq_default!(state, nulls, &self.#id, #value, #name, #default);
+            },
+            (EncodingType::Basic | EncodingType::List, None) => quote! {
+                buffer_if_none!(state, nulls, &self.#id, #value, #name, #ty);
+            },
+            (EncodingType::Map, Some(default)) => quote! {
+                serialize_if_neq_default!(state, &self.#id, #value, #name, #default);
+            },
+            (EncodingType::Map, None) => quote! {
+                serialize_if_some!(state, &self.#id, #value, #name, #ty);
+            },
+        };
+        field_impls.push(token);
+    }
+
+    Ok(quote! {
         #declarative_macro
 
         #[automatically_derived]
@@ -277,8 +350,11 @@ fn expand_serialize_struct(
                 _S: serde_amqp::serde::ser::Serializer,
             {
                 use serde_amqp::serde::ser::SerializeStruct;
+
+                #( #serialize_with_impls )*
+
                 // let mut null_count = 0u32;
-                let mut nulls: Vec<&str> = Vec::new();
+                let mut nulls: serde_amqp::__private::Vec<&str> = serde_amqp::__private::Vec::new();
                 // len + 1 for compatibility with other serializer
                 let mut state = serializer.serialize_struct(#struct_name, #len + 1)?;
                 // serialize descriptor
@@ -287,9 +363,9 @@ fn expand_serialize_struct(
                 state.serialize_field(serde_amqp::__constants::DESCRIPTOR, &#descriptor)?;
                 // #( state.serialize_field(#field_names, &self.#field_idents)?; )*
                 // #(buffer_if_none!(state, null_count, &self.#field_idents, #field_names, #field_types);) *
-                #( #field_impls; )*
+                #( #field_impls )*
                 state.end()
             }
         }
-    }
+    })
 }
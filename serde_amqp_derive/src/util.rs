@@ -1,11 +1,14 @@
 use std::num::ParseIntError;
 
-use darling::{FromDeriveInput, FromMeta};
+use darling::{util::Override, FromDeriveInput, FromMeta};
 use proc_macro2::Span;
 use quote::quote;
-use syn::{parse::Parser, DeriveInput, Field};
+use syn::{parse::Parser, DeriveInput};
 
-use crate::{DescribedAttr, DescribedStructAttr, EncodingType, FieldAttr};
+use crate::{
+    DescribedAttr, DescribedStructAttr, DescribedVariant, EncodingType, FieldAttr, NamedField,
+    VariantAttr,
+};
 
 pub(crate) fn parse_described_struct_attr(input: &syn::DeriveInput) -> DescribedStructAttr {
     let attr = DescribedAttr::from_derive_input(input).unwrap();
@@ -22,6 +25,68 @@ pub(crate) fn parse_described_struct_attr(input: &syn::DeriveInput) -> Described
     }
 }
 
+pub(crate) fn parse_described_variants(
+    data: &syn::DataEnum,
+) -> Result<Vec<DescribedVariant<'_>>, syn::Error> {
+    data.variants
+        .iter()
+        .map(|variant| {
+            match &variant.fields {
+                syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {}
+                _ => {
+                    return Err(syn::Error::new(
+                        variant.ident.span(),
+                        "Only newtype variants wrapping a described type are supported",
+                    ))
+                }
+            }
+
+            let attr = variant
+                .attrs
+                .iter()
+                .filter(|a| a.path().is_ident("amqp_contract"))
+                .map(|a| VariantAttr::from_meta(&a.meta))
+                .next()
+                .transpose()
+                .map_err(|err| syn::Error::new(variant.ident.span(), err))?
+                .unwrap_or(VariantAttr {
+                    name: None,
+                    code: None,
+                });
+
+            if attr.name.is_none() && attr.code.is_none() {
+                return Err(syn::Error::new(
+                    variant.ident.span(),
+                    "Expecting descriptor name or code, ie. #[amqp_contract(name = \"...\", code = \"...\")]",
+                ));
+            }
+
+            let code = attr
+                .code
+                .map(parse_descriptor_code)
+                .transpose()
+                .map_err(|err| {
+                    syn::Error::new(
+                        variant.ident.span(),
+                        format!("Invalid descriptor code {:?}", err),
+                    )
+                })?;
+            let cfg_attrs = variant
+                .attrs
+                .iter()
+                .filter(|a| a.path().is_ident("cfg"))
+                .collect();
+
+            Ok(DescribedVariant {
+                ident: &variant.ident,
+                name: attr.name,
+                code,
+                cfg_attrs,
+            })
+        })
+        .collect()
+}
+
 /// Error with parsing descriptor code
 #[derive(Debug)]
 pub enum ParseDescriptorCodeError {
@@ -109,17 +174,74 @@ pub(crate) fn convert_to_case(
     Ok(s)
 }
 
-pub(crate) fn parse_named_field_attrs<'a>(
-    fields: impl Iterator<Item = &'a Field>,
-) -> Vec<FieldAttr> {
-    fields
-        .map(|f| {
-            f.attrs
-                .iter()
-                .find_map(|a| FieldAttr::from_meta(&a.meta).ok())
-        })
-        .map(|o| o.unwrap_or(FieldAttr { default: false }))
-        .collect()
+pub(crate) fn parse_named_fields<'a>(
+    fields: &'a syn::FieldsNamed,
+    rename_all: &str,
+    ctx: &DeriveInput,
+) -> Result<Vec<NamedField<'a>>, syn::Error> {
+    let mut parsed = Vec::with_capacity(fields.named.len());
+    for field in fields.named.iter() {
+        let ident = field.ident.as_ref().expect("Expecting named field");
+        let ty = &field.ty;
+        let attr = match field
+            .attrs
+            .iter()
+            .find(|a| a.path().is_ident("amqp_contract"))
+        {
+            Some(a) => {
+                FieldAttr::from_meta(&a.meta).map_err(|err| syn::Error::new(ident.span(), err))?
+            }
+            None => FieldAttr::default(),
+        };
+
+        if attr.rest && (attr.skip || attr.default.is_some() || attr.with.is_some()) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "rest cannot be combined with skip, default or with",
+            ));
+        }
+
+        let name = match attr.rename {
+            Some(name) => name,
+            None => convert_to_case(rename_all, ident.to_string(), ctx)?,
+        };
+        let default = match attr.default {
+            Some(Override::Inherit) => Some(quote!(<#ty as Default>::default())),
+            Some(Override::Explicit(path)) => {
+                let path = parse_path(&path, ident)?;
+                Some(quote!(#path()))
+            }
+            None => None,
+        };
+        let with = attr.with.map(|path| parse_path(&path, ident)).transpose()?;
+
+        parsed.push(NamedField {
+            ident,
+            name,
+            ty,
+            default,
+            with,
+            skip: attr.skip,
+            rest: attr.rest,
+        });
+    }
+
+    // The rest field must be the last field that is not skipped
+    let mut iter = parsed.iter().filter(|f| !f.skip);
+    if iter.by_ref().any(|f| f.rest) {
+        if let Some(f) = iter.next() {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "rest field must be the last field that is not skipped",
+            ));
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_path(s: &str, ident: &syn::Ident) -> Result<syn::Path, syn::Error> {
+    syn::parse_str(s).map_err(|err| syn::Error::new(ident.span(), err))
 }
 
 pub(crate) fn get_span_of(ident_str: &str, ctx: &DeriveInput) -> Option<Span> {
@@ -195,7 +317,7 @@ pub(crate) fn generic_visitor(generics: &syn::Generics) -> proc_macro2::TokenStr
         impl<#(#generic_types),*> Visitor<#(#generic_types),*> {
             fn new() -> Self {
                 Self {
-                    #(#field_ids: std::marker::PhantomData),*
+                    #(#field_ids: ::core::marker::PhantomData),*
                 }
             }
         }
@@ -221,7 +343,7 @@ pub(crate) fn generic_visitor_fields(
         .for_each(|(i, ty)| {
             types.push(ty);
             let field_id = syn::Ident::new(&format!("_field{}", i), ty.span());
-            let token = quote!(#field_id: std::marker::PhantomData<#ty>);
+            let token = quote!(#field_id: ::core::marker::PhantomData<#ty>);
             let field = syn::Field::parse_named.parse2(token);
             fields.push(field.unwrap());
         });
@@ -259,23 +381,23 @@ pub(crate) fn macro_rules_buffer_if_none() -> proc_macro2::TokenStream {
     quote! {
         macro_rules! buffer_if_none {
             // for struct
-            ($state: ident, $nulls: ident, $fident: expr, $fname: expr, Option<$ftype: ty>) => {
+            ($state: ident, $nulls: ident, $fident: expr, $fvalue: expr, $fname: expr, Option<$ftype: ty>) => {
                 if $fident.is_some() {
                     for field_name in $nulls.drain(..) {
                         // name is not used in list encoding
                         $state.serialize_field(field_name, &())?; // `None` and `()` share the same encoding
                     }
-                    $state.serialize_field($fname, $fident)?;
+                    $state.serialize_field($fname, $fvalue)?;
                 } else {
                     $nulls.push($fname);
                 }
             };
-            ($state: ident, $nulls: ident, $fident: expr, $fname: expr, $ftype: ty) => {
+            ($state: ident, $nulls: ident, $fident: expr, $fvalue: expr, $fname: expr, $ftype: ty) => {
                 for field_name in $nulls.drain(..) {
                     // name is not used in list encoding
                     $state.serialize_field(field_name, &())?; // `None` and `()` share the same encoding
                 }
-                $state.serialize_field($fname, $fident)?;
+                $state.serialize_field($fname, $fvalue)?;
             };
         }
     }
@@ -286,14 +408,14 @@ pub(crate) fn macro_rules_buffer_if_eq_default() -> proc_macro2::TokenStream {
     quote! {
         macro_rules! buffer_if_eq_default {
             // for struct
-            ($state: ident, $nulls: ident, $fident: expr, $fname: expr, $ftype: ty) => {
-                if *$fident != <$ftype as Default>::default() {
+            ($state: ident, $nulls: ident, $fident: expr, $fvalue: expr, $fname: expr, $fdefault: expr) => {
+                if *$fident != $fdefault {
                     // Only serialize if value is not equal to default
                     for field_name in $nulls.drain(..) {
                         // name is not used in list encoding
                         $state.serialize_field(field_name, &())?; // `None` and `()` share the same encoding
                     }
-                    $state.serialize_field($fname, $fident)?;
+                    $state.serialize_field($fname, $fvalue)?;
                 } else {
                     $nulls.push($fname);
                 }
@@ -306,13 +428,13 @@ pub(crate) fn macro_rules_serialize_if_some() -> proc_macro2::TokenStream {
     quote! {
         macro_rules! serialize_if_some {
             // for struct
-            ($state: ident, $fident: expr, $fname: expr, Option<$ftype: ty>) => {
+            ($state: ident, $fident: expr, $fvalue: expr, $fname: expr, Option<$ftype: ty>) => {
                 if $fident.is_some() {
-                    $state.serialize_field($fname, $fident)?;
+                    $state.serialize_field($fname, $fvalue)?;
                 }
             };
-            ($state: ident, $fident: expr, $fname: expr, $ftype: ty) => {
-                $state.serialize_field($fname, $fident)?;
+            ($state: ident, $fident: expr, $fvalue: expr, $fname: expr, $ftype: ty) => {
+                $state.serialize_field($fname, $fvalue)?;
             };
         }
     }
@@ -322,9 +444,9 @@ pub(crate) fn macro_rules_serialize_if_neq_default() -> proc_macro2::TokenStream
     quote! {
         macro_rules! serialize_if_neq_default {
             // for struct
-            ($state: ident, $fident: expr, $fname: expr, $ftype: ty) => {
-                if *$fident != <$ftype as Default>::default() {
-                    $state.serialize_field($fname, $fident)?;
+            ($state: ident, $fident: expr, $fvalue: expr, $fname: expr, $fdefault: expr) => {
+                if *$fident != $fdefault {
+                    $state.serialize_field($fname, $fvalue)?;
                 }
             };
         }
@@ -353,10 +475,10 @@ pub(crate) fn macro_rules_unwrap_or_none() -> proc_macro2::TokenStream {
 pub(crate) fn macro_rules_unwrap_or_default() -> proc_macro2::TokenStream {
     quote! {
         macro_rules! unwrap_or_default {
-            ($fident: ident, $seq: expr, $ftype: ty) => {
+            ($fident: ident, $seq: expr, $ftype: ty, $fdefault: expr) => {
                 let $fident: $ftype = match $seq {
                     Some(val) => val,
-                    None => <$ftype as Default>::default(),
+                    None => $fdefault,
                 };
             };
         }